@@ -44,8 +44,9 @@ elif sys.platform.startswith("linux"):
 
 binary_name = f"gytmdl{platform_suffix}"
 
+# Runs gytmdl, or the yt-dlp it bundles when the first argument is --yt-dlp
 a = Analysis(
-    [os.path.join(gytmdl_src, "gytmdl", "__main__.py")],
+    [os.path.join(spec_dir, "sidecar_entry.py")],
     pathex=[gytmdl_src],
     binaries=[],
     datas=[
@@ -60,6 +61,7 @@ a = Analysis(
         'gytmdl.enums',
         'gytmdl.constants',
         'gytmdl.custom_logger_formatter',
+        'yt_dlp',
     ],
     hookspath=[],
     hooksconfig={},
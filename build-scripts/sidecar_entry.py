@@ -0,0 +1,28 @@
+"""
+Entry point of the gytmdl sidecar binary.
+
+gytmdl uses yt-dlp as a library, so the sidecar already contains it. Passing
+`--yt-dlp` as the first argument runs that yt-dlp with the remaining arguments
+instead of gytmdl; the GUI uses it to list the tracks of artists, albums and
+playlists. Anything else is handed to gytmdl unchanged.
+"""
+
+import sys
+
+YT_DLP_FLAG = "--yt-dlp"
+
+
+def main():
+    if len(sys.argv) > 1 and sys.argv[1] == YT_DLP_FLAG:
+        import yt_dlp
+
+        sys.argv = ["yt-dlp"] + sys.argv[2:]
+        yt_dlp.main()
+    else:
+        from gytmdl.cli import main as gytmdl_main
+
+        gytmdl_main()
+
+
+if __name__ == "__main__":
+    main()
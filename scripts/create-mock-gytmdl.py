@@ -49,6 +49,12 @@ import os
 def main():
     args = sys.argv[1:]
     
+    # Handle the bundled yt-dlp's flat listing: id, URL and title per line
+    if args[:1] == ["--yt-dlp"]:
+        for i in range(1, 4):
+            print(f"mock{{i:07d}}\\thttps://music.youtube.com/watch?v=mock{{i:07d}}\\tMock Track {{i}}")
+        return 0
+    
     # Handle --version command
     if "--version" in args:
         print("gytmdl 1.0.0 (mock version for testing)")
@@ -1,20 +1,76 @@
 pub mod modules;
 
-use modules::state::{AppState, AppConfig, DownloadJob, JobStatus};
+use modules::state::{AppState, AppConfig, CoverFormat, DownloadJob, JobStatus, TrashedJob, UrlRewriteRule};
+use modules::url_rewriter;
+use modules::template;
+use modules::duplicate_detector;
+use modules::timezone::{DisplayTimezone, LocalTimestamps};
+use modules::job_query::JobQuery;
+use modules::track_selection::{self, TrackSelection};
+use modules::app_lock;
+use modules::app_error::AppError;
+use modules::gytmdl_wrapper::GytmdlError;
+use modules::runtime_settings::{RuntimeSettings, RuntimeSettingsResult};
+use modules::config_history::{self, ConfigChange, ConfigHistory};
+use modules::temp_janitor::{self, TempCleanup, TempUsage};
+use modules::listen_log::{self, CredentialStore, ListenLogCredentials, ListenLogStatus, ListenSubmission};
 use modules::config_manager::ConfigManager;
-use modules::queue_manager::QueueManager;
-use modules::cookie_manager::CookieManager;
-use modules::sidecar_manager::{get_sidecar_status, validate_sidecar_binaries, select_best_sidecar, check_sidecar_compatibility};
+use modules::queue_manager::{BatchAction, BatchActionResult, QueueManager};
+use modules::process_runner::ProcessRunner;
+use modules::cookie_manager::{CookieInfo, CookieManager};
+use modules::events::EventEmitter;
+use modules::job_events::{JobBus, JobEvent};
+use modules::job_groups::GroupProgress;
+use modules::cover_art::{CoverSource, CoverUpdate};
+use modules::tagging::{self, TrackTags};
+use modules::notifier::{self, Notifier};
+use modules::clipboard_watcher::{self, ClipboardAction, ClipboardWatcher};
+use modules::deep_link;
+use modules::subscriptions::{self, Subscription, SubscriptionInfo, SubscriptionStore};
+use modules::sidecar_isolation::{IsolationInfo, SidecarIsolation};
+use modules::api_tokens::{ApiAction, ApiTokenInfo, TokenScope, TokenStore};
+use modules::history::{CleanupSummary, HistoryStore};
+use modules::session_report::SessionSummary;
+use modules::job_export::{self, ExportFilter, ExportFormat, ExportSource};
+use modules::mini_window::{self, MiniProgress, MINI_WINDOW_LABEL};
+use modules::metadata_fetcher::MetadataFetcher;
+use modules::self_test::{self, DegradedFlags, SelfTestReport};
+use modules::setup_diagnostics::{self, SetupReport};
+use modules::app_updater::{AppUpdateInfo, UpdateDownloadProgress};
+use modules::atomic_file::{self, BackupInfo};
+use modules::cookie_monitor;
+use modules::connectivity;
+use modules::autosave;
+use modules::debug_logger::{DEBUG_LOGGER, LogLevel, LogLevels, LogPage, LogQuery};
+use modules::log_files::{self, LogFileSettings};
+use modules::sidecar_manager::{get_sidecar_status, validate_sidecar_binaries, select_best_sidecar, check_sidecar_compatibility, check_sidecar_dependencies, check_sidecar_updates, SidecarComponent, SidecarManager, SidecarUpdate};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use chrono::{DateTime, Utc};
 use std::path::PathBuf;
 use tokio::sync::RwLock;
 use tauri::Manager;
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tauri_plugin_deep_link::DeepLinkExt;
+use tauri_plugin_updater::{Update, UpdaterExt};
 
 /// Application context that holds shared state and managers
 pub struct AppContext {
     pub state: Arc<RwLock<AppState>>,
     pub queue_manager: Arc<RwLock<Option<QueueManager>>>,
     pub cookie_manager: Arc<RwLock<CookieManager>>,
+    pub events: Arc<RwLock<EventEmitter>>,
+    pub metadata_fetcher: Arc<MetadataFetcher>,
+    pub api_tokens: Arc<RwLock<TokenStore>>,
+    /// Sandbox every gytmdl process runs in unless the user honors system config
+    pub isolation: SidecarIsolation,
+    pub subscriptions: Arc<RwLock<SubscriptionStore>>,
+    /// Set once the user agreed to close with downloads still running
+    pub exit_confirmed: AtomicBool,
+    /// Release found by the last `check_app_update`, installed on request
+    pub pending_update: RwLock<Option<Update>>,
+    /// Jobs archived or deduplicated while loading state, for `get_startup_cleanup`
+    pub startup_cleanup: Option<CleanupSummary>,
 }
 
 impl AppContext {
@@ -23,29 +79,156 @@ impl AppContext {
             state: Arc::clone(&state),
             queue_manager: Arc::new(RwLock::new(None)),
             cookie_manager: Arc::new(RwLock::new(CookieManager::new())),
+            events: Arc::new(RwLock::new(EventEmitter::disabled())),
+            metadata_fetcher: Arc::new(MetadataFetcher::new()),
+            api_tokens: Arc::new(RwLock::new(TokenStore::new())),
+            isolation: SidecarIsolation::new(),
+            subscriptions: Arc::new(RwLock::new(SubscriptionStore::new())),
+            exit_confirmed: AtomicBool::new(false),
+            pending_update: RwLock::new(None),
+            startup_cleanup: None,
         }
     }
 
-    pub async fn initialize_queue_manager(&self) -> Result<(), String> {
+    pub async fn initialize_queue_manager(&self, app_handle: tauri::AppHandle) -> Result<(), String> {
         let concurrent_limit = {
             let state_guard = self.state.read().await;
             state_guard.config.concurrent_limit
         };
 
+        *self.events.write().await = EventEmitter::new(app_handle.clone());
+
+        match QueueManager::with_app_handle(Arc::clone(&self.state), concurrent_limit, app_handle) {
+            Ok(manager) => self.start_queue_manager(manager).await,
+            Err(e) => Err(format!("Failed to create queue manager: {}", e))
+        }
+    }
+
+    /// Initialize a queue manager without a window to send events to
+    #[cfg(feature = "headless")]
+    pub async fn initialize_headless_queue_manager(&self) -> Result<(), String> {
+        let concurrent_limit = self.state.read().await.config.concurrent_limit;
         match QueueManager::new(Arc::clone(&self.state), concurrent_limit) {
-            Ok(manager) => {
-                // Start the queue manager
-                if let Err(e) = manager.start().await {
-                    return Err(format!("Failed to start queue manager: {}", e));
-                }
-                
-                let mut queue_manager_guard = self.queue_manager.write().await;
-                *queue_manager_guard = Some(manager);
-                Ok(())
-            }
+            Ok(manager) => self.start_queue_manager(manager).await,
             Err(e) => Err(format!("Failed to create queue manager: {}", e))
         }
     }
+
+    async fn start_queue_manager(&self, manager: QueueManager) -> Result<(), String> {
+        let manager = manager.with_isolation(self.isolation.clone());
+        // Start the queue manager
+        if let Err(e) = manager.start().await {
+            return Err(format!("Failed to start queue manager: {}", e));
+        }
+
+        let mut queue_manager_guard = self.queue_manager.write().await;
+        *queue_manager_guard = Some(manager);
+        Ok(())
+    }
+
+    /// Submit jobs that were cut off when the app last stopped, if the user
+    /// turned on resuming. Otherwise they wait in the queue for a manual retry.
+    pub async fn resume_interrupted_jobs(&self, job_ids: &[String]) {
+        if job_ids.is_empty() || !self.state.read().await.config.auto_resume_on_start {
+            return;
+        }
+        let queue_manager_guard = self.queue_manager.read().await;
+        let Some(queue_manager) = queue_manager_guard.as_ref() else {
+            return;
+        };
+        for job_id in job_ids {
+            match queue_manager.submit_job(job_id.clone()).await {
+                Ok(()) => DEBUG_LOGGER.info("startup", format!("Resuming interrupted job {}", job_id)),
+                Err(e) => DEBUG_LOGGER.error("startup", format!("Failed to resume job {}: {}", job_id, e)),
+            }
+        }
+    }
+
+    /// Replace the job queue with a rotated backup of the state file. The
+    /// current configuration is kept, since it is stored separately.
+    pub async fn restore_state_backup(&self, index: usize) -> Result<(), String> {
+        let state_file = get_state_file_path();
+        let backup_file = atomic_file::backup_path(&state_file, index);
+        if !backup_file.exists() {
+            return Err(format!("No state backup {}", index));
+        }
+        let mut restored = AppState::load_from_file(&backup_file)
+            .map_err(|e| format!("Failed to read state backup {}: {}", index, e))?;
+
+        {
+            let mut state_guard = self.state.write().await;
+            if state_guard.count_jobs_by_status(&JobStatus::Downloading) > 0 {
+                return Err("Stop running downloads before restoring a backup".to_string());
+            }
+            restored.recover_interrupted_jobs();
+            restored.config = state_guard.config.clone();
+            restored.degraded = state_guard.degraded;
+            restored.self_test = state_guard.self_test.clone();
+            restored.live_progress = Arc::clone(&state_guard.live_progress);
+            *state_guard = restored;
+            state_guard.save_to_file(&state_file)
+                .map_err(|e| format!("Failed to save restored state: {}", e))?;
+        }
+        DEBUG_LOGGER.info("state", format!("Restored state from {:?}", backup_file));
+
+        if let Some(queue_manager) = self.queue_manager.read().await.as_ref() {
+            queue_manager.process_queued_jobs().await?;
+        }
+        Ok(())
+    }
+
+    /// Check everything a first download needs, for the setup wizard
+    pub async fn run_setup_diagnostics(&self) -> SetupReport {
+        let config = self.state.read().await.config.clone();
+        let cookie_manager = self.cookie_manager.read().await;
+        setup_diagnostics::run(&config, &cookie_manager).await
+    }
+
+    /// Run the startup self-test and record which features are degraded
+    pub async fn run_self_test(&self) -> SelfTestReport {
+        let config = self.state.read().await.config.clone();
+        let report = {
+            let cookie_manager = self.cookie_manager.read().await;
+            self_test::run(get_state_file_path(), &config, &cookie_manager).await
+        };
+
+        let mut state_guard = self.state.write().await;
+        // The self-test doesn't check for stale cookies; keep what the queue found
+        let cookies_suspect = state_guard.degraded.contains(DegradedFlags::COOKIES_SUSPECT);
+        state_guard.degraded = report.degraded;
+        if cookies_suspect {
+            state_guard.degraded.insert(DegradedFlags::COOKIES_SUSPECT);
+        }
+        state_guard.self_test = Some(report.clone());
+        report
+    }
+
+    /// Number of running downloads if closing should wait for the user to confirm
+    pub async fn pending_exit_confirmation(&self) -> Option<usize> {
+        if self.exit_confirmed.load(Ordering::SeqCst) {
+            return None;
+        }
+        let state_guard = self.state.read().await;
+        if !state_guard.config.confirm_exit_with_active_downloads {
+            return None;
+        }
+        let active_jobs = state_guard.count_jobs_by_status(&JobStatus::Downloading);
+        (active_jobs > 0).then_some(active_jobs)
+    }
+
+    /// Stop the queue, killing any running gytmdl processes, and write the queue to disk
+    pub async fn shutdown(&self) {
+        if let Some(queue_manager) = self.queue_manager.read().await.as_ref() {
+            queue_manager.shutdown().await;
+        }
+
+        let state_file = get_state_file_path();
+        match self.state.read().await.save_to_file(&state_file) {
+            Ok(()) => DEBUG_LOGGER.info("shutdown", format!("Saved state to {:?}", state_file)),
+            Err(e) => DEBUG_LOGGER.error("shutdown", format!("Failed to save state on exit: {}", e)),
+        }
+        DEBUG_LOGGER.flush_file();
+    }
 }
 
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
@@ -61,52 +244,102 @@ struct AddJobResponse {
     success: bool,
     job_id: Option<String>,
     error: Option<String>,
+    /// Set when the track was downloaded before and the job was not queued
+    duplicate: bool,
+    existing_path: Option<String>,
+    /// Group of the jobs queued for a track selection; `job_id` is the first of them
+    group_id: Option<String>,
 }
 
 #[derive(serde::Deserialize)]
 struct AddJobRequest {
     url: String,
+    /// Queue the URL even if it was downloaded before
+    #[serde(default)]
+    force: bool,
+    /// Don't start before this time (RFC 3339, or local time in the configured timezone)
+    scheduled_at: Option<String>,
+    /// Proxy URL for this job only, overriding the configured proxy
+    proxy: Option<String>,
+    /// Only report what would be downloaded and where, without downloading
+    #[serde(default)]
+    dry_run: bool,
+    /// Only these tracks of an album or playlist URL, each queued as its own job
+    track_selection: Option<TrackSelection>,
+    /// Jobs that must complete before this one starts
+    #[serde(default)]
+    depends_on: Vec<String>,
 }
 
-#[tauri::command]
-async fn add_to_queue(request: AddJobRequest, context: tauri::State<'_, Arc<AppContext>>) -> Result<AddJobResponse, String> {
-    let url = request.url;
-    
-    // Validate URL format
-    if url.trim().is_empty() {
-        return Ok(AddJobResponse {
-            success: false,
-            job_id: None,
-            error: Some("URL cannot be empty".to_string()),
-        });
+/// Validate that a URL is an http(s) YouTube Music link that can be queued
+fn validate_queue_url(url: &str) -> Result<(), GytmdlError> {
+    let is_http = url.starts_with("http://") || url.starts_with("https://");
+    let is_youtube = url.contains("music.youtube.com")
+        || url.contains("youtube.com/watch")
+        || url.contains("youtube.com/playlist")
+        || url.contains("youtu.be/");
+    if !is_http || !is_youtube {
+        return Err(GytmdlError::InvalidUrl(url.to_string()));
     }
 
-    // Basic URL validation - check if it's a valid HTTP/HTTPS URL
-    if !url.starts_with("http://") && !url.starts_with("https://") {
-        return Ok(AddJobResponse {
-            success: false,
-            job_id: None,
-            error: Some("URL must start with http:// or https://".to_string()),
-        });
-    }
+    Ok(())
+}
 
-    // Check if it's a YouTube Music URL
-    if !url.contains("music.youtube.com") && 
-       !url.contains("youtube.com/watch") &&
-       !url.contains("youtube.com/playlist") &&
-       !url.contains("youtu.be/") {
-        return Ok(AddJobResponse {
-            success: false,
-            job_id: None,
-            error: Some("URL must be a valid YouTube Music URL".to_string()),
-        });
-    }
+/// Run a URL through the intake pipeline: trim, apply the configured
+/// rewrite rules, then validate the result
+async fn prepare_queue_url(url: &str, context: &AppContext) -> Result<String, AppError> {
+    let rules = context.state.read().await.config.url_rewrite_rules.clone();
+    let result = url_rewriter::rewrite_url(url.trim(), &rules)?;
+    validate_queue_url(&result.rewritten)?;
+    Ok(result.rewritten)
+}
+
+/// Parse a user-supplied schedule time using the configured timezone
+async fn parse_schedule_time(input: &str, context: &AppContext) -> Result<DateTime<Utc>, String> {
+    let timezone_name = context.state.read().await.config.timezone.clone();
+    DisplayTimezone::from_config(timezone_name.as_deref())?.parse_schedule(input)
+}
+
+/// Per-job settings chosen when a URL is queued
+#[derive(Clone, Default)]
+struct JobOptions {
+    scheduled_at: Option<DateTime<Utc>>,
+    /// Proxy for this job instead of the configured one
+    proxy: Option<String>,
+    dry_run: bool,
+    group_id: Option<String>,
+    /// Jobs that must complete first
+    depends_on: Vec<String>,
+}
 
+/// Add a validated URL to state and submit it to the queue manager
+async fn enqueue_url(url: String, options: JobOptions, context: &AppContext) -> Result<String, String> {
     // Add job to state
-    let job_id = {
-        let mut state_guard = context.state.write().await;
-        state_guard.add_job(url)
+    let mut depends_on = options.depends_on;
+    depends_on.sort();
+    depends_on.dedup();
+    // Only the bare proxy URL is persisted with the job
+    let (proxy, proxy_credentials) = match options.proxy.as_deref() {
+        Some(proxy) => {
+            let (bare, credentials) = ProcessRunner::split_proxy_credentials(proxy);
+            (Some(bare), credentials)
+        }
+        None => (None, None),
+    };
+    let job = DownloadJob {
+        scheduled_at: options.scheduled_at,
+        proxy,
+        proxy_requires_auth: proxy_credentials.is_some(),
+        proxy_credentials,
+        dry_run: options.dry_run,
+        group_id: options.group_id,
+        depends_on,
+        ..DownloadJob::new(url)
     };
+    let job_id = job.id.clone();
+    context.state.read().await.check_dependencies(&job_id, &job.depends_on)?;
+    let events = context.events.read().await.clone();
+    JobBus::new(Arc::clone(&context.state), events).publish(JobEvent::Added(Box::new(job))).await;
 
     // Submit job to queue manager if available
     if let Some(queue_manager) = context.queue_manager.read().await.as_ref() {
@@ -114,73 +347,409 @@ async fn add_to_queue(request: AddJobRequest, context: tauri::State<'_, Arc<AppC
             // If submission fails, remove the job from state
             let mut state_guard = context.state.write().await;
             state_guard.remove_job(&job_id);
+            return Err(format!("Failed to submit job to queue: {}", e));
+        }
+    }
+
+    spawn_metadata_prefetch(job_id.clone(), context).await;
+
+    Ok(job_id)
+}
+
+/// Fetch metadata for a freshly queued job in the background so the queue
+/// shows titles before the download starts
+async fn spawn_metadata_prefetch(job_id: String, context: &AppContext) {
+    let url = {
+        let state_guard = context.state.read().await;
+        if !state_guard.config.prefetch_metadata {
+            return;
+        }
+        match state_guard.get_job(&job_id) {
+            Some(job) => job.url.clone(),
+            None => return,
+        }
+    };
+
+    let state = Arc::clone(&context.state);
+    let fetcher = Arc::clone(&context.metadata_fetcher);
+    let isolation = context.isolation.clone();
+    let events = context.events.read().await.clone();
+
+    tauri::async_runtime::spawn(async move {
+        let mut metadata = match fetcher.fetch(&url).await {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                DEBUG_LOGGER.warn("metadata", format!("Metadata prefetch failed for job {}: {}", job_id, e));
+                return;
+            }
+        };
+        // Playlists and albums are listed so disk and time estimates count their tracks
+        if track_selection::is_collection_url(&url) {
+            match subscriptions::list_entries(&isolation, &url).await {
+                Ok(entries) => metadata.track_count = Some(entries.len() as u32),
+                Err(e) => DEBUG_LOGGER.warn("metadata", format!("Failed to list the tracks of job {}: {}", job_id, e)),
+            }
+        }
+
+        let mut state_guard = state.write().await;
+        // Don't clobber metadata the download itself may have filled in
+        let Some(job) = state_guard.get_job_mut(&job_id) else {
+            return;
+        };
+        let metadata = match &mut job.metadata {
+            Some(existing) if existing.track_count.is_none() && metadata.track_count.is_some() => {
+                existing.track_count = metadata.track_count;
+                existing.clone()
+            }
+            Some(_) => return,
+            None => {
+                job.metadata = Some(metadata.clone());
+                metadata
+            }
+        };
+        events.job_metadata(&job_id, &metadata);
+    });
+}
+
+#[tauri::command]
+async fn add_to_queue(request: AddJobRequest, context: tauri::State<'_, Arc<AppContext>>) -> Result<AddJobResponse, AppError> {
+    let url = prepare_queue_url(&request.url, &context).await?;
+
+    let scheduled_at = match request.scheduled_at.as_deref() {
+        Some(input) => match parse_schedule_time(input, &context).await {
+            Ok(scheduled_at) => Some(scheduled_at),
+            Err(e) => {
+                return Ok(AddJobResponse {
+                    success: false,
+                    job_id: None,
+                    error: Some(e),
+                    duplicate: false,
+                    existing_path: None,
+                    group_id: None,
+                });
+            }
+        },
+        None => None,
+    };
+
+    if let Some(selection) = &request.track_selection {
+        if let Err(e) = selection.validate(&url) {
+            return Ok(AddJobResponse {
+                success: false,
+                job_id: None,
+                error: Some(e),
+                duplicate: false,
+                existing_path: None,
+                group_id: None,
+            });
+        }
+    }
+
+    // A dry run writes nothing, so it can't duplicate anything
+    if !request.force && !request.dry_run {
+        let mut known_jobs = tokio::task::spawn_blocking(|| HistoryStore::new().load())
+            .await
+            .ok()
+            .and_then(Result::ok)
+            .unwrap_or_default();
+        let scan_root = {
+            let state_guard = context.state.read().await;
+            known_jobs.extend(state_guard.get_jobs_by_status(&JobStatus::Completed).into_iter().cloned());
+            state_guard.config.scan_output_for_duplicates
+                .then(|| state_guard.config.output_path.clone())
+        };
+
+        let duplicate = duplicate_detector::find_duplicate(
+            &url,
+            &known_jobs,
+            scan_root.as_deref(),
+            &context.metadata_fetcher,
+        ).await;
+
+        if let Some(duplicate) = duplicate {
+            return Ok(AddJobResponse {
+                success: false,
+                job_id: None,
+                error: Some("This track has already been downloaded".to_string()),
+                duplicate: true,
+                existing_path: duplicate.existing_path.map(|path| path.to_string_lossy().to_string()),
+                group_id: None,
+            });
+        }
+    }
+
+    let proxy = request.proxy.filter(|proxy| !proxy.trim().is_empty());
+    if let Some(proxy) = &proxy {
+        if let Err(e) = ConfigManager::validate_proxy_url(proxy) {
             return Ok(AddJobResponse {
                 success: false,
                 job_id: None,
-                error: Some(format!("Failed to submit job to queue: {}", e)),
+                error: Some(e.to_string()),
+                duplicate: false,
+                existing_path: None,
+                group_id: None,
             });
         }
     }
 
-    Ok(AddJobResponse {
-        success: true,
-        job_id: Some(job_id),
-        error: None,
+    let options = JobOptions {
+        scheduled_at,
+        proxy,
+        dry_run: request.dry_run,
+        depends_on: request.depends_on,
+        ..JobOptions::default()
+    };
+
+    // gytmdl can't pick tracks out of an album, so the selected ones are
+    // queued as single tracks, grouped under the album
+    let queued = match &request.track_selection {
+        Some(selection) => enqueue_selection(&url, selection, options, &context).await
+            .map(|(group_id, job_ids)| (job_ids.first().cloned(), Some(group_id))),
+        None => enqueue_url(url, options, &context).await
+            .map(|job_id| (Some(job_id), None)),
+    };
+
+    match queued {
+        Ok((job_id, group_id)) => Ok(AddJobResponse {
+            success: true,
+            job_id,
+            error: None,
+            duplicate: false,
+            existing_path: None,
+            group_id,
+        }),
+        Err(e) => Ok(AddJobResponse {
+            success: false,
+            job_id: None,
+            error: Some(e),
+            duplicate: false,
+            existing_path: None,
+            group_id: None,
+        }),
+    }
+}
+
+/// Queue the selected tracks of an album or playlist as one group of jobs
+async fn enqueue_selection(
+    url: &str,
+    selection: &TrackSelection,
+    options: JobOptions,
+    context: &AppContext,
+) -> Result<(String, Vec<String>), String> {
+    let entries = subscriptions::list_entries(&context.isolation, url).await
+        .map_err(|e| e.to_string())?;
+    let selected = selection.select(&entries);
+    if selected.is_empty() {
+        return Err(format!("None of the selected tracks are in {}", url));
+    }
+
+    let group_id = context.state.write().await.add_group(url.to_string(), None);
+    let options = JobOptions { group_id: Some(group_id.clone()), ..options };
+    let job_ids = enqueue_group(&selected, &options, "queue", context).await;
+    if job_ids.is_empty() {
+        context.state.write().await.prune_empty_groups();
+        return Err("None of the tracks could be queued".to_string());
+    }
+    Ok((group_id, job_ids))
+}
+
+#[derive(serde::Deserialize)]
+struct AddBatchRequest {
+    urls: Option<Vec<String>>,
+    file_path: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+struct BatchUrlResult {
+    url: String,
+    success: bool,
+    job_id: Option<String>,
+    error: Option<String>,
+    /// `AppError` code of the failure, e.g. "invalid_url"
+    code: Option<&'static str>,
+}
+
+#[derive(serde::Serialize)]
+struct AddBatchResponse {
+    added: usize,
+    failed: usize,
+    results: Vec<BatchUrlResult>,
+}
+
+/// Read URLs from a text file, one per line. Blank lines and `#` comments are skipped.
+fn read_url_list(path: &std::path::Path) -> Result<Vec<String>, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read URL list {:?}: {}", path, e))?;
+
+    Ok(content
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| line.to_string())
+        .collect())
+}
+
+#[tauri::command]
+async fn add_batch_to_queue(request: AddBatchRequest, context: tauri::State<'_, Arc<AppContext>>) -> Result<AddBatchResponse, AppError> {
+    let mut urls = request.urls.unwrap_or_default();
+    if let Some(file_path) = request.file_path {
+        urls.extend(read_url_list(std::path::Path::new(&file_path))?);
+    }
+
+    if urls.is_empty() {
+        return Err(AppError::Other("No URLs provided".to_string()));
+    }
+
+    let mut results = Vec::with_capacity(urls.len());
+    let mut seen = std::collections::HashSet::new();
+
+    for url in urls {
+        let url = url.trim().to_string();
+
+        let outcome = match prepare_queue_url(&url, &context).await {
+            Err(e) => Err(e),
+            Ok(rewritten) if !seen.insert(rewritten.clone()) => {
+                Err(AppError::Other("Duplicate URL in batch".to_string()))
+            }
+            Ok(rewritten) if context.state.read().await.find_job_by_url(&rewritten).is_some() => {
+                Err(AppError::Other("URL is already in the queue".to_string()))
+            }
+            Ok(rewritten) => enqueue_url(rewritten, JobOptions::default(), &context).await.map_err(AppError::from),
+        };
+
+        results.push(match outcome {
+            Ok(job_id) => BatchUrlResult { url, success: true, job_id: Some(job_id), error: None, code: None },
+            Err(e) => BatchUrlResult { url, success: false, job_id: None, error: Some(e.to_string()), code: Some(e.code()) },
+        });
+    }
+
+    let added = results.iter().filter(|r| r.success).count();
+    Ok(AddBatchResponse {
+        added,
+        failed: results.len() - added,
+        results,
     })
 }
 
+#[derive(serde::Deserialize)]
+struct PreviewUrlRewriteRequest {
+    url: String,
+    /// Rules to test; defaults to the saved config so unsaved edits can be previewed
+    rules: Option<Vec<UrlRewriteRule>>,
+}
+
+#[tauri::command]
+async fn preview_url_rewrite(request: PreviewUrlRewriteRequest, context: tauri::State<'_, Arc<AppContext>>) -> Result<url_rewriter::RewriteResult, String> {
+    let rules = match request.rules {
+        Some(rules) => rules,
+        None => context.state.read().await.config.url_rewrite_rules.clone(),
+    };
+
+    url_rewriter::rewrite_url(request.url.trim(), &rules)
+}
+
+#[tauri::command]
+async fn get_isolation_info(context: tauri::State<'_, Arc<AppContext>>) -> Result<IsolationInfo, String> {
+    Ok(context.isolation.info())
+}
+
+#[derive(serde::Deserialize)]
+struct PreviewTemplateRequest {
+    /// Templates to test; each defaults to the saved config so unsaved edits can be previewed
+    template_folder: Option<String>,
+    template_file: Option<String>,
+    template_date: Option<String>,
+    sample: Option<template::SampleMetadata>,
+}
+
+#[tauri::command]
+async fn preview_template(request: PreviewTemplateRequest, context: tauri::State<'_, Arc<AppContext>>) -> Result<template::TemplatePreview, String> {
+    let config = context.state.read().await.config.clone();
+    template::preview(
+        &config.output_path,
+        request.template_folder.as_deref().unwrap_or(&config.template_folder),
+        request.template_file.as_deref().unwrap_or(&config.template_file),
+        request.template_date.as_deref().unwrap_or(&config.template_date),
+        &request.sample.unwrap_or_default(),
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// A job plus its timestamps pre-formatted in the display timezone
+#[derive(serde::Serialize)]
+struct QueueJob {
+    #[serde(flatten)]
+    job: DownloadJob,
+    local_times: LocalTimestamps,
+}
+
 #[derive(serde::Serialize)]
 struct QueueState {
-    jobs: Vec<DownloadJob>,
+    jobs: Vec<QueueJob>,
+    /// Jobs matching the query before paging
+    total_jobs: usize,
     is_paused: bool,
     concurrent_limit: usize,
+    timezone: String,
 }
 
+/// Without a query, every job is returned in queue order
 #[tauri::command]
-async fn get_queue(context: tauri::State<'_, Arc<AppContext>>) -> Result<QueueState, String> {
+async fn get_queue(query: Option<JobQuery>, context: tauri::State<'_, Arc<AppContext>>) -> Result<QueueState, AppError> {
     let state_guard = context.state.read().await;
+    let timezone = DisplayTimezone::from_config(state_guard.config.timezone.as_deref())
+        .unwrap_or(DisplayTimezone::System);
+    let page = query.unwrap_or_default().apply(&state_guard.jobs);
+
     Ok(QueueState {
-        jobs: state_guard.jobs.clone(),
+        jobs: page.jobs.into_iter()
+            .map(|job| QueueJob {
+                job: state_guard.job_snapshot(job),
+                local_times: LocalTimestamps::for_job(job, &timezone),
+            })
+            .collect(),
+        total_jobs: page.total,
         is_paused: state_guard.is_paused,
         concurrent_limit: state_guard.config.concurrent_limit,
+        timezone: timezone.name(),
     })
 }
 
 #[tauri::command]
-async fn retry_job(job_id: String, context: tauri::State<'_, Arc<AppContext>>) -> Result<(), String> {
+async fn retry_job(job_id: String, context: tauri::State<'_, Arc<AppContext>>) -> Result<(), AppError> {
     // Check if job exists and can be retried
     {
         let state_guard = context.state.read().await;
         if let Some(job) = state_guard.get_job(&job_id) {
             if !job.can_retry() {
-                return Err("Job cannot be retried".to_string());
+                return Err(AppError::Other("Job cannot be retried".to_string()));
             }
         } else {
-            return Err("Job not found".to_string());
+            return Err(AppError::NotFound("Job"));
         }
     }
 
     // Retry job using queue manager
     if let Some(queue_manager) = context.queue_manager.read().await.as_ref() {
-        queue_manager.retry_job(job_id).await
+        Ok(queue_manager.retry_job(job_id).await?)
     } else {
-        Err("Queue manager not available".to_string())
+        Err(AppError::Unavailable("Queue manager"))
     }
 }
 
 #[tauri::command]
-async fn cancel_job(job_id: String, context: tauri::State<'_, Arc<AppContext>>) -> Result<(), String> {
+async fn cancel_job(job_id: String, context: tauri::State<'_, Arc<AppContext>>) -> Result<(), AppError> {
     // Check if job exists
     {
         let state_guard = context.state.read().await;
         if state_guard.get_job(&job_id).is_none() {
-            return Err("Job not found".to_string());
+            return Err(AppError::NotFound("Job"));
         }
     }
 
     // Cancel job using queue manager
     if let Some(queue_manager) = context.queue_manager.read().await.as_ref() {
-        queue_manager.cancel_job(&job_id).await
+        Ok(queue_manager.cancel_job(&job_id).await?)
     } else {
         // If queue manager not available, just update state
         let mut state_guard = context.state.write().await;
@@ -189,6 +758,48 @@ async fn cancel_job(job_id: String, context: tauri::State<'_, Arc<AppContext>>)
     }
 }
 
+/// Hold the whole queue until `start_at`; `None` clears the schedule
+#[tauri::command]
+async fn schedule_queue_start(start_at: Option<String>, context: tauri::State<'_, Arc<AppContext>>) -> Result<Option<DateTime<Utc>>, String> {
+    let start_at = match start_at.as_deref() {
+        Some(input) => Some(parse_schedule_time(input, &context).await?),
+        None => None,
+    };
+
+    context.state.write().await.queue_start_at = start_at;
+    if let Some(queue_manager) = context.queue_manager.read().await.as_ref() {
+        queue_manager.reschedule();
+    }
+    Ok(start_at)
+}
+
+/// Set or clear the time a single job may start
+#[tauri::command]
+async fn set_job_schedule(job_id: String, scheduled_at: Option<String>, context: tauri::State<'_, Arc<AppContext>>) -> Result<Option<DateTime<Utc>>, String> {
+    let scheduled_at = match scheduled_at.as_deref() {
+        Some(input) => Some(parse_schedule_time(input, &context).await?),
+        None => None,
+    };
+
+    if !context.state.write().await.set_job_schedule(&job_id, scheduled_at) {
+        return Err("Job not found".to_string());
+    }
+    if let Some(queue_manager) = context.queue_manager.read().await.as_ref() {
+        queue_manager.reschedule();
+    }
+    Ok(scheduled_at)
+}
+
+/// Replace the jobs a queued job waits on; an empty list clears them
+#[tauri::command]
+async fn set_job_dependencies(job_id: String, depends_on: Vec<String>, context: tauri::State<'_, Arc<AppContext>>) -> Result<(), String> {
+    context.state.write().await.set_job_dependencies(&job_id, depends_on)?;
+    if let Some(queue_manager) = context.queue_manager.read().await.as_ref() {
+        queue_manager.reschedule();
+    }
+    Ok(())
+}
+
 #[tauri::command]
 async fn pause_queue(context: tauri::State<'_, Arc<AppContext>>) -> Result<(), String> {
     if let Some(queue_manager) = context.queue_manager.read().await.as_ref() {
@@ -215,69 +826,756 @@ async fn resume_queue(context: tauri::State<'_, Arc<AppContext>>) -> Result<(),
     }
 }
 
-fn get_state_file_path() -> PathBuf {
-    // Use a simple approach for state file location
-    let app_data_dir = std::env::current_dir()
-        .unwrap_or_else(|_| PathBuf::from("."));
-    app_data_dir.join(".gytmdl-gui").join("state.json")
+#[tauri::command]
+async fn get_disk_space_projection(context: tauri::State<'_, Arc<AppContext>>) -> Result<modules::disk_space::SpaceProjection, String> {
+    if let Some(queue_manager) = context.queue_manager.read().await.as_ref() {
+        queue_manager.get_disk_space_projection().await
+    } else {
+        Err("Queue manager not available".to_string())
+    }
 }
 
-fn initialize_app_state() -> Arc<RwLock<AppState>> {
-    let state_file = get_state_file_path();
-    let config_manager = ConfigManager::with_default_path();
-    
-    // Try to load existing state, fallback to default if it fails
-    let mut app_state = match AppState::load_from_file(&state_file) {
-        Ok(state) => {
-            println!("Loaded existing state from: {:?}", state_file);
-            state
-        }
-        Err(e) => {
-            println!("Failed to load state from {:?}: {}. Using default state.", state_file, e);
-            AppState::default()
-        }
+#[tauri::command]
+async fn verify_library_integrity(
+    queue_redownloads: bool,
+    context: tauri::State<'_, Arc<AppContext>>,
+) -> Result<modules::library_integrity::IntegrityReport, String> {
+    let mut report = match context.queue_manager.read().await.as_ref() {
+        Some(queue_manager) => queue_manager.verify_library().await,
+        None => return Err("Queue manager not available".to_string()),
     };
-    
-    // Load configuration separately and update state
-    match config_manager.load_config() {
-        Ok(config) => {
-            println!("Loaded configuration from: {:?}", config_manager.get_config_file_path());
-            app_state.config = config;
+
+    if queue_redownloads {
+        // Move corrupt files aside so gytmdl doesn't skip them as already downloaded
+        for issue in report.corrupt.iter().filter(|issue| issue.source_url.is_some()) {
+            let mut aside = issue.path.clone().into_os_string();
+            aside.push(".corrupt");
+            if let Err(e) = std::fs::rename(&issue.path, &aside) {
+                DEBUG_LOGGER.error("library", format!("Failed to move aside corrupt file {:?}: {}", issue.path, e));
+            }
         }
-        Err(e) => {
-            println!("Failed to load config: {}. Using default config.", e);
-            app_state.config = AppConfig::default();
-            // Try to save the default config
-            if let Err(save_err) = config_manager.save_config(&app_state.config) {
-                println!("Failed to save default config: {}", save_err);
+
+        for url in report.redownload_urls() {
+            match enqueue_url(url.clone(), JobOptions::default(), &context).await {
+                Ok(job_id) => report.queued_job_ids.push(job_id),
+                Err(e) => DEBUG_LOGGER.error("library", format!("Failed to queue re-download of {}: {}", url, e)),
             }
         }
     }
-    
-    Arc::new(RwLock::new(app_state))
+
+    Ok(report)
 }
 
 #[tauri::command]
-async fn remove_job(job_id: String, context: tauri::State<'_, Arc<AppContext>>) -> Result<(), String> {
-    // Check if job exists
-    {
-        let state_guard = context.state.read().await;
-        if state_guard.get_job(&job_id).is_none() {
-            return Err("Job not found".to_string());
-        }
+async fn get_queue_forecast(context: tauri::State<'_, Arc<AppContext>>) -> Result<modules::throughput::QueueForecast, String> {
+    if let Some(queue_manager) = context.queue_manager.read().await.as_ref() {
+        Ok(queue_manager.get_queue_forecast().await)
+    } else {
+        Err("Queue manager not available".to_string())
     }
-
-    // Remove job from state
-    let mut state_guard = context.state.write().await;
-    state_guard.remove_job(&job_id);
-    Ok(())
 }
 
 #[tauri::command]
-async fn clear_completed_jobs(context: tauri::State<'_, Arc<AppContext>>) -> Result<(), String> {
-    let mut state_guard = context.state.write().await;
-    state_guard.jobs.retain(|job| job.status != JobStatus::Completed);
-    Ok(())
+async fn get_queue_metrics(context: tauri::State<'_, Arc<AppContext>>) -> Result<modules::queue_metrics::QueueMetrics, String> {
+    if let Some(queue_manager) = context.queue_manager.read().await.as_ref() {
+        Ok(queue_manager.get_queue_metrics().await)
+    } else {
+        Err("Queue manager not available".to_string())
+    }
+}
+
+#[tauri::command]
+async fn get_queue_stats(context: tauri::State<'_, Arc<AppContext>>) -> Result<modules::queue_manager::QueueStats, String> {
+    if let Some(queue_manager) = context.queue_manager.read().await.as_ref() {
+        Ok(queue_manager.get_queue_stats().await)
+    } else {
+        Err("Queue manager not available".to_string())
+    }
+}
+
+/// Check that the gytmdl binary runs; returns its version
+#[tauri::command]
+async fn queue_health_check(context: tauri::State<'_, Arc<AppContext>>) -> Result<String, String> {
+    if let Some(queue_manager) = context.queue_manager.read().await.as_ref() {
+        queue_manager.health_check().await
+    } else {
+        Err("Queue manager not available".to_string())
+    }
+}
+
+/// Cancel every queued and running job; returns how many were cancelled
+#[tauri::command]
+async fn cancel_all_jobs(context: tauri::State<'_, Arc<AppContext>>) -> Result<usize, String> {
+    if let Some(queue_manager) = context.queue_manager.read().await.as_ref() {
+        queue_manager.cancel_all_jobs().await
+    } else {
+        Err("Queue manager not available".to_string())
+    }
+}
+
+/// Queue every failed job again; returns how many were retried
+#[tauri::command]
+async fn retry_all_failed_jobs(context: tauri::State<'_, Arc<AppContext>>) -> Result<usize, String> {
+    if let Some(queue_manager) = context.queue_manager.read().await.as_ref() {
+        queue_manager.retry_all_failed_jobs().await
+    } else {
+        Err("Queue manager not available".to_string())
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct AddPlaylistRequest {
+    /// Playlist or album URL whose tracks are queued as separate jobs
+    url: String,
+    /// Display name for the group; defaults to none
+    name: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+struct AddPlaylistResponse {
+    group_id: String,
+    job_ids: Vec<String>,
+}
+
+/// Expand a playlist or album into one job per track, grouped together
+#[tauri::command]
+async fn add_playlist_to_queue(request: AddPlaylistRequest, context: tauri::State<'_, Arc<AppContext>>) -> Result<AddPlaylistResponse, String> {
+    let source_url = prepare_queue_url(&request.url, &context).await.map_err(|e| e.to_string())?;
+    let entries = subscriptions::list_entries(&context.isolation, &source_url).await
+        .map_err(|e| e.to_string())?;
+    if entries.is_empty() {
+        return Err(format!("No tracks found in {}", source_url));
+    }
+
+    let group_id = context.state.write().await.add_group(source_url, request.name);
+    let options = JobOptions { group_id: Some(group_id.clone()), ..JobOptions::default() };
+    let job_ids = enqueue_group(&entries, &options, "queue", &context).await;
+    if job_ids.is_empty() {
+        context.state.write().await.prune_empty_groups();
+        return Err("None of the tracks could be queued".to_string());
+    }
+    Ok(AddPlaylistResponse { group_id, job_ids })
+}
+
+/// Queue listed tracks as jobs with `options`, which name their group,
+/// logging the ones that can't be queued
+async fn enqueue_group(entries: &[subscriptions::ListedEntry], options: &JobOptions, component: &str, context: &AppContext) -> Vec<String> {
+    let mut job_ids = Vec::new();
+    for entry in entries {
+        let queued = match prepare_queue_url(&entry.url, context).await {
+            Ok(url) => enqueue_url(url, options.clone(), context).await,
+            Err(e) => Err(e.to_string()),
+        };
+        match queued {
+            Ok(job_id) => job_ids.push(job_id),
+            Err(e) => DEBUG_LOGGER.warn(component, format!("Could not queue {}: {}", entry.url, e)),
+        }
+    }
+    job_ids
+}
+
+/// Every job group with the combined progress of its tracks
+#[tauri::command]
+async fn get_job_groups(context: tauri::State<'_, Arc<AppContext>>) -> Result<Vec<GroupProgress>, String> {
+    Ok(context.state.read().await.group_summaries())
+}
+
+/// Apply a batch action to the jobs of a group that match `filter`
+async fn group_job_action(
+    group_id: &str,
+    action: BatchAction,
+    filter: impl Fn(&DownloadJob) -> bool,
+    context: &AppContext,
+) -> Result<Vec<BatchActionResult>, String> {
+    let job_ids: Vec<String> = {
+        let state_guard = context.state.read().await;
+        if state_guard.get_group(group_id).is_none() {
+            return Err("Group not found".to_string());
+        }
+        state_guard.group_jobs(group_id).into_iter()
+            .filter(|job| filter(job))
+            .map(|job| job.id.clone())
+            .collect()
+    };
+
+    if let Some(queue_manager) = context.queue_manager.read().await.as_ref() {
+        Ok(queue_manager.batch_job_action(&job_ids, action).await)
+    } else {
+        Err("Queue manager not available".to_string())
+    }
+}
+
+/// Cancel the queued and running jobs of a group
+#[tauri::command]
+async fn cancel_group(group_id: String, context: tauri::State<'_, Arc<AppContext>>) -> Result<Vec<BatchActionResult>, String> {
+    group_job_action(&group_id, BatchAction::Cancel, |job| !job.is_terminal(), &context).await
+}
+
+/// Retry the failed jobs of a group
+#[tauri::command]
+async fn retry_failed_in_group(group_id: String, context: tauri::State<'_, Arc<AppContext>>) -> Result<Vec<BatchActionResult>, String> {
+    group_job_action(&group_id, BatchAction::Retry, |job| job.status == JobStatus::Failed, &context).await
+}
+
+/// Write an .m3u8 of a group's downloaded tracks, next to them unless `path` is given
+#[tauri::command]
+async fn generate_playlist_file(
+    group_id: String,
+    path: Option<String>,
+    context: tauri::State<'_, Arc<AppContext>>,
+) -> Result<String, String> {
+    if let Some(queue_manager) = context.queue_manager.read().await.as_ref() {
+        let path = queue_manager.generate_playlist_file(&group_id, path.map(PathBuf::from)).await?;
+        Ok(path.to_string_lossy().to_string())
+    } else {
+        Err("Queue manager not available".to_string())
+    }
+}
+
+/// Cancel, retry, remove or reprioritize several jobs at once. Removing
+/// running jobs needs the PIN when the app lock is on, as in `remove_job`.
+#[tauri::command]
+async fn batch_job_action(
+    job_ids: Vec<String>,
+    action: BatchAction,
+    pin: Option<String>,
+    context: tauri::State<'_, Arc<AppContext>>,
+) -> Result<Vec<BatchActionResult>, AppError> {
+    if matches!(action, BatchAction::Remove) {
+        let any_active = {
+            let state_guard = context.state.read().await;
+            job_ids.iter().any(|id| state_guard.get_job(id).is_some_and(|job| job.is_active()))
+        };
+        if any_active {
+            require_unlocked(pin.as_deref(), &context).await?;
+        }
+    }
+
+    match context.queue_manager.read().await.as_ref() {
+        Some(queue_manager) => Ok(queue_manager.batch_job_action(&job_ids, action).await),
+        None => Err(AppError::Unavailable("Queue manager")),
+    }
+}
+
+/// Write the queue and/or history to a CSV or JSON file. Returns the number of jobs written.
+#[tauri::command]
+async fn export_jobs(
+    format: ExportFormat,
+    path: String,
+    filter: Option<ExportFilter>,
+    context: tauri::State<'_, Arc<AppContext>>,
+) -> Result<usize, String> {
+    let filter = filter.unwrap_or_default();
+    let history = if filter.source == ExportSource::Queue {
+        Vec::new()
+    } else {
+        HistoryStore::new().load().map_err(|e| format!("Failed to read history: {}", e))?
+    };
+    let rows = {
+        let state_guard = context.state.read().await;
+        job_export::select_rows(history, &state_guard.jobs, &filter)
+    };
+
+    let count = rows.len();
+    tokio::task::spawn_blocking(move || job_export::write_export(std::path::Path::new(&path), format, &rows))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("Failed to write export: {}", e))?;
+    Ok(count)
+}
+
+/// Summaries of past sessions, most recent first, up to `limit`
+#[tauri::command]
+async fn get_session_summaries(limit: Option<usize>) -> Result<Vec<SessionSummary>, String> {
+    let mut sessions = HistoryStore::new().load_sessions()
+        .map_err(|e| format!("Failed to read session summaries: {}", e))?;
+    sessions.reverse();
+    if let Some(limit) = limit {
+        sessions.truncate(limit);
+    }
+    Ok(sessions)
+}
+
+#[tauri::command]
+async fn get_debug_logs(query: Option<LogQuery>) -> Result<LogPage, String> {
+    Ok(DEBUG_LOGGER.query(&query.unwrap_or_default()))
+}
+
+/// Set the lowest level recorded, overall or for one component
+#[tauri::command]
+async fn set_log_level(level: LogLevel, component: Option<String>) -> Result<LogLevels, String> {
+    DEBUG_LOGGER.set_level(level, component.as_deref());
+    Ok(DEBUG_LOGGER.levels())
+}
+
+/// Zip the debug log files into `path` for attaching to a bug report
+#[tauri::command]
+async fn export_debug_logs(path: String) -> Result<(), String> {
+    DEBUG_LOGGER.flush_file();
+    let entries = DEBUG_LOGGER.entries(LogLevel::Debug);
+    tokio::task::spawn_blocking(move || log_files::export_logs(&get_log_dir(), std::path::Path::new(&path), &entries))
+        .await
+        .map_err(|e| format!("Failed to export debug logs: {}", e))?
+        .map_err(|e| format!("Failed to export debug logs: {}", e))
+}
+
+#[tauri::command]
+async fn clear_debug_logs() -> Result<(), String> {
+    DEBUG_LOGGER.clear();
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_job_logs(job_id: String, context: tauri::State<'_, Arc<AppContext>>) -> Result<Vec<modules::job_logs::LogLine>, String> {
+    if let Some(queue_manager) = context.queue_manager.read().await.as_ref() {
+        Ok(queue_manager.get_job_logs(&job_id).await)
+    } else {
+        Err("Queue manager not available".to_string())
+    }
+}
+
+/// Get the first output file of a job that still exists on disk
+async fn existing_job_file(job_id: &str, context: &AppContext) -> Result<PathBuf, String> {
+    let state_guard = context.state.read().await;
+    let job = state_guard.get_job(job_id).ok_or("Job not found")?;
+    job.output_files.iter()
+        .find(|path| path.exists())
+        .cloned()
+        .ok_or_else(|| "No downloaded files found for this job".to_string())
+}
+
+#[tauri::command]
+async fn reveal_job_in_file_manager(job_id: String, context: tauri::State<'_, Arc<AppContext>>) -> Result<(), String> {
+    let path = existing_job_file(&job_id, &context).await?;
+    tauri_plugin_opener::reveal_item_in_dir(path).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn open_job_file(job_id: String, context: tauri::State<'_, Arc<AppContext>>) -> Result<(), String> {
+    let path = existing_job_file(&job_id, &context).await?;
+    tauri_plugin_opener::open_path(path, None::<&str>).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn organize_completed(job_id: String, context: tauri::State<'_, Arc<AppContext>>) -> Result<Vec<modules::library::OrganizedFile>, String> {
+    if let Some(queue_manager) = context.queue_manager.read().await.as_ref() {
+        queue_manager.organize_completed(&job_id).await
+    } else {
+        Err("Queue manager not available".to_string())
+    }
+}
+
+#[tauri::command]
+async fn refetch_cover(
+    job_id: String,
+    size: Option<u32>,
+    format: Option<CoverFormat>,
+    context: tauri::State<'_, Arc<AppContext>>,
+) -> Result<CoverUpdate, String> {
+    if let Some(queue_manager) = context.queue_manager.read().await.as_ref() {
+        queue_manager.update_cover(&job_id, None, size, format).await
+    } else {
+        Err("Queue manager not available".to_string())
+    }
+}
+
+#[tauri::command]
+async fn replace_cover(job_id: String, image_path: String, context: tauri::State<'_, Arc<AppContext>>) -> Result<CoverUpdate, String> {
+    let image_path = PathBuf::from(image_path);
+    if !image_path.is_file() {
+        return Err(format!("Image not found: {}", image_path.display()));
+    }
+    if let Some(queue_manager) = context.queue_manager.read().await.as_ref() {
+        queue_manager.update_cover(&job_id, Some(CoverSource::File(image_path)), None, None).await
+    } else {
+        Err("Queue manager not available".to_string())
+    }
+}
+
+#[tauri::command]
+async fn read_tags(path: String) -> Result<TrackTags, String> {
+    tokio::task::spawn_blocking(move || tagging::read_tags(std::path::Path::new(&path)))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn write_tags(path: String, tags: TrackTags) -> Result<(), String> {
+    let file = PathBuf::from(path);
+    let target = file.clone();
+    tokio::task::spawn_blocking(move || tagging::write_tags(&target, &tags))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+    DEBUG_LOGGER.info("tagging", format!("Updated tags of {}", file.display()));
+    Ok(())
+}
+
+/// Open or close the always-on-top mini progress window. Returns whether it is open.
+#[tauri::command]
+async fn toggle_mini_window(app_handle: tauri::AppHandle, context: tauri::State<'_, Arc<AppContext>>) -> Result<bool, String> {
+    let events = context.events.read().await.clone();
+    mini_window::toggle(&app_handle, Arc::clone(&context.state), events)
+}
+
+/// What the mini window shows, for its first render
+#[tauri::command]
+async fn get_mini_progress(context: tauri::State<'_, Arc<AppContext>>) -> Result<MiniProgress, String> {
+    Ok(MiniProgress::from_state(&*context.state.read().await))
+}
+
+#[tauri::command]
+async fn get_job_progress_history(job_id: String, context: tauri::State<'_, Arc<AppContext>>) -> Result<Vec<modules::progress_journal::JournalEntry>, String> {
+    if let Some(queue_manager) = context.queue_manager.read().await.as_ref() {
+        Ok(queue_manager.get_progress_history(&job_id).await)
+    } else {
+        Err("Queue manager not available".to_string())
+    }
+}
+
+#[derive(serde::Serialize)]
+struct Capabilities {
+    /// True until the startup self-test has finished
+    pending: bool,
+    degraded: DegradedFlags,
+    degraded_features: Vec<&'static str>,
+    self_test: Option<SelfTestReport>,
+}
+
+#[tauri::command]
+async fn get_capabilities(context: tauri::State<'_, Arc<AppContext>>) -> Result<Capabilities, String> {
+    let state_guard = context.state.read().await;
+    Ok(Capabilities {
+        pending: state_guard.self_test.is_none(),
+        degraded: state_guard.degraded,
+        degraded_features: state_guard.degraded.names(),
+        self_test: state_guard.self_test.clone(),
+    })
+}
+
+/// Jobs moved to history or dropped as duplicates while loading state, if any.
+/// Queried by the frontend once it's up, since an event sent at startup
+/// would arrive before it listens.
+#[tauri::command]
+async fn get_startup_cleanup(context: tauri::State<'_, Arc<AppContext>>) -> Result<Option<CleanupSummary>, String> {
+    Ok(context.startup_cleanup.clone())
+}
+
+/// Check the sidecar, ffmpeg, cookies, output and temp folders, and network
+/// access in one report for the first-run wizard
+#[tauri::command]
+async fn run_setup_diagnostics(context: tauri::State<'_, Arc<AppContext>>) -> Result<SetupReport, String> {
+    Ok(context.run_setup_diagnostics().await)
+}
+
+/// Whether the build was configured with an updater endpoint and signing
+/// key; see "Enabling updates" in the README
+fn updater_configured(app_handle: &tauri::AppHandle) -> bool {
+    app_handle.config().plugins.0.contains_key("updater")
+}
+
+/// Look for a newer release of the app; `None` when up to date
+#[tauri::command]
+async fn check_app_update(app_handle: tauri::AppHandle, context: tauri::State<'_, Arc<AppContext>>) -> Result<Option<AppUpdateInfo>, String> {
+    if !updater_configured(&app_handle) {
+        return Err("Updates are not configured in this build".to_string());
+    }
+    let update = app_handle.updater()
+        .map_err(|e| format!("Updater is not available: {}", e))?
+        .check()
+        .await
+        .map_err(|e| format!("Failed to check for updates: {}", e))?;
+
+    let info = update.as_ref().map(AppUpdateInfo::from);
+    if let Some(info) = &info {
+        DEBUG_LOGGER.info("updater", format!("Update available: {} -> {}", info.current_version, info.version));
+    }
+    *context.pending_update.write().await = update;
+    Ok(info)
+}
+
+/// Download and install the release found by `check_app_update`, then
+/// restart. The updater plugin rejects installers whose signature doesn't
+/// match the configured public key.
+#[tauri::command]
+async fn install_app_update(app_handle: tauri::AppHandle, context: tauri::State<'_, Arc<AppContext>>) -> Result<(), String> {
+    let update = context.pending_update.read().await.clone()
+        .ok_or_else(|| "No update to install; check for updates first".to_string())?;
+
+    let events = EventEmitter::new(app_handle.clone());
+    let mut progress = UpdateDownloadProgress::new();
+    update.download_and_install(
+        |chunk_length, content_length| {
+            if progress.record(chunk_length, content_length) {
+                events.app_update_progress(&progress);
+            }
+        },
+        || DEBUG_LOGGER.info("updater", "Update downloaded, installing"),
+    )
+    .await
+    .map_err(|e| format!("Failed to install update: {}", e))?;
+
+    DEBUG_LOGGER.info("updater", format!("Installed {}, restarting", update.version));
+    context.shutdown().await;
+    app_handle.restart()
+}
+
+/// Download, verify and install the latest build of a sidecar. Refused while
+/// a download is running, since it would be using the binary being replaced.
+#[tauri::command]
+async fn apply_sidecar_update(component: SidecarComponent, context: tauri::State<'_, Arc<AppContext>>) -> Result<SidecarUpdate, String> {
+    const BUSY: &str = "Downloads are running; wait for them to finish or pause the queue before updating";
+    if context.state.read().await.count_jobs_by_status(&JobStatus::Downloading) > 0 {
+        return Err(BUSY.to_string());
+    }
+
+    let Some(verified) = SidecarManager::download_update(component).await? else {
+        return SidecarManager::current_update_status(component).await;
+    };
+
+    // Hold the state lock while swapping so no job can start in the meantime
+    let state_guard = context.state.read().await;
+    if state_guard.count_jobs_by_status(&JobStatus::Downloading) > 0 {
+        return Err(BUSY.to_string());
+    }
+    let installed = SidecarManager::install_update(verified);
+    drop(state_guard);
+    installed
+}
+
+fn get_state_file_path() -> PathBuf {
+    // Use a simple approach for state file location
+    let app_data_dir = std::env::current_dir()
+        .unwrap_or_else(|_| PathBuf::from("."));
+    app_data_dir.join(".gytmdl-gui").join("state.json")
+}
+
+fn get_log_dir() -> PathBuf {
+    get_state_file_path().with_file_name("logs")
+}
+
+/// Start, stop or reconfigure writing the debug log to files
+fn apply_log_file_settings(config: &AppConfig) {
+    DEBUG_LOGGER.set_file_output(config.log_to_file.then(|| {
+        LogFileSettings::new(get_log_dir(), config.log_max_file_size_mb, config.log_retention_days)
+    }));
+}
+
+/// State read from disk at startup, with what was cleaned up while loading it
+struct LoadedState {
+    state: Arc<RwLock<AppState>>,
+    cleanup_summary: Option<CleanupSummary>,
+    /// Jobs that were downloading when the app last stopped, now queued again
+    interrupted_jobs: Vec<String>,
+}
+
+fn initialize_app_state() -> LoadedState {
+    let state_file = get_state_file_path();
+    let config_manager = ConfigManager::with_default_path();
+    
+    // Try to load existing state, fallback to default if it fails
+    let mut app_state = match AppState::load_from_file(&state_file) {
+        Ok(state) => {
+            DEBUG_LOGGER.info("startup", format!("Loaded existing state from: {:?}", state_file));
+            state
+        }
+        Err(e) if state_file.exists() => match AppState::load_from_backups(&state_file) {
+            Some((index, state)) => {
+                DEBUG_LOGGER.warn("startup", format!("Failed to load state from {:?}: {}. Restored backup {}.", state_file, e, index));
+                state
+            }
+            None => {
+                DEBUG_LOGGER.warn("startup", format!("Failed to load state from {:?}: {}. No usable backup, using default state.", state_file, e));
+                AppState::default()
+            }
+        },
+        Err(e) => {
+            DEBUG_LOGGER.warn("startup", format!("Failed to load state from {:?}: {}. Using default state.", state_file, e));
+            AppState::default()
+        }
+    };
+    
+    // Load configuration separately and update state
+    match config_manager.load_config() {
+        Ok(config) => {
+            DEBUG_LOGGER.info("startup", format!("Loaded configuration from: {:?}", config_manager.get_config_file_path()));
+            app_state.config = config;
+        }
+        Err(e) => match config_manager.load_config_from_backups() {
+            Some((index, config)) => {
+                DEBUG_LOGGER.warn("startup", format!("Failed to load config: {}. Restored backup {}.", e, index));
+                app_state.config = config;
+            }
+            None => {
+                DEBUG_LOGGER.warn("startup", format!("Failed to load config: {}. Using default config.", e));
+                app_state.config = AppConfig::default();
+                // Try to save the default config
+                if let Err(save_err) = config_manager.save_config(&app_state.config) {
+                    DEBUG_LOGGER.error("startup", format!("Failed to save default config: {}", save_err));
+                }
+            }
+        },
+    }
+
+    apply_log_file_settings(&app_state.config);
+
+    // Nothing is running yet, so a job still marked Downloading was cut off
+    let interrupted_jobs = app_state.recover_interrupted_jobs();
+    if !interrupted_jobs.is_empty() {
+        DEBUG_LOGGER.info("startup", format!("Re-queued {} interrupted job(s)", interrupted_jobs.len()));
+    }
+
+    // Move old finished jobs to history and drop duplicate queued URLs
+    let mut cleanup_summary = None;
+    if app_state.config.prune_jobs_on_load {
+        let retention_days = app_state.config.job_retention_days;
+        match HistoryStore::new().prune_state(&mut app_state, retention_days) {
+            Ok(summary) if !summary.is_empty() => {
+                DEBUG_LOGGER.info("startup", format!(
+                    "Archived {} old jobs and removed {} duplicate queued jobs",
+                    summary.archived_jobs, summary.deduplicated_jobs
+                ));
+                if let Err(e) = app_state.save_to_file(&state_file) {
+                    DEBUG_LOGGER.error("startup", format!("Failed to save pruned state: {}", e));
+                }
+                cleanup_summary = Some(summary);
+            }
+            Ok(_) => {}
+            Err(e) => DEBUG_LOGGER.error("startup", format!("Failed to prune jobs: {}", e)),
+        }
+    }
+
+    // Keep only the configured number of finished jobs in the queue
+    if let Some(keep) = app_state.config.max_completed_jobs_in_memory {
+        match HistoryStore::new().archive_overflow(&mut app_state, keep as usize) {
+            Ok(0) => {}
+            Ok(archived) => {
+                DEBUG_LOGGER.info("startup", format!("Moved {} finished jobs over the limit to history", archived));
+                if let Err(e) = app_state.save_to_file(&state_file) {
+                    DEBUG_LOGGER.error("startup", format!("Failed to save pruned state: {}", e));
+                }
+            }
+            Err(e) => DEBUG_LOGGER.error("startup", format!("Failed to move finished jobs to history: {}", e)),
+        }
+    }
+
+    // Drop removed jobs that have been in the trash past the retention period
+    let purged = app_state.purge_expired_trash(Utc::now());
+    if purged > 0 {
+        DEBUG_LOGGER.info("startup", format!("Deleted {} jobs from the trash", purged));
+    }
+
+    LoadedState {
+        state: Arc::new(RwLock::new(app_state)),
+        cleanup_summary,
+        interrupted_jobs,
+    }
+}
+
+/// Removing a running job needs the PIN when the app lock is on
+#[tauri::command]
+async fn remove_job(job_id: String, pin: Option<String>, context: tauri::State<'_, Arc<AppContext>>) -> Result<(), AppError> {
+    // Check if job exists
+    let active = {
+        let state_guard = context.state.read().await;
+        match state_guard.get_job(&job_id) {
+            Some(job) => job.is_active(),
+            None => return Err(AppError::NotFound("Job")),
+        }
+    };
+    if active {
+        require_unlocked(pin.as_deref(), &context).await?;
+    }
+
+    // Move the job to the trash so it can be restored
+    let mut state_guard = context.state.write().await;
+    state_guard.purge_expired_trash(Utc::now());
+    state_guard.trash_job(&job_id);
+    Ok(())
+}
+
+/// Jobs removed within the trash retention period, oldest first
+#[tauri::command]
+async fn get_trash(context: tauri::State<'_, Arc<AppContext>>) -> Result<Vec<TrashedJob>, String> {
+    let mut state_guard = context.state.write().await;
+    state_guard.purge_expired_trash(Utc::now());
+    Ok(state_guard.trash.clone())
+}
+
+/// Put a removed job back in the queue, dispatching it again if it was queued
+#[tauri::command]
+async fn restore_job(job_id: String, context: tauri::State<'_, Arc<AppContext>>) -> Result<(), String> {
+    let queued = {
+        let mut state_guard = context.state.write().await;
+        state_guard.restore_job(&job_id)?.status == JobStatus::Queued
+    };
+
+    if queued {
+        if let Some(queue_manager) = context.queue_manager.read().await.as_ref() {
+            queue_manager.submit_job(job_id).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Permanently delete every job in the trash. Returns the number deleted.
+#[tauri::command]
+async fn empty_trash(pin: Option<String>, context: tauri::State<'_, Arc<AppContext>>) -> Result<usize, AppError> {
+    require_unlocked(pin.as_deref(), &context).await?;
+    Ok(context.state.write().await.empty_trash())
+}
+
+#[tauri::command]
+async fn set_job_priority(job_id: String, priority: u32, context: tauri::State<'_, Arc<AppContext>>) -> Result<(), String> {
+    let mut state_guard = context.state.write().await;
+    if state_guard.set_job_priority(&job_id, priority) {
+        Ok(())
+    } else {
+        Err("Job not found".to_string())
+    }
+}
+
+#[tauri::command]
+async fn reorder_job(job_id: String, new_index: usize, context: tauri::State<'_, Arc<AppContext>>) -> Result<(), String> {
+    let mut state_guard = context.state.write().await;
+    if state_guard.reorder_job(&job_id, new_index) {
+        Ok(())
+    } else {
+        Err("Job not found".to_string())
+    }
+}
+
+#[tauri::command]
+async fn clear_completed_jobs(pin: Option<String>, context: tauri::State<'_, Arc<AppContext>>) -> Result<(), AppError> {
+    require_unlocked(pin.as_deref(), &context).await?;
+    let mut state_guard = context.state.write().await;
+    state_guard.jobs.retain(|job| job.status != JobStatus::Completed);
+    state_guard.prune_empty_groups();
+    Ok(())
+}
+
+/// Move finished jobs to history, keeping the `keep` most recent (by default
+/// the configured limit, or none). Returns the number of jobs moved.
+#[tauri::command]
+async fn archive_old_jobs(keep: Option<u32>, context: tauri::State<'_, Arc<AppContext>>) -> Result<usize, String> {
+    let keep = match keep {
+        Some(keep) => keep,
+        None => context.state.read().await.config.max_completed_jobs_in_memory.unwrap_or(0),
+    };
+    if let Some(queue_manager) = context.queue_manager.read().await.as_ref() {
+        queue_manager.archive_old_jobs(keep as usize).await
+    } else {
+        Err("Queue manager not available".to_string())
+    }
+}
+
+/// Close the app after the user confirmed stopping running downloads
+#[tauri::command]
+async fn confirm_exit(app_handle: tauri::AppHandle, context: tauri::State<'_, Arc<AppContext>>) -> Result<(), String> {
+    context.exit_confirmed.store(true, Ordering::SeqCst);
+    app_handle.exit(0);
+    Ok(())
 }
 
 #[tauri::command]
@@ -291,6 +1589,24 @@ async fn save_state(context: tauri::State<'_, Arc<AppContext>>) -> Result<(), St
     Ok(())
 }
 
+/// Rotated backups of the state file, most recent first
+#[tauri::command]
+async fn list_state_backups() -> Result<Vec<BackupInfo>, String> {
+    Ok(atomic_file::list_backups(&get_state_file_path()))
+}
+
+/// Replace the job queue with backup `index` from `list_state_backups`.
+/// Needs the PIN when the app lock is on.
+#[tauri::command]
+async fn restore_state_backup(
+    index: usize,
+    pin: Option<String>,
+    context: tauri::State<'_, Arc<AppContext>>,
+) -> Result<(), AppError> {
+    require_unlocked(pin.as_deref(), &context).await?;
+    Ok(context.restore_state_backup(index).await?)
+}
+
 // Configuration Management Commands (Task 5.2)
 
 #[tauri::command]
@@ -305,53 +1621,192 @@ struct UpdateConfigRequest {
 }
 
 #[tauri::command]
-async fn update_config(
-    request: UpdateConfigRequest,
-    context: tauri::State<'_, Arc<AppContext>>
-) -> Result<(), String> {
+async fn update_config(
+    request: UpdateConfigRequest,
+    context: tauri::State<'_, Arc<AppContext>>
+) -> Result<(), AppError> {
+    let config_manager = ConfigManager::with_default_path();
+    
+    // Validate the new config
+    config_manager.validate_config(&request.config)?;
+    
+    let previous = context.state.read().await.config.clone();
+    apply_config(&config_manager, request.config, &context).await?;
+    record_config_change(&previous, &context, None).await;
+    Ok(())
+}
+
+/// Add the change from `previous` to the current config to the changelog.
+/// The config is already saved, so failing to record it is only logged.
+async fn record_config_change(previous: &AppConfig, context: &AppContext, rollback_of: Option<String>) {
+    let current = context.state.read().await.config.clone();
+    let previous = previous.clone();
+    let recorded = tokio::task::spawn_blocking(move || {
+        ConfigHistory::new().record(&previous, &current, rollback_of)
+    }).await;
+    match recorded {
+        Ok(Ok(_)) => {}
+        Ok(Err(e)) => DEBUG_LOGGER.warn("config", format!("Failed to record config change: {}", e)),
+        Err(e) => DEBUG_LOGGER.warn("config", format!("Recording config change panicked: {}", e)),
+    }
+}
+
+/// Saved config changes, newest first
+#[tauri::command]
+async fn get_config_history(limit: Option<usize>) -> Result<Vec<ConfigChange>, AppError> {
+    let mut changes = tokio::task::spawn_blocking(|| ConfigHistory::new().load())
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("Failed to read config history: {}", e))?;
+    changes.reverse();
+    if let Some(limit) = limit {
+        changes.truncate(limit);
+    }
+    Ok(changes)
+}
+
+/// Restore the config as it was before a recorded change. The rollback is
+/// recorded as a change of its own. Secrets aren't in the changelog, so the
+/// current ones are kept.
+#[tauri::command]
+async fn rollback_config(
+    change_id: String,
+    context: tauri::State<'_, Arc<AppContext>>,
+) -> Result<AppConfig, AppError> {
+    let change = tokio::task::spawn_blocking({
+        let change_id = change_id.clone();
+        move || ConfigHistory::new().get(&change_id)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| format!("Failed to read config history: {}", e))?
+    .ok_or(AppError::NotFound("Config change"))?;
+
+    let previous = context.state.read().await.config.clone();
+    let mut snapshot = change.previous;
+    config_history::restore_secrets(&mut snapshot, &previous);
+
+    let config_manager = ConfigManager::with_default_path();
+    let config = config_manager.config_from_snapshot(snapshot)?;
+
+    apply_config(&config_manager, config, &context).await?;
+    record_config_change(&previous, &context, Some(change_id)).await;
+    Ok(context.state.read().await.config.clone())
+}
+
+/// Make a validated config current: update the state, save it and apply it to the queue
+async fn apply_config(config_manager: &ConfigManager, mut config: AppConfig, context: &AppContext) -> Result<(), AppError> {
+    // Update the state; the lock PIN can only be changed through set_app_lock
+    {
+        let mut state_guard = context.state.write().await;
+        config.lock_pin_hash = state_guard.config.lock_pin_hash.clone();
+        state_guard.config = config.clone();
+    }
+    
+    // Save the config to file
+    config_manager.save_config(&config)?;
+    
+    apply_log_file_settings(&config);
+
+    // Apply the concurrent limit to the running queue
+    if let Some(queue_manager) = context.queue_manager.read().await.as_ref() {
+        queue_manager.set_concurrent_limit(config.concurrent_limit).await?;
+    }
+    
+    Ok(())
+}
+
+#[tauri::command]
+async fn export_config(
+    path: String,
+    include_cookies: Option<bool>,
+    context: tauri::State<'_, Arc<AppContext>>,
+) -> Result<(), AppError> {
+    let config = context.state.read().await.config.clone();
+    ConfigManager::with_default_path()
+        .export_config(&config, std::path::Path::new(&path), include_cookies.unwrap_or(false))?;
+    Ok(())
+}
+
+#[derive(serde::Serialize)]
+struct ImportConfigResult {
+    config: AppConfig,
+    /// Settings that could not be carried over, e.g. cookie files missing on this machine
+    warnings: Vec<String>,
+}
+
+#[tauri::command]
+async fn import_config(path: String, context: tauri::State<'_, Arc<AppContext>>) -> Result<ImportConfigResult, AppError> {
+    let config_manager = ConfigManager::with_default_path();
+    let imported = config_manager.import_config(std::path::Path::new(&path))?;
+
+    apply_config(&config_manager, imported.config.clone(), &context).await?;
+    Ok(ImportConfigResult {
+        config: imported.config,
+        warnings: imported.warnings,
+    })
+}
+
+#[tauri::command]
+async fn set_concurrent_limit(limit: usize, context: tauri::State<'_, Arc<AppContext>>) -> Result<(), AppError> {
     let config_manager = ConfigManager::with_default_path();
-    
-    // Validate the new config
-    config_manager.validate_config(&request.config)
-        .map_err(|e| format!("Configuration validation failed: {}", e))?;
-    
-    // Update the state
-    {
-        let mut state_guard = context.state.write().await;
-        state_guard.config = request.config.clone();
-    }
-    
-    // Save the config to file
-    config_manager.save_config(&request.config)
-        .map_err(|e| format!("Failed to save configuration: {}", e))?;
-    
-    // Update queue manager concurrent limit if it changed
-    if let Some(_queue_manager) = context.queue_manager.read().await.as_ref() {
-        // Note: QueueManager::set_concurrent_limit requires &mut self, 
-        // so we'd need to restructure this or add a method that works with Arc<RwLock<>>
-        // For now, we'll just log that the limit should be updated on next restart
-        println!("Configuration updated. Queue manager concurrent limit will be updated on next restart.");
+
+    let mut config = context.state.read().await.config.clone();
+    config.concurrent_limit = limit;
+    config_manager.validate_config(&config)?;
+
+    match context.queue_manager.read().await.as_ref() {
+        Some(queue_manager) => queue_manager.set_concurrent_limit(limit).await?,
+        None => {
+            let mut state_guard = context.state.write().await;
+            state_guard.config.concurrent_limit = limit;
+            state_guard.concurrent_limit = limit;
+        }
     }
-    
+
+    config_manager.save_config(&config)?;
     Ok(())
 }
 
+/// Change queue settings while downloads run and save them. The result
+/// lists which settings took effect.
+#[tauri::command]
+async fn apply_runtime_settings(
+    settings: RuntimeSettings,
+    context: tauri::State<'_, Arc<AppContext>>,
+) -> Result<RuntimeSettingsResult, AppError> {
+    let result = match context.queue_manager.read().await.as_ref() {
+        Some(queue_manager) => queue_manager.apply_runtime_settings(settings).await?,
+        None => return Err(AppError::Unavailable("Queue manager")),
+    };
+
+    let config = context.state.read().await.config.clone();
+    ConfigManager::with_default_path().save_config(&config)?;
+    Ok(result)
+}
+
 #[tauri::command]
 async fn reset_config_to_defaults(
+    pin: Option<String>,
     context: tauri::State<'_, Arc<AppContext>>
-) -> Result<AppConfig, String> {
+) -> Result<AppConfig, AppError> {
+    require_unlocked(pin.as_deref(), &context).await?;
     let config_manager = ConfigManager::with_default_path();
-    let default_config = AppConfig::default();
+    let mut default_config = AppConfig::default();
     
-    // Update the state
+    // Update the state, keeping the lock in place
     {
         let mut state_guard = context.state.write().await;
+        default_config.lock_pin_hash = state_guard.config.lock_pin_hash.clone();
         state_guard.config = default_config.clone();
     }
     
     // Save the default config to file
-    config_manager.save_config(&default_config)
-        .map_err(|e| format!("Failed to save default configuration: {}", e))?;
+    config_manager.save_config(&default_config)?;
+
+    if let Some(queue_manager) = context.queue_manager.read().await.as_ref() {
+        queue_manager.set_concurrent_limit(default_config.concurrent_limit).await?;
+    }
     
     Ok(default_config)
 }
@@ -413,11 +1868,14 @@ async fn import_cookies(request: CookieImportRequest, context: tauri::State<'_,
     let source_path = std::path::Path::new(&request.file_path);
     
     match cookie_manager.import_cookies(source_path).await {
-        Ok(_cookie_info) => Ok(CookieImportResult {
-            success: true,
-            cookies_count: Some(1), // We don't track individual cookie count, just indicate success
-            error: None,
-        }),
+        Ok(_cookie_info) => {
+            context.state.write().await.degraded.remove(DegradedFlags::COOKIES_SUSPECT);
+            Ok(CookieImportResult {
+                success: true,
+                cookies_count: Some(1), // We don't track individual cookie count, just indicate success
+                error: None,
+            })
+        }
         Err(e) => Ok(CookieImportResult {
             success: false,
             cookies_count: None,
@@ -474,6 +1932,22 @@ async fn validate_cookies(context: tauri::State<'_, Arc<AppContext>>) -> Result<
     }
 }
 
+#[tauri::command]
+async fn inspect_cookie_file(path: String, context: tauri::State<'_, Arc<AppContext>>) -> Result<CookieInfo, AppError> {
+    let cookie_manager = context.cookie_manager.read().await;
+
+    Ok(cookie_manager.inspect_cookies(std::path::Path::new(&path)).await?)
+}
+
+#[tauri::command]
+async fn extract_cookies_from_browser(browser: String, context: tauri::State<'_, Arc<AppContext>>) -> Result<CookieInfo, AppError> {
+    let cookie_manager = context.cookie_manager.read().await;
+
+    let info = cookie_manager.extract_cookies_from_browser(&browser).await?;
+    context.state.write().await.degraded.remove(DegradedFlags::COOKIES_SUSPECT);
+    Ok(info)
+}
+
 #[tauri::command]
 async fn get_cookies_path(context: tauri::State<'_, Arc<AppContext>>) -> Result<String, String> {
     let cookie_manager = context.cookie_manager.read().await;
@@ -482,32 +1956,642 @@ async fn get_cookies_path(context: tauri::State<'_, Arc<AppContext>>) -> Result<
 }
 
 #[tauri::command]
-async fn clear_cookies(context: tauri::State<'_, Arc<AppContext>>) -> Result<(), String> {
+async fn clear_cookies(pin: Option<String>, context: tauri::State<'_, Arc<AppContext>>) -> Result<(), AppError> {
+    require_unlocked(pin.as_deref(), &context).await?;
     let cookie_manager = context.cookie_manager.read().await;
     
-    cookie_manager.clear_cookies().await
+    Ok(cookie_manager.clear_cookies().await?)
+}
+
+// Temp File Commands
+
+/// Space used by the temp folder and the stale entries that can be deleted
+#[tauri::command]
+async fn get_temp_usage(context: tauri::State<'_, Arc<AppContext>>) -> Result<TempUsage, AppError> {
+    Ok(temp_janitor::scan_state(&context.state).await)
+}
+
+/// Delete stale temp entries once the user has confirmed. The folder is
+/// scanned again so entries that became active meanwhile are kept.
+#[tauri::command]
+async fn clean_temp_files(context: tauri::State<'_, Arc<AppContext>>) -> Result<TempCleanup, AppError> {
+    let usage = temp_janitor::scan_state(&context.state).await;
+    Ok(tokio::task::spawn_blocking(move || temp_janitor::clean(&usage))
+        .await
+        .map_err(|e| e.to_string())?)
+}
+
+// Listen Log Commands
+
+/// Selected listen log service and which credentials are saved
+#[tauri::command]
+async fn get_listen_log_status(context: tauri::State<'_, Arc<AppContext>>) -> Result<ListenLogStatus, AppError> {
+    let credentials = tokio::task::spawn_blocking(|| CredentialStore::new().load())
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(ListenLogStatus::new(&context.state.read().await.config, &credentials))
+}
+
+/// Save the listen log token (ListenBrainz user token or Last.fm session key)
+/// and Last.fm API secret. Blank values remove them.
+#[tauri::command]
+async fn set_listen_log_credentials(
+    token: Option<String>,
+    api_secret: Option<String>,
+    context: tauri::State<'_, Arc<AppContext>>,
+) -> Result<ListenLogStatus, AppError> {
+    let non_blank = |value: Option<String>| value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+    let credentials = ListenLogCredentials {
+        token: non_blank(token),
+        api_secret: non_blank(api_secret),
+    };
+    let saved = credentials.clone();
+    tokio::task::spawn_blocking(move || CredentialStore::new().save(&saved))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+    Ok(ListenLogStatus::new(&context.state.read().await.config, &credentials))
+}
+
+/// Log a completed job again, e.g. after a failed submission
+#[tauri::command]
+async fn resubmit_listen(
+    job_id: String,
+    context: tauri::State<'_, Arc<AppContext>>,
+) -> Result<ListenSubmission, AppError> {
+    let (config, job) = {
+        let state_guard = context.state.read().await;
+        let job = state_guard.get_job(&job_id).cloned().ok_or(AppError::NotFound("Job"))?;
+        (state_guard.config.clone(), job)
+    };
+    if job.status != JobStatus::Completed {
+        return Err(AppError::Other("Only completed jobs can be logged".to_string()));
+    }
+
+    let submission = listen_log::submit_job(&config, &job)
+        .await
+        .ok_or_else(|| AppError::Other("Listen logging is off".to_string()))?;
+    if let Some(job) = context.state.write().await.get_job_mut(&job_id) {
+        job.listen_submission = Some(submission.clone());
+    }
+    Ok(submission)
+}
+
+// App Lock Commands
+
+/// Fail unless the app lock is off or `pin` unlocks it
+async fn require_unlocked(pin: Option<&str>, context: &AppContext) -> Result<(), AppError> {
+    let stored = context.state.read().await.config.lock_pin_hash.clone();
+    let pin = pin.map(str::to_string);
+    tokio::task::spawn_blocking(move || app_lock::check(stored.as_deref(), pin.as_deref()))
+        .await
+        .map_err(|e| e.to_string())??;
+    Ok(())
+}
+
+/// Whether destructive actions need the PIN
+#[tauri::command]
+async fn get_app_lock_enabled(context: tauri::State<'_, Arc<AppContext>>) -> Result<bool, AppError> {
+    Ok(context.state.read().await.config.lock_pin_hash.is_some())
+}
+
+/// Set, change or (with `new_pin` unset) remove the PIN. Needs the current
+/// PIN when one is set.
+#[tauri::command]
+async fn set_app_lock(
+    new_pin: Option<String>,
+    current_pin: Option<String>,
+    context: tauri::State<'_, Arc<AppContext>>,
+) -> Result<(), AppError> {
+    require_unlocked(current_pin.as_deref(), &context).await?;
+    let lock_pin_hash = match new_pin.as_deref() {
+        Some(pin) => {
+            app_lock::validate_pin(pin)?;
+            let pin = pin.to_string();
+            Some(tokio::task::spawn_blocking(move || app_lock::hash_pin(&pin)).await.map_err(|e| e.to_string())?)
+        }
+        None => None,
+    };
+
+    let config = {
+        let mut state_guard = context.state.write().await;
+        state_guard.config.lock_pin_hash = lock_pin_hash;
+        state_guard.config.clone()
+    };
+    ConfigManager::with_default_path().save_config(&config)?;
+    DEBUG_LOGGER.info("config", if config.lock_pin_hash.is_some() { "App lock PIN set" } else { "App lock removed" });
+    Ok(())
+}
+
+/// Check a PIN without doing anything, e.g. before showing locked settings
+#[tauri::command]
+async fn verify_app_lock(pin: String, context: tauri::State<'_, Arc<AppContext>>) -> Result<bool, AppError> {
+    let stored = context.state.read().await.config.lock_pin_hash.clone();
+    match stored {
+        Some(stored) => Ok(tokio::task::spawn_blocking(move || app_lock::verify_pin(&pin, &stored))
+            .await
+            .map_err(|e| e.to_string())?),
+        None => Ok(true),
+    }
+}
+
+// API Token Commands
+
+#[derive(serde::Deserialize)]
+struct CreateApiTokenRequest {
+    name: String,
+    scope: TokenScope,
+}
+
+#[derive(serde::Serialize)]
+struct CreateApiTokenResponse {
+    token: ApiTokenInfo,
+    /// The bearer secret; only returned once, at creation
+    secret: Option<String>,
+}
+
+#[tauri::command]
+async fn create_api_token(request: CreateApiTokenRequest, context: tauri::State<'_, Arc<AppContext>>) -> Result<CreateApiTokenResponse, String> {
+    let (token, secret) = context.api_tokens.write().await
+        .create_token(&request.name, request.scope)
+        .map_err(|e| e.to_string())?;
+    Ok(CreateApiTokenResponse { token, secret: Some(secret) })
+}
+
+#[tauri::command]
+async fn list_api_tokens(context: tauri::State<'_, Arc<AppContext>>) -> Result<Vec<ApiTokenInfo>, String> {
+    Ok(context.api_tokens.read().await.list())
+}
+
+#[tauri::command]
+async fn revoke_token(token_id: String, context: tauri::State<'_, Arc<AppContext>>) -> Result<(), String> {
+    let mut token_store = context.api_tokens.write().await;
+    match token_store.revoke(&token_id) {
+        Ok(true) => Ok(()),
+        Ok(false) => Err("Token not found".to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Read the clipboard once and queue or offer any newly copied URLs that
+/// aren't already in the queue
+async fn check_clipboard(context: &AppContext, app_handle: &tauri::AppHandle, watcher: &mut ClipboardWatcher) {
+    let action = {
+        let config = &context.state.read().await.config;
+        if !config.clipboard_watcher {
+            watcher.reset();
+            return;
+        }
+        config.clipboard_action
+    };
+
+    // Empty or non-text clipboards read as errors
+    let Ok(text) = app_handle.clipboard().read_text() else {
+        return;
+    };
+
+    for copied in watcher.observe(&text) {
+        let url = match prepare_queue_url(&copied, context).await {
+            Ok(url) => url,
+            Err(_) => continue,
+        };
+        let already_queued = context.state.read().await.jobs.iter().any(|job| job.url == url);
+        if already_queued {
+            continue;
+        }
+
+        match action {
+            ClipboardAction::Prompt => context.events.read().await.clipboard_url(&url),
+            ClipboardAction::AutoQueue => match enqueue_url(url.clone(), JobOptions::default(), context).await {
+                Ok(job_id) => DEBUG_LOGGER.info("clipboard", format!("Queued copied URL {} as job {}", url, job_id)),
+                Err(e) => DEBUG_LOGGER.warn("clipboard", format!("Could not queue copied URL {}: {}", url, e)),
+            },
+        }
+    }
+}
+
+/// Queue the URL from a deep link carrying an API token, or hand it to the
+/// frontend, which asks before queueing
+async fn handle_deep_link(context: &AppContext, link: &str) {
+    let request = match deep_link::parse(link) {
+        Ok(request) => request,
+        Err(e) => {
+            DEBUG_LOGGER.warn("deep_link", e.to_string());
+            return;
+        }
+    };
+    let url = match prepare_queue_url(&request.url, context).await {
+        Ok(url) => url,
+        Err(e) => {
+            DEBUG_LOGGER.warn("deep_link", format!("Ignoring deep link from {}: {}", request.source, e));
+            return;
+        }
+    };
+
+    // The link's source is self-declared and proves nothing, so without a
+    // token the user confirms. A token that may enqueue skips the question.
+    let Some(token) = request.token.as_deref() else {
+        context.events.read().await.deep_link(&url, &request.source);
+        return;
+    };
+    if let Err(e) = context.api_tokens.write().await.authorize_token(token, ApiAction::Enqueue) {
+        DEBUG_LOGGER.warn("deep_link", format!("Ignoring deep link from {}: {}", request.source, e));
+        return;
+    }
+    match enqueue_url(url.clone(), JobOptions::default(), context).await {
+        Ok(job_id) => DEBUG_LOGGER.info("deep_link", format!("Queued {} from {} as job {}", url, request.source, job_id)),
+        Err(e) => DEBUG_LOGGER.warn("deep_link", format!("Could not queue {} from {}: {}", url, request.source, e)),
+    }
+}
+
+/// Queue a URL the app was launched with. It came from the local command
+/// line, so unlike a deep link it needs no confirmation.
+async fn queue_launch_url(context: &AppContext, url: &str) {
+    let result = match prepare_queue_url(url, context).await {
+        Ok(url) => enqueue_url(url, JobOptions::default(), context).await,
+        Err(e) => Err(e.to_string()),
+    };
+    match result {
+        Ok(job_id) => DEBUG_LOGGER.info("startup", format!("Queued {} from the command line as job {}", url, job_id)),
+        Err(e) => DEBUG_LOGGER.warn("startup", format!("Could not queue {} from the command line: {}", url, e)),
+    }
+}
+
+/// Bring the main window to the front, e.g. when the app is launched again
+fn focus_main_window(app_handle: &tauri::AppHandle) {
+    if let Some(window) = app_handle.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+// Subscription Commands
+
+/// Outcome of checking one subscription
+#[derive(serde::Serialize)]
+struct SubscriptionCheckResult {
+    subscription_id: String,
+    new_entries: usize,
+    job_ids: Vec<String>,
+    error: Option<String>,
+}
+
+/// List each subscription (all of them, or only those that are due) and queue
+/// anything new. The store lock isn't held while yt-dlp runs.
+async fn check_subscriptions(context: &AppContext, only_due: bool) -> Vec<SubscriptionCheckResult> {
+    let pending: Vec<Subscription> = {
+        let store = context.subscriptions.read().await;
+        if only_due { store.due(Utc::now()) } else { store.all() }
+    };
+
+    let mut results = Vec::new();
+    for subscription in pending {
+        let listing = subscriptions::list_entries(&context.isolation, &subscription.url).await;
+        let recorded = {
+            let mut store = context.subscriptions.write().await;
+            match listing {
+                Ok(entries) => store.record_listing(&subscription.id, &entries, Utc::now()),
+                Err(e) => {
+                    let _ = store.record_error(&subscription.id, &e.to_string(), Utc::now());
+                    Err(e)
+                }
+            }
+        };
+
+        let new_entries = match recorded {
+            Ok(new_entries) => new_entries,
+            Err(e) => {
+                DEBUG_LOGGER.warn("subscriptions", format!("Checking {} failed: {}", subscription.url, e));
+                results.push(SubscriptionCheckResult {
+                    subscription_id: subscription.id.clone(),
+                    new_entries: 0,
+                    job_ids: Vec::new(),
+                    error: Some(e.to_string()),
+                });
+                continue;
+            }
+        };
+
+        let mut job_ids = Vec::new();
+        if !new_entries.is_empty() {
+            let name = subscription.name.clone();
+            let group_id = context.state.write().await.add_group(subscription.url.clone(), name);
+            let options = JobOptions { group_id: Some(group_id), ..JobOptions::default() };
+            job_ids = enqueue_group(&new_entries, &options, "subscriptions", context).await;
+            if job_ids.is_empty() {
+                context.state.write().await.prune_empty_groups();
+            }
+        }
+
+        if !job_ids.is_empty() {
+            DEBUG_LOGGER.info("subscriptions", format!("Queued {} new track(s) from {}", job_ids.len(), subscription.url));
+            context.events.read().await.subscription_content(&subscription, job_ids.clone());
+        }
+        results.push(SubscriptionCheckResult {
+            subscription_id: subscription.id.clone(),
+            new_entries: new_entries.len(),
+            job_ids,
+            error: None,
+        });
+    }
+
+    results
+}
+
+#[derive(serde::Deserialize)]
+struct AddSubscriptionRequest {
+    url: String,
+    name: Option<String>,
+    interval_minutes: u32,
+}
+
+#[tauri::command]
+async fn add_subscription(request: AddSubscriptionRequest, context: tauri::State<'_, Arc<AppContext>>) -> Result<SubscriptionInfo, String> {
+    let url = prepare_queue_url(&request.url, &context).await.map_err(|e| e.to_string())?;
+    context.subscriptions.write().await
+        .add(&url, request.name, request.interval_minutes)
         .map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+async fn list_subscriptions(context: tauri::State<'_, Arc<AppContext>>) -> Result<Vec<SubscriptionInfo>, String> {
+    Ok(context.subscriptions.read().await.list())
+}
+
+#[tauri::command]
+async fn remove_subscription(subscription_id: String, context: tauri::State<'_, Arc<AppContext>>) -> Result<(), String> {
+    match context.subscriptions.write().await.remove(&subscription_id) {
+        Ok(true) => Ok(()),
+        Ok(false) => Err("Subscription not found".to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+#[tauri::command]
+async fn check_subscriptions_now(context: tauri::State<'_, Arc<AppContext>>) -> Result<Vec<SubscriptionCheckResult>, String> {
+    Ok(check_subscriptions(&context, false).await)
+}
+
+/// Run from the command line without a window when the arguments include
+/// `--headless`. Returns the exit code, or `None` to start the app normally.
+#[cfg(feature = "headless")]
+pub fn run_headless(args: &[String]) -> Option<i32> {
+    let args = match modules::headless::parse_args(args) {
+        Ok(Some(args)) => args,
+        Ok(None) => return None,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, modules::headless::USAGE);
+            return Some(2);
+        }
+    };
+    if args.help {
+        println!("{}", modules::headless::USAGE);
+        return Some(0);
+    }
+
+    // Interrupted jobs are queued again and run with the rest on --wait
+    let context = AppContext::new(initialize_app_state().state);
+    Some(tauri::async_runtime::block_on(run_headless_queue(&context, args)))
+}
+
+#[cfg(feature = "headless")]
+async fn run_headless_queue(context: &AppContext, args: modules::headless::HeadlessArgs) -> i32 {
+    let mut exit_code = 0;
+
+    let cookies_path = context.state.read().await.config.cookies_path.clone();
+    if let Some(cookies_path) = cookies_path {
+        match context.cookie_manager.read().await.inspect_cookies(&cookies_path).await {
+            Ok(info) => {
+                if let Some(warning) = info.expiration_warning {
+                    DEBUG_LOGGER.warn("headless", format!("Cookies: {}", warning));
+                }
+            }
+            Err(e) => DEBUG_LOGGER.warn("headless", format!("Cookies at {:?} can't be used: {}", cookies_path, e)),
+        }
+    }
+
+    for url in &args.add {
+        let result = match prepare_queue_url(url, context).await {
+            Ok(url) => enqueue_url(url, JobOptions::default(), context).await,
+            Err(e) => Err(e.to_string()),
+        };
+        match result {
+            Ok(job_id) => println!("Queued {} as job {}", url, job_id),
+            Err(e) => {
+                eprintln!("Could not queue {}: {}", url, e);
+                exit_code = 1;
+            }
+        }
+    }
+
+    if args.wait {
+        if let Err(e) = context.initialize_headless_queue_manager().await {
+            eprintln!("{}", e);
+            context.shutdown().await;
+            return 1;
+        }
+
+        tokio::select! {
+            failed = wait_for_due_jobs(context) => {
+                if failed > 0 {
+                    exit_code = 1;
+                }
+            }
+            _ = tokio::signal::ctrl_c() => {
+                eprintln!("Interrupted; unfinished jobs stay queued");
+                exit_code = 130;
+            }
+        }
+    }
+
+    context.shutdown().await;
+    exit_code
+}
+
+/// Submit every queued job that is due and wait until they have all
+/// finished, printing each outcome. Returns how many failed.
+#[cfg(feature = "headless")]
+async fn wait_for_due_jobs(context: &AppContext) -> usize {
+    if let Some(queue_manager) = context.queue_manager.read().await.as_ref() {
+        if let Err(e) = queue_manager.process_queued_jobs().await {
+            DEBUG_LOGGER.error("headless", format!("Failed to submit queued jobs: {}", e));
+        }
+    }
+
+    let mut pending: Vec<String> = {
+        let state_guard = context.state.read().await;
+        let now = Utc::now();
+        state_guard.jobs.iter()
+            .filter(|job| !job.is_terminal() && job.is_due(now))
+            .map(|job| job.id.clone())
+            .collect()
+    };
+    println!("Waiting for {} job(s)", pending.len());
+
+    let mut failed = 0;
+    while !pending.is_empty() {
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+
+        let state_guard = context.state.read().await;
+        pending.retain(|job_id| {
+            let Some(job) = state_guard.get_job(job_id) else {
+                return false;
+            };
+            if !job.is_terminal() {
+                return true;
+            }
+            match job.status {
+                JobStatus::Completed => println!("Completed {}", job.url),
+                JobStatus::Failed => {
+                    failed += 1;
+                    println!("Failed {}: {}", job.url, job.error.as_deref().unwrap_or("unknown error"));
+                }
+                _ => println!("Cancelled {}", job.url),
+            }
+            false
+        });
+    }
+    failed
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    let app_state = initialize_app_state();
-    let app_context = Arc::new(AppContext::new(app_state));
+    let LoadedState { state: app_state, cleanup_summary, interrupted_jobs } = initialize_app_state();
+    let app_context = Arc::new(AppContext {
+        startup_cleanup: cleanup_summary,
+        ..AppContext::new(app_state)
+    });
 
     tauri::Builder::default()
+        // Must come first so a second launch exits before it touches the
+        // state file. With its deep-link feature, `gytmdl://` links the second
+        // instance was launched with arrive through `on_open_url` below.
+        .plugin(tauri_plugin_single_instance::init(|app, argv, _cwd| {
+            let logged_args: Vec<String> = argv.iter().map(|arg| deep_link::redact_token(arg)).collect();
+            DEBUG_LOGGER.info("startup", format!("Second instance launched with {:?}", logged_args));
+            focus_main_window(app);
+
+            let context = Arc::clone(app.state::<Arc<AppContext>>().inner());
+            let urls = deep_link::urls_from_args(&argv);
+            tauri::async_runtime::spawn(async move {
+                for url in urls {
+                    queue_launch_url(&context, &url).await;
+                }
+            });
+        }))
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
         .manage(app_context)
         .setup(|app| {
+            DEBUG_LOGGER.attach(EventEmitter::new(app.handle().clone()));
+
+            // Without a configured public key the updater can't verify anything
+            if updater_configured(app.handle()) {
+                app.handle().plugin(tauri_plugin_updater::Builder::new().build())?;
+            }
+
             // Initialize queue manager after Tauri runtime is available
             let app_context = app.state::<Arc<AppContext>>();
             let context_for_init: Arc<AppContext> = Arc::clone(app_context.inner());
+            let app_handle = app.handle().clone();
+
+            // Self-test runs alongside queue startup so the window isn't held up by it
+            let context_for_self_test = Arc::clone(&context_for_init);
+            let events_handle = app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                let report = context_for_self_test.run_self_test().await;
+                if !report.degraded.is_empty() {
+                    DEBUG_LOGGER.warn("startup", format!("Startup self-test found degraded features: {:?}", report.degraded.names()));
+                }
+                EventEmitter::new(events_handle).self_test(&report);
+            });
+
+            tauri::async_runtime::spawn(autosave::run(
+                Arc::clone(&context_for_init.state),
+                get_state_file_path(),
+            ));
+
+            let context_for_clipboard = Arc::clone(&context_for_init);
+            let clipboard_handle = app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                let mut watcher = ClipboardWatcher::new();
+                loop {
+                    tokio::time::sleep(clipboard_watcher::POLL_INTERVAL).await;
+                    check_clipboard(&context_for_clipboard, &clipboard_handle, &mut watcher).await;
+                }
+            });
+
+            let context_for_subscriptions = Arc::clone(&context_for_init);
+            tauri::async_runtime::spawn(async move {
+                loop {
+                    tokio::time::sleep(subscriptions::POLL_INTERVAL).await;
+                    check_subscriptions(&context_for_subscriptions, true).await;
+                }
+            });
+
+            // Installers register the scheme; AppImages and dev builds need it at runtime
+            #[cfg(any(windows, target_os = "linux"))]
+            if let Err(e) = app.deep_link().register_all() {
+                DEBUG_LOGGER.warn("startup", format!("Failed to register {}:// links: {}", deep_link::SCHEME, e));
+            }
+
+            let context_for_links = Arc::clone(&context_for_init);
+            app.deep_link().on_open_url(move |event| {
+                let context = Arc::clone(&context_for_links);
+                let links: Vec<String> = event.urls().into_iter().map(String::from).collect();
+                tauri::async_runtime::spawn(async move {
+                    for link in links {
+                        handle_deep_link(&context, &link).await;
+                    }
+                });
+            });
+            let launch_links: Vec<String> = app.deep_link().get_current()
+                .ok()
+                .flatten()
+                .unwrap_or_default()
+                .into_iter()
+                .map(String::from)
+                .collect();
+            let launch_urls = deep_link::urls_from_args(&std::env::args().collect::<Vec<_>>());
+
+            tauri::async_runtime::spawn(connectivity::run(
+                Arc::clone(&context_for_init.queue_manager),
+                Arc::clone(&context_for_init.state),
+                EventEmitter::new(app_handle.clone()),
+            ));
+
+            tauri::async_runtime::spawn(temp_janitor::run(
+                Arc::clone(&context_for_init.state),
+                EventEmitter::new(app_handle.clone()),
+            ));
+
+            notifier::handle_clicks(&app_handle, focus_main_window);
+
+            tauri::async_runtime::spawn(cookie_monitor::run(
+                Arc::clone(&context_for_init.cookie_manager),
+                Arc::clone(&context_for_init.state),
+                EventEmitter::new(app_handle.clone()),
+                Notifier::new(app_handle.clone()),
+            ));
             
             tauri::async_runtime::spawn(async move {
-                if let Err(e) = context_for_init.initialize_queue_manager().await {
-                    eprintln!("Failed to initialize queue manager: {}", e);
-                    eprintln!("Queue functionality will be limited until gytmdl binary is available");
+                if let Err(e) = context_for_init.initialize_queue_manager(app_handle).await {
+                    DEBUG_LOGGER.error("startup", format!("Failed to initialize queue manager: {}", e));
+                    DEBUG_LOGGER.warn("startup", "Queue functionality will be limited until gytmdl binary is available");
                 } else {
-                    println!("Queue manager initialized successfully");
+                    DEBUG_LOGGER.info("startup", "Queue manager initialized successfully");
+                    context_for_init.resume_interrupted_jobs(&interrupted_jobs).await;
+                }
+
+                // Links and URLs the app was started with, handled once the queue can take them
+                for link in launch_links {
+                    handle_deep_link(&context_for_init, &link).await;
+                }
+                for url in launch_urls {
+                    queue_launch_url(&context_for_init, &url).await;
                 }
             });
             
@@ -517,32 +2601,134 @@ pub fn run() {
             greet,
             // Queue Management Commands
             add_to_queue,
+            add_batch_to_queue,
+            preview_url_rewrite,
+            preview_template,
             get_queue, 
             retry_job,
             cancel_job,
             pause_queue,
             resume_queue,
+            schedule_queue_start,
+            set_job_schedule,
+            set_job_dependencies,
+            get_disk_space_projection,
+            get_queue_forecast,
+            get_queue_metrics,
+            get_queue_stats,
+            queue_health_check,
+            cancel_all_jobs,
+            retry_all_failed_jobs,
+            batch_job_action,
+            export_jobs,
+            get_session_summaries,
+            add_playlist_to_queue,
+            get_job_groups,
+            cancel_group,
+            retry_failed_in_group,
+            generate_playlist_file,
+            get_job_progress_history,
+            get_job_logs,
+            reveal_job_in_file_manager,
+            open_job_file,
+            organize_completed,
+            refetch_cover,
+            replace_cover,
+            read_tags,
+            write_tags,
+            toggle_mini_window,
+            get_mini_progress,
+            verify_library_integrity,
+            get_capabilities,
+            get_startup_cleanup,
+            run_setup_diagnostics,
+            check_app_update,
+            install_app_update,
             // Configuration Management Commands
             get_config,
             update_config,
+            get_config_history,
+            rollback_config,
+            get_temp_usage,
+            clean_temp_files,
+            get_listen_log_status,
+            set_listen_log_credentials,
+            resubmit_listen,
             reset_config_to_defaults,
+            export_config,
+            import_config,
+            set_concurrent_limit,
+            apply_runtime_settings,
             validate_config,
             // Cookie Management Commands
             import_cookies,
             validate_cookies,
+            inspect_cookie_file,
+            extract_cookies_from_browser,
             get_cookies_path,
             clear_cookies,
+            get_app_lock_enabled,
+            set_app_lock,
+            verify_app_lock,
+            // API Token Commands
+            create_api_token,
+            list_api_tokens,
+            revoke_token,
+            // Subscription Commands
+            add_subscription,
+            list_subscriptions,
+            remove_subscription,
+            check_subscriptions_now,
             // Additional Queue Commands
             remove_job,
+            get_trash,
+            restore_job,
+            empty_trash,
+            set_job_priority,
+            reorder_job,
             clear_completed_jobs,
+            archive_old_jobs,
             // Utility Commands
             save_state,
+            list_state_backups,
+            restore_state_backup,
+            confirm_exit,
+            get_debug_logs,
+            set_log_level,
+            clear_debug_logs,
+            export_debug_logs,
             // Sidecar Management Commands
             get_sidecar_status,
             validate_sidecar_binaries,
             select_best_sidecar,
-            check_sidecar_compatibility
+            check_sidecar_compatibility,
+            check_sidecar_dependencies,
+            get_isolation_info,
+            check_sidecar_updates,
+            apply_sidecar_update
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .on_window_event(|window, event| {
+            if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+                // Closing the mini window never exits the app
+                if window.label() == MINI_WINDOW_LABEL {
+                    return;
+                }
+                let context = Arc::clone(window.state::<Arc<AppContext>>().inner());
+                if let Some(active_jobs) = tauri::async_runtime::block_on(context.pending_exit_confirmation()) {
+                    api.prevent_close();
+                    EventEmitter::new(window.app_handle().clone()).exit_requested(active_jobs);
+                    return;
+                }
+                // The app only exits once every window is gone
+                mini_window::close(window.app_handle());
+            }
+        })
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app_handle, event| {
+            if let tauri::RunEvent::Exit = event {
+                let context = Arc::clone(app_handle.state::<Arc<AppContext>>().inner());
+                tauri::async_runtime::block_on(context.shutdown());
+            }
+        });
 }
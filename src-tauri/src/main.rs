@@ -2,5 +2,10 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+    #[cfg(feature = "headless")]
+    if let Some(code) = gytmdl_gui_lib::run_headless(&std::env::args().collect::<Vec<_>>()) {
+        std::process::exit(code);
+    }
+
     gytmdl_gui_lib::run()
 }
@@ -0,0 +1,268 @@
+use crate::modules::debug_logger::DEBUG_LOGGER;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+/// What a token is allowed to do
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum TokenScope {
+    ReadOnly,
+    EnqueueOnly,
+    FullControl,
+}
+
+/// Class of request made with a token, e.g. a deep link carrying one
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ApiAction {
+    /// Reading queue/config state
+    Read,
+    /// Adding URLs to the queue
+    Enqueue,
+    /// Anything else that mutates state: cancel, pause, config changes...
+    Control,
+}
+
+impl TokenScope {
+    /// Check if this scope permits an action
+    pub fn allows(&self, action: ApiAction) -> bool {
+        match self {
+            TokenScope::FullControl => true,
+            TokenScope::EnqueueOnly => matches!(action, ApiAction::Read | ApiAction::Enqueue),
+            TokenScope::ReadOnly => matches!(action, ApiAction::Read),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum PermissionError {
+    UnknownCredential,
+    Forbidden { grant: String, action: ApiAction },
+    StorageError(io::Error),
+}
+
+impl std::fmt::Display for PermissionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PermissionError::UnknownCredential => write!(f, "Unknown or revoked API token"),
+            PermissionError::Forbidden { grant, action } => {
+                write!(f, "'{}' is not allowed to perform {:?} requests", grant, action)
+            }
+            PermissionError::StorageError(e) => write!(f, "Failed to store API tokens: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for PermissionError {}
+
+/// A stored bearer token, kept only as a hash
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ApiGrant {
+    id: String,
+    name: String,
+    scope: TokenScope,
+    /// None for the origin grants older versions stored; they authorize nothing
+    #[serde(default)]
+    token_hash: Option<String>,
+    created_at: DateTime<Utc>,
+    last_used_at: Option<DateTime<Utc>>,
+}
+
+/// Public view of a grant returned to the frontend; never includes the secret
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiTokenInfo {
+    pub id: String,
+    pub name: String,
+    pub scope: TokenScope,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+impl From<&ApiGrant> for ApiTokenInfo {
+    fn from(grant: &ApiGrant) -> Self {
+        Self {
+            id: grant.id.clone(),
+            name: grant.name.clone(),
+            scope: grant.scope,
+            created_at: grant.created_at,
+            last_used_at: grant.last_used_at,
+        }
+    }
+}
+
+/// Persistent store of API tokens
+pub struct TokenStore {
+    tokens_file: PathBuf,
+    grants: Vec<ApiGrant>,
+}
+
+impl TokenStore {
+    /// Load the token store from the app data directory
+    pub fn new() -> Self {
+        let tokens_file = std::env::current_dir()
+            .unwrap_or_else(|_| PathBuf::from("."))
+            .join(".gytmdl-gui")
+            .join("api_tokens.json");
+
+        Self::with_tokens_file(tokens_file)
+    }
+
+    /// Load the token store from a specific file. A missing or unreadable file starts empty.
+    pub fn with_tokens_file(tokens_file: PathBuf) -> Self {
+        let mut grants: Vec<ApiGrant> = fs::read_to_string(&tokens_file)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        grants.retain(|grant| grant.token_hash.is_some());
+
+        Self { tokens_file, grants }
+    }
+
+    fn hash_token(token: &str) -> String {
+        format!("{:x}", Sha256::digest(token.as_bytes()))
+    }
+
+    fn save(&self) -> Result<(), PermissionError> {
+        if let Some(parent) = self.tokens_file.parent() {
+            fs::create_dir_all(parent).map_err(PermissionError::StorageError)?;
+        }
+        let content = serde_json::to_string_pretty(&self.grants)
+            .map_err(|e| PermissionError::StorageError(io::Error::new(io::ErrorKind::InvalidData, e)))?;
+        fs::write(&self.tokens_file, content).map_err(PermissionError::StorageError)
+    }
+
+    /// Create a bearer token. The secret is returned once and only its hash is stored.
+    pub fn create_token(&mut self, name: &str, scope: TokenScope) -> Result<(ApiTokenInfo, String), PermissionError> {
+        let secret = format!("gytmdl_{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+        let grant = ApiGrant {
+            id: Uuid::new_v4().to_string(),
+            name: name.to_string(),
+            scope,
+            token_hash: Some(Self::hash_token(&secret)),
+            created_at: Utc::now(),
+            last_used_at: None,
+        };
+
+        let info = ApiTokenInfo::from(&grant);
+        self.grants.push(grant);
+        self.save()?;
+        Ok((info, secret))
+    }
+
+    /// List all tokens
+    pub fn list(&self) -> Vec<ApiTokenInfo> {
+        self.grants.iter().map(ApiTokenInfo::from).collect()
+    }
+
+    /// Revoke a token by ID. Returns false if it didn't exist.
+    pub fn revoke(&mut self, id: &str) -> Result<bool, PermissionError> {
+        let initial_len = self.grants.len();
+        self.grants.retain(|grant| grant.id != id);
+        if self.grants.len() == initial_len {
+            return Ok(false);
+        }
+        self.save()?;
+        Ok(true)
+    }
+
+    /// Check a bearer token against an action, recording its use and logging the decision
+    pub fn authorize_token(&mut self, token: &str, action: ApiAction) -> Result<ApiTokenInfo, PermissionError> {
+        let hash = Self::hash_token(token);
+        let grant = self.grants.iter_mut()
+            .find(|grant| grant.token_hash.as_deref() == Some(hash.as_str()));
+        let Some(grant) = grant else {
+            DEBUG_LOGGER.warn("audit", format!("Rejected {:?} request with unknown credential", action));
+            return Err(PermissionError::UnknownCredential);
+        };
+
+        if !grant.scope.allows(action) {
+            DEBUG_LOGGER.warn("audit", format!("Denied {:?} request for '{}' ({:?})", action, grant.name, grant.scope));
+            return Err(PermissionError::Forbidden { grant: grant.name.clone(), action });
+        }
+
+        if action != ApiAction::Read {
+            DEBUG_LOGGER.info("audit", format!("Allowed {:?} request for '{}' ({:?})", action, grant.name, grant.scope));
+        }
+        grant.last_used_at = Some(Utc::now());
+        let info = ApiTokenInfo::from(&*grant);
+
+        // Usage timestamps are best effort; a failed save shouldn't block the request
+        if let Err(e) = self.save() {
+            DEBUG_LOGGER.warn("audit", format!("Failed to record token usage: {}", e));
+        }
+        Ok(info)
+    }
+}
+
+impl Default for TokenStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_scope_allows() {
+        assert!(TokenScope::ReadOnly.allows(ApiAction::Read));
+        assert!(!TokenScope::ReadOnly.allows(ApiAction::Enqueue));
+        assert!(TokenScope::EnqueueOnly.allows(ApiAction::Enqueue));
+        assert!(!TokenScope::EnqueueOnly.allows(ApiAction::Control));
+        assert!(TokenScope::FullControl.allows(ApiAction::Control));
+    }
+
+    #[test]
+    fn test_token_lifecycle() {
+        let temp_dir = tempdir().unwrap();
+        let tokens_file = temp_dir.path().join("api_tokens.json");
+        let mut store = TokenStore::with_tokens_file(tokens_file.clone());
+
+        let (info, secret) = store.create_token("Stream Deck", TokenScope::EnqueueOnly).unwrap();
+        assert!(store.authorize_token(&secret, ApiAction::Enqueue).is_ok());
+        assert!(matches!(
+            store.authorize_token(&secret, ApiAction::Control),
+            Err(PermissionError::Forbidden { .. })
+        ));
+        assert!(matches!(
+            store.authorize_token("wrong", ApiAction::Read),
+            Err(PermissionError::UnknownCredential)
+        ));
+
+        // Only the hash is persisted
+        let content = fs::read_to_string(&tokens_file).unwrap();
+        assert!(!content.contains(&secret));
+
+        // Survives reload, then revocation
+        let mut reloaded = TokenStore::with_tokens_file(tokens_file);
+        assert_eq!(reloaded.list().len(), 1);
+        assert!(reloaded.list()[0].last_used_at.is_some());
+        assert!(reloaded.revoke(&info.id).unwrap());
+        assert!(!reloaded.revoke(&info.id).unwrap());
+        assert!(reloaded.authorize_token(&secret, ApiAction::Read).is_err());
+    }
+
+    #[test]
+    fn test_drops_stored_origin_grants() {
+        let temp_dir = tempdir().unwrap();
+        let tokens_file = temp_dir.path().join("api_tokens.json");
+        let mut store = TokenStore::with_tokens_file(tokens_file.clone());
+        store.create_token("Stream Deck", TokenScope::EnqueueOnly).unwrap();
+
+        let mut grants: Vec<serde_json::Value> = serde_json::from_str(&fs::read_to_string(&tokens_file).unwrap()).unwrap();
+        let mut origin_grant = grants[0].clone();
+        origin_grant["token_hash"] = serde_json::Value::Null;
+        origin_grant["origin"] = serde_json::Value::String("https://example.com".to_string());
+        grants.push(origin_grant);
+        fs::write(&tokens_file, serde_json::to_string(&grants).unwrap()).unwrap();
+
+        let reloaded = TokenStore::with_tokens_file(tokens_file);
+        assert_eq!(reloaded.list().len(), 1);
+        assert_eq!(reloaded.list()[0].name, "Stream Deck");
+    }
+}
@@ -0,0 +1,128 @@
+use crate::modules::app_lock::LockError;
+use crate::modules::config_manager::ConfigError;
+use crate::modules::cookie_manager::CookieError;
+use crate::modules::gytmdl_wrapper::GytmdlError;
+use serde::{Serialize, Serializer};
+
+/// Error returned by Tauri commands. Serialized as
+/// `{ "code": "...", "message": "...", "details": ... }` so the frontend can
+/// branch on `code` instead of matching message text.
+///
+/// So far it is returned by the commands that add, retry, cancel and remove
+/// jobs and read the queue, that change, import or roll back the config, and
+/// by the cookie file, temp file, listen log, state backup and app lock
+/// commands. The rest still fail with a plain message string. The frontend
+/// reads both through `errorMessage` in `services/errorHandler.ts`.
+#[derive(Debug, thiserror::Error)]
+pub enum AppError {
+    #[error(transparent)]
+    Gytmdl(#[from] GytmdlError),
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    #[error(transparent)]
+    Cookie(#[from] CookieError),
+    #[error(transparent)]
+    Lock(#[from] LockError),
+    #[error("{0} not found")]
+    NotFound(&'static str),
+    /// A subsystem (e.g. the queue manager) hasn't started
+    #[error("{0} not available")]
+    Unavailable(&'static str),
+    /// Errors that don't have a code of their own yet
+    #[error("{0}")]
+    Other(String),
+}
+
+impl AppError {
+    /// Stable identifier for the frontend
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::Gytmdl(e) => match e {
+                GytmdlError::BinaryNotFound(_) => "binary_not_found",
+                GytmdlError::ProcessSpawnError(_) => "process_spawn_failed",
+                GytmdlError::InvalidUrl(_) => "invalid_url",
+                GytmdlError::ConfigError(_) => "invalid_download_config",
+                GytmdlError::ProcessError(_) => "process_failed",
+                GytmdlError::ValidationError(_) => "binary_invalid",
+                GytmdlError::IntegrityError(_) => "binary_integrity_failed",
+                GytmdlError::ManifestError(_) => "binary_manifest_invalid",
+            },
+            AppError::Config(e) => match e {
+                ConfigError::IoError(_) => "config_io_failed",
+                ConfigError::SerializationError(_) => "config_malformed",
+                ConfigError::ValidationError(_) => "config_invalid",
+                ConfigError::MigrationError(_) => "config_unsupported_version",
+            },
+            AppError::Cookie(e) => match e {
+                CookieError::FileNotFound(_) => "cookies_not_found",
+                CookieError::InvalidFormat(_) => "cookies_malformed",
+                CookieError::ReadError(_) => "cookies_read_failed",
+                CookieError::ValidationError(_) => "cookies_invalid",
+            },
+            AppError::Lock(e) => match e {
+                LockError::Locked => "locked",
+                LockError::WrongPin => "wrong_pin",
+                LockError::InvalidPin(_) => "invalid_pin",
+            },
+            AppError::NotFound(_) => "not_found",
+            AppError::Unavailable(_) => "unavailable",
+            AppError::Other(_) => "other",
+        }
+    }
+
+    /// The path, URL or item the error is about, when there is one
+    pub fn details(&self) -> Option<String> {
+        match self {
+            AppError::Gytmdl(GytmdlError::BinaryNotFound(path)) => Some(path.clone()),
+            AppError::Gytmdl(GytmdlError::InvalidUrl(url)) => Some(url.clone()),
+            AppError::Cookie(CookieError::FileNotFound(path)) => Some(path.to_string_lossy().into_owned()),
+            AppError::NotFound(item) | AppError::Unavailable(item) => Some(item.to_string()),
+            _ => None,
+        }
+    }
+}
+
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        AppError::Other(message)
+    }
+}
+
+#[derive(Serialize)]
+struct ErrorPayload {
+    code: &'static str,
+    message: String,
+    details: Option<String>,
+}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ErrorPayload {
+            code: self.code(),
+            message: self.to_string(),
+            details: self.details(),
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serializes_code_message_and_details() {
+        let error = AppError::from(GytmdlError::InvalidUrl("https://example.com".to_string()));
+        assert_eq!(serde_json::to_value(&error).unwrap(), serde_json::json!({
+            "code": "invalid_url",
+            "message": "Invalid URL: https://example.com",
+            "details": "https://example.com",
+        }));
+
+        let error = AppError::from(ConfigError::ValidationError("Concurrent limit must be at least 1".to_string()));
+        assert_eq!(error.code(), "config_invalid");
+        assert_eq!(error.details(), None);
+
+        assert_eq!(AppError::Unavailable("Queue manager").to_string(), "Queue manager not available");
+    }
+}
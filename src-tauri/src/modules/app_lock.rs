@@ -0,0 +1,116 @@
+use sha2::Sha256;
+use uuid::Uuid;
+
+/// PBKDF2 rounds for hashing the PIN
+const PIN_ITERATIONS: u32 = 100_000;
+const MIN_PIN_LENGTH: usize = 4;
+const MAX_PIN_LENGTH: usize = 12;
+
+#[derive(Debug, PartialEq)]
+pub enum LockError {
+    /// The action needs the PIN and none was given
+    Locked,
+    WrongPin,
+    InvalidPin(String),
+}
+
+impl std::fmt::Display for LockError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LockError::Locked => write!(f, "This action is locked; enter the PIN to continue"),
+            LockError::WrongPin => write!(f, "Incorrect PIN"),
+            LockError::InvalidPin(msg) => write!(f, "Invalid PIN: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for LockError {}
+
+/// A PIN is 4 to 12 digits
+pub fn validate_pin(pin: &str) -> Result<(), LockError> {
+    if !(MIN_PIN_LENGTH..=MAX_PIN_LENGTH).contains(&pin.len()) {
+        return Err(LockError::InvalidPin(format!(
+            "must be {} to {} digits", MIN_PIN_LENGTH, MAX_PIN_LENGTH
+        )));
+    }
+    if !pin.chars().all(|c| c.is_ascii_digit()) {
+        return Err(LockError::InvalidPin("must only contain digits".to_string()));
+    }
+    Ok(())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn derive(pin: &str, salt: &str, iterations: u32) -> String {
+    let mut key = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(pin.as_bytes(), salt.as_bytes(), iterations, &mut key);
+    to_hex(&key)
+}
+
+/// Salted hash of `pin` as stored in the config:
+/// `pbkdf2-sha256$<iterations>$<salt>$<hash>`
+pub fn hash_pin(pin: &str) -> String {
+    hash_with_iterations(pin, PIN_ITERATIONS)
+}
+
+fn hash_with_iterations(pin: &str, iterations: u32) -> String {
+    let salt = Uuid::new_v4().simple().to_string();
+    format!("pbkdf2-sha256${}${}${}", iterations, salt, derive(pin, &salt, iterations))
+}
+
+/// Check `pin` against a hash from `hash_pin`
+pub fn verify_pin(pin: &str, stored: &str) -> bool {
+    let mut parts = stored.split('$');
+    let (Some("pbkdf2-sha256"), Some(iterations), Some(salt), Some(hash), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return false;
+    };
+    let Ok(iterations) = iterations.parse() else {
+        return false;
+    };
+
+    // Compare every byte so the time taken doesn't depend on where they differ
+    let derived = derive(pin, salt, iterations);
+    derived.len() == hash.len()
+        && derived.bytes().zip(hash.bytes()).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// Allow a locked action when no PIN is set, or when `pin` matches it
+pub fn check(stored: Option<&str>, pin: Option<&str>) -> Result<(), LockError> {
+    let Some(stored) = stored else {
+        return Ok(());
+    };
+    match pin {
+        None => Err(LockError::Locked),
+        Some(pin) if verify_pin(pin, stored) => Ok(()),
+        Some(_) => Err(LockError::WrongPin),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pin_hash_and_check() {
+        assert!(validate_pin("1234").is_ok());
+        assert!(validate_pin("123").is_err());
+        assert!(validate_pin("12a4").is_err());
+
+        // Fewer rounds than real hashes so the test stays fast
+        let stored = hash_with_iterations("2468", 1_000);
+        assert!(stored.starts_with("pbkdf2-sha256$1000$"));
+        assert_ne!(stored, hash_with_iterations("2468", 1_000));
+        assert!(verify_pin("2468", &stored));
+        assert!(!verify_pin("2469", &stored));
+        assert!(!verify_pin("2468", "garbage"));
+
+        assert_eq!(check(None, None), Ok(()));
+        assert_eq!(check(Some(&stored), None), Err(LockError::Locked));
+        assert_eq!(check(Some(&stored), Some("0000")), Err(LockError::WrongPin));
+        assert_eq!(check(Some(&stored), Some("2468")), Ok(()));
+    }
+}
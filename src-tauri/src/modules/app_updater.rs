@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+use tauri_plugin_updater::Update;
+
+/// Report download progress at most this often when the size is unknown
+const UNKNOWN_SIZE_REPORT_BYTES: u64 = 1024 * 1024;
+
+/// A newer release found on the update feed. The updater plugin checks the
+/// installer against the public key in `tauri.conf.json` before installing.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AppUpdateInfo {
+    pub version: String,
+    pub current_version: String,
+    /// Release notes from the feed
+    pub notes: Option<String>,
+}
+
+impl From<&Update> for AppUpdateInfo {
+    fn from(update: &Update) -> Self {
+        Self {
+            version: update.version.clone(),
+            current_version: update.current_version.clone(),
+            notes: update.body.clone(),
+        }
+    }
+}
+
+/// Bytes received while downloading an update, reported in whole-percent
+/// steps so the frontend isn't flooded with an event per chunk
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct UpdateDownloadProgress {
+    pub downloaded_bytes: u64,
+    pub total_bytes: Option<u64>,
+    #[serde(skip)]
+    last_reported_bytes: u64,
+}
+
+impl UpdateDownloadProgress {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a received chunk. Returns whether the progress should be reported.
+    pub fn record(&mut self, chunk_length: usize, total_bytes: Option<u64>) -> bool {
+        self.downloaded_bytes += chunk_length as u64;
+        self.total_bytes = total_bytes;
+
+        let report = match total_bytes.filter(|total| *total > 0) {
+            Some(total) => {
+                self.downloaded_bytes >= total
+                    || self.downloaded_bytes * 100 / total > self.last_reported_bytes * 100 / total
+            }
+            None => self.downloaded_bytes - self.last_reported_bytes >= UNKNOWN_SIZE_REPORT_BYTES,
+        };
+        if report {
+            self.last_reported_bytes = self.downloaded_bytes;
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_reports_whole_percent_steps() {
+        let mut progress = UpdateDownloadProgress::new();
+        let total = Some(10_000);
+        assert!(!progress.record(50, total));
+        assert!(progress.record(50, total));
+        assert!(!progress.record(99, total));
+        assert!(progress.record(9_801, total));
+        assert_eq!(progress.downloaded_bytes, 10_000);
+
+        let mut progress = UpdateDownloadProgress::new();
+        assert!(!progress.record(512 * 1024, None));
+        assert!(progress.record(512 * 1024, None));
+    }
+}
@@ -0,0 +1,191 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// Rotated backups kept next to the state and config files
+pub const BACKUP_COUNT: usize = 3;
+
+/// Shortest time between two rotations of the same file, so the backups reach
+/// further back than the last few autosaves
+pub const BACKUP_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// When each file was last rotated by this process; the first write of a
+/// session always rotates
+static LAST_ROTATION: LazyLock<Mutex<HashMap<PathBuf, Instant>>> = LazyLock::new(Default::default);
+
+/// A rotated copy of a file, 1 being the most recent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupInfo {
+    pub index: usize,
+    pub path: PathBuf,
+    pub modified_at: Option<DateTime<Utc>>,
+    pub size_bytes: u64,
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Path of the `index`th backup, e.g. `state.json` -> `state.json.1.bak`
+pub fn backup_path(path: &Path, index: usize) -> PathBuf {
+    with_suffix(path, &format!(".{}.bak", index))
+}
+
+/// Replace `path` with `content` so that a crash leaves either the old or the
+/// new file, never a partial one. The content is written and synced to a
+/// staging file first, then renamed over the target. Each write stages to a
+/// file of its own, so concurrent writes to one path can't clobber each other.
+pub fn write_atomic(path: &Path, content: &[u8]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let staging_path = with_suffix(path, &format!(".{}.tmp", Uuid::new_v4()));
+    let result = File::options().write(true).create_new(true).open(&staging_path)
+        .and_then(|mut file| {
+            file.write_all(content)?;
+            file.sync_all()
+        })
+        .and_then(|_| fs::rename(&staging_path, path));
+    if let Err(e) = result {
+        let _ = fs::remove_file(&staging_path);
+        return Err(e);
+    }
+
+    // Persist the rename itself; directories can't be opened for sync on Windows
+    #[cfg(unix)]
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        File::open(parent)?.sync_all()?;
+    }
+    Ok(())
+}
+
+/// Copy the current `path` to backup 1, shifting older backups up and
+/// dropping any beyond `keep`. Does nothing if `path` doesn't exist yet.
+pub fn rotate_backups(path: &Path, keep: usize) -> io::Result<()> {
+    if keep == 0 || !path.exists() {
+        return Ok(());
+    }
+
+    for index in (1..keep).rev() {
+        let from = backup_path(path, index);
+        if from.exists() {
+            fs::rename(&from, backup_path(path, index + 1))?;
+        }
+    }
+    fs::copy(path, backup_path(path, 1))?;
+    Ok(())
+}
+
+/// Replace `path` atomically, rotating its backups first if none was taken
+/// this session or in the last `BACKUP_INTERVAL`
+pub fn write_with_backups(path: &Path, content: &[u8], keep: usize) -> io::Result<()> {
+    if path.exists() {
+        let mut last_rotation = LAST_ROTATION.lock().unwrap_or_else(|e| e.into_inner());
+        let due = match last_rotation.get(path) {
+            Some(rotated_at) => rotated_at.elapsed() >= BACKUP_INTERVAL,
+            None => true,
+        };
+        if due {
+            rotate_backups(path, keep)?;
+            last_rotation.insert(path.to_path_buf(), Instant::now());
+        }
+    }
+    write_atomic(path, content)
+}
+
+/// Existing backups of `path`, most recent first
+pub fn list_backups(path: &Path) -> Vec<BackupInfo> {
+    (1..=BACKUP_COUNT)
+        .filter_map(|index| {
+            let backup = backup_path(path, index);
+            let metadata = fs::metadata(&backup).ok()?;
+            Some(BackupInfo {
+                index,
+                modified_at: metadata.modified().ok().map(DateTime::<Utc>::from),
+                size_bytes: metadata.len(),
+                path: backup,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_write_atomic_replaces_file() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("nested").join("state.json");
+
+        write_atomic(&path, b"first").unwrap();
+        write_atomic(&path, b"second").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+        assert_eq!(fs::read_dir(path.parent().unwrap()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_concurrent_writes_to_one_path() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("state.json");
+
+        let writers: Vec<_> = (0..8)
+            .map(|i| {
+                let path = path.clone();
+                std::thread::spawn(move || write_atomic(&path, format!("write {}", i).as_bytes()))
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap().unwrap();
+        }
+
+        assert!(fs::read_to_string(&path).unwrap().starts_with("write "));
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_backups_rotate_and_are_capped() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("state.json");
+
+        for content in ["1", "2", "3", "4", "5"] {
+            rotate_backups(&path, BACKUP_COUNT).unwrap();
+            write_atomic(&path, content.as_bytes()).unwrap();
+        }
+        assert_eq!(fs::read_to_string(&path).unwrap(), "5");
+
+        let backups = list_backups(&path);
+        assert_eq!(backups.len(), BACKUP_COUNT);
+        let contents: Vec<String> = backups.iter()
+            .map(|backup| fs::read_to_string(&backup.path).unwrap())
+            .collect();
+        assert_eq!(contents, vec!["4", "3", "2"]);
+        assert!(!backup_path(&path, BACKUP_COUNT + 1).exists());
+    }
+
+    #[test]
+    fn test_write_with_backups_rotates_once_per_interval() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("config.json");
+
+        for content in ["1", "2", "3"] {
+            write_with_backups(&path, content.as_bytes(), BACKUP_COUNT).unwrap();
+        }
+        assert_eq!(fs::read_to_string(&path).unwrap(), "3");
+
+        let backups = list_backups(&path);
+        assert_eq!(backups.len(), 1);
+        assert_eq!(fs::read_to_string(&backups[0].path).unwrap(), "1");
+    }
+}
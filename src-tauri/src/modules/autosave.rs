@@ -0,0 +1,138 @@
+use crate::modules::debug_logger::DEBUG_LOGGER;
+use crate::modules::state::AppState;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+/// Decides when a changed state should be written.
+///
+/// A change is saved once it has stopped changing for one debounce tick, or
+/// once it has waited for the full autosave interval while changes kept coming
+/// in (e.g. progress updates during a long download).
+#[derive(Debug, Default)]
+pub struct AutosaveTracker {
+    last_saved: Option<Vec<u8>>,
+    pending: Option<PendingChange>,
+}
+
+#[derive(Debug)]
+struct PendingChange {
+    snapshot: Vec<u8>,
+    first_seen: Instant,
+}
+
+impl AutosaveTracker {
+    /// Start from the state that is already on disk
+    pub fn new(saved: Vec<u8>) -> Self {
+        Self { last_saved: Some(saved), pending: None }
+    }
+
+    /// Record the current state; returns true when it should be saved now
+    pub fn observe(&mut self, snapshot: &[u8], now: Instant, max_delay: Duration) -> bool {
+        if self.last_saved.as_deref() == Some(snapshot) {
+            self.pending = None;
+            return false;
+        }
+
+        match &mut self.pending {
+            None => {
+                self.pending = Some(PendingChange { snapshot: snapshot.to_vec(), first_seen: now });
+                false
+            }
+            Some(pending) => {
+                if pending.snapshot == snapshot || now.duration_since(pending.first_seen) >= max_delay {
+                    true
+                } else {
+                    pending.snapshot = snapshot.to_vec();
+                    false
+                }
+            }
+        }
+    }
+
+    pub fn mark_saved(&mut self, snapshot: Vec<u8>) {
+        self.last_saved = Some(snapshot);
+        self.pending = None;
+    }
+
+    /// Whether a change is waiting to settle, so the state has to be looked at
+    /// again even if nothing marked it changed
+    pub fn has_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+}
+
+/// Periodically write the app state to `state_file` when it has changed.
+/// Runs for the lifetime of the app; an interval of 0 pauses autosaving.
+///
+/// The state is only serialized on ticks where it was marked changed or a
+/// change is still settling. Changes made without marking it are picked up by
+/// a full comparison once per autosave interval.
+pub async fn run(state: Arc<RwLock<AppState>>, state_file: PathBuf) {
+    let snapshot_of = |state: &AppState| serde_json::to_vec(state).unwrap_or_default();
+    let mut tracker = AutosaveTracker::new(snapshot_of(&*state.read().await));
+    let mut last_compared = Instant::now();
+
+    loop {
+        let (interval_secs, debounce_ms) = {
+            let config = &state.read().await.config;
+            (config.autosave_interval_secs, config.autosave_debounce_ms)
+        };
+        tokio::time::sleep(Duration::from_millis(debounce_ms.max(100))).await;
+        if interval_secs == 0 {
+            continue;
+        }
+
+        let max_delay = Duration::from_secs(interval_secs);
+        let changed = state.read().await.take_changed();
+        if !changed && !tracker.has_pending() && last_compared.elapsed() < max_delay {
+            continue;
+        }
+        last_compared = Instant::now();
+
+        let snapshot = snapshot_of(&*state.read().await);
+        if !tracker.observe(&snapshot, Instant::now(), max_delay) {
+            continue;
+        }
+
+        match state.read().await.save_to_file(&state_file) {
+            Ok(()) => tracker.mark_saved(snapshot),
+            Err(e) => DEBUG_LOGGER.error("autosave", format!("Failed to save state to {:?}: {}", state_file, e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_saves_once_change_settles() {
+        let start = Instant::now();
+        let max_delay = Duration::from_secs(30);
+        let mut tracker = AutosaveTracker::new(b"a".to_vec());
+
+        assert!(!tracker.observe(b"a", start, max_delay));
+        assert!(!tracker.has_pending());
+        assert!(!tracker.observe(b"b", start, max_delay));
+        assert!(tracker.has_pending());
+        assert!(tracker.observe(b"b", start + Duration::from_secs(2), max_delay));
+
+        tracker.mark_saved(b"b".to_vec());
+        assert!(!tracker.has_pending());
+        assert!(!tracker.observe(b"b", start + Duration::from_secs(4), max_delay));
+    }
+
+    #[test]
+    fn test_saves_continuous_changes_after_max_delay() {
+        let start = Instant::now();
+        let max_delay = Duration::from_secs(30);
+        let mut tracker = AutosaveTracker::new(b"0".to_vec());
+
+        assert!(!tracker.observe(b"1", start, max_delay));
+        assert!(!tracker.observe(b"2", start + Duration::from_secs(10), max_delay));
+        assert!(!tracker.observe(b"3", start + Duration::from_secs(20), max_delay));
+        assert!(tracker.observe(b"4", start + Duration::from_secs(30), max_delay));
+    }
+}
@@ -0,0 +1,402 @@
+use crate::modules::gytmdl_wrapper::GytmdlError;
+use crate::modules::debug_logger::DEBUG_LOGGER;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+use std::time::SystemTime;
+
+/// Modification time and size of each binary that last matched its manifest,
+/// so unchanged binaries aren't hashed again on every spawn
+static VERIFIED_BINARIES: LazyLock<Mutex<HashMap<PathBuf, (SystemTime, u64)>>> = LazyLock::new(Default::default);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BinaryManifest {
+    pub binary_name: String,
+    pub platform: PlatformInfo,
+    pub size_bytes: u64,
+    pub sha256: String,
+    pub build_timestamp: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlatformInfo {
+    pub os: String,
+    pub arch: String,
+    pub target: String,
+    pub extension: String,
+}
+
+/// Finds gytmdl binaries and checks them against their build manifests
+#[derive(Debug, Clone)]
+pub struct BinaryLocator {
+    binary_path: PathBuf,
+}
+
+impl BinaryLocator {
+    /// Locate the gytmdl binary for the current platform
+    pub fn detect() -> Result<Self, GytmdlError> {
+        let binary_path = Self::detect_binary_path()?;
+        Ok(Self { binary_path })
+    }
+
+    /// Use a specific binary path
+    pub fn with_binary_path(binary_path: PathBuf) -> Result<Self, GytmdlError> {
+        if !binary_path.exists() {
+            return Err(GytmdlError::BinaryNotFound(binary_path.to_string_lossy().to_string()));
+        }
+        Ok(Self { binary_path })
+    }
+
+    /// Detect the appropriate gytmdl binary for the current platform
+    fn detect_binary_path() -> Result<PathBuf, GytmdlError> {
+        let binary_name = Self::get_platform_binary_name();
+        
+        // ALWAYS check sidecar directory first and prefer it
+        let sidecar_path = Self::get_sidecar_directory().join(&binary_name);
+        DEBUG_LOGGER.debug("gytmdl", format!("Checking sidecar path: {:?}", sidecar_path));
+        if sidecar_path.exists() {
+            DEBUG_LOGGER.debug("gytmdl", format!("Using sidecar binary: {:?}", sidecar_path));
+            return Ok(sidecar_path);
+        }
+
+        // Check in current directory
+        let current_dir_path = std::env::current_dir()
+            .map_err(GytmdlError::ProcessSpawnError)?
+            .join(&binary_name);
+        if current_dir_path.exists() {
+            DEBUG_LOGGER.debug("gytmdl", format!("Using current directory binary: {:?}", current_dir_path));
+            return Ok(current_dir_path);
+        }
+
+        // Only use system PATH as last resort and warn about it
+        if let Ok(path_binary) = which::which("gytmdl") {
+            DEBUG_LOGGER.warn("gytmdl", format!("Using system gytmdl binary: {:?}", path_binary));
+            return Ok(path_binary);
+        }
+
+        Err(GytmdlError::BinaryNotFound(format!(
+            "Could not find gytmdl binary. Searched for: {} in sidecar directory: {:?}, current directory, and PATH",
+            binary_name, sidecar_path
+        )))
+    }
+
+    /// Get the platform-specific binary name
+    pub fn get_platform_binary_name() -> String {
+        if cfg!(target_os = "windows") {
+            if cfg!(target_arch = "x86_64") {
+                "gytmdl-x86_64-pc-windows-msvc.exe".to_string()
+            } else {
+                "gytmdl.exe".to_string()
+            }
+        } else if cfg!(target_os = "macos") {
+            if cfg!(target_arch = "aarch64") {
+                "gytmdl-aarch64-apple-darwin".to_string()
+            } else {
+                "gytmdl-x86_64-apple-darwin".to_string()
+            }
+        } else if cfg!(target_os = "linux") {
+            if cfg!(target_arch = "x86_64") {
+                "gytmdl-x86_64-unknown-linux-gnu".to_string()
+            } else {
+                "gytmdl".to_string()
+            }
+        } else {
+            "gytmdl".to_string()
+        }
+    }
+
+    /// Get the sidecar directory path where bundled binaries are stored
+    pub fn get_sidecar_directory() -> PathBuf {
+        // In Tauri, sidecar binaries are typically in the resource directory
+        // For development, we'll check relative to the current executable
+        if let Ok(exe_path) = std::env::current_exe() {
+            if let Some(exe_dir) = exe_path.parent() {
+                return exe_dir.join("sidecars");
+            }
+        }
+        
+        // Fallback to current directory
+        std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")).join("sidecars")
+    }
+
+    /// Load and validate binary manifest
+    pub fn load_manifest(&self) -> Result<BinaryManifest, GytmdlError> {
+        let manifest_path = self.binary_path.with_extension("json");
+        
+        if !manifest_path.exists() {
+            return Err(GytmdlError::ManifestError(format!(
+                "Manifest file not found: {}", 
+                manifest_path.display()
+            )));
+        }
+
+        let manifest_content = fs::read_to_string(&manifest_path)
+            .map_err(|e| GytmdlError::ManifestError(format!(
+                "Failed to read manifest: {}", e
+            )))?;
+
+        let manifest: BinaryManifest = serde_json::from_str(&manifest_content)
+            .map_err(|e| GytmdlError::ManifestError(format!(
+                "Failed to parse manifest: {}", e
+            )))?;
+
+        Ok(manifest)
+    }
+
+    /// Calculate SHA256 hash of the binary file
+    fn calculate_sha256(&self) -> Result<String, GytmdlError> {
+        let mut file = fs::File::open(&self.binary_path)
+            .map_err(|e| GytmdlError::IntegrityError(format!(
+                "Failed to open binary for hashing: {}", e
+            )))?;
+
+        let mut hasher = Sha256::new();
+        io::copy(&mut file, &mut hasher)
+            .map_err(|e| GytmdlError::IntegrityError(format!(
+                "Failed to read binary for hashing: {}", e
+            )))?;
+
+        Ok(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+    }
+
+    /// Validate binary integrity against manifest
+    pub fn validate_integrity(&self) -> Result<bool, GytmdlError> {
+        let manifest = self.load_manifest()?;
+        
+        // Check file size
+        let actual_size = fs::metadata(&self.binary_path)
+            .map_err(|e| GytmdlError::IntegrityError(format!(
+                "Failed to get binary metadata: {}", e
+            )))?
+            .len();
+
+        if actual_size != manifest.size_bytes {
+            return Err(GytmdlError::IntegrityError(format!(
+                "Binary size mismatch. Expected: {}, Actual: {}", 
+                manifest.size_bytes, actual_size
+            )));
+        }
+
+        let actual_hash = self.calculate_sha256()?;
+        if !actual_hash.eq_ignore_ascii_case(&manifest.sha256) {
+            return Err(GytmdlError::IntegrityError(format!(
+                "Binary hash mismatch. Expected: {}, Actual: {}", 
+                manifest.sha256, actual_hash
+            )));
+        }
+
+        Ok(true)
+    }
+
+    /// Refuse to run a binary that doesn't match its manifest. A bundled sidecar
+    /// must have one; other binaries (system installs, development builds) are
+    /// run without one, with a warning.
+    pub fn verify_integrity(&self) -> Result<(), GytmdlError> {
+        if !self.binary_path.with_extension("json").exists() {
+            if self.binary_path.starts_with(Self::get_sidecar_directory()) {
+                return Err(GytmdlError::IntegrityError(format!(
+                    "{:?} has no manifest and will not be run", self.binary_path
+                )));
+            }
+            DEBUG_LOGGER.warn("gytmdl", format!("No manifest for {:?}, running it unverified", self.binary_path));
+            return Ok(());
+        }
+
+        let metadata = fs::metadata(&self.binary_path)
+            .map_err(|e| GytmdlError::IntegrityError(format!("Failed to get binary metadata: {}", e)))?;
+        let fingerprint = metadata.modified().ok().map(|modified| (modified, metadata.len()));
+        let mut verified = VERIFIED_BINARIES.lock().unwrap_or_else(|e| e.into_inner());
+        if fingerprint.is_some() && verified.get(&self.binary_path) == fingerprint.as_ref() {
+            return Ok(());
+        }
+        verified.remove(&self.binary_path);
+
+        self.validate_integrity().map_err(|e| match e {
+            GytmdlError::IntegrityError(msg) => GytmdlError::IntegrityError(format!(
+                "{:?} does not match its manifest and will not be run: {}", self.binary_path, msg
+            )),
+            other => other,
+        })?;
+        if let Some(fingerprint) = fingerprint {
+            verified.insert(self.binary_path.clone(), fingerprint);
+        }
+        Ok(())
+    }
+
+    /// Get all available sidecar binaries in the sidecar directory
+    pub fn list_available_binaries() -> Result<Vec<PathBuf>, GytmdlError> {
+        let sidecar_dir = Self::get_sidecar_directory();
+        
+        if !sidecar_dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut binaries = Vec::new();
+        
+        let entries = fs::read_dir(&sidecar_dir)
+            .map_err(|e| GytmdlError::BinaryNotFound(format!(
+                "Failed to read sidecar directory: {}", e
+            )))?;
+
+        for entry in entries {
+            let entry = entry.map_err(|e| GytmdlError::BinaryNotFound(format!(
+                "Failed to read directory entry: {}", e
+            )))?;
+            
+            let path = entry.path();
+            let filename = path.file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("");
+
+            // Check if it's a gytmdl binary (starts with "gytmdl" and is executable)
+            if filename.starts_with("gytmdl") && 
+               !filename.ends_with(".json") && 
+               path.is_file() {
+                binaries.push(path);
+            }
+        }
+
+        Ok(binaries)
+    }
+
+    /// Select the best available binary for the current platform
+    pub fn select_best_binary() -> Result<PathBuf, GytmdlError> {
+        let available_binaries = Self::list_available_binaries()?;
+        
+        if available_binaries.is_empty() {
+            return Err(GytmdlError::BinaryNotFound(
+                "No gytmdl binaries found in sidecar directory".to_string()
+            ));
+        }
+
+        let platform_binary_name = Self::get_platform_binary_name();
+        
+        // First, try to find exact platform match
+        for binary in &available_binaries {
+            if let Some(filename) = binary.file_name().and_then(|n| n.to_str()) {
+                if filename == platform_binary_name {
+                    return Ok(binary.clone());
+                }
+            }
+        }
+
+        // If no exact match, try to find a compatible binary
+        let current_os = if cfg!(target_os = "windows") {
+            "windows"
+        } else if cfg!(target_os = "macos") {
+            "darwin"
+        } else if cfg!(target_os = "linux") {
+            "linux"
+        } else {
+            "unknown"
+        };
+
+        for binary in &available_binaries {
+            if let Some(filename) = binary.file_name().and_then(|n| n.to_str()) {
+                if filename.contains(current_os) {
+                    return Ok(binary.clone());
+                }
+            }
+        }
+
+        // As a last resort, return the first available binary
+        Ok(available_binaries[0].clone())
+    }
+
+    /// Get the binary path
+    pub fn get_binary_path(&self) -> &Path {
+        &self.binary_path
+    }
+
+    /// Check if binary exists and is executable
+    pub fn is_binary_available(&self) -> bool {
+        self.binary_path.exists() && self.binary_path.is_file()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn write_manifest(binary_path: &Path, size_bytes: u64, sha256: &str) {
+        let manifest = BinaryManifest {
+            binary_name: "gytmdl-test".to_string(),
+            platform: PlatformInfo {
+                os: "linux".to_string(),
+                arch: "x86_64".to_string(),
+                target: "x86_64-unknown-linux-gnu".to_string(),
+                extension: String::new(),
+            },
+            size_bytes,
+            sha256: sha256.to_string(),
+            build_timestamp: "2024-01-01T00:00:00Z".to_string(),
+        };
+        fs::write(binary_path.with_extension("json"), serde_json::to_string(&manifest).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_with_binary_path() {
+        let temp_dir = tempdir().unwrap();
+        let binary_path = temp_dir.path().join("gytmdl-test");
+
+        assert!(matches!(
+            BinaryLocator::with_binary_path(binary_path.clone()),
+            Err(GytmdlError::BinaryNotFound(_))
+        ));
+
+        fs::write(&binary_path, b"binary").unwrap();
+        let locator = BinaryLocator::with_binary_path(binary_path.clone()).unwrap();
+        assert_eq!(locator.get_binary_path(), binary_path.as_path());
+        assert!(locator.is_binary_available());
+    }
+
+    #[test]
+    fn test_manifest_and_integrity() {
+        let temp_dir = tempdir().unwrap();
+        let binary_path = temp_dir.path().join("gytmdl-test");
+        fs::write(&binary_path, b"binary").unwrap();
+        let locator = BinaryLocator::with_binary_path(binary_path.clone()).unwrap();
+
+        // No manifest yet; outside the sidecar directory that is only a warning
+        assert!(matches!(locator.load_manifest(), Err(GytmdlError::ManifestError(_))));
+        assert!(locator.verify_integrity().is_ok());
+
+        // Size mismatch is reported before hashing
+        write_manifest(&binary_path, 1, "unused");
+        assert_eq!(locator.load_manifest().unwrap().size_bytes, 1);
+        assert!(matches!(locator.validate_integrity(), Err(GytmdlError::IntegrityError(_))));
+
+        // Matching size and hash
+        let hash = locator.calculate_sha256().unwrap();
+        assert_eq!(hash, "9a3a45d01531a20e89ac6ae10b0b0beb0492acd7216a368aa062d1a5fecaf9cd");
+        write_manifest(&binary_path, 6, &hash.to_uppercase());
+        assert!(locator.validate_integrity().unwrap());
+        assert!(locator.verify_integrity().is_ok());
+
+        // Same size and modification time is trusted without hashing again
+        let verified_at = fs::metadata(&binary_path).unwrap().modified().unwrap();
+        fs::write(&binary_path, b"BINARY").unwrap();
+        let file = fs::File::options().write(true).open(&binary_path).unwrap();
+        file.set_modified(verified_at).unwrap();
+        assert!(locator.verify_integrity().is_ok());
+
+        // A changed modification time is hashed again and the new content rejected
+        file.set_modified(SystemTime::UNIX_EPOCH).unwrap();
+        assert!(matches!(locator.verify_integrity(), Err(GytmdlError::IntegrityError(_))));
+    }
+
+    #[test]
+    fn test_platform_binary_name() {
+        let name = BinaryLocator::get_platform_binary_name();
+        assert!(name.starts_with("gytmdl"));
+        if cfg!(target_os = "windows") {
+            assert!(name.ends_with(".exe"));
+        }
+        assert!(BinaryLocator::get_sidecar_directory().ends_with("sidecars"));
+    }
+}
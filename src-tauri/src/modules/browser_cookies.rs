@@ -0,0 +1,434 @@
+use aes::cipher::{block_padding::Pkcs7, BlockDecryptMut, KeyIvInit};
+use rusqlite::{Connection, OpenFlags};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Seconds between the Windows epoch (1601-01-01) used by Chromium and the Unix epoch
+const CHROMIUM_EPOCH_OFFSET_SECS: i64 = 11_644_473_600;
+
+/// Chromium's fallback password when no keyring is available on Linux
+const CHROMIUM_LINUX_PASSWORD: &[u8] = b"peanuts";
+const CHROMIUM_SALT: &[u8] = b"saltysalt";
+
+/// Only cookies for these domains are extracted
+const COOKIE_DOMAINS: [&str; 2] = ["youtube.com", "google.com"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Browser {
+    Chrome,
+    Edge,
+    Firefox,
+}
+
+impl FromStr for Browser {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "chrome" => Ok(Browser::Chrome),
+            "edge" => Ok(Browser::Edge),
+            "firefox" => Ok(Browser::Firefox),
+            other => Err(format!("Unsupported browser '{}'. Use chrome, edge or firefox", other)),
+        }
+    }
+}
+
+impl Browser {
+    /// Name of the keychain/keyring entry holding the cookie encryption password
+    fn safe_storage_name(&self) -> &'static str {
+        match self {
+            Browser::Chrome => "Chrome",
+            Browser::Edge => "Microsoft Edge",
+            Browser::Firefox => "Firefox",
+        }
+    }
+
+    /// Directory holding the browser's profiles
+    fn profile_root(&self) -> Option<PathBuf> {
+        let home = std::env::var_os("HOME")
+            .or_else(|| std::env::var_os("USERPROFILE"))
+            .map(PathBuf::from)?;
+
+        let root = if cfg!(target_os = "windows") {
+            let local = std::env::var_os("LOCALAPPDATA").map(PathBuf::from)
+                .unwrap_or_else(|| home.join("AppData").join("Local"));
+            let roaming = std::env::var_os("APPDATA").map(PathBuf::from)
+                .unwrap_or_else(|| home.join("AppData").join("Roaming"));
+            match self {
+                Browser::Chrome => local.join("Google").join("Chrome").join("User Data"),
+                Browser::Edge => local.join("Microsoft").join("Edge").join("User Data"),
+                Browser::Firefox => roaming.join("Mozilla").join("Firefox").join("Profiles"),
+            }
+        } else if cfg!(target_os = "macos") {
+            let support = home.join("Library").join("Application Support");
+            match self {
+                Browser::Chrome => support.join("Google").join("Chrome"),
+                Browser::Edge => support.join("Microsoft Edge"),
+                Browser::Firefox => support.join("Firefox").join("Profiles"),
+            }
+        } else {
+            match self {
+                Browser::Chrome => home.join(".config").join("google-chrome"),
+                Browser::Edge => home.join(".config").join("microsoft-edge"),
+                Browser::Firefox => home.join(".mozilla").join("firefox"),
+            }
+        };
+
+        Some(root)
+    }
+
+    /// Find the cookie database of the most recently used profile
+    pub fn find_cookie_database(&self) -> Option<PathBuf> {
+        let root = self.profile_root()?;
+        let candidates: Vec<PathBuf> = match self {
+            Browser::Firefox => fs::read_dir(&root).ok()?
+                .flatten()
+                .map(|entry| entry.path().join("cookies.sqlite"))
+                .collect(),
+            Browser::Chrome | Browser::Edge => fs::read_dir(&root).ok()?
+                .flatten()
+                .flat_map(|entry| {
+                    let profile = entry.path();
+                    [profile.join("Network").join("Cookies"), profile.join("Cookies")]
+                })
+                .collect(),
+        };
+
+        candidates.into_iter()
+            .filter(|path| path.is_file())
+            .max_by_key(|path| fs::metadata(path).and_then(|m| m.modified()).ok())
+    }
+}
+
+/// A cookie read from a browser database
+#[derive(Debug, Clone, PartialEq)]
+pub struct BrowserCookie {
+    pub domain: String,
+    pub path: String,
+    pub secure: bool,
+    /// Unix timestamp, 0 for session cookies
+    pub expires: i64,
+    pub name: String,
+    pub value: String,
+}
+
+impl BrowserCookie {
+    /// Format as a Netscape cookies.txt line
+    fn to_netscape_line(&self) -> String {
+        let include_subdomains = if self.domain.starts_with('.') { "TRUE" } else { "FALSE" };
+        let secure = if self.secure { "TRUE" } else { "FALSE" };
+        format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            self.domain, include_subdomains, self.path, secure, self.expires, self.name, self.value
+        )
+    }
+}
+
+/// Render cookies as a Netscape cookies.txt file
+pub fn to_netscape(cookies: &[BrowserCookie]) -> String {
+    let mut content = String::from("# Netscape HTTP Cookie File\n");
+    for cookie in cookies {
+        content.push_str(&cookie.to_netscape_line());
+        content.push('\n');
+    }
+    content
+}
+
+/// Read the YouTube and Google cookies from a browser's cookie database
+pub fn read_cookies(browser: Browser, database: &Path) -> Result<Vec<BrowserCookie>, String> {
+    // The browser keeps its database locked while running, so read a copy
+    let copy = std::env::temp_dir().join(format!("gytmdl-gui-cookies-{}.sqlite", uuid::Uuid::new_v4()));
+    fs::copy(database, &copy)
+        .map_err(|e| format!("Failed to copy cookie database {}: {}", database.display(), e))?;
+    let wal = PathBuf::from(format!("{}-wal", database.display()));
+    let copy_wal = PathBuf::from(format!("{}-wal", copy.display()));
+    if wal.is_file() {
+        let _ = fs::copy(&wal, &copy_wal);
+    }
+
+    let result = Connection::open_with_flags(&copy, OpenFlags::SQLITE_OPEN_READ_WRITE)
+        .map_err(|e| format!("Failed to open cookie database: {}", e))
+        .and_then(|connection| match browser {
+            Browser::Firefox => read_firefox_cookies(&connection),
+            Browser::Chrome | Browser::Edge => read_chromium_cookies(&connection, browser),
+        });
+
+    let _ = fs::remove_file(&copy);
+    let _ = fs::remove_file(&copy_wal);
+    result
+}
+
+fn is_wanted_domain(domain: &str) -> bool {
+    let domain = domain.trim_start_matches('.');
+    COOKIE_DOMAINS.iter().any(|wanted| domain == *wanted || domain.ends_with(&format!(".{}", wanted)))
+}
+
+fn read_firefox_cookies(connection: &Connection) -> Result<Vec<BrowserCookie>, String> {
+    let mut statement = connection
+        .prepare("SELECT host, name, value, path, expiry, isSecure FROM moz_cookies")
+        .map_err(|e| format!("Unexpected Firefox cookie database: {}", e))?;
+
+    let rows = statement
+        .query_map([], |row| {
+            let expiry: i64 = row.get(4)?;
+            Ok(BrowserCookie {
+                domain: row.get(0)?,
+                name: row.get(1)?,
+                value: row.get(2)?,
+                path: row.get(3)?,
+                // Newer Firefox versions store milliseconds
+                expires: if expiry > 100_000_000_000 { expiry / 1000 } else { expiry },
+                secure: row.get::<_, i64>(5)? != 0,
+            })
+        })
+        .map_err(|e| format!("Failed to read Firefox cookies: {}", e))?;
+
+    Ok(rows.flatten().filter(|cookie| is_wanted_domain(&cookie.domain)).collect())
+}
+
+fn read_chromium_cookies(connection: &Connection, browser: Browser) -> Result<Vec<BrowserCookie>, String> {
+    // From database version 24 the decrypted value is prefixed with a hash of the host
+    let version: i64 = connection
+        .query_row("SELECT value FROM meta WHERE key = 'version'", [], |row| row.get::<_, String>(0))
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(0);
+
+    let mut statement = connection
+        .prepare("SELECT host_key, name, value, encrypted_value, path, expires_utc, is_secure FROM cookies")
+        .map_err(|e| format!("Unexpected {:?} cookie database: {}", browser, e))?;
+
+    // Cookies paired with their encrypted value, decrypted below
+    let rows: Vec<(BrowserCookie, Vec<u8>)> = statement
+        .query_map([], |row| {
+            let expires_utc: i64 = row.get(5)?;
+            let cookie = BrowserCookie {
+                domain: row.get(0)?,
+                name: row.get(1)?,
+                value: row.get(2)?,
+                path: row.get(4)?,
+                expires: if expires_utc > 0 {
+                    expires_utc / 1_000_000 - CHROMIUM_EPOCH_OFFSET_SECS
+                } else {
+                    0
+                },
+                secure: row.get::<_, i64>(6)? != 0,
+            };
+            Ok((cookie, row.get(3)?))
+        })
+        .map_err(|e| format!("Failed to read {:?} cookies: {}", browser, e))?
+        .flatten()
+        .filter(|(cookie, _)| is_wanted_domain(&cookie.domain))
+        .collect();
+
+    let mut decryptor = ChromiumDecryptor::new(browser);
+    let mut cookies = Vec::new();
+    for (mut cookie, encrypted_value) in rows {
+        if cookie.value.is_empty() && !encrypted_value.is_empty() {
+            let mut plaintext = decryptor.decrypt(&encrypted_value)?;
+            if version >= 24 && plaintext.len() >= 32 {
+                plaintext.drain(..32);
+            }
+            cookie.value = String::from_utf8(plaintext)
+                .map_err(|_| format!("Cookie '{}' did not decrypt to text", cookie.name))?;
+        }
+        cookies.push(cookie);
+    }
+
+    Ok(cookies)
+}
+
+/// Decrypts Chromium's `v10`/`v11` AES-128-CBC cookie values.
+/// Each prefix has its own key, derived lazily since looking it up may prompt the user.
+struct ChromiumDecryptor {
+    browser: Browser,
+    v10_key: Option<[u8; 16]>,
+    v11_key: Option<[u8; 16]>,
+}
+
+impl ChromiumDecryptor {
+    fn new(browser: Browser) -> Self {
+        Self { browser, v10_key: None, v11_key: None }
+    }
+
+    #[cfg(test)]
+    fn with_keys(browser: Browser, v10_key: [u8; 16], v11_key: [u8; 16]) -> Self {
+        Self { browser, v10_key: Some(v10_key), v11_key: Some(v11_key) }
+    }
+
+    fn decrypt(&mut self, encrypted: &[u8]) -> Result<Vec<u8>, String> {
+        let (prefix, ciphertext) = encrypted.split_at(encrypted.len().min(3));
+        if prefix != b"v10" && prefix != b"v11" {
+            return Err(format!(
+                "{:?} cookies use an encryption scheme that can't be read here; export a cookies.txt file instead",
+                self.browser
+            ));
+        }
+
+        let v11 = prefix == b"v11";
+        let browser = self.browser;
+        let cached_key = if v11 { &mut self.v11_key } else { &mut self.v10_key };
+        let key = match *cached_key {
+            Some(key) => key,
+            None => {
+                let key = derive_chromium_key(browser, v11)?;
+                *cached_key = Some(key);
+                key
+            }
+        };
+
+        cbc::Decryptor::<aes::Aes128>::new(&key.into(), &[b' '; 16].into())
+            .decrypt_padded_vec_mut::<Pkcs7>(ciphertext)
+            .map_err(|_| format!("Failed to decrypt {:?} cookies", self.browser))
+    }
+}
+
+fn chromium_key_from_password(password: &[u8], iterations: u32) -> [u8; 16] {
+    let mut key = [0u8; 16];
+    pbkdf2::pbkdf2_hmac::<sha1::Sha1>(password, CHROMIUM_SALT, iterations, &mut key);
+    key
+}
+
+/// Look up the cookie encryption password and derive the AES key from it
+fn derive_chromium_key(browser: Browser, from_keyring: bool) -> Result<[u8; 16], String> {
+    let service = format!("{} Safe Storage", browser.safe_storage_name());
+
+    if cfg!(target_os = "macos") {
+        let output = std::process::Command::new("security")
+            .args(["find-generic-password", "-w", "-s", &service])
+            .output()
+            .map_err(|e| format!("Failed to read {} from the keychain: {}", service, e))?;
+        if !output.status.success() {
+            return Err(format!("{} was not found in the keychain", service));
+        }
+        let password = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        Ok(chromium_key_from_password(password.as_bytes(), 1003))
+    } else if cfg!(target_os = "windows") {
+        Err(format!(
+            "Reading {:?} cookies is not supported on Windows; export a cookies.txt file instead",
+            browser
+        ))
+    } else if from_keyring {
+        let application = match browser {
+            Browser::Edge => "microsoft-edge",
+            _ => "chrome",
+        };
+        let output = std::process::Command::new("secret-tool")
+            .args(["lookup", "application", application])
+            .output()
+            .map_err(|e| format!("Failed to read {} from the keyring: {}", service, e))?;
+        if !output.status.success() || output.stdout.is_empty() {
+            return Err(format!("{} was not found in the keyring", service));
+        }
+        let password = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        Ok(chromium_key_from_password(password.as_bytes(), 1))
+    } else {
+        Ok(chromium_key_from_password(CHROMIUM_LINUX_PASSWORD, 1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aes::cipher::BlockEncryptMut;
+    use tempfile::tempdir;
+
+    fn encrypt(prefix: &[u8], key: [u8; 16], plaintext: &[u8]) -> Vec<u8> {
+        let ciphertext = cbc::Encryptor::<aes::Aes128>::new(&key.into(), &[b' '; 16].into())
+            .encrypt_padded_vec_mut::<Pkcs7>(plaintext);
+        [prefix, &ciphertext].concat()
+    }
+
+    fn encrypt_v10(plaintext: &[u8]) -> Vec<u8> {
+        encrypt(b"v10", chromium_key_from_password(CHROMIUM_LINUX_PASSWORD, 1), plaintext)
+    }
+
+    #[test]
+    fn test_parse_browser() {
+        assert_eq!("Chrome".parse::<Browser>(), Ok(Browser::Chrome));
+        assert_eq!(" firefox ".parse::<Browser>(), Ok(Browser::Firefox));
+        assert!("safari".parse::<Browser>().is_err());
+    }
+
+    #[test]
+    fn test_read_firefox_cookies() {
+        let temp_dir = tempdir().unwrap();
+        let database = temp_dir.path().join("cookies.sqlite");
+        let connection = Connection::open(&database).unwrap();
+        connection.execute_batch("
+            CREATE TABLE moz_cookies (host TEXT, name TEXT, value TEXT, path TEXT, expiry INTEGER, isSecure INTEGER);
+            INSERT INTO moz_cookies VALUES ('.youtube.com', 'SAPISID', 'abc', '/', 1900000000, 1);
+            INSERT INTO moz_cookies VALUES ('accounts.google.com', 'SID', 'def', '/', 1900000000000, 0);
+            INSERT INTO moz_cookies VALUES ('.example.com', 'other', 'x', '/', 0, 0);
+        ").unwrap();
+        drop(connection);
+
+        let cookies = read_cookies(Browser::Firefox, &database).unwrap();
+        assert_eq!(cookies.len(), 2);
+        assert_eq!(cookies[0].to_netscape_line(), ".youtube.com\tTRUE\t/\tTRUE\t1900000000\tSAPISID\tabc");
+        assert_eq!(cookies[1].expires, 1_900_000_000);
+        assert!(!cookies[1].secure);
+    }
+
+    #[test]
+    fn test_read_chromium_cookies() {
+        let temp_dir = tempdir().unwrap();
+        let database = temp_dir.path().join("Cookies");
+        let connection = Connection::open(&database).unwrap();
+        connection.execute_batch("
+            CREATE TABLE meta (key TEXT, value TEXT);
+            INSERT INTO meta VALUES ('version', '23');
+            CREATE TABLE cookies (host_key TEXT, name TEXT, value TEXT, encrypted_value BLOB, path TEXT, expires_utc INTEGER, is_secure INTEGER);
+        ").unwrap();
+        // 2030-03-17 in microseconds since 1601
+        let expires_utc = (1_900_000_000 + CHROMIUM_EPOCH_OFFSET_SECS) * 1_000_000;
+        connection.execute(
+            "INSERT INTO cookies VALUES ('.youtube.com', 'HSID', '', ?1, '/', ?2, 1)",
+            rusqlite::params![encrypt_v10(b"secret-value"), expires_utc],
+        ).unwrap();
+        connection.execute(
+            "INSERT INTO cookies VALUES ('music.youtube.com', 'PREF', 'plain', X'', '/', 0, 0)",
+            [],
+        ).unwrap();
+        drop(connection);
+
+        // Without a keyring, v10 values use the fixed Linux password
+        if cfg!(target_os = "linux") {
+            let cookies = read_cookies(Browser::Chrome, &database).unwrap();
+            assert_eq!(cookies.len(), 2);
+            assert_eq!(cookies[0].value, "secret-value");
+            assert_eq!(cookies[0].expires, 1_900_000_000);
+            assert_eq!(cookies[1].value, "plain");
+            assert_eq!(cookies[1].expires, 0);
+        }
+    }
+
+    #[test]
+    fn test_chromium_decrypt() {
+        let v10_key = chromium_key_from_password(CHROMIUM_LINUX_PASSWORD, 1);
+        let v11_key = chromium_key_from_password(b"keyring-password", 1);
+        let mut decryptor = ChromiumDecryptor::with_keys(Browser::Chrome, v10_key, v11_key);
+        assert_eq!(decryptor.decrypt(&encrypt_v10(b"abc")).unwrap(), b"abc");
+
+        // v11 values are read with the keyring key, not the fixed one
+        assert_eq!(decryptor.decrypt(&encrypt(b"v11", v11_key, b"def")).unwrap(), b"def");
+
+        // DPAPI blobs and other unknown schemes are rejected
+        assert!(decryptor.decrypt(b"\x01\x00\x00\x00").is_err());
+    }
+
+    #[test]
+    fn test_to_netscape() {
+        let content = to_netscape(&[BrowserCookie {
+            domain: "music.youtube.com".to_string(),
+            path: "/".to_string(),
+            secure: false,
+            expires: 0,
+            name: "PREF".to_string(),
+            value: "f6=8".to_string(),
+        }]);
+        assert!(content.starts_with("# Netscape HTTP Cookie File\n"));
+        assert!(content.contains("music.youtube.com\tFALSE\t/\tFALSE\t0\tPREF\tf6=8\n"));
+    }
+}
@@ -0,0 +1,138 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+use std::time::Duration;
+
+/// How often the clipboard is read while watching
+pub const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// URLs remembered as already seen; the oldest are forgotten first
+const MAX_SEEN_URLS: usize = 500;
+
+/// Characters trimmed from the ends of a URL found in running text
+const URL_TRIM_CHARS: &[char] = &['"', '\'', '<', '>', '(', ')', '[', ']', ',', '.', ';'];
+
+/// What to do with a YouTube Music URL found on the clipboard
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub enum ClipboardAction {
+    /// Emit an event so the frontend can ask before queueing
+    #[default]
+    Prompt,
+    AutoQueue,
+}
+
+/// Picks new YouTube Music URLs out of successive clipboard reads
+#[derive(Debug, Default)]
+pub struct ClipboardWatcher {
+    last_text: Option<String>,
+    seen_urls: HashSet<String>,
+    /// `seen_urls` in the order they were first seen
+    seen_order: VecDeque<String>,
+}
+
+impl ClipboardWatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the current clipboard text and return URLs that weren't seen
+    /// before. The first read only sets a baseline, so whatever was on the
+    /// clipboard when watching started isn't picked up.
+    pub fn observe(&mut self, text: &str) -> Vec<String> {
+        if self.last_text.as_deref() == Some(text) {
+            return Vec::new();
+        }
+        let is_baseline = self.last_text.is_none();
+        self.last_text = Some(text.to_string());
+
+        extract_urls(text)
+            .into_iter()
+            .filter(|url| self.remember(url))
+            .filter(|_| !is_baseline)
+            .collect()
+    }
+
+    /// Add a URL to the seen ones, forgetting the oldest past `MAX_SEEN_URLS`.
+    /// Returns whether it is new.
+    fn remember(&mut self, url: &str) -> bool {
+        if !self.seen_urls.insert(url.to_string()) {
+            return false;
+        }
+        self.seen_order.push_back(url.to_string());
+        if self.seen_order.len() > MAX_SEEN_URLS {
+            if let Some(oldest) = self.seen_order.pop_front() {
+                self.seen_urls.remove(&oldest);
+            }
+        }
+        true
+    }
+
+    /// Forget the baseline and the seen URLs, e.g. when watching is turned off
+    pub fn reset(&mut self) {
+        self.last_text = None;
+        self.seen_urls.clear();
+        self.seen_order.clear();
+    }
+}
+
+/// YouTube Music URLs in a piece of copied text, in order and without repeats
+pub fn extract_urls(text: &str) -> Vec<String> {
+    let mut urls: Vec<String> = Vec::new();
+    for word in text.split_whitespace() {
+        let candidate = word.trim_matches(URL_TRIM_CHARS);
+        if is_music_url(candidate) && !urls.iter().any(|url| url == candidate) {
+            urls.push(candidate.to_string());
+        }
+    }
+    urls
+}
+
+/// Same hosts the queue accepts
+fn is_music_url(candidate: &str) -> bool {
+    (candidate.starts_with("https://") || candidate.starts_with("http://"))
+        && (candidate.contains("music.youtube.com/")
+            || candidate.contains("youtube.com/watch")
+            || candidate.contains("youtube.com/playlist")
+            || candidate.contains("youtu.be/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_urls() {
+        let text = "check this (https://music.youtube.com/watch?v=abc) and https://example.com, \
+                    also https://youtu.be/xyz. https://music.youtube.com/watch?v=abc";
+        assert_eq!(
+            extract_urls(text),
+            vec!["https://music.youtube.com/watch?v=abc", "https://youtu.be/xyz"]
+        );
+    }
+
+    #[test]
+    fn test_watcher_skips_baseline_and_repeats() {
+        let mut watcher = ClipboardWatcher::new();
+        assert!(watcher.observe("https://music.youtube.com/watch?v=old").is_empty());
+        assert!(watcher.observe("https://music.youtube.com/watch?v=old").is_empty());
+
+        assert_eq!(watcher.observe("https://music.youtube.com/watch?v=new"), vec!["https://music.youtube.com/watch?v=new"]);
+        assert!(watcher.observe("some text").is_empty());
+        // Copying the same link again doesn't queue it twice
+        assert!(watcher.observe("https://music.youtube.com/watch?v=new").is_empty());
+
+        watcher.reset();
+        assert!(watcher.observe("https://music.youtube.com/watch?v=other").is_empty());
+        assert!(watcher.seen_urls.len() == 1 && watcher.seen_order.len() == 1);
+    }
+
+    #[test]
+    fn test_watcher_forgets_oldest_seen_urls() {
+        let mut watcher = ClipboardWatcher::new();
+        for i in 0..=MAX_SEEN_URLS {
+            watcher.observe(&format!("https://music.youtube.com/watch?v={}", i));
+        }
+        assert_eq!(watcher.seen_urls.len(), MAX_SEEN_URLS);
+        assert!(!watcher.seen_urls.contains("https://music.youtube.com/watch?v=0"));
+        assert_eq!(watcher.observe("https://music.youtube.com/watch?v=0"), vec!["https://music.youtube.com/watch?v=0"]);
+    }
+}
@@ -0,0 +1,305 @@
+use crate::modules::atomic_file;
+use crate::modules::state::AppConfig;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use uuid::Uuid;
+
+/// Changes kept in the changelog; older ones are dropped
+const MAX_CHANGES: usize = 200;
+
+/// Fields left out of the changelog diff. The lock PIN only changes through
+/// `set_app_lock` and its hash isn't worth showing.
+const UNTRACKED_FIELDS: &[&str] = &["lock_pin_hash"];
+
+/// Fields whose values never reach the changelog file. Changes to them are
+/// still listed, with the values replaced by `REDACTED`.
+const SECRET_FIELDS: &[&str] = &["proxy_password", "po_token", "lock_pin_hash"];
+
+const REDACTED: &str = "<redacted>";
+
+/// One field's value before and after a change
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FieldChange {
+    pub field: String,
+    pub old: Value,
+    pub new: Value,
+}
+
+/// A saved config change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigChange {
+    pub id: String,
+    pub changed_at: DateTime<Utc>,
+    pub changes: Vec<FieldChange>,
+    /// The change being rolled back, when this entry is a rollback
+    #[serde(default)]
+    pub rollback_of: Option<String>,
+    /// The whole config as it was before the change, to roll back to
+    pub previous: Value,
+}
+
+/// Replace a set secret value with `REDACTED`. Returns whether it was changed.
+fn redact_value(value: &mut Value) -> bool {
+    if value.is_null() || *value == Value::String(REDACTED.to_string()) {
+        return false;
+    }
+    *value = Value::String(REDACTED.to_string());
+    true
+}
+
+/// Strip the secret fields from a change: they're cleared in the snapshot and
+/// masked in the diff. Returns whether anything was still in plain text.
+fn redact(change: &mut ConfigChange) -> bool {
+    let mut redacted = false;
+    if let Value::Object(previous) = &mut change.previous {
+        for field in SECRET_FIELDS {
+            if let Some(value) = previous.get_mut(*field) {
+                redacted |= !value.is_null();
+                *value = Value::Null;
+            }
+        }
+    }
+    for field_change in &mut change.changes {
+        if SECRET_FIELDS.contains(&field_change.field.as_str()) {
+            redacted |= redact_value(&mut field_change.old);
+            redacted |= redact_value(&mut field_change.new);
+        }
+    }
+    redacted
+}
+
+/// Fill the secrets left out of a snapshot with the current ones, so rolling
+/// back doesn't clear them. The proxy password is only kept for a snapshot
+/// that has a proxy username to go with it.
+pub fn restore_secrets(previous: &mut Value, current: &AppConfig) {
+    let Value::Object(previous) = previous else {
+        return;
+    };
+    previous.insert("po_token".to_string(), current.po_token.clone().into());
+    if previous.get("proxy_username").is_some_and(|username| !username.is_null()) {
+        previous.insert("proxy_password".to_string(), current.proxy_password.clone().into());
+    }
+}
+
+/// Fields that differ between two configs, in field order
+pub fn diff(old: &AppConfig, new: &AppConfig) -> Result<Vec<FieldChange>, serde_json::Error> {
+    let (Value::Object(old), Value::Object(mut new)) = (serde_json::to_value(old)?, serde_json::to_value(new)?) else {
+        return Ok(Vec::new());
+    };
+
+    Ok(old
+        .into_iter()
+        .filter(|(field, _)| !UNTRACKED_FIELDS.contains(&field.as_str()))
+        .filter_map(|(field, old)| {
+            let new = new.remove(&field).unwrap_or(Value::Null);
+            (old != new).then_some(FieldChange { field, old, new })
+        })
+        .collect())
+}
+
+/// Changelog of config updates. Each line of the file is one JSON-encoded
+/// `ConfigChange`, oldest first.
+pub struct ConfigHistory {
+    history_file: PathBuf,
+}
+
+impl ConfigHistory {
+    /// Create a ConfigHistory in the app data directory
+    pub fn new() -> Self {
+        let history_file = std::env::current_dir()
+            .unwrap_or_else(|_| PathBuf::from("."))
+            .join(".gytmdl-gui")
+            .join("config_history.jsonl");
+
+        Self { history_file }
+    }
+
+    /// Create a ConfigHistory backed by a specific file
+    pub fn with_history_file(history_file: PathBuf) -> Self {
+        Self { history_file }
+    }
+
+    /// Append the change from `old` to `new`. Returns None when nothing changed.
+    pub fn record(
+        &self,
+        old: &AppConfig,
+        new: &AppConfig,
+        rollback_of: Option<String>,
+    ) -> Result<Option<ConfigChange>, io::Error> {
+        let to_io = |e: serde_json::Error| io::Error::new(io::ErrorKind::InvalidData, e);
+        let changes = diff(old, new).map_err(to_io)?;
+        if changes.is_empty() {
+            return Ok(None);
+        }
+
+        let mut change = ConfigChange {
+            id: Uuid::new_v4().to_string(),
+            changed_at: Utc::now(),
+            changes,
+            rollback_of,
+            previous: serde_json::to_value(old).map_err(to_io)?,
+        };
+        redact(&mut change);
+
+        if let Some(parent) = self.history_file.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut line = serde_json::to_string(&change).map_err(to_io)?;
+        line.push('\n');
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.history_file)?;
+        file.write_all(line.as_bytes())?;
+
+        self.trim()?;
+        Ok(Some(change))
+    }
+
+    /// Load all changes, oldest first, skipping unreadable lines
+    pub fn load(&self) -> Result<Vec<ConfigChange>, io::Error> {
+        if !self.history_file.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content = fs::read_to_string(&self.history_file)?;
+        Ok(content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+
+    /// Find a change by id
+    pub fn get(&self, id: &str) -> Result<Option<ConfigChange>, io::Error> {
+        Ok(self.load()?.into_iter().find(|change| change.id == id))
+    }
+
+    /// Drop the oldest changes beyond `MAX_CHANGES`, and redact entries
+    /// written before secrets were left out of the changelog
+    fn trim(&self) -> Result<(), io::Error> {
+        let mut changes = self.load()?;
+        let mut redacted = false;
+        for change in &mut changes {
+            redacted |= redact(change);
+        }
+        if changes.len() <= MAX_CHANGES && !redacted {
+            return Ok(());
+        }
+
+        let mut content = String::new();
+        for change in &changes[changes.len().saturating_sub(MAX_CHANGES)..] {
+            content.push_str(&serde_json::to_string(change).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?);
+            content.push('\n');
+        }
+        atomic_file::write_atomic(&self.history_file, content.as_bytes())
+    }
+}
+
+impl Default for ConfigHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_record_diffs_and_keeps_previous_snapshot() {
+        let temp_dir = TempDir::new().unwrap();
+        let history = ConfigHistory::with_history_file(temp_dir.path().join("config_history.jsonl"));
+
+        let old = AppConfig::default();
+        let mut new = old.clone();
+        new.output_path = PathBuf::from("/mnt/music");
+        new.lock_pin_hash = Some("hash".to_string());
+
+        assert!(history.record(&old, &old, None).unwrap().is_none());
+        let change = history.record(&old, &new, None).unwrap().unwrap();
+        assert_eq!(change.changes, vec![FieldChange {
+            field: "output_path".to_string(),
+            old: serde_json::to_value(&old.output_path).unwrap(),
+            new: Value::String("/mnt/music".to_string()),
+        }]);
+
+        let loaded = history.get(&change.id).unwrap().unwrap();
+        let previous: AppConfig = serde_json::from_value(loaded.previous).unwrap();
+        assert_eq!(previous.output_path, old.output_path);
+        assert_eq!(history.load().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_record_redacts_secrets() {
+        let temp_dir = TempDir::new().unwrap();
+        let history_file = temp_dir.path().join("config_history.jsonl");
+        let history = ConfigHistory::with_history_file(history_file.clone());
+
+        let mut old = AppConfig::default();
+        old.proxy_username = Some("me".to_string());
+        old.proxy_password = Some("old-password".to_string());
+        old.po_token = Some("old-token".to_string());
+        let mut new = old.clone();
+        new.proxy_password = Some("new-password".to_string());
+        new.po_token = None;
+
+        let change = history.record(&old, &new, None).unwrap().unwrap();
+        assert_eq!(change.changes, vec![
+            FieldChange {
+                field: "po_token".to_string(),
+                old: Value::String(REDACTED.to_string()),
+                new: Value::Null,
+            },
+            FieldChange {
+                field: "proxy_password".to_string(),
+                old: Value::String(REDACTED.to_string()),
+                new: Value::String(REDACTED.to_string()),
+            },
+        ]);
+
+        let content = fs::read_to_string(&history_file).unwrap();
+        for secret in ["old-password", "new-password", "old-token"] {
+            assert!(!content.contains(secret));
+        }
+        let previous: AppConfig = serde_json::from_value(history.get(&change.id).unwrap().unwrap().previous).unwrap();
+        assert_eq!(previous.proxy_username.as_deref(), Some("me"));
+        assert!(previous.proxy_password.is_none() && previous.po_token.is_none());
+
+        let mut snapshot = history.get(&change.id).unwrap().unwrap().previous;
+        restore_secrets(&mut snapshot, &new);
+        let restored: AppConfig = serde_json::from_value(snapshot).unwrap();
+        assert_eq!(restored.proxy_password.as_deref(), Some("new-password"));
+        assert!(restored.po_token.is_none());
+    }
+
+    #[test]
+    fn test_trim_redacts_existing_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        let history_file = temp_dir.path().join("config_history.jsonl");
+        let history = ConfigHistory::with_history_file(history_file.clone());
+
+        let mut old = AppConfig::default();
+        old.po_token = Some("old-token".to_string());
+        let legacy = ConfigChange {
+            id: "legacy".to_string(),
+            changed_at: Utc::now(),
+            changes: diff(&old, &AppConfig::default()).unwrap(),
+            rollback_of: None,
+            previous: serde_json::to_value(&old).unwrap(),
+        };
+        fs::write(&history_file, format!("{}\n", serde_json::to_string(&legacy).unwrap())).unwrap();
+
+        let mut new = AppConfig::default();
+        new.output_path = PathBuf::from("/mnt/music");
+        history.record(&AppConfig::default(), &new, None).unwrap();
+
+        assert!(!fs::read_to_string(&history_file).unwrap().contains("old-token"));
+        assert_eq!(history.load().unwrap().len(), 2);
+    }
+}
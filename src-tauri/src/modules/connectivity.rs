@@ -0,0 +1,189 @@
+use crate::modules::debug_logger::DEBUG_LOGGER;
+use crate::modules::events::EventEmitter;
+use crate::modules::queue_manager::QueueManager;
+use crate::modules::state::AppState;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::time::Instant;
+
+/// Failed checks in a row before the connection counts as lost, so one
+/// dropped request doesn't pause the queue
+const FAILURES_BEFORE_OFFLINE: u32 = 2;
+const CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+/// How often to check while checks are turned off, in case they are turned on
+const DISABLED_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Payload of the `connectivity-changed` event
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ConnectivityEvent {
+    pub online: bool,
+    /// The monitor paused the queue when the connection dropped and will
+    /// resume it once the connection is back
+    pub queue_paused: bool,
+    pub message: String,
+}
+
+/// What the monitor should do after a check
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Transition {
+    WentOffline,
+    /// Reachable again for the whole grace period
+    CameOnline,
+}
+
+/// Turns check results into offline/online transitions
+#[derive(Debug)]
+pub struct ConnectivityTracker {
+    online: bool,
+    consecutive_failures: u32,
+    /// When the endpoint first answered again after an outage
+    reachable_since: Option<Instant>,
+}
+
+impl ConnectivityTracker {
+    pub fn new() -> Self {
+        Self { online: true, consecutive_failures: 0, reachable_since: None }
+    }
+
+    pub fn is_online(&self) -> bool {
+        self.online
+    }
+
+    /// Record one check. Going offline takes a few failures in a row; coming
+    /// back takes `grace` of successful checks.
+    pub fn observe(&mut self, reachable: bool, now: Instant, grace: Duration) -> Option<Transition> {
+        if !reachable {
+            self.reachable_since = None;
+            self.consecutive_failures += 1;
+            if self.online && self.consecutive_failures >= FAILURES_BEFORE_OFFLINE {
+                self.online = false;
+                return Some(Transition::WentOffline);
+            }
+            return None;
+        }
+
+        self.consecutive_failures = 0;
+        if self.online {
+            return None;
+        }
+        let since = *self.reachable_since.get_or_insert(now);
+        if now.duration_since(since) >= grace {
+            self.online = true;
+            self.reachable_since = None;
+            return Some(Transition::CameOnline);
+        }
+        None
+    }
+}
+
+impl Default for ConnectivityTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Whether `url` answers at all; any HTTP status counts as reachable
+pub async fn check(client: &reqwest::Client, url: &str) -> bool {
+    client.head(url).send().await.is_ok()
+}
+
+/// Check the configured endpoint for the lifetime of the app. When the
+/// connection drops the queue is paused and running jobs are marked to be
+/// queued again when they fail; once it is back for the grace period, a queue
+/// paused here is resumed.
+pub async fn run(
+    queue_manager: Arc<RwLock<Option<QueueManager>>>,
+    state: Arc<RwLock<AppState>>,
+    events: EventEmitter,
+) {
+    let client = reqwest::Client::builder()
+        .timeout(CHECK_TIMEOUT)
+        .build()
+        .unwrap_or_default();
+    let mut tracker = ConnectivityTracker::new();
+    let mut paused_queue = false;
+
+    loop {
+        let (url, interval, grace) = {
+            let config = &state.read().await.config;
+            (
+                config.connectivity_check_url.clone(),
+                Duration::from_secs(config.connectivity_check_interval_secs),
+                Duration::from_secs(config.connectivity_resume_grace_secs),
+            )
+        };
+        if interval.is_zero() || url.trim().is_empty() {
+            // Checks turned off mid-outage: nothing would ever end it
+            if !tracker.is_online() {
+                if let Some(queue_manager) = queue_manager.read().await.as_ref() {
+                    queue_manager.end_outage(false).await;
+                }
+                tracker = ConnectivityTracker::new();
+                paused_queue = false;
+            }
+            tokio::time::sleep(DISABLED_POLL_INTERVAL).await;
+            continue;
+        }
+
+        let reachable = check(&client, &url).await;
+        match tracker.observe(reachable, Instant::now(), grace) {
+            Some(Transition::WentOffline) => {
+                if let Some(queue_manager) = queue_manager.read().await.as_ref() {
+                    paused_queue = queue_manager.pause_for_outage().await;
+                }
+                let message = if paused_queue {
+                    "Lost the connection, so the queue was paused. It resumes once the connection is back.".to_string()
+                } else {
+                    "Lost the connection.".to_string()
+                };
+                DEBUG_LOGGER.warn("connectivity", format!("{} ({} did not answer)", message, url));
+                events.connectivity_changed(&ConnectivityEvent { online: false, queue_paused: paused_queue, message });
+            }
+            Some(Transition::CameOnline) => {
+                if let Some(queue_manager) = queue_manager.read().await.as_ref() {
+                    queue_manager.end_outage(paused_queue).await;
+                }
+                let message = if paused_queue {
+                    "The connection is back, so the queue was resumed.".to_string()
+                } else {
+                    "The connection is back.".to_string()
+                };
+                DEBUG_LOGGER.info("connectivity", &message);
+                events.connectivity_changed(&ConnectivityEvent { online: true, queue_paused: false, message });
+                paused_queue = false;
+            }
+            None => {}
+        }
+
+        tokio::time::sleep(interval).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tracker_transitions() {
+        let grace = Duration::from_secs(15);
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+        let mut tracker = ConnectivityTracker::new();
+
+        assert_eq!(tracker.observe(false, at(0), grace), None);
+        assert_eq!(tracker.observe(false, at(30), grace), Some(Transition::WentOffline));
+        assert!(!tracker.is_online());
+        assert_eq!(tracker.observe(false, at(60), grace), None);
+
+        // Back, but a failure inside the grace period starts it over
+        assert_eq!(tracker.observe(true, at(90), grace), None);
+        assert_eq!(tracker.observe(false, at(100), grace), None);
+        assert_eq!(tracker.observe(true, at(110), grace), None);
+        assert_eq!(tracker.observe(true, at(120), grace), None);
+        assert_eq!(tracker.observe(true, at(125), grace), Some(Transition::CameOnline));
+        assert!(tracker.is_online());
+        assert_eq!(tracker.observe(true, at(130), grace), None);
+    }
+}
@@ -0,0 +1,235 @@
+use crate::modules::binary_locator::BinaryLocator;
+use crate::modules::process_priority::{self, ProcessPriority};
+use serde::{Deserialize, Serialize};
+use std::ffi::OsString;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
+use tokio::process::Command;
+
+/// Audio formats the downloaded m4a can be transcoded to
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum AudioFormat {
+    Mp3,
+    Flac,
+    Opus,
+}
+
+impl AudioFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            AudioFormat::Mp3 => "mp3",
+            AudioFormat::Flac => "flac",
+            AudioFormat::Opus => "opus",
+        }
+    }
+
+    /// Whether the format takes a bitrate; FLAC is lossless
+    pub fn is_lossy(&self) -> bool {
+        !matches!(self, AudioFormat::Flac)
+    }
+}
+
+impl fmt::Display for AudioFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.extension().to_uppercase())
+    }
+}
+
+#[derive(Debug)]
+pub enum ConvertError {
+    FfmpegNotFound,
+    IoError(io::Error),
+    FfmpegFailed(String),
+}
+
+impl fmt::Display for ConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConvertError::FfmpegNotFound => write!(f, "ffmpeg was not found in the sidecar folder or on PATH"),
+            ConvertError::IoError(e) => write!(f, "IO error: {}", e),
+            ConvertError::FfmpegFailed(msg) => write!(f, "ffmpeg failed: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for ConvertError {}
+
+impl From<io::Error> for ConvertError {
+    fn from(error: io::Error) -> Self {
+        ConvertError::IoError(error)
+    }
+}
+
+/// Transcodes downloaded audio with an ffmpeg sidecar
+pub struct Converter {
+    ffmpeg_path: PathBuf,
+    priority: ProcessPriority,
+}
+
+impl Converter {
+    /// Use the bundled ffmpeg next to the gytmdl sidecar, falling back to PATH
+    pub fn detect() -> Result<Self, ConvertError> {
+        let bundled = BinaryLocator::get_sidecar_directory().join(Self::ffmpeg_binary_name());
+        if bundled.exists() {
+            return Ok(Self::with_ffmpeg_path(bundled));
+        }
+
+        which::which("ffmpeg")
+            .map(Self::with_ffmpeg_path)
+            .map_err(|_| ConvertError::FfmpegNotFound)
+    }
+
+    pub fn with_ffmpeg_path(ffmpeg_path: PathBuf) -> Self {
+        Self { ffmpeg_path, priority: ProcessPriority::Normal }
+    }
+
+    /// Run ffmpeg at `priority`
+    pub fn with_priority(mut self, priority: ProcessPriority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn ffmpeg_binary_name() -> &'static str {
+        if cfg!(target_os = "windows") {
+            "ffmpeg.exe"
+        } else {
+            "ffmpeg"
+        }
+    }
+
+    pub fn get_ffmpeg_path(&self) -> &Path {
+        &self.ffmpeg_path
+    }
+
+    /// Converted file path: the input with the new format's extension
+    pub fn output_path_for(input: &Path, format: AudioFormat) -> PathBuf {
+        input.with_extension(format.extension())
+    }
+
+    /// ffmpeg arguments for one conversion. Tags are carried over and the cover
+    /// is kept where the container supports it.
+    pub fn build_args(input: &Path, output: &Path, format: AudioFormat, bitrate_kbps: u32) -> Vec<OsString> {
+        let mut args: Vec<OsString> = ["-hide_banner", "-nostdin", "-nostats", "-loglevel", "error", "-y", "-i"]
+            .iter()
+            .map(OsString::from)
+            .collect();
+        args.push(input.as_os_str().to_owned());
+
+        let codec_args: &[&str] = match format {
+            AudioFormat::Mp3 => &["-map", "0:a", "-map", "0:v?", "-c:v", "copy", "-c:a", "libmp3lame", "-id3v2_version", "3"],
+            AudioFormat::Flac => &["-map", "0:a", "-map", "0:v?", "-c:v", "copy", "-disposition:v", "attached_pic", "-c:a", "flac"],
+            // Ogg can't carry an attached picture stream
+            AudioFormat::Opus => &["-map", "0:a", "-vn", "-c:a", "libopus"],
+        };
+        args.extend(codec_args.iter().map(OsString::from));
+
+        if format.is_lossy() {
+            args.push("-b:a".into());
+            args.push(format!("{}k", bitrate_kbps).into());
+        }
+
+        args.extend(["-map_metadata", "0", "-progress", "pipe:1"].iter().map(OsString::from));
+        args.push(output.as_os_str().to_owned());
+        args
+    }
+
+    /// Convert one file, reporting percentage progress when the duration is known
+    pub async fn convert<F>(
+        &self,
+        input: &Path,
+        output: &Path,
+        format: AudioFormat,
+        bitrate_kbps: u32,
+        duration_secs: Option<f64>,
+        mut on_progress: F,
+    ) -> Result<(), ConvertError>
+    where
+        F: FnMut(f32),
+    {
+        let mut command = Command::new(&self.ffmpeg_path);
+        command
+            .args(Self::build_args(input, output, format, bitrate_kbps))
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .stdin(Stdio::null())
+            .kill_on_drop(true);
+        process_priority::apply(&mut command, self.priority);
+        let mut child = command.spawn()?;
+
+        let stdout = child.stdout.take().expect("stdout is piped");
+        let mut stderr = child.stderr.take().expect("stderr is piped");
+        let stderr_task = tokio::spawn(async move {
+            let mut buffer = String::new();
+            let _ = stderr.read_to_string(&mut buffer).await;
+            buffer
+        });
+
+        let mut lines = BufReader::new(stdout).lines();
+        while let Some(line) = lines.next_line().await? {
+            if let Some(percentage) = duration_secs.and_then(|duration| parse_progress_line(&line, duration)) {
+                on_progress(percentage);
+            }
+        }
+
+        let status = child.wait().await?;
+        let stderr_output = stderr_task.await.unwrap_or_default();
+        if status.success() {
+            Ok(())
+        } else {
+            // A half-written output file would look like a finished conversion
+            let _ = std::fs::remove_file(output);
+            let message = stderr_output.lines().last().unwrap_or("").trim().to_string();
+            Err(ConvertError::FfmpegFailed(if message.is_empty() {
+                format!("exit status {}", status)
+            } else {
+                message
+            }))
+        }
+    }
+}
+
+/// Percentage from an ffmpeg `-progress` line such as `out_time_us=12345678`
+pub fn parse_progress_line(line: &str, duration_secs: f64) -> Option<f32> {
+    // out_time_ms is also in microseconds; older ffmpeg builds only print that one
+    let value = line.strip_prefix("out_time_us=")
+        .or_else(|| line.strip_prefix("out_time_ms="))?;
+    let micros = value.trim().parse::<i64>().ok()?;
+    if duration_secs <= 0.0 || micros < 0 {
+        return None;
+    }
+    let percentage = micros as f64 / 1_000_000.0 / duration_secs * 100.0;
+    Some(percentage.min(100.0) as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_args() {
+        let input = Path::new("/music/01 Song.m4a");
+        let output = Converter::output_path_for(input, AudioFormat::Mp3);
+        assert_eq!(output, Path::new("/music/01 Song.mp3"));
+
+        let args = Converter::build_args(input, &output, AudioFormat::Mp3, 256);
+        let args: Vec<String> = args.iter().map(|a| a.to_string_lossy().into_owned()).collect();
+        assert!(args.windows(2).any(|w| w == ["-c:a", "libmp3lame"]));
+        assert!(args.windows(2).any(|w| w == ["-b:a", "256k"]));
+        assert_eq!(args.last().unwrap(), "/music/01 Song.mp3");
+
+        let flac = Converter::build_args(input, &output, AudioFormat::Flac, 256);
+        assert!(!flac.iter().any(|a| a == "-b:a"));
+    }
+
+    #[test]
+    fn test_parse_progress_line() {
+        assert_eq!(parse_progress_line("out_time_us=30000000", 120.0), Some(25.0));
+        assert_eq!(parse_progress_line("out_time_ms=60000000", 120.0), Some(50.0));
+        assert_eq!(parse_progress_line("out_time_us=999000000", 120.0), Some(100.0));
+        assert_eq!(parse_progress_line("out_time_us=N/A", 120.0), None);
+        assert_eq!(parse_progress_line("progress=continue", 120.0), None);
+    }
+}
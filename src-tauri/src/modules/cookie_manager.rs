@@ -1,3 +1,5 @@
+use crate::modules::browser_cookies::{self, Browser};
+use std::collections::BTreeSet;
 use std::path::{Path, PathBuf};
 use std::fs;
 use std::io;
@@ -10,6 +12,12 @@ pub struct CookieInfo {
     pub expiration_warning: Option<String>,
     pub po_token_present: bool,
     pub file_path: Option<PathBuf>,
+    /// Distinct cookie domains found in the file
+    #[serde(default)]
+    pub domains: Vec<String>,
+    /// Earliest expiration among persistent YouTube cookies
+    #[serde(default)]
+    pub earliest_expiration: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 #[derive(Debug)]
@@ -53,17 +61,7 @@ impl CookieManager {
 
     /// Import cookies from a file
     pub async fn import_cookies(&self, source_path: &Path) -> Result<CookieInfo, CookieError> {
-        // Check if source file exists
-        if !source_path.exists() {
-            return Err(CookieError::FileNotFound(source_path.to_path_buf()));
-        }
-
-        // Read and validate the cookie file
-        let content = fs::read_to_string(source_path)
-            .map_err(CookieError::ReadError)?;
-
-        // Validate cookie format
-        self.validate_cookie_content(&content)?;
+        let content = self.read_and_validate(source_path)?;
 
         // Create cookies directory if it doesn't exist
         if let Err(e) = fs::create_dir_all(&self.cookies_dir) {
@@ -80,11 +78,73 @@ impl CookieManager {
         let cookie_info = self.analyze_cookies(&content)?;
 
         Ok(CookieInfo {
-            is_valid: cookie_info.is_valid,
-            expiration_warning: cookie_info.expiration_warning,
-            po_token_present: cookie_info.po_token_present,
             file_path: Some(target_path),
+            ..cookie_info
+        })
+    }
+
+    /// Extract YouTube cookies from a locally installed browser ("chrome",
+    /// "edge" or "firefox") into the managed cookies file
+    pub async fn extract_cookies_from_browser(&self, browser: &str) -> Result<CookieInfo, CookieError> {
+        let browser: Browser = browser.parse().map_err(CookieError::ValidationError)?;
+
+        // Reading the database and looking up the key may block on disk or a keyring prompt
+        let manager = Self::with_cookies_dir(self.cookies_dir.clone());
+        tokio::task::spawn_blocking(move || {
+            let database = browser.find_cookie_database()
+                .ok_or_else(|| CookieError::ValidationError(format!("No {:?} cookie database found", browser)))?;
+            manager.extract_cookies_from_database(browser, &database)
         })
+        .await
+        .map_err(|e| CookieError::ValidationError(format!("Cookie extraction did not finish: {}", e)))?
+    }
+
+    /// Convert a browser cookie database into the managed Netscape cookies file
+    fn extract_cookies_from_database(&self, browser: Browser, database: &Path) -> Result<CookieInfo, CookieError> {
+        let cookies = browser_cookies::read_cookies(browser, database)
+            .map_err(CookieError::ValidationError)?;
+        let content = browser_cookies::to_netscape(&cookies);
+        self.validate_cookie_content(&content)?;
+
+        fs::create_dir_all(&self.cookies_dir).map_err(CookieError::ReadError)?;
+        let target_path = self.get_cookies_path();
+        fs::write(&target_path, &content).map_err(CookieError::ReadError)?;
+
+        let cookie_info = self.analyze_cookies(&content)?;
+
+        Ok(CookieInfo {
+            file_path: Some(target_path),
+            ..cookie_info
+        })
+    }
+
+    /// Inspect an arbitrary cookie file without importing it.
+    /// The managed cookies are left untouched.
+    pub async fn inspect_cookies(&self, source_path: &Path) -> Result<CookieInfo, CookieError> {
+        let content = self.read_and_validate(source_path)?;
+        let cookie_info = self.analyze_cookies(&content)?;
+
+        Ok(CookieInfo {
+            file_path: Some(source_path.to_path_buf()),
+            ..cookie_info
+        })
+    }
+
+    /// Read a cookie file and check its format
+    fn read_and_validate(&self, source_path: &Path) -> Result<String, CookieError> {
+        // Check if source file exists
+        if !source_path.exists() {
+            return Err(CookieError::FileNotFound(source_path.to_path_buf()));
+        }
+
+        // Read and validate the cookie file
+        let content = fs::read_to_string(source_path)
+            .map_err(CookieError::ReadError)?;
+
+        // Validate cookie format
+        self.validate_cookie_content(&content)?;
+
+        Ok(content)
     }
 
     /// Validate cookies from the managed location
@@ -97,6 +157,8 @@ impl CookieManager {
                 expiration_warning: Some("No cookies file found".to_string()),
                 po_token_present: false,
                 file_path: None,
+                domains: Vec::new(),
+                earliest_expiration: None,
             });
         }
 
@@ -106,10 +168,8 @@ impl CookieManager {
         let cookie_info = self.analyze_cookies(&content)?;
 
         Ok(CookieInfo {
-            is_valid: cookie_info.is_valid,
-            expiration_warning: cookie_info.expiration_warning,
-            po_token_present: cookie_info.po_token_present,
             file_path: Some(cookie_path),
+            ..cookie_info
         })
     }
 
@@ -183,6 +243,8 @@ impl CookieManager {
         let mut has_youtube_cookies = false;
         let mut has_po_token = false;
         let mut expiration_warnings = Vec::new();
+        let mut domains = BTreeSet::new();
+        let mut earliest_expiration: Option<i64> = None;
         let current_time = chrono::Utc::now().timestamp();
 
         for line in content.lines() {
@@ -202,6 +264,8 @@ impl CookieManager {
             let name = parts[5];
             let value = parts[6];
 
+            domains.insert(domain.trim_start_matches('.').to_string());
+
             // Check for YouTube domain
             if domain.contains("youtube.com") {
                 has_youtube_cookies = true;
@@ -213,6 +277,11 @@ impl CookieManager {
 
                 // Check expiration (parts[4] is expiration timestamp)
                 if let Ok(expiration) = parts[4].parse::<i64>() {
+                    // Session cookies have an expiration of 0
+                    if expiration > 0 {
+                        earliest_expiration = Some(earliest_expiration.map_or(expiration, |e| e.min(expiration)));
+                    }
+
                     let days_until_expiration = (expiration - current_time) / 86400; // seconds to days
                     
                     if days_until_expiration < 0 {
@@ -242,6 +311,9 @@ impl CookieManager {
             expiration_warning,
             po_token_present: has_po_token,
             file_path: None, // Will be set by caller
+            domains: domains.into_iter().collect(),
+            earliest_expiration: earliest_expiration
+                .and_then(|timestamp| chrono::DateTime::from_timestamp(timestamp, 0)),
         })
     }
 }
@@ -331,6 +403,50 @@ mod tests {
         assert!(cookie_info.file_path.is_some());
     }
 
+    #[tokio::test]
+    async fn test_extract_cookies_from_database() {
+        let temp_dir = tempdir().unwrap();
+        let cookies_dir = temp_dir.path().join("cookies");
+        let manager = CookieManager::with_cookies_dir(cookies_dir.clone());
+
+        let database = temp_dir.path().join("cookies.sqlite");
+        let connection = rusqlite::Connection::open(&database).unwrap();
+        connection.execute_batch("
+            CREATE TABLE moz_cookies (host TEXT, name TEXT, value TEXT, path TEXT, expiry INTEGER, isSecure INTEGER);
+            INSERT INTO moz_cookies VALUES ('.youtube.com', 'SAPISID', 'abc', '/', 1900000000, 1);
+        ").unwrap();
+        drop(connection);
+
+        let info = manager.extract_cookies_from_database(Browser::Firefox, &database).unwrap();
+        assert!(info.is_valid);
+        assert_eq!(info.file_path, Some(cookies_dir.join("cookies.txt")));
+        assert!(fs::read_to_string(cookies_dir.join("cookies.txt")).unwrap().contains("SAPISID\tabc"));
+
+        assert!(matches!(
+            manager.extract_cookies_from_browser("safari").await,
+            Err(CookieError::ValidationError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_inspect_cookies_does_not_import() {
+        let temp_dir = tempdir().unwrap();
+        let manager = CookieManager::with_cookies_dir(temp_dir.path().join("cookies"));
+
+        let source_file = temp_dir.path().join("export.txt");
+        let content = ".youtube.com\tTRUE\t/\tTRUE\t9999999999\tSAPISID\ttest_value\n.music.youtube.com\tTRUE\t/\tTRUE\t1999999999\tHSID\tvalue\n.google.com\tTRUE\t/\tTRUE\t0\tNID\tvalue";
+        fs::write(&source_file, content).unwrap();
+
+        let info = manager.inspect_cookies(&source_file).await.unwrap();
+        assert!(info.is_valid);
+        assert_eq!(info.file_path, Some(source_file));
+        assert_eq!(info.domains, vec!["google.com", "music.youtube.com", "youtube.com"]);
+        assert_eq!(info.earliest_expiration.unwrap().timestamp(), 1999999999);
+
+        // Nothing was copied into the managed store
+        assert!(!manager.get_cookies_path().exists());
+    }
+
     #[tokio::test]
     async fn test_validate_cookies_no_file() {
         let temp_dir = tempdir().unwrap();
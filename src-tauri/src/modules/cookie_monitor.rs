@@ -0,0 +1,125 @@
+use crate::modules::cookie_manager::{CookieInfo, CookieManager};
+use crate::modules::debug_logger::DEBUG_LOGGER;
+use crate::modules::events::EventEmitter;
+use crate::modules::notifier::Notifier;
+use crate::modules::state::AppState;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+/// How often the managed cookies are re-checked
+const CHECK_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Cookies that have expired or will expire within the warning window
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CookieExpiryWarning {
+    pub expired: bool,
+    /// Whole days until the earliest YouTube cookie expires, negative once expired
+    pub days_remaining: i64,
+    pub expires_at: DateTime<Utc>,
+    pub message: String,
+}
+
+/// Check whether cookies need attention. Returns nothing for healthy
+/// cookies, for a missing cookie file, or when `warning_days` is 0.
+pub fn check_expiry(info: &CookieInfo, warning_days: u32, now: DateTime<Utc>) -> Option<CookieExpiryWarning> {
+    if warning_days == 0 {
+        return None;
+    }
+    let expires_at = info.earliest_expiration?;
+    let days_remaining = (expires_at - now).num_days();
+
+    if expires_at <= now {
+        Some(CookieExpiryWarning {
+            expired: true,
+            days_remaining,
+            expires_at,
+            message: "YouTube cookies have expired. Re-import them to keep downloading age-restricted and premium content.".to_string(),
+        })
+    } else if days_remaining < warning_days as i64 {
+        Some(CookieExpiryWarning {
+            expired: false,
+            days_remaining,
+            expires_at,
+            message: format!("YouTube cookies expire in {} day(s). Re-import them soon to avoid failed downloads.", days_remaining),
+        })
+    } else {
+        None
+    }
+}
+
+/// Periodically validate the managed cookies and emit a `cookie-expiry`
+/// event whenever the warning changes. Runs for the lifetime of the app.
+pub async fn run(
+    cookie_manager: Arc<RwLock<CookieManager>>,
+    state: Arc<RwLock<AppState>>,
+    events: EventEmitter,
+    notifier: Notifier,
+) {
+    let mut last_warning: Option<CookieExpiryWarning> = None;
+    let mut ticker = tokio::time::interval(CHECK_INTERVAL);
+
+    loop {
+        ticker.tick().await;
+
+        let (warning_days, notify) = {
+            let config = &state.read().await.config;
+            (config.cookie_expiry_warning_days, config.notify_on_cookie_expiry)
+        };
+        let info = match cookie_manager.read().await.validate_cookies().await {
+            Ok(info) => info,
+            Err(e) => {
+                DEBUG_LOGGER.warn("cookies", format!("Cookie expiry check failed: {}", e));
+                continue;
+            }
+        };
+
+        let warning = check_expiry(&info, warning_days, Utc::now());
+        if warning != last_warning {
+            if let Some(warning) = &warning {
+                DEBUG_LOGGER.warn("cookies", warning.message.clone());
+                events.cookie_expiry(warning);
+                if notify {
+                    notifier.cookie_expiry(warning);
+                }
+            }
+            last_warning = warning;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn info(earliest_expiration: Option<DateTime<Utc>>) -> CookieInfo {
+        CookieInfo {
+            is_valid: true,
+            expiration_warning: None,
+            po_token_present: false,
+            file_path: None,
+            domains: vec!["youtube.com".to_string()],
+            earliest_expiration,
+        }
+    }
+
+    #[test]
+    fn test_check_expiry() {
+        let now = Utc.with_ymd_and_hms(2024, 6, 1, 12, 0, 0).unwrap();
+
+        // Healthy, missing and disabled checks produce nothing
+        assert!(check_expiry(&info(Some(now + chrono::Duration::days(30))), 7, now).is_none());
+        assert!(check_expiry(&info(None), 7, now).is_none());
+        assert!(check_expiry(&info(Some(now - chrono::Duration::days(1))), 0, now).is_none());
+
+        let warning = check_expiry(&info(Some(now + chrono::Duration::days(3))), 7, now).unwrap();
+        assert!(!warning.expired);
+        assert_eq!(warning.days_remaining, 3);
+
+        let warning = check_expiry(&info(Some(now - chrono::Duration::hours(1))), 7, now).unwrap();
+        assert!(warning.expired);
+    }
+}
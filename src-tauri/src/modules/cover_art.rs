@@ -0,0 +1,335 @@
+use crate::modules::converter::ConvertError;
+use crate::modules::library::sanitize_component;
+use crate::modules::state::{CoverFormat, JobMetadata};
+use serde::Serialize;
+use std::ffi::OsString;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::process::Command;
+use url::Url;
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug)]
+pub enum CoverError {
+    /// The job has no thumbnail and its URL doesn't name a video
+    NoCoverSource,
+    RequestFailed(String),
+    Convert(ConvertError),
+    IoError(io::Error),
+}
+
+impl fmt::Display for CoverError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CoverError::NoCoverSource => write!(f, "No cover art URL is known for this job"),
+            CoverError::RequestFailed(msg) => write!(f, "Cover download failed: {}", msg),
+            CoverError::Convert(e) => write!(f, "{}", e),
+            CoverError::IoError(e) => write!(f, "IO error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for CoverError {}
+
+impl From<ConvertError> for CoverError {
+    fn from(e: ConvertError) -> Self {
+        CoverError::Convert(e)
+    }
+}
+
+impl From<io::Error> for CoverError {
+    fn from(e: io::Error) -> Self {
+        CoverError::IoError(e)
+    }
+}
+
+/// Where a new cover comes from
+#[derive(Debug, Clone)]
+pub enum CoverSource {
+    Url(String),
+    File(PathBuf),
+}
+
+/// How a cover is rendered and where copies of it are kept
+#[derive(Debug, Clone)]
+pub struct CoverOptions {
+    pub size: u32,
+    pub format: CoverFormat,
+    pub quality: u8,
+    /// Write `<track>.<ext>` next to each track, as gytmdl's save-cover does
+    pub save_next_to_tracks: bool,
+    /// Also copy the cover to this path in the central cover folder
+    pub central_path: Option<PathBuf>,
+}
+
+/// What a cover update changed
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CoverUpdate {
+    /// Audio files the new cover was embedded into
+    pub embedded: Vec<PathBuf>,
+    /// Audio files whose container can't carry a cover
+    pub skipped: Vec<PathBuf>,
+    /// Cover image files written
+    pub saved: Vec<PathBuf>,
+}
+
+/// Ask for a `size`x`size` image. YouTube Music thumbnails are served from
+/// googleusercontent with the size in a `=w544-h544...` suffix; other URLs are
+/// returned as they are.
+pub fn cover_url(thumbnail: &str, size: u32) -> String {
+    if thumbnail.contains("googleusercontent.com") {
+        if let Some((base, _)) = thumbnail.rsplit_once('=') {
+            return format!("{}=w{}-h{}-l90-rj", base, size, size);
+        }
+    }
+    thumbnail.to_string()
+}
+
+/// Highest resolution video thumbnail for a watch URL
+pub fn video_thumbnail_url(url: &str) -> Option<String> {
+    let parsed = Url::parse(url).ok()?;
+    let video_id = parsed.query_pairs()
+        .find(|(key, _)| key == "v")
+        .map(|(_, value)| value.into_owned())
+        .or_else(|| (parsed.host_str() == Some("youtu.be")).then(|| parsed.path().trim_matches('/').to_string()))
+        .filter(|id| !id.is_empty())?;
+    Some(format!("https://i.ytimg.com/vi/{}/maxresdefault.jpg", video_id))
+}
+
+/// `<folder>/<artist> - <album>.<ext>`, falling back to the title and then the job id
+pub fn central_cover_path(folder: &Path, metadata: Option<&JobMetadata>, job_id: &str, format: &CoverFormat) -> PathBuf {
+    let name = metadata
+        .and_then(|metadata| {
+            let artist = metadata.artist.as_deref()?;
+            let album = metadata.album.as_deref().or(metadata.title.as_deref())?;
+            Some(format!("{} - {}", artist, album))
+        })
+        .unwrap_or_else(|| job_id.to_string());
+    folder.join(format!("{}.{}", sanitize_component(&name), format.extension()))
+}
+
+/// The cover gytmdl saves for a track: the track path with the cover's extension
+pub fn track_cover_path(track: &Path, format: &CoverFormat) -> PathBuf {
+    track.with_extension(format.extension())
+}
+
+/// ffmpeg arguments to scale an image to fit `size` and encode it as `format`
+pub fn build_render_args(input: &Path, output: &Path, size: u32, format: &CoverFormat, quality: u8) -> Vec<OsString> {
+    let mut args: Vec<OsString> = ["-hide_banner", "-nostdin", "-loglevel", "error", "-y", "-i"]
+        .iter()
+        .map(OsString::from)
+        .collect();
+    args.push(input.as_os_str().to_owned());
+    args.push("-vf".into());
+    args.push(format!("scale={0}:{0}:force_original_aspect_ratio=decrease", size).into());
+    args.push("-frames:v".into());
+    args.push("1".into());
+
+    match format {
+        // mjpeg quality runs from 2 (best) to 31
+        CoverFormat::Jpg => {
+            let scale = 2 + (100 - u32::from(quality.min(100))) * 29 / 100;
+            args.push("-q:v".into());
+            args.push(scale.to_string().into());
+        }
+        CoverFormat::Webp => {
+            args.push("-quality".into());
+            args.push(quality.to_string().into());
+        }
+        CoverFormat::Png => {}
+    }
+
+    args.push(output.as_os_str().to_owned());
+    args
+}
+
+/// ffmpeg arguments to replace the cover of `audio`, or None if its container
+/// can't carry one. The existing cover stream is dropped and tags are kept.
+pub fn build_embed_args(audio: &Path, cover: &Path, output: &Path) -> Option<Vec<OsString>> {
+    let extension = audio.extension()?.to_string_lossy().to_lowercase();
+    let container_args: &[&str] = match extension.as_str() {
+        "m4a" | "mp4" => &["-movflags", "use_metadata_tags"],
+        "mp3" => &["-id3v2_version", "3"],
+        "flac" => &[],
+        _ => return None,
+    };
+
+    let mut args: Vec<OsString> = ["-hide_banner", "-nostdin", "-loglevel", "error", "-y", "-i"]
+        .iter()
+        .map(OsString::from)
+        .collect();
+    args.push(audio.as_os_str().to_owned());
+    args.push("-i".into());
+    args.push(cover.as_os_str().to_owned());
+    args.extend(
+        ["-map", "0:a", "-map", "1:v", "-c", "copy", "-disposition:v:0", "attached_pic", "-map_metadata", "0"]
+            .iter()
+            .map(OsString::from),
+    );
+    args.extend(container_args.iter().map(OsString::from));
+    args.push(output.as_os_str().to_owned());
+    Some(args)
+}
+
+async fn run_ffmpeg(ffmpeg: &Path, args: Vec<OsString>) -> Result<(), ConvertError> {
+    let output = Command::new(ffmpeg)
+        .args(args)
+        .stdin(Stdio::null())
+        .kill_on_drop(true)
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(ConvertError::FfmpegFailed(stderr.lines().last().unwrap_or("").trim().to_string()));
+    }
+    Ok(())
+}
+
+/// Download an image to `dest`
+pub async fn download(url: &str, dest: &Path) -> Result<(), CoverError> {
+    let client = reqwest::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .build()
+        .map_err(|e| CoverError::RequestFailed(e.to_string()))?;
+    let response = client.get(url).send().await
+        .map_err(|e| CoverError::RequestFailed(e.to_string()))?;
+    if !response.status().is_success() {
+        return Err(CoverError::RequestFailed(format!("HTTP {}", response.status())));
+    }
+    let bytes = response.bytes().await
+        .map_err(|e| CoverError::RequestFailed(e.to_string()))?;
+    tokio::fs::write(dest, &bytes).await?;
+    Ok(())
+}
+
+/// Scale and re-encode an image
+pub async fn render(ffmpeg: &Path, input: &Path, output: &Path, size: u32, format: &CoverFormat, quality: u8) -> Result<(), ConvertError> {
+    run_ffmpeg(ffmpeg, build_render_args(input, output, size, format, quality)).await
+}
+
+/// Replace the embedded cover of one audio file. Returns false if its
+/// container can't carry a cover.
+pub async fn embed(ffmpeg: &Path, audio: &Path, cover: &Path) -> Result<bool, ConvertError> {
+    let temp_output = embed_temp_path(audio);
+    let Some(args) = build_embed_args(audio, cover, &temp_output) else {
+        return Ok(false);
+    };
+    if let Err(e) = run_ffmpeg(ffmpeg, args).await {
+        let _ = std::fs::remove_file(&temp_output);
+        return Err(e);
+    }
+    std::fs::rename(&temp_output, audio)?;
+    Ok(true)
+}
+
+/// `song.m4a` -> `song.cover.m4a`; ffmpeg picks the muxer from the extension
+fn embed_temp_path(audio: &Path) -> PathBuf {
+    let stem = audio.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    match audio.extension() {
+        Some(ext) => audio.with_file_name(format!("{}.cover.{}", stem, ext.to_string_lossy())),
+        None => audio.with_file_name(format!("{}.cover", stem)),
+    }
+}
+
+/// Fetch or read a cover, render it, embed it into `files` and save the
+/// copies `options` asks for. `work_dir` holds intermediate images and is
+/// left for the caller to remove.
+pub async fn apply(
+    ffmpeg: &Path,
+    source: &CoverSource,
+    files: &[PathBuf],
+    options: &CoverOptions,
+    work_dir: &Path,
+) -> Result<CoverUpdate, CoverError> {
+    tokio::fs::create_dir_all(work_dir).await?;
+    let original = match source {
+        CoverSource::Url(url) => {
+            let dest = work_dir.join("original");
+            download(&cover_url(url, options.size), &dest).await?;
+            dest
+        }
+        CoverSource::File(path) => path.clone(),
+    };
+
+    let rendered = work_dir.join(format!("cover.{}", options.format.extension()));
+    render(ffmpeg, &original, &rendered, options.size, &options.format, options.quality).await?;
+
+    // mp4 and ID3 can't hold webp, so embed a jpg rendering instead
+    let embeddable = if options.format == CoverFormat::Webp {
+        let jpg = work_dir.join("cover.jpg");
+        render(ffmpeg, &original, &jpg, options.size, &CoverFormat::Jpg, options.quality).await?;
+        jpg
+    } else {
+        rendered.clone()
+    };
+
+    let mut update = CoverUpdate::default();
+    for file in files {
+        if embed(ffmpeg, file, &embeddable).await? {
+            update.embedded.push(file.clone());
+        } else {
+            update.skipped.push(file.clone());
+        }
+        if options.save_next_to_tracks {
+            let dest = track_cover_path(file, &options.format);
+            tokio::fs::copy(&rendered, &dest).await?;
+            update.saved.push(dest);
+        }
+    }
+
+    if let Some(central_path) = &options.central_path {
+        if let Some(parent) = central_path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::copy(&rendered, central_path).await?;
+        update.saved.push(central_path.clone());
+    }
+
+    Ok(update)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cover_urls() {
+        assert_eq!(
+            cover_url("https://lh3.googleusercontent.com/abc=w544-h544-l90-rj", 1400),
+            "https://lh3.googleusercontent.com/abc=w1400-h1400-l90-rj"
+        );
+        assert_eq!(cover_url("https://i.ytimg.com/vi/abc/hqdefault.jpg", 1400), "https://i.ytimg.com/vi/abc/hqdefault.jpg");
+
+        assert_eq!(
+            video_thumbnail_url("https://music.youtube.com/watch?v=abc123&list=xyz").as_deref(),
+            Some("https://i.ytimg.com/vi/abc123/maxresdefault.jpg")
+        );
+        assert!(video_thumbnail_url("https://music.youtube.com/playlist?list=xyz").is_none());
+    }
+
+    #[test]
+    fn test_embed_args_and_paths() {
+        let args = build_embed_args(Path::new("song.mp3"), Path::new("cover.jpg"), Path::new("song.cover.mp3")).unwrap();
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string_lossy().into_owned()).collect();
+        assert!(args.windows(2).any(|pair| pair == ["-disposition:v:0", "attached_pic"]));
+        assert!(args.windows(2).any(|pair| pair == ["-id3v2_version", "3"]));
+        assert_eq!(args.last().map(String::as_str), Some("song.cover.mp3"));
+        assert!(build_embed_args(Path::new("song.opus"), Path::new("cover.jpg"), Path::new("out.opus")).is_none());
+
+        let metadata = JobMetadata {
+            artist: Some("AC/DC".to_string()),
+            album: Some("Back in Black".to_string()),
+            ..JobMetadata::default()
+        };
+        assert_eq!(
+            central_cover_path(Path::new("covers"), Some(&metadata), "job", &CoverFormat::Png),
+            Path::new("covers").join("AC_DC - Back in Black.png")
+        );
+        assert_eq!(central_cover_path(Path::new("covers"), None, "job", &CoverFormat::Jpg), Path::new("covers").join("job.jpg"));
+    }
+}
@@ -0,0 +1,325 @@
+use crate::modules::events::{EventEmitter, LOG_ENTRY_EVENT};
+use crate::modules::log_files::{LogFileSettings, RotatingLogFile};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{LazyLock, Mutex, RwLock};
+
+/// Maximum number of entries kept for the debug panel
+const DEFAULT_CAPACITY: usize = 2000;
+
+/// Process-wide logger shared by every module
+pub static DEBUG_LOGGER: LazyLock<DebugLogger> = LazyLock::new(DebugLogger::new);
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    #[default]
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+/// A single log message tagged with the component that produced it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogEntry {
+    pub timestamp: DateTime<Utc>,
+    pub level: LogLevel,
+    pub component: String,
+    pub message: String,
+}
+
+/// Lowest level recorded, overall and for specific components
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct LogLevels {
+    pub default: LogLevel,
+    /// Overrides of `default`, keyed by component
+    pub components: HashMap<String, LogLevel>,
+}
+
+impl LogLevels {
+    pub fn level_for(&self, component: &str) -> LogLevel {
+        self.components.get(component).copied().unwrap_or(self.default)
+    }
+}
+
+/// Filter and page over recorded entries. Every field is optional, so an
+/// empty query returns all entries, oldest first.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct LogQuery {
+    pub min_level: LogLevel,
+    /// Only entries from these components; all components when empty
+    pub components: Vec<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    /// Case-insensitive text matched against the message
+    pub search: Option<String>,
+    pub newest_first: bool,
+    pub offset: usize,
+    pub limit: Option<usize>,
+}
+
+/// One page of entries matching a query
+#[derive(Debug, Clone, Serialize)]
+pub struct LogPage {
+    pub entries: Vec<LogEntry>,
+    /// Entries matching the filters, before paging
+    pub total: usize,
+}
+
+impl LogQuery {
+    pub fn matches(&self, entry: &LogEntry) -> bool {
+        if entry.level < self.min_level
+            || (!self.components.is_empty() && !self.components.contains(&entry.component))
+            || self.since.is_some_and(|since| entry.timestamp < since)
+            || self.until.is_some_and(|until| entry.timestamp > until)
+        {
+            return false;
+        }
+        match self.search.as_deref().map(str::trim).filter(|search| !search.is_empty()) {
+            Some(search) => entry.message.to_lowercase().contains(&search.to_lowercase()),
+            None => true,
+        }
+    }
+}
+
+/// In-memory log ring that mirrors entries to the terminal, to rotating log
+/// files when enabled and, once attached to the app, to the frontend as
+/// `log-entry` events
+pub struct DebugLogger {
+    capacity: usize,
+    entries: Mutex<VecDeque<LogEntry>>,
+    events: RwLock<EventEmitter>,
+    file: Mutex<Option<RotatingLogFile>>,
+    levels: RwLock<LogLevels>,
+}
+
+impl DebugLogger {
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::new(VecDeque::new()),
+            events: RwLock::new(EventEmitter::disabled()),
+            file: Mutex::new(None),
+            levels: RwLock::new(LogLevels::default()),
+        }
+    }
+
+    /// Start forwarding new entries to the frontend
+    pub fn attach(&self, events: EventEmitter) {
+        if let Ok(mut guard) = self.events.write() {
+            *guard = events;
+        }
+    }
+
+    /// Write entries to log files with these settings, or stop writing them
+    pub fn set_file_output(&self, settings: Option<LogFileSettings>) {
+        if let Ok(mut file) = self.file.lock() {
+            if file.as_ref().map(RotatingLogFile::settings) != settings.as_ref() {
+                *file = settings.map(RotatingLogFile::new);
+            }
+        }
+    }
+
+    /// Write buffered entries to the current log file
+    pub fn flush_file(&self) {
+        if let Ok(mut file) = self.file.lock() {
+            if let Some(Err(e)) = file.as_mut().map(RotatingLogFile::flush) {
+                eprintln!("[Error debug_logger] Failed to flush log file: {}", e);
+            }
+        }
+    }
+
+    /// Record entries at or above `level`, for `component` only when given
+    pub fn set_level(&self, level: LogLevel, component: Option<&str>) {
+        if let Ok(mut levels) = self.levels.write() {
+            match component {
+                Some(component) => {
+                    levels.components.insert(component.to_string(), level);
+                }
+                None => levels.default = level,
+            }
+        }
+    }
+
+    pub fn levels(&self) -> LogLevels {
+        self.levels.read().map(|levels| levels.clone()).unwrap_or_default()
+    }
+
+    /// Record an entry, unless its level is below the one set for its component
+    pub fn log(&self, level: LogLevel, component: &str, message: impl Into<String>) {
+        let recorded = self.levels.read().map_or(true, |levels| level >= levels.level_for(component));
+        if !recorded {
+            return;
+        }
+
+        let entry = LogEntry {
+            timestamp: Utc::now(),
+            level,
+            component: component.to_string(),
+            message: message.into(),
+        };
+
+        match level {
+            LogLevel::Warn | LogLevel::Error => {
+                eprintln!("[{:?} {}] {}", entry.level, entry.component, entry.message)
+            }
+            LogLevel::Debug | LogLevel::Info => {
+                println!("[{:?} {}] {}", entry.level, entry.component, entry.message)
+            }
+        }
+
+        if let Ok(events) = self.events.read() {
+            events.emit(LOG_ENTRY_EVENT, entry.clone());
+        }
+
+        if let Ok(mut file) = self.file.lock() {
+            // Logging the failure through the logger would recurse; stop writing instead
+            if let Some(Err(e)) = file.as_mut().map(|file| file.write_entry(&entry)) {
+                eprintln!("[Error debug_logger] Failed to write log file, disabling it: {}", e);
+                *file = None;
+            }
+        }
+
+        if let Ok(mut entries) = self.entries.lock() {
+            if entries.len() >= self.capacity {
+                entries.pop_front();
+            }
+            entries.push_back(entry);
+        }
+    }
+
+    pub fn debug(&self, component: &str, message: impl Into<String>) {
+        self.log(LogLevel::Debug, component, message);
+    }
+
+    pub fn info(&self, component: &str, message: impl Into<String>) {
+        self.log(LogLevel::Info, component, message);
+    }
+
+    pub fn warn(&self, component: &str, message: impl Into<String>) {
+        self.log(LogLevel::Warn, component, message);
+    }
+
+    pub fn error(&self, component: &str, message: impl Into<String>) {
+        self.log(LogLevel::Error, component, message);
+    }
+
+    /// Get recorded entries at or above `min_level`, oldest first
+    pub fn entries(&self, min_level: LogLevel) -> Vec<LogEntry> {
+        self.entries.lock()
+            .map(|entries| entries.iter().filter(|entry| entry.level >= min_level).cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Recorded entries matching `query`
+    pub fn query(&self, query: &LogQuery) -> LogPage {
+        let Ok(entries) = self.entries.lock() else {
+            return LogPage { entries: Vec::new(), total: 0 };
+        };
+        let mut matching: Vec<&LogEntry> = entries.iter().filter(|entry| query.matches(entry)).collect();
+        let total = matching.len();
+        if query.newest_first {
+            matching.reverse();
+        }
+        let entries = matching.into_iter()
+            .skip(query.offset)
+            .take(query.limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect();
+        LogPage { entries, total }
+    }
+
+    /// Drop all recorded entries
+    pub fn clear(&self) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.clear();
+        }
+    }
+}
+
+impl Default for DebugLogger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entries_filtered_by_level() {
+        let logger = DebugLogger::with_capacity(10);
+        logger.debug("queue", "dispatching");
+        logger.warn("wrapper", "using system binary");
+        logger.error("queue", "spawn failed");
+
+        assert_eq!(logger.entries(LogLevel::Debug).len(), 3);
+        let warnings = logger.entries(LogLevel::Warn);
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(warnings[0].component, "wrapper");
+
+        logger.clear();
+        assert!(logger.entries(LogLevel::Debug).is_empty());
+    }
+
+    #[test]
+    fn test_query() {
+        let logger = DebugLogger::with_capacity(10);
+        logger.info("queue", "dispatching job 1");
+        logger.warn("wrapper", "using system binary");
+        logger.error("queue", "Spawn failed for job 2");
+        logger.info("queue", "dispatching job 3");
+
+        let page = logger.query(&LogQuery {
+            components: vec!["queue".to_string()],
+            search: Some("JOB".to_string()),
+            newest_first: true,
+            limit: Some(2),
+            ..LogQuery::default()
+        });
+        assert_eq!(page.total, 3);
+        let messages: Vec<&str> = page.entries.iter().map(|entry| entry.message.as_str()).collect();
+        assert_eq!(messages, vec!["dispatching job 3", "Spawn failed for job 2"]);
+
+        let page = logger.query(&LogQuery {
+            min_level: LogLevel::Warn,
+            since: Some(Utc::now() + chrono::Duration::minutes(1)),
+            ..LogQuery::default()
+        });
+        assert_eq!(page.total, 0);
+    }
+
+    #[test]
+    fn test_levels_limit_recording() {
+        let logger = DebugLogger::with_capacity(10);
+        logger.set_level(LogLevel::Warn, None);
+        logger.set_level(LogLevel::Debug, Some("queue"));
+
+        logger.info("wrapper", "dropped");
+        logger.warn("wrapper", "kept");
+        logger.debug("queue", "kept");
+
+        let entries = logger.entries(LogLevel::Debug);
+        assert_eq!(entries.len(), 2);
+        assert!(entries.iter().all(|entry| entry.message == "kept"));
+    }
+
+    #[test]
+    fn test_capacity() {
+        let logger = DebugLogger::with_capacity(2);
+        for i in 0..5 {
+            logger.info("test", format!("message {}", i));
+        }
+
+        let entries = logger.entries(LogLevel::Debug);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].message, "message 3");
+    }
+}
@@ -0,0 +1,163 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use url::Url;
+
+/// Custom URL scheme registered with the OS, e.g. `gytmdl://add?url=…`
+pub const SCHEME: &str = "gytmdl";
+
+/// Label for links that don't name a `source`
+pub const DEFAULT_SOURCE: &str = "gytmdl://";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeepLinkError {
+    InvalidLink(String),
+    UnsupportedScheme(String),
+    UnknownAction(String),
+    MissingUrl,
+}
+
+impl fmt::Display for DeepLinkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeepLinkError::InvalidLink(link) => write!(f, "Invalid deep link: '{}'", link),
+            DeepLinkError::UnsupportedScheme(scheme) => write!(f, "Unsupported deep link scheme: '{}'", scheme),
+            DeepLinkError::UnknownAction(action) => write!(f, "Unknown deep link action: '{}'", action),
+            DeepLinkError::MissingUrl => write!(f, "Deep link is missing the 'url' parameter"),
+        }
+    }
+}
+
+impl std::error::Error for DeepLinkError {}
+
+/// A URL to queue, taken from a deep link
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeepLinkRequest {
+    pub url: String,
+    /// Self-declared origin of the link (a browser extension, a web page...).
+    /// Anyone can open a link claiming any source, so this is only shown to
+    /// the user and never authorizes anything.
+    pub source: String,
+    /// API token that lets the link queue without asking, if its scope allows
+    #[serde(skip_serializing)]
+    pub token: Option<String>,
+}
+
+/// Parse a link handed over by the OS. Accepts
+/// `gytmdl://add?url=<percent-encoded URL>&source=<origin>&token=<API token>`,
+/// where `source` and `token` are optional, and, for share
+/// targets, plain `https://` links, which are passed through as-is.
+pub fn parse(link: &str) -> Result<DeepLinkRequest, DeepLinkError> {
+    let parsed = Url::parse(link.trim()).map_err(|_| DeepLinkError::InvalidLink(link.to_string()))?;
+
+    match parsed.scheme() {
+        "http" | "https" => Ok(DeepLinkRequest {
+            url: parsed.to_string(),
+            source: DEFAULT_SOURCE.to_string(),
+            token: None,
+        }),
+        SCHEME => {
+            let action = parsed.host_str().unwrap_or_default();
+            if action != "add" {
+                return Err(DeepLinkError::UnknownAction(action.to_string()));
+            }
+
+            let mut url = None;
+            let mut source = None;
+            let mut token = None;
+            for (key, value) in parsed.query_pairs() {
+                match key.as_ref() {
+                    "url" => url = Some(value.into_owned()),
+                    "source" => source = Some(value.into_owned()),
+                    "token" => token = Some(value.into_owned()),
+                    _ => {}
+                }
+            }
+
+            Ok(DeepLinkRequest {
+                url: url.filter(|url| !url.trim().is_empty()).ok_or(DeepLinkError::MissingUrl)?,
+                source: source.filter(|source| !source.is_empty()).unwrap_or_else(|| DEFAULT_SOURCE.to_string()),
+                token: token.filter(|token| !token.is_empty()),
+            })
+        }
+        other => Err(DeepLinkError::UnsupportedScheme(other.to_string())),
+    }
+}
+
+/// `link` with the value of its `token` parameter masked, for logging
+pub fn redact_token(link: &str) -> String {
+    let Ok(mut parsed) = Url::parse(link.trim()) else {
+        return link.to_string();
+    };
+    if parsed.scheme() != SCHEME || !parsed.query_pairs().any(|(key, _)| key == "token") {
+        return link.to_string();
+    }
+
+    let pairs: Vec<(String, String)> = parsed.query_pairs()
+        .map(|(key, value)| {
+            let value = if key == "token" { "<redacted>".to_string() } else { value.into_owned() };
+            (key.into_owned(), value)
+        })
+        .collect();
+    parsed.query_pairs_mut().clear().extend_pairs(pairs);
+    parsed.to_string()
+}
+
+/// Web links passed on the command line, e.g. `gytmdl-gui https://music.youtube.com/…`.
+/// The first argument is the executable; `gytmdl://` links are left to the
+/// deep-link plugin, which picks them out of the arguments itself.
+pub fn urls_from_args(args: &[String]) -> Vec<String> {
+    args.iter()
+        .skip(1)
+        .filter(|arg| arg.starts_with("https://") || arg.starts_with("http://"))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_add_link() {
+        let request = parse(
+            "gytmdl://add?url=https%3A%2F%2Fmusic.youtube.com%2Fwatch%3Fv%3Dabc%26list%3Dxyz&source=https%3A%2F%2Fexample.com",
+        )
+        .unwrap();
+        assert_eq!(request.url, "https://music.youtube.com/watch?v=abc&list=xyz");
+        assert_eq!(request.source, "https://example.com");
+        assert_eq!(request.token, None);
+
+        let request = parse("https://music.youtube.com/watch?v=abc").unwrap();
+        assert_eq!(request.url, "https://music.youtube.com/watch?v=abc");
+        assert_eq!(request.source, DEFAULT_SOURCE);
+    }
+
+    #[test]
+    fn test_token_links() {
+        let link = "gytmdl://add?url=https%3A%2F%2Fmusic.youtube.com%2Fwatch%3Fv%3Dabc&token=gytmdl_secret";
+        assert_eq!(parse(link).unwrap().token.as_deref(), Some("gytmdl_secret"));
+
+        let redacted = redact_token(link);
+        assert!(!redacted.contains("gytmdl_secret"));
+        assert_eq!(parse(&redacted).unwrap().url, "https://music.youtube.com/watch?v=abc");
+        assert_eq!(redact_token("https://music.youtube.com/watch?v=abc"), "https://music.youtube.com/watch?v=abc");
+    }
+
+    #[test]
+    fn test_rejects_bad_links() {
+        assert_eq!(parse("gytmdl://add?source=x"), Err(DeepLinkError::MissingUrl));
+        assert_eq!(parse("gytmdl://remove?url=x"), Err(DeepLinkError::UnknownAction("remove".to_string())));
+        assert!(matches!(parse("ftp://example.com"), Err(DeepLinkError::UnsupportedScheme(_))));
+        assert!(matches!(parse("not a link"), Err(DeepLinkError::InvalidLink(_))));
+    }
+
+    #[test]
+    fn test_urls_from_args() {
+        let args: Vec<String> = ["/usr/bin/gytmdl-gui", "https://music.youtube.com/watch?v=abc", "--verbose", "gytmdl://add?url=x"]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect();
+        assert_eq!(urls_from_args(&args), vec!["https://music.youtube.com/watch?v=abc"]);
+        assert!(urls_from_args(&args[..1]).is_empty());
+    }
+}
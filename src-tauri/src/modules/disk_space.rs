@@ -0,0 +1,238 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Number of recent track sizes used for the rolling average
+const SIZE_WINDOW: usize = 50;
+
+/// Extensions counted as downloaded tracks (covers, lyrics etc. are skipped)
+const MEDIA_EXTENSIONS: &[&str] = &["m4a", "mp4", "opus", "webm", "mp3", "flac", "ogg"];
+
+/// Projection of how much of the queue fits on the output volume
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpaceProjection {
+    pub volume_path: PathBuf,
+    pub available_bytes: u64,
+    pub average_track_bytes: Option<u64>,
+    pub tracks_that_fit: Option<u64>,
+    pub queued_tracks: usize,
+    pub will_fit: bool,
+    pub message: String,
+}
+
+/// Get the free space on the volume containing `path`.
+/// Walks up to the nearest existing ancestor so unborn output folders still resolve.
+pub fn available_space(path: &Path) -> Result<u64, io::Error> {
+    let mut current = path;
+    loop {
+        if current.exists() {
+            return fs2::available_space(current);
+        }
+        match current.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => current = parent,
+            _ => return fs2::available_space(Path::new(".")),
+        }
+    }
+}
+
+/// Check that every volume in `paths` has at least `min_free_bytes` free.
+/// Returns a message naming the first volume that is short on space.
+pub fn preflight(paths: &[&Path], min_free_bytes: u64) -> Result<(), String> {
+    for path in paths {
+        let available = available_space(path)
+            .map_err(|e| format!("Failed to read free space for {}: {}", path.display(), e))?;
+        if available < min_free_bytes {
+            return Err(format!(
+                "Not enough disk space for {}: {} MB free, at least {} MB required",
+                path.display(),
+                available / (1024 * 1024),
+                min_free_bytes / (1024 * 1024)
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Check if a path looks like a downloaded audio/video file
+pub fn is_media_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| MEDIA_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+/// Collect the sizes of media files under `dir` modified at or after `since`
+pub fn file_sizes_since(dir: &Path, since: SystemTime) -> Vec<u64> {
+    media_files_since(dir, since).into_iter().map(|(_, size)| size).collect()
+}
+
+/// Collect the paths and sizes of media files under `dir` modified at or after `since`
+pub fn media_files_since(dir: &Path, since: SystemTime) -> Vec<(PathBuf, u64)> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+
+    while let Some(current) = pending.pop() {
+        let entries = match fs::read_dir(&current) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+
+        for entry in entries.flatten() {
+            let metadata = match entry.metadata() {
+                Ok(metadata) => metadata,
+                Err(_) => continue,
+            };
+
+            if metadata.is_dir() {
+                pending.push(entry.path());
+            } else if is_media_file(&entry.path()) &&
+                      metadata.modified().map(|m| m >= since).unwrap_or(false) {
+                files.push((entry.path(), metadata.len()));
+            }
+        }
+    }
+
+    files
+}
+
+/// Tracks per-track download sizes and projects remaining capacity
+#[derive(Debug, Default)]
+pub struct DiskSpaceMonitor {
+    recent_sizes: VecDeque<u64>,
+}
+
+impl DiskSpaceMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the size of a downloaded track
+    pub fn record_track_size(&mut self, bytes: u64) {
+        if bytes == 0 {
+            return;
+        }
+        if self.recent_sizes.len() == SIZE_WINDOW {
+            self.recent_sizes.pop_front();
+        }
+        self.recent_sizes.push_back(bytes);
+    }
+
+    /// Get the rolling average track size, if any samples were recorded
+    pub fn average_track_size(&self) -> Option<u64> {
+        if self.recent_sizes.is_empty() {
+            return None;
+        }
+        Some(self.recent_sizes.iter().sum::<u64>() / self.recent_sizes.len() as u64)
+    }
+
+    /// Project how many more tracks fit in `available_bytes`
+    pub fn project(&self, volume_path: PathBuf, available_bytes: u64, queued_tracks: usize) -> SpaceProjection {
+        let average_track_bytes = self.average_track_size();
+        let tracks_that_fit = average_track_bytes.map(|avg| available_bytes / avg.max(1));
+        let will_fit = tracks_that_fit.map(|fit| fit >= queued_tracks as u64).unwrap_or(true);
+
+        let message = match tracks_that_fit {
+            Some(fit) if will_fit => format!("Space for ~{} more tracks", fit),
+            Some(fit) => format!(
+                "Space for ~{} more tracks, but {} are queued",
+                fit, queued_tracks
+            ),
+            None => "Not enough downloads yet to estimate track size".to_string(),
+        };
+
+        SpaceProjection {
+            volume_path,
+            available_bytes,
+            average_track_bytes,
+            tracks_that_fit,
+            queued_tracks,
+            will_fit,
+            message,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_preflight() {
+        let temp_dir = tempdir().unwrap();
+        let missing = temp_dir.path().join("not-yet-created");
+
+        assert!(preflight(&[temp_dir.path(), &missing], 0).is_ok());
+
+        let error = preflight(&[&missing], u64::MAX).unwrap_err();
+        assert!(error.contains("Not enough disk space"));
+    }
+
+    #[test]
+    fn test_average_track_size_rolls() {
+        let mut monitor = DiskSpaceMonitor::new();
+        assert!(monitor.average_track_size().is_none());
+
+        monitor.record_track_size(1000);
+        monitor.record_track_size(3000);
+        assert_eq!(monitor.average_track_size(), Some(2000));
+
+        // Empty files are ignored
+        monitor.record_track_size(0);
+        assert_eq!(monitor.average_track_size(), Some(2000));
+
+        for _ in 0..SIZE_WINDOW {
+            monitor.record_track_size(500);
+        }
+        assert_eq!(monitor.average_track_size(), Some(500));
+    }
+
+    #[test]
+    fn test_projection() {
+        let mut monitor = DiskSpaceMonitor::new();
+        monitor.record_track_size(10);
+
+        let projection = monitor.project(PathBuf::from("/music"), 370, 5);
+        assert_eq!(projection.tracks_that_fit, Some(37));
+        assert!(projection.will_fit);
+        assert_eq!(projection.message, "Space for ~37 more tracks");
+
+        let projection = monitor.project(PathBuf::from("/music"), 370, 40);
+        assert!(!projection.will_fit);
+    }
+
+    #[test]
+    fn test_projection_without_samples() {
+        let monitor = DiskSpaceMonitor::new();
+        let projection = monitor.project(PathBuf::from("/music"), 1000, 100);
+        assert!(projection.tracks_that_fit.is_none());
+        assert!(projection.will_fit);
+    }
+
+    #[test]
+    fn test_available_space_for_missing_directory() {
+        let temp_dir = tempdir().unwrap();
+        let missing = temp_dir.path().join("not").join("created");
+        assert!(available_space(&missing).is_ok());
+    }
+
+    #[test]
+    fn test_file_sizes_since() {
+        let temp_dir = tempdir().unwrap();
+        let since = SystemTime::now() - std::time::Duration::from_secs(5);
+
+        fs::create_dir_all(temp_dir.path().join("Artist/Album")).unwrap();
+        fs::write(temp_dir.path().join("Artist/Album/01 Track.m4a"), vec![0u8; 128]).unwrap();
+        fs::write(temp_dir.path().join("cover.jpg"), vec![0u8; 16]).unwrap();
+
+        // Only media files count as tracks
+        let sizes = file_sizes_since(temp_dir.path(), since);
+        assert_eq!(sizes, vec![128]);
+
+        let future = SystemTime::now() + std::time::Duration::from_secs(60);
+        assert!(file_sizes_since(temp_dir.path(), future).is_empty());
+    }
+}
@@ -0,0 +1,104 @@
+use std::sync::{Arc, Mutex};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+#[derive(Debug)]
+struct SlotCount {
+    limit: usize,
+    /// Permits still to be retired after the limit was lowered below the
+    /// number of running jobs
+    debt: usize,
+}
+
+/// Concurrency slots for the queue dispatcher. Every running job holds a
+/// permit, so a finishing job frees its slot and wakes the dispatcher without
+/// any polling. Only the dispatcher acquires permits.
+#[derive(Debug)]
+pub struct DispatchSlots {
+    semaphore: Arc<Semaphore>,
+    count: Mutex<SlotCount>,
+}
+
+impl DispatchSlots {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(limit)),
+            count: Mutex::new(SlotCount { limit, debt: 0 }),
+        }
+    }
+
+    pub fn limit(&self) -> usize {
+        self.count.lock().map(|count| count.limit).unwrap_or_default()
+    }
+
+    /// Change the number of slots. Raising it frees slots right away; when it
+    /// is lowered below the running count, running jobs finish but their
+    /// slots are retired instead of reused.
+    pub fn set_limit(&self, limit: usize) {
+        let Ok(mut count) = self.count.lock() else {
+            return;
+        };
+        if limit > count.limit {
+            let mut added = limit - count.limit;
+            let repaid = added.min(count.debt);
+            count.debt -= repaid;
+            added -= repaid;
+            self.semaphore.add_permits(added);
+        } else if limit < count.limit {
+            let removed = count.limit - limit;
+            let forgotten = self.semaphore.forget_permits(removed);
+            count.debt += removed - forgotten;
+        }
+        count.limit = limit;
+    }
+
+    /// Wait for a free slot
+    pub async fn acquire(&self) -> OwnedSemaphorePermit {
+        loop {
+            let permit = Arc::clone(&self.semaphore)
+                .acquire_owned()
+                .await
+                .expect("dispatch semaphore is never closed");
+
+            let Ok(mut count) = self.count.lock() else {
+                return permit;
+            };
+            if count.debt == 0 {
+                return permit;
+            }
+            count.debt -= 1;
+            permit.forget();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    async fn acquired_within(slots: &DispatchSlots, millis: u64) -> Option<OwnedSemaphorePermit> {
+        tokio::time::timeout(Duration::from_millis(millis), slots.acquire()).await.ok()
+    }
+
+    #[tokio::test]
+    async fn test_lowered_limit_retires_released_slots() {
+        let slots = DispatchSlots::new(2);
+        let first = slots.acquire().await;
+        let second = slots.acquire().await;
+        assert!(acquired_within(&slots, 20).await.is_none());
+
+        // Both slots are taken, so one is retired when its job finishes
+        slots.set_limit(1);
+        drop(first);
+        assert!(acquired_within(&slots, 20).await.is_none());
+        drop(second);
+        let third = acquired_within(&slots, 20).await.expect("slot freed");
+
+        slots.set_limit(3);
+        let _fourth = acquired_within(&slots, 20).await.expect("raised limit");
+        let _fifth = acquired_within(&slots, 20).await.expect("raised limit");
+        assert!(acquired_within(&slots, 20).await.is_none());
+        drop(third);
+        assert_eq!(slots.limit(), 3);
+    }
+}
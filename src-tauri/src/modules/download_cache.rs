@@ -0,0 +1,161 @@
+use crate::modules::state::AppConfig;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Identifies a downloaded track independently of which job produced it
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    pub video_id: String,
+    pub format: String,
+}
+
+impl CacheKey {
+    /// Build a key for a single-track URL under the given config.
+    /// Playlist and album URLs return `None` since their tracks aren't known up front.
+    pub fn for_url(url: &str, config: &AppConfig) -> Option<Self> {
+        let video_id = extract_video_id(url)?;
+        Some(Self {
+            video_id,
+            format: format!("{:?}:{}", config.download_mode, config.itag),
+        })
+    }
+}
+
+/// Extract the video ID from a single-track YouTube URL
+pub fn extract_video_id(url: &str) -> Option<String> {
+    // Watch URLs carrying a playlist may expand to the whole list
+    if url.contains("list=") {
+        return None;
+    }
+
+    let id = if let Some((_, rest)) = url.split_once("youtu.be/") {
+        rest.split(['?', '&', '/', '#']).next()
+    } else if let Some((_, query)) = url.split_once('?') {
+        query.split('&')
+            .find_map(|pair| pair.strip_prefix("v="))
+            .and_then(|value| value.split('#').next())
+    } else {
+        None
+    }?;
+
+    if id.is_empty() {
+        None
+    } else {
+        Some(id.to_string())
+    }
+}
+
+/// A file produced by an earlier job this session
+#[derive(Debug, Clone)]
+pub struct CachedFile {
+    pub path: PathBuf,
+    /// Output root the file was written under, used to rebuild its relative path
+    pub output_root: PathBuf,
+}
+
+impl CachedFile {
+    /// Place this file under `output_root`, hard-linking when possible and copying otherwise.
+    /// Returns the destination path; an existing destination is left untouched.
+    pub fn materialize(&self, output_root: &Path) -> Result<PathBuf, io::Error> {
+        let relative = self.path.strip_prefix(&self.output_root)
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|_| PathBuf::from(self.path.file_name().unwrap_or_default()));
+        let destination = output_root.join(relative);
+
+        if destination.exists() {
+            return Ok(destination);
+        }
+
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        if fs::hard_link(&self.path, &destination).is_err() {
+            fs::copy(&self.path, &destination)?;
+        }
+
+        Ok(destination)
+    }
+}
+
+/// Session-wide record of completed single-track jobs so a track queued by
+/// several jobs is only downloaded once. Playlist and album jobs are neither
+/// recorded nor satisfied from it: gytmdl doesn't report which of their files
+/// belongs to which video. Tracks picked from a playlist are queued as
+/// single-track jobs and are covered.
+#[derive(Debug, Default)]
+pub struct DownloadCache {
+    entries: HashMap<CacheKey, CachedFile>,
+}
+
+impl DownloadCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the file a completed job produced
+    pub fn record(&mut self, key: CacheKey, path: PathBuf, output_root: PathBuf) {
+        self.entries.insert(key, CachedFile { path, output_root });
+    }
+
+    /// Look up a cached file, forgetting entries whose file has since disappeared
+    pub fn lookup(&mut self, key: &CacheKey) -> Option<CachedFile> {
+        match self.entries.get(key) {
+            Some(cached) if cached.path.exists() => Some(cached.clone()),
+            Some(_) => {
+                self.entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_extract_video_id() {
+        assert_eq!(extract_video_id("https://music.youtube.com/watch?v=abc123"), Some("abc123".to_string()));
+        assert_eq!(extract_video_id("https://www.youtube.com/watch?feature=share&v=abc123"), Some("abc123".to_string()));
+        assert_eq!(extract_video_id("https://youtu.be/abc123?si=xyz"), Some("abc123".to_string()));
+        assert_eq!(extract_video_id("https://music.youtube.com/playlist?list=PL123"), None);
+        assert_eq!(extract_video_id("https://music.youtube.com/watch?v=abc123&list=PL123"), None);
+    }
+
+    #[test]
+    fn test_cache_key_includes_format() {
+        let mut config = AppConfig::default();
+        let audio = CacheKey::for_url("https://music.youtube.com/watch?v=abc", &config).unwrap();
+        config.itag = "251".to_string();
+        let opus = CacheKey::for_url("https://music.youtube.com/watch?v=abc", &config).unwrap();
+        assert_ne!(audio, opus);
+    }
+
+    #[test]
+    fn test_lookup_and_materialize() {
+        let temp_dir = tempdir().unwrap();
+        let first_root = temp_dir.path().join("first");
+        let second_root = temp_dir.path().join("second");
+        let source = first_root.join("Artist/Album/01 Track.m4a");
+        fs::create_dir_all(source.parent().unwrap()).unwrap();
+        fs::write(&source, b"audio").unwrap();
+
+        let key = CacheKey { video_id: "abc".to_string(), format: "Audio:140".to_string() };
+        let mut cache = DownloadCache::new();
+        cache.record(key.clone(), source.clone(), first_root.clone());
+
+        let cached = cache.lookup(&key).unwrap();
+        let destination = cached.materialize(&second_root).unwrap();
+        assert_eq!(destination, second_root.join("Artist/Album/01 Track.m4a"));
+        assert_eq!(fs::read(&destination).unwrap(), b"audio");
+
+        // Entries whose file is gone are dropped
+        fs::remove_file(&source).unwrap();
+        assert!(cache.lookup(&key).is_none());
+    }
+}
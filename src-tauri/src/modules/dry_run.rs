@@ -0,0 +1,225 @@
+use crate::modules::gytmdl_wrapper::GytmdlError;
+use crate::modules::library;
+use crate::modules::metadata_fetcher::MetadataFetcher;
+use crate::modules::process_runner::ProcessRunner;
+use crate::modules::state::{AppConfig, DownloadJob, JobMetadata};
+use crate::modules::subscriptions::{self, ListedEntry};
+use crate::modules::template::{self, SampleMetadata};
+use crate::modules::track_selection;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// What a dry-run job would have downloaded, and where to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DryRunReport {
+    /// gytmdl arguments the download would use, with proxy credentials masked
+    pub command: Vec<String>,
+    pub metadata: Option<JobMetadata>,
+    /// Why the metadata lookup failed; the download itself may still work
+    pub metadata_error: Option<String>,
+    /// File gytmdl would write, from the folder and file templates. For a
+    /// playlist or album, see `entries`.
+    pub output_path: PathBuf,
+    /// Where the file would end up after auto-organize, when it is on
+    pub library_path: Option<PathBuf>,
+    /// Each track of a playlist or album URL, with the paths it would get
+    #[serde(default)]
+    pub entries: Vec<DryRunEntry>,
+    /// Why the playlist or album couldn't be listed
+    #[serde(default)]
+    pub listing_error: Option<String>,
+    pub checked_at: DateTime<Utc>,
+}
+
+/// One track of a playlist or album dry run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DryRunEntry {
+    pub url: String,
+    pub title: Option<String>,
+    pub output_path: PathBuf,
+    pub library_path: Option<PathBuf>,
+}
+
+/// Template values for a track. The metadata lookup doesn't return the track
+/// number or release date, so those are placeholders.
+fn template_metadata(url: &str, metadata: Option<&JobMetadata>) -> SampleMetadata {
+    let field = |value: Option<&String>| value.cloned().unwrap_or_default();
+    let artist = field(metadata.and_then(|m| m.artist.as_ref()));
+    SampleMetadata {
+        album: field(metadata.and_then(|m| m.album.as_ref())),
+        album_artist: artist.clone(),
+        artist,
+        comment: url.to_string(),
+        date: Utc::now().date_naive(),
+        media_type: 1,
+        rating: 0,
+        title: field(metadata.and_then(|m| m.title.as_ref())),
+        track: 1,
+        track_total: 1,
+    }
+}
+
+/// The file gytmdl would write for a track, and where auto-organize would move it
+fn output_paths(
+    config: &AppConfig,
+    sample: &SampleMetadata,
+    metadata: Option<&JobMetadata>,
+) -> Result<(PathBuf, Option<PathBuf>), GytmdlError> {
+    let preview = template::preview(
+        &config.output_path,
+        &config.template_folder,
+        &config.template_file,
+        &config.template_date,
+        sample,
+    )
+    .map_err(|e| GytmdlError::ConfigError(e.to_string()))?;
+
+    let library_path = config.library_root.as_ref()
+        .filter(|_| config.auto_organize)
+        .map(|root| {
+            let dir = library::target_directory(root, &config.library_template, metadata);
+            match preview.path.file_name() {
+                Some(file_name) => dir.join(file_name),
+                None => dir,
+            }
+        });
+    Ok((preview.path, library_path))
+}
+
+/// Paths of each listed track of a playlist or album, numbered in listing
+/// order. Album and artist come from the collection's metadata.
+fn entry_paths(
+    config: &AppConfig,
+    entries: &[ListedEntry],
+    metadata: Option<&JobMetadata>,
+) -> Result<Vec<DryRunEntry>, GytmdlError> {
+    entries.iter()
+        .enumerate()
+        .map(|(index, entry)| {
+            let entry_metadata = JobMetadata {
+                title: entry.title.clone(),
+                ..metadata.cloned().unwrap_or_default()
+            };
+            let sample = SampleMetadata {
+                track: index as u32 + 1,
+                track_total: entries.len() as u32,
+                ..template_metadata(&entry.url, Some(&entry_metadata))
+            };
+            let (output_path, library_path) = output_paths(config, &sample, Some(&entry_metadata))?;
+            Ok(DryRunEntry {
+                url: entry.url.clone(),
+                title: entry.title.clone(),
+                output_path,
+                library_path,
+            })
+        })
+        .collect()
+}
+
+/// Build and validate the gytmdl command for `job` and look up what it would
+/// download, without running gytmdl or writing any files
+pub async fn resolve(
+    process_runner: &ProcessRunner,
+    metadata_fetcher: &MetadataFetcher,
+    config: &AppConfig,
+    job: &DownloadJob,
+) -> Result<DryRunReport, GytmdlError> {
+    let args = process_runner.build_command_args(config, &job.url, &job.id)?;
+    process_runner.verify_binary().await?;
+
+    let (metadata, metadata_error) = match metadata_fetcher.fetch(&job.url).await {
+        Ok(metadata) => (Some(metadata), None),
+        Err(e) => (None, Some(e.to_string())),
+    };
+
+    let (output_path, library_path) = output_paths(
+        config,
+        &template_metadata(&job.url, metadata.as_ref()),
+        metadata.as_ref(),
+    )?;
+
+    let (entries, listing_error) = if track_selection::is_collection_url(&job.url) {
+        match subscriptions::list_entries(process_runner.get_isolation(), &job.url).await {
+            Ok(entries) => (entry_paths(config, &entries, metadata.as_ref())?, None),
+            Err(e) => (Vec::new(), Some(e.to_string())),
+        }
+    } else {
+        (Vec::new(), None)
+    };
+
+    Ok(DryRunReport {
+        command: ProcessRunner::redacted_args(&args),
+        metadata,
+        metadata_error,
+        output_path,
+        library_path,
+        entries,
+        listing_error,
+        checked_at: Utc::now(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::state::AppState;
+
+    #[test]
+    fn test_template_metadata_uses_job_metadata() {
+        let mut state = AppState::new();
+        let job_id = state.add_job("https://music.youtube.com/watch?v=abc".to_string());
+        let job = state.get_job(&job_id).unwrap().clone();
+        let metadata = JobMetadata {
+            title: Some("Get Lucky".to_string()),
+            artist: Some("Daft Punk".to_string()),
+            ..JobMetadata::default()
+        };
+
+        let sample = template_metadata(&job.url, Some(&metadata));
+        assert_eq!(sample.title, "Get Lucky");
+        assert_eq!(sample.album_artist, "Daft Punk");
+        assert_eq!(sample.album, "");
+        assert_eq!(sample.comment, job.url);
+
+        let preview = template::preview(
+            std::path::Path::new("/music"),
+            "{album_artist}",
+            "{title}",
+            "%Y",
+            &sample,
+        ).unwrap();
+        assert_eq!(preview.path, PathBuf::from("/music/Daft Punk/Get Lucky.m4a"));
+    }
+
+    #[test]
+    fn test_entry_paths_resolve_each_track() {
+        let config = AppConfig {
+            output_path: PathBuf::from("/music"),
+            template_folder: "{album_artist}".to_string(),
+            template_file: "{track:02d} {title}".to_string(),
+            ..AppConfig::default()
+        };
+        let metadata = JobMetadata {
+            artist: Some("Daft Punk".to_string()),
+            ..JobMetadata::default()
+        };
+        let entries: Vec<ListedEntry> = ["Give Life Back to Music", "Get Lucky"].iter()
+            .enumerate()
+            .map(|(index, title)| ListedEntry {
+                id: index.to_string(),
+                url: format!("https://music.youtube.com/watch?v={}", index),
+                title: Some(title.to_string()),
+            })
+            .collect();
+
+        let paths: Vec<PathBuf> = entry_paths(&config, &entries, Some(&metadata)).unwrap()
+            .into_iter()
+            .map(|entry| entry.output_path)
+            .collect();
+        assert_eq!(paths, vec![
+            PathBuf::from("/music/Daft Punk/01 Give Life Back to Music.m4a"),
+            PathBuf::from("/music/Daft Punk/02 Get Lucky.m4a"),
+        ]);
+    }
+}
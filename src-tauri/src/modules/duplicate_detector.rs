@@ -0,0 +1,142 @@
+use crate::modules::disk_space;
+use crate::modules::download_cache::extract_video_id;
+use crate::modules::metadata_fetcher::MetadataFetcher;
+use crate::modules::state::{DownloadJob, JobStatus};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Titles shorter than this (after normalization) are too generic to match on disk
+const MIN_TITLE_LENGTH: usize = 3;
+
+/// A previous download of the same track
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateMatch {
+    /// ID of the completed job in history, if found there
+    pub history_job_id: Option<String>,
+    /// Existing file in the output directory, if found on disk
+    pub existing_path: Option<PathBuf>,
+}
+
+/// Check whether two URLs point at the same track
+fn same_track(a: &str, b: &str) -> bool {
+    if a == b {
+        return true;
+    }
+    match (extract_video_id(a), extract_video_id(b)) {
+        (Some(a), Some(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// Find a completed job in history for the same URL or video
+pub fn find_in_history<'a>(url: &str, history: &'a [DownloadJob]) -> Option<&'a DownloadJob> {
+    history.iter()
+        .rev()
+        .find(|job| job.status == JobStatus::Completed && !job.dry_run && same_track(&job.url, url))
+}
+
+/// Lowercase and strip everything but letters and digits
+fn normalize(text: &str) -> String {
+    text.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Find a media file under `output_path` whose name contains `title`
+pub fn find_on_disk(output_path: &Path, title: &str) -> Option<PathBuf> {
+    let title = normalize(title);
+    if title.chars().count() < MIN_TITLE_LENGTH {
+        return None;
+    }
+
+    disk_space::media_files_since(output_path, SystemTime::UNIX_EPOCH)
+        .into_iter()
+        .map(|(path, _)| path)
+        .find(|path| {
+            path.file_stem()
+                .map(|stem| normalize(&stem.to_string_lossy()).contains(&title))
+                .unwrap_or(false)
+        })
+}
+
+/// Look for an earlier download of `url` among `known_jobs` (history plus
+/// the current queue) and, when `scan_root` is given, in the output directory
+pub async fn find_duplicate(
+    url: &str,
+    known_jobs: &[DownloadJob],
+    scan_root: Option<&Path>,
+    fetcher: &MetadataFetcher,
+) -> Option<DuplicateMatch> {
+    let history_match = find_in_history(url, known_jobs);
+
+    let existing_path = match scan_root {
+        Some(root) => {
+            // Prefer the title recorded in history to avoid a network round trip
+            let title = match history_match.and_then(|job| job.metadata.as_ref()?.title.clone()) {
+                Some(title) => Some(title),
+                None => fetcher.fetch(url).await.ok().and_then(|metadata| metadata.title),
+            };
+
+            match title {
+                Some(title) => {
+                    let root = root.to_path_buf();
+                    tokio::task::spawn_blocking(move || find_on_disk(&root, &title))
+                        .await
+                        .ok()
+                        .flatten()
+                }
+                None => None,
+            }
+        }
+        None => None,
+    };
+
+    if history_match.is_none() && existing_path.is_none() {
+        return None;
+    }
+
+    Some(DuplicateMatch {
+        history_job_id: history_match.map(|job| job.id.clone()),
+        existing_path,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_find_in_history() {
+        let mut completed = DownloadJob::new("https://www.youtube.com/watch?v=abc".to_string());
+        completed.status = JobStatus::Completed;
+        let mut failed = DownloadJob::new("https://music.youtube.com/watch?v=xyz".to_string());
+        failed.status = JobStatus::Failed;
+        let history = vec![completed.clone(), failed];
+
+        // Matches by video ID across hosts
+        let found = find_in_history("https://music.youtube.com/watch?v=abc", &history).unwrap();
+        assert_eq!(found.id, completed.id);
+
+        // Failed downloads don't count
+        assert!(find_in_history("https://music.youtube.com/watch?v=xyz", &history).is_none());
+    }
+
+    #[test]
+    fn test_find_on_disk() {
+        let temp_dir = tempdir().unwrap();
+        let album_dir = temp_dir.path().join("Artist/Album");
+        fs::create_dir_all(&album_dir).unwrap();
+        fs::write(album_dir.join("03 Don't Stop Me Now.m4a"), b"audio").unwrap();
+        fs::write(album_dir.join("Cover.jpg"), b"image").unwrap();
+
+        let found = find_on_disk(temp_dir.path(), "Don’t Stop Me Now").unwrap();
+        assert_eq!(found, album_dir.join("03 Don't Stop Me Now.m4a"));
+
+        assert!(find_on_disk(temp_dir.path(), "Cover").is_none());
+        assert!(find_on_disk(temp_dir.path(), "a").is_none());
+    }
+}
@@ -0,0 +1,191 @@
+use crate::modules::progress_parser::ProgressParser;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Broad cause of a failed download
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum JobErrorKind {
+    /// YouTube refused the request (403) or asked to sign in
+    AuthRequired,
+    AgeRestricted,
+    GeoBlocked,
+    MissingFfmpeg,
+    /// DNS lookup or connection failure
+    Network,
+    DiskFull,
+    /// Killed by the watchdog for running too long or printing nothing
+    Timeout,
+    Unknown,
+}
+
+impl JobErrorKind {
+    /// Short description of what went wrong
+    pub fn summary(&self) -> &'static str {
+        match self {
+            JobErrorKind::AuthRequired => "YouTube refused the download or asked to sign in",
+            JobErrorKind::AgeRestricted => "This content is age-restricted",
+            JobErrorKind::GeoBlocked => "This content isn't available in your region",
+            JobErrorKind::MissingFfmpeg => "ffmpeg could not be found",
+            JobErrorKind::Network => "Could not reach YouTube",
+            JobErrorKind::DiskFull => "Ran out of disk space",
+            JobErrorKind::Timeout => "The download stopped making progress and was stopped",
+            JobErrorKind::Unknown => "The download failed",
+        }
+    }
+
+    /// What the user can do about it
+    pub fn suggestion(&self) -> Option<&'static str> {
+        match self {
+            JobErrorKind::AuthRequired => Some("Re-import cookies from a signed-in browser session, then retry"),
+            JobErrorKind::AgeRestricted => Some("Import cookies from a signed-in adult account as the age-verified cookies, then retry"),
+            JobErrorKind::GeoBlocked => Some("Configure a proxy in a region where the content is available"),
+            JobErrorKind::MissingFfmpeg => Some("Install ffmpeg or add the bundled copy to PATH"),
+            JobErrorKind::Network => Some("Check your internet connection and proxy settings, then retry"),
+            JobErrorKind::DiskFull => Some("Free up space on the output and temp drives, then retry"),
+            JobErrorKind::Timeout => Some("Retry the download, or raise the timeouts in settings for very long playlists"),
+            JobErrorKind::Unknown => None,
+        }
+    }
+}
+
+/// A failure with its cause and remediation, shown in place of the raw error
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ClassifiedError {
+    pub kind: JobErrorKind,
+    pub summary: String,
+    pub suggestion: Option<String>,
+}
+
+impl From<JobErrorKind> for ClassifiedError {
+    fn from(kind: JobErrorKind) -> Self {
+        Self {
+            kind,
+            summary: kind.summary().to_string(),
+            suggestion: kind.suggestion().map(str::to_string),
+        }
+    }
+}
+
+impl fmt::Display for ClassifiedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.suggestion {
+            Some(suggestion) => write!(f, "{}. {}.", self.summary, suggestion),
+            None => write!(f, "{}.", self.summary),
+        }
+    }
+}
+
+/// Substrings (lowercase) that identify each kind, checked in order
+const PATTERNS: &[(JobErrorKind, &[&str])] = &[
+    // Written by the watchdog
+    (JobErrorKind::Timeout, &[
+        "download timed out",
+        "download stalled",
+    ]),
+    (JobErrorKind::GeoBlocked, &[
+        "available in your country",
+        "blocked it in your country",
+        "not available in your region",
+        "geo restriction",
+        "geo-restrict",
+    ]),
+    (JobErrorKind::AuthRequired, &[
+        "http error 403",
+        "403: forbidden",
+        "http error 401",
+        "sign in to confirm",
+        "please sign in",
+        "login required",
+        "cookies are no longer valid",
+        "requires authentication",
+    ]),
+    (JobErrorKind::MissingFfmpeg, &[
+        "ffmpeg not found",
+        "ffmpeg was not found",
+        "ffmpeg is not installed",
+        "ffprobe not found",
+        "ffmpeg: command not found",
+        "no such file or directory: 'ffmpeg'",
+    ]),
+    (JobErrorKind::DiskFull, &[
+        "no space left on device",
+        "errno 28",
+        "not enough disk space",
+        "disk full",
+        "disk quota exceeded",
+    ]),
+    (JobErrorKind::Network, &[
+        "getaddrinfo failed",
+        "name or service not known",
+        "temporary failure in name resolution",
+        "nodename nor servname provided",
+        "failed to resolve",
+        "no address associated with hostname",
+        "network is unreachable",
+        "connection refused",
+        "connection reset",
+        "unable to connect to proxy",
+    ]),
+];
+
+/// Classify a single error message. The age gate is checked first since
+/// YouTube asks to "sign in to confirm your age".
+pub fn classify(error: &str) -> JobErrorKind {
+    if ProgressParser::is_age_restricted_error(error) {
+        return JobErrorKind::AgeRestricted;
+    }
+
+    let lower = error.to_lowercase();
+    PATTERNS.iter()
+        .find(|(_, patterns)| patterns.iter().any(|pattern| lower.contains(pattern)))
+        .map(|(kind, _)| *kind)
+        .unwrap_or(JobErrorKind::Unknown)
+}
+
+/// Classify a failure from its error message, falling back to the process's
+/// stderr (most recent line first) when the message alone, e.g. an exit code,
+/// doesn't say what happened
+pub fn classify_output<'a>(error: &str, stderr: impl DoubleEndedIterator<Item = &'a str>) -> ClassifiedError {
+    let kind = match classify(error) {
+        JobErrorKind::Unknown => stderr.rev()
+            .map(classify)
+            .find(|kind| *kind != JobErrorKind::Unknown)
+            .unwrap_or(JobErrorKind::Unknown),
+        kind => kind,
+    };
+    ClassifiedError::from(kind)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        let cases = [
+            ("ERROR: [youtube] abc: Sign in to confirm you're not a bot", JobErrorKind::AuthRequired),
+            ("ERROR: unable to download video data: HTTP Error 403: Forbidden", JobErrorKind::AuthRequired),
+            ("ERROR: [youtube] abc: Sign in to confirm your age", JobErrorKind::AgeRestricted),
+            ("ERROR: The uploader has not made this video available in your country", JobErrorKind::GeoBlocked),
+            ("ERROR: ffprobe and ffmpeg not found. Please install or provide the path", JobErrorKind::MissingFfmpeg),
+            ("urlopen error [Errno -3] Temporary failure in name resolution", JobErrorKind::Network),
+            ("OSError: [Errno 28] No space left on device", JobErrorKind::DiskFull),
+            ("Download stalled: no output for 10 minutes", JobErrorKind::Timeout),
+            ("Process exited with code: 1", JobErrorKind::Unknown),
+        ];
+        for (error, kind) in cases {
+            assert_eq!(classify(error), kind, "{}", error);
+        }
+    }
+
+    #[test]
+    fn test_classify_output_falls_back_to_stderr() {
+        let stderr = ["[download] Destination: a.m4a", "ERROR: HTTP Error 403: Forbidden", "Traceback (most recent call last):"];
+        let classified = classify_output("Process exited with code: 1", stderr.iter().copied());
+        assert_eq!(classified.kind, JobErrorKind::AuthRequired);
+        assert!(classified.to_string().contains("Re-import cookies"));
+
+        let classified = classify_output("No space left on device", std::iter::empty());
+        assert_eq!(classified.kind, JobErrorKind::DiskFull);
+    }
+}
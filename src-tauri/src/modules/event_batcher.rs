@@ -0,0 +1,113 @@
+use crate::modules::events::JobProgressEvent;
+use crate::modules::state::Progress;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// How often coalesced progress is flushed to the webview, which caps each
+/// job at 10 progress updates a second
+pub const DEFAULT_BATCH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Payload for [`QUEUE_UPDATE_EVENT`](crate::modules::events::QUEUE_UPDATE_EVENT)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueueUpdateEvent {
+    pub jobs: Vec<JobProgressEvent>,
+}
+
+#[derive(Default)]
+struct PendingUpdates {
+    progress: HashMap<String, Progress>,
+    /// Job IDs in the order they first reported progress since the last flush
+    order: Vec<String>,
+}
+
+/// Coalesces per-job progress so only the latest value for each job is sent
+/// once per interval, however often the workers report
+#[derive(Default)]
+pub struct EventBatcher {
+    pending: Mutex<PendingUpdates>,
+}
+
+impl EventBatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue a progress update, replacing any unsent one for the same job
+    pub fn push_progress(&self, job_id: &str, progress: &Progress) {
+        if let Ok(mut pending) = self.pending.lock() {
+            if pending.progress.insert(job_id.to_string(), progress.clone()).is_none() {
+                pending.order.push(job_id.to_string());
+            }
+        }
+    }
+
+    /// Take a job's unsent progress so it can be sent ahead of a status change
+    pub fn take_job(&self, job_id: &str) -> Option<Progress> {
+        let mut pending = self.pending.lock().ok()?;
+        let progress = pending.progress.remove(job_id)?;
+        pending.order.retain(|id| id != job_id);
+        Some(progress)
+    }
+
+    /// Take everything queued since the last flush, if anything was
+    pub fn drain(&self) -> Option<QueueUpdateEvent> {
+        let mut pending = self.pending.lock().ok()?;
+        if pending.order.is_empty() {
+            return None;
+        }
+
+        let order = std::mem::take(&mut pending.order);
+        let jobs = order.into_iter()
+            .filter_map(|job_id| {
+                let progress = pending.progress.remove(&job_id)?;
+                Some(JobProgressEvent { job_id, progress })
+            })
+            .collect();
+        Some(QueueUpdateEvent { jobs })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn progress(percentage: f32) -> Progress {
+        Progress {
+            percentage: Some(percentage),
+            ..Progress::default()
+        }
+    }
+
+    #[test]
+    fn test_coalesces_to_latest() {
+        let batcher = EventBatcher::new();
+        assert!(batcher.drain().is_none());
+
+        batcher.push_progress("a", &progress(10.0));
+        batcher.push_progress("b", &progress(5.0));
+        batcher.push_progress("a", &progress(30.0));
+
+        let update = batcher.drain().unwrap();
+        assert_eq!(update.jobs.len(), 2);
+        assert_eq!(update.jobs[0].job_id, "a");
+        assert_eq!(update.jobs[0].progress.percentage, Some(30.0));
+        assert_eq!(update.jobs[1].job_id, "b");
+        assert!(batcher.drain().is_none());
+    }
+
+    #[test]
+    fn test_take_job() {
+        let batcher = EventBatcher::new();
+        batcher.push_progress("a", &progress(99.0));
+        batcher.push_progress("b", &progress(5.0));
+
+        assert_eq!(batcher.take_job("a").unwrap().percentage, Some(99.0));
+        assert!(batcher.take_job("a").is_none());
+
+        let update = batcher.drain().unwrap();
+        assert_eq!(update.jobs.len(), 1);
+        assert_eq!(update.jobs[0].job_id, "b");
+    }
+}
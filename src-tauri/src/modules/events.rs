@@ -0,0 +1,546 @@
+use crate::modules::app_updater::UpdateDownloadProgress;
+use crate::modules::connectivity::ConnectivityEvent;
+use crate::modules::cookie_monitor::CookieExpiryWarning;
+use crate::modules::throughput::QueueForecast;
+use crate::modules::temp_janitor::TempUsage;
+use crate::modules::disk_space::SpaceProjection;
+use crate::modules::event_batcher::EventBatcher;
+use crate::modules::self_test::SelfTestReport;
+use crate::modules::session_report::SessionSummary;
+use crate::modules::job_events::JobEvent;
+use crate::modules::mini_window::{MiniProgress, MINI_WINDOW_LABEL};
+use crate::modules::state::{DownloadJob, JobMetadata, JobStatus, Progress};
+use crate::modules::subscriptions::Subscription;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// Emitted when a job is added to the queue.
+///
+/// Payload: [`DownloadJob`]
+/// ```json
+/// { "id": "…", "url": "https://music.youtube.com/watch?v=…", "status": "Queued", … }
+/// ```
+pub const JOB_ADDED_EVENT: &str = "job-added";
+
+/// Emitted whenever a job's `Progress` changes.
+///
+/// Payload: [`JobProgressEvent`]
+/// ```json
+/// { "job_id": "…", "progress": { "stage": "DownloadingAudio", "percentage": 42.0, … } }
+/// ```
+pub const JOB_PROGRESS_EVENT: &str = "job-progress";
+
+/// Emitted whenever a job's `JobStatus` changes.
+///
+/// Payload: [`JobStatusEvent`]
+/// ```json
+/// { "job_id": "…", "status": "Failed", "error": "Process exited with code: 1" }
+/// ```
+pub const JOB_STATUS_EVENT: &str = "job-status";
+
+/// Emitted when pre-fetched metadata has been attached to a queued job.
+///
+/// Payload: [`JobMetadataEvent`]
+/// ```json
+/// { "job_id": "…", "metadata": { "title": "…", "artist": "…", "thumbnail": "…" } }
+/// ```
+pub const JOB_METADATA_EVENT: &str = "job-metadata";
+
+/// Emitted after a job completes when the queued tracks are projected
+/// not to fit on the output volume.
+///
+/// Payload: [`SpaceProjection`]
+/// ```json
+/// { "available_bytes": 370000000, "tracks_that_fit": 37, "queued_tracks": 120, "will_fit": false, "message": "…" }
+/// ```
+pub const DISK_SPACE_WARNING_EVENT: &str = "disk-space-warning";
+
+/// Emitted once the startup self-test has finished.
+///
+/// Payload: [`SelfTestReport`]
+/// ```json
+/// { "checks": [{ "name": "sidecar", "status": "Degraded", "message": "…", "degraded": 1, "duration_ms": 12 }], "degraded": 1, … }
+/// ```
+pub const SELF_TEST_EVENT: &str = "self-test-complete";
+
+/// Emitted for every message recorded by the debug logger.
+///
+/// Payload: [`LogEntry`](crate::modules::debug_logger::LogEntry)
+/// ```json
+/// { "timestamp": "…", "level": "Debug", "component": "queue", "message": "Process exited with status: …" }
+/// ```
+pub const LOG_ENTRY_EVENT: &str = "log-entry";
+
+/// Emitted at most once per batch interval with the latest progress of every
+/// job that reported since the previous update. Replaces [`JOB_PROGRESS_EVENT`]
+/// when batching is enabled; status changes are never batched.
+///
+/// Payload: [`QueueUpdateEvent`](crate::modules::event_batcher::QueueUpdateEvent)
+/// ```json
+/// { "jobs": [{ "job_id": "…", "progress": { "stage": "DownloadingAudio", "percentage": 42.0, … } }] }
+/// ```
+pub const QUEUE_UPDATE_EVENT: &str = "queue-update";
+
+/// Emitted when the managed cookies enter the expiry warning window or expire.
+///
+/// Payload: [`CookieExpiryWarning`]
+/// ```json
+/// { "expired": false, "days_remaining": 3, "expires_at": "…", "message": "YouTube cookies expire in 3 day(s)…" }
+/// ```
+pub const COOKIE_EXPIRY_EVENT: &str = "cookie-expiry";
+
+/// Emitted when the queue pauses itself rather than being paused by the user.
+///
+/// Payload: [`QueuePausedEvent`]
+/// ```json
+/// { "reason": "Not enough disk space for /music: 120 MB free, at least 500 MB required" }
+/// ```
+pub const QUEUE_PAUSED_EVENT: &str = "queue-paused";
+
+/// Emitted when the queue paused itself because several downloads in a row
+/// were refused by YouTube. The frontend prompts the user to re-import
+/// cookies, which clears the `cookies_suspect` degraded flag.
+///
+/// Payload: [`CookiesSuspectEvent`]
+/// ```json
+/// { "consecutive_failures": 3, "message": "3 downloads in a row were refused by YouTube…" }
+/// ```
+pub const COOKIES_SUSPECT_EVENT: &str = "cookies-suspect";
+
+/// Emitted when the connectivity monitor finds the connection lost or back.
+/// While offline the frontend shows a banner; the `offline` degraded flag is
+/// set for as long as the connection is down.
+///
+/// Payload: [`ConnectivityEvent`]
+/// ```json
+/// { "online": false, "queue_paused": true, "message": "Lost the connection, so the queue was paused…" }
+/// ```
+pub const CONNECTIVITY_CHANGED_EVENT: &str = "connectivity-changed";
+
+/// Emitted when the main window is closed while downloads are running and
+/// `confirm_exit_with_active_downloads` is on. The window stays open until the
+/// frontend calls `confirm_exit`.
+///
+/// Payload: [`ExitRequestedEvent`]
+/// ```json
+/// { "active_jobs": 2 }
+/// ```
+pub const EXIT_REQUESTED_EVENT: &str = "exit-requested";
+
+/// Emitted when a subscription check found new tracks and queued them.
+///
+/// Payload: [`SubscriptionContentEvent`]
+/// ```json
+/// { "subscription_id": "…", "url": "https://music.youtube.com/channel/…", "name": "Artist", "job_ids": ["…"] }
+/// ```
+pub const SUBSCRIPTION_CONTENT_EVENT: &str = "subscription-new-content";
+
+/// Emitted when a YouTube Music URL is copied while the clipboard watcher is
+/// on and `clipboard_action` is `Prompt`. The frontend asks the user and
+/// calls `add_to_queue` to accept.
+///
+/// Payload: [`ClipboardUrlEvent`]
+/// ```json
+/// { "url": "https://music.youtube.com/watch?v=…" }
+/// ```
+pub const CLIPBOARD_URL_EVENT: &str = "clipboard-url";
+
+/// Emitted for every `gytmdl://` link the app is opened with. Any page can
+/// open such a link, so nothing is queued: the frontend asks the user and
+/// calls `add_to_queue` to accept. `source` is only a label the link claims.
+///
+/// Payload: [`DeepLinkEvent`]
+/// ```json
+/// { "url": "https://music.youtube.com/watch?v=…", "source": "https://example.com" }
+/// ```
+pub const DEEP_LINK_EVENT: &str = "deep-link";
+
+/// Emitted when the user clicks one of the app's desktop notifications,
+/// after the main window was brought forward. `job_id` is set for job
+/// notifications so the frontend can show that job.
+///
+/// Payload: [`NotificationClickedEvent`]
+/// ```json
+/// { "job_id": "…" }
+/// ```
+pub const NOTIFICATION_CLICKED_EVENT: &str = "notification-clicked";
+
+/// Emitted while `install_app_update` downloads a new release, once per
+/// whole percent (or per MiB when the size is unknown). The app restarts
+/// when the install finishes.
+///
+/// Payload: [`UpdateDownloadProgress`]
+/// ```json
+/// { "downloaded_bytes": 1048576, "total_bytes": 8388608 }
+/// ```
+pub const APP_UPDATE_PROGRESS_EVENT: &str = "app-update-progress";
+
+/// Emitted whenever a job finishes, with the same forecast
+/// `get_queue_forecast` returns.
+///
+/// Payload: [`QueueForecast`]
+/// ```json
+/// { "remaining_tracks": 4.5, "remaining_mb": 35.0, "tracks_sized_from_metadata": 3, "bytes_per_second": 393216.0, "seconds_remaining": 95, "estimated_completion": "2024-05-01T12:03:05Z", "estimated_cost": null, "message": "~35 MB left, about 1m remaining" }
+/// ```
+pub const QUEUE_FORECAST_EVENT: &str = "queue-forecast";
+
+/// Emitted after the startup scan of the temp folder, and after hourly
+/// re-scans, when it holds stale job folders or partial files. With
+/// `auto_clean_stale_temp` on they have already been deleted and the payload
+/// lists any that couldn't be.
+///
+/// Payload: [`TempUsage`]
+/// ```json
+/// { "temp_path": "/tmp/gytmdl", "total_bytes": 52428800, "reclaimable_bytes": 41943040, "stale_entries": [{ "path": "/tmp/gytmdl/3f2b...", "bytes": 41943040, "modified": "2024-05-01T12:00:00Z" }] }
+/// ```
+pub const TEMP_USAGE_EVENT: &str = "temp-usage";
+
+/// Sent only to the mini window, twice a second while it is open and the
+/// running downloads changed.
+///
+/// Payload: [`MiniProgress`]
+/// ```json
+/// { "active": [{ "job_id": "…", "title": "…", "stage": "DownloadingAudio", "percentage": 42.0, … }], "queued": 3, "paused": false }
+/// ```
+pub const MINI_PROGRESS_EVENT: &str = "mini-progress";
+
+/// Emitted when the queue drains, with nothing left queued or downloading,
+/// after at least one job finished since the last time.
+///
+/// Payload: [`SessionSummary`]
+/// ```json
+/// { "started_at": "2024-05-01T12:00:00Z", "finished_at": "2024-05-01T12:20:00Z", "duration_secs": 1200, "completed": 11, "failed": 1, "cancelled": 0, "total_bytes": 94371840, "failures": [{ "job_id": "…", "url": "…", "title": "…", "error": "HTTP Error 403: Forbidden" }], "new_files": ["/music/…"], "report_path": null }
+/// ```
+pub const SESSION_SUMMARY_EVENT: &str = "session-summary";
+
+/// Payload for [`JOB_PROGRESS_EVENT`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobProgressEvent {
+    pub job_id: String,
+    pub progress: Progress,
+}
+
+/// Payload for [`JOB_STATUS_EVENT`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobStatusEvent {
+    pub job_id: String,
+    pub status: JobStatus,
+    pub error: Option<String>,
+}
+
+/// Payload for [`QUEUE_PAUSED_EVENT`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuePausedEvent {
+    pub reason: String,
+}
+
+/// Payload for [`COOKIES_SUSPECT_EVENT`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CookiesSuspectEvent {
+    pub consecutive_failures: u32,
+    pub message: String,
+}
+
+/// Payload for [`EXIT_REQUESTED_EVENT`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExitRequestedEvent {
+    pub active_jobs: usize,
+}
+
+/// Payload for [`SUBSCRIPTION_CONTENT_EVENT`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubscriptionContentEvent {
+    pub subscription_id: String,
+    pub url: String,
+    pub name: Option<String>,
+    pub job_ids: Vec<String>,
+}
+
+/// Payload for [`CLIPBOARD_URL_EVENT`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClipboardUrlEvent {
+    pub url: String,
+}
+
+/// Payload for [`NOTIFICATION_CLICKED_EVENT`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationClickedEvent {
+    pub job_id: Option<String>,
+}
+
+/// Payload for [`DEEP_LINK_EVENT`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeepLinkEvent {
+    pub url: String,
+    pub source: String,
+}
+
+/// Payload for [`JOB_METADATA_EVENT`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobMetadataEvent {
+    pub job_id: String,
+    pub metadata: JobMetadata,
+}
+
+/// Emits job events to the frontend.
+///
+/// Holds an optional `AppHandle` so the queue can run without a Tauri
+/// runtime (tests, headless use); emitting is then a no-op.
+#[derive(Clone, Default)]
+pub struct EventEmitter {
+    app_handle: Option<AppHandle>,
+    batcher: Option<Arc<EventBatcher>>,
+}
+
+impl EventEmitter {
+    /// Create an emitter that forwards events to the given app
+    pub fn new(app_handle: AppHandle) -> Self {
+        Self { app_handle: Some(app_handle), batcher: None }
+    }
+
+    /// Create an emitter that drops all events
+    pub fn disabled() -> Self {
+        Self { app_handle: None, batcher: None }
+    }
+
+    /// Coalesce job progress into one `queue-update` event per `interval`.
+    /// The flush task stops once every clone of this emitter is dropped.
+    pub fn with_batching(mut self, interval: Duration) -> Self {
+        if self.app_handle.is_none() {
+            return self;
+        }
+
+        let batcher = Arc::new(EventBatcher::new());
+        let pending = Arc::downgrade(&batcher);
+        let flusher = Self { app_handle: self.app_handle.clone(), batcher: None };
+        tauri::async_runtime::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            loop {
+                ticker.tick().await;
+                let Some(batcher) = pending.upgrade() else {
+                    break;
+                };
+                if let Some(update) = batcher.drain() {
+                    flusher.emit(QUEUE_UPDATE_EVENT, update);
+                }
+            }
+        });
+
+        self.batcher = Some(batcher);
+        self
+    }
+
+    /// Check if events are forwarded anywhere
+    pub fn is_enabled(&self) -> bool {
+        self.app_handle.is_some()
+    }
+
+    /// Emit a serializable payload under the given event name
+    pub fn emit<S: Serialize + Clone>(&self, event: &str, payload: S) {
+        if let Some(app_handle) = &self.app_handle {
+            if let Err(e) = app_handle.emit(event, payload) {
+                // Not routed through the debug logger, which emits through here itself
+                eprintln!("Failed to emit {} event: {}", event, e);
+            }
+        }
+    }
+
+    /// Emit to one window only
+    pub fn emit_to<S: Serialize + Clone>(&self, window_label: &str, event: &str, payload: S) {
+        if let Some(app_handle) = &self.app_handle {
+            if let Err(e) = app_handle.emit_to(window_label, event, payload) {
+                eprintln!("Failed to emit {} event to {}: {}", event, window_label, e);
+            }
+        }
+    }
+
+    /// Emit a job progress update
+    pub fn job_progress(&self, job_id: &str, progress: &Progress) {
+        if let Some(batcher) = &self.batcher {
+            batcher.push_progress(job_id, progress);
+            return;
+        }
+        self.emit(JOB_PROGRESS_EVENT, JobProgressEvent {
+            job_id: job_id.to_string(),
+            progress: progress.clone(),
+        });
+    }
+
+    /// Emit pre-fetched job metadata
+    pub fn job_metadata(&self, job_id: &str, metadata: &JobMetadata) {
+        self.emit(JOB_METADATA_EVENT, JobMetadataEvent {
+            job_id: job_id.to_string(),
+            metadata: metadata.clone(),
+        });
+    }
+
+    /// Emit a disk space warning
+    pub fn disk_space_warning(&self, projection: &SpaceProjection) {
+        self.emit(DISK_SPACE_WARNING_EVENT, projection.clone());
+    }
+
+    /// Emit the startup self-test report
+    pub fn self_test(&self, report: &SelfTestReport) {
+        self.emit(SELF_TEST_EVENT, report.clone());
+    }
+
+    /// Emit that the queue paused itself
+    pub fn queue_paused(&self, reason: &str) {
+        self.emit(QUEUE_PAUSED_EVENT, QueuePausedEvent {
+            reason: reason.to_string(),
+        });
+    }
+
+    /// Emit that the connection was lost or came back
+    pub fn connectivity_changed(&self, event: &ConnectivityEvent) {
+        self.emit(CONNECTIVITY_CHANGED_EVENT, event.clone());
+    }
+
+    /// Ask the frontend to confirm closing with downloads still running
+    pub fn exit_requested(&self, active_jobs: usize) {
+        self.emit(EXIT_REQUESTED_EVENT, ExitRequestedEvent { active_jobs });
+    }
+
+    /// Emit that a subscription check queued new tracks
+    pub fn subscription_content(&self, subscription: &Subscription, job_ids: Vec<String>) {
+        self.emit(SUBSCRIPTION_CONTENT_EVENT, SubscriptionContentEvent {
+            subscription_id: subscription.id.clone(),
+            url: subscription.url.clone(),
+            name: subscription.name.clone(),
+            job_ids,
+        });
+    }
+
+    /// Offer a copied URL for queueing
+    pub fn clipboard_url(&self, url: &str) {
+        self.emit(CLIPBOARD_URL_EVENT, ClipboardUrlEvent { url: url.to_string() });
+    }
+
+    /// Report a deep link waiting for the user to confirm
+    pub fn deep_link(&self, url: &str, source: &str) {
+        self.emit(DEEP_LINK_EVENT, DeepLinkEvent {
+            url: url.to_string(),
+            source: source.to_string(),
+        });
+    }
+
+    /// Report a click on a notification, and the job it was about
+    pub fn notification_clicked(&self, job_id: Option<&str>) {
+        self.emit(NOTIFICATION_CLICKED_EVENT, NotificationClickedEvent {
+            job_id: job_id.map(str::to_string),
+        });
+    }
+
+    /// Report how much of an app update has been downloaded
+    pub fn app_update_progress(&self, progress: &UpdateDownloadProgress) {
+        self.emit(APP_UPDATE_PROGRESS_EVENT, progress.clone());
+    }
+
+    /// Ask the user to refresh cookies after repeated authentication failures
+    pub fn cookies_suspect(&self, consecutive_failures: u32, message: &str) {
+        self.emit(COOKIES_SUSPECT_EVENT, CookiesSuspectEvent {
+            consecutive_failures,
+            message: message.to_string(),
+        });
+    }
+
+    /// Emit a cookie expiry warning
+    pub fn cookie_expiry(&self, warning: &CookieExpiryWarning) {
+        self.emit(COOKIE_EXPIRY_EVENT, warning.clone());
+    }
+
+    /// Emit a newly queued job
+    pub fn job_added(&self, job: &DownloadJob) {
+        self.emit(JOB_ADDED_EVENT, job.clone());
+    }
+
+    /// Emit the updated queue forecast
+    pub fn queue_forecast(&self, forecast: &QueueForecast) {
+        self.emit(QUEUE_FORECAST_EVENT, forecast.clone());
+    }
+
+    /// Report stale files in the temp folder
+    pub fn temp_usage(&self, usage: &TempUsage) {
+        self.emit(TEMP_USAGE_EVENT, usage.clone());
+    }
+
+    /// Send the mini window its summary of running downloads
+    pub fn mini_progress(&self, summary: &MiniProgress) {
+        self.emit_to(MINI_WINDOW_LABEL, MINI_PROGRESS_EVENT, summary.clone());
+    }
+
+    /// Emit the summary of a session that ended with the queue draining
+    pub fn session_summary(&self, summary: &SessionSummary) {
+        self.emit(SESSION_SUMMARY_EVENT, summary.clone());
+    }
+
+    /// Emit the frontend event for a job change
+    pub fn job_event(&self, event: &JobEvent) {
+        match event {
+            JobEvent::Added(job) => self.job_added(job),
+            JobEvent::StatusChanged { job_id, status } => self.job_status(job_id, status.clone(), None),
+            JobEvent::Failed { job_id, error, .. } => self.job_status(job_id, JobStatus::Failed, Some(error.clone())),
+            JobEvent::ProgressUpdated { job_id, progress } => self.job_progress(job_id, progress),
+        }
+    }
+
+    /// Emit a job status change immediately, sending any progress still
+    /// waiting in the batch first so it doesn't arrive after the status
+    pub fn job_status(&self, job_id: &str, status: JobStatus, error: Option<String>) {
+        if let Some(progress) = self.batcher.as_ref().and_then(|batcher| batcher.take_job(job_id)) {
+            self.emit(JOB_PROGRESS_EVENT, JobProgressEvent {
+                job_id: job_id.to_string(),
+                progress,
+            });
+        }
+        self.emit(JOB_STATUS_EVENT, JobStatusEvent {
+            job_id: job_id.to_string(),
+            status,
+            error,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disabled_emitter_is_noop() {
+        let emitter = EventEmitter::disabled();
+        assert!(!emitter.is_enabled());
+
+        // Should not panic without an app handle
+        emitter.job_progress("job-1", &Progress::default());
+        emitter.job_status("job-1", JobStatus::Completed, None);
+    }
+
+    #[test]
+    fn test_job_status_event_serialization() {
+        let event = JobStatusEvent {
+            job_id: "job-1".to_string(),
+            status: JobStatus::Failed,
+            error: Some("Network error".to_string()),
+        };
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["job_id"], "job-1");
+        assert_eq!(json["status"], "Failed");
+        assert_eq!(json["error"], "Network error");
+    }
+
+    #[test]
+    fn test_job_progress_event_serialization() {
+        let event = JobProgressEvent {
+            job_id: "job-1".to_string(),
+            progress: Progress::default(),
+        };
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["job_id"], "job-1");
+        assert_eq!(json["progress"]["stage"], "Initializing");
+    }
+}
@@ -0,0 +1,233 @@
+use crate::modules::library::unique_destination;
+use crate::modules::state::AppConfig;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+/// Characters Windows and FAT32 refuse in file names
+const WINDOWS_ILLEGAL: &[char] = &['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+/// Device names Windows reserves regardless of extension
+const WINDOWS_RESERVED: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL",
+    "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9",
+    "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// How downloaded files and the folders gytmdl created for them are renamed
+/// so they can be copied to Windows shares and FAT32 devices
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FilenameRules {
+    pub windows_safe: bool,
+    pub strip_emoji: bool,
+    pub transliterate: bool,
+    /// Longest full path allowed, in characters; the file name is shortened to fit
+    pub max_path_length: Option<usize>,
+}
+
+impl FilenameRules {
+    pub fn for_config(config: &AppConfig) -> Self {
+        Self {
+            windows_safe: config.windows_safe_filenames,
+            strip_emoji: config.strip_emoji_from_filenames,
+            transliterate: config.transliterate_filenames,
+            max_path_length: config.max_path_length.map(|length| length as usize),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.windows_safe || self.strip_emoji || self.transliterate || self.max_path_length.is_some()
+    }
+
+    /// Apply the rules to one file or folder name
+    pub fn sanitize_component(&self, name: &str) -> String {
+        let mut name = name.to_string();
+        if self.strip_emoji {
+            name = name.chars().filter(|c| !is_emoji(*c)).collect();
+            name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+        }
+        if self.transliterate {
+            name = deunicode::deunicode(&name);
+        }
+        if self.windows_safe {
+            name = name.chars()
+                .map(|c| if WINDOWS_ILLEGAL.contains(&c) || c.is_control() { '_' } else { c })
+                .collect();
+            name = name.trim_end_matches(['.', ' ']).to_string();
+            let stem = name.split('.').next().unwrap_or("");
+            if WINDOWS_RESERVED.iter().any(|reserved| reserved.eq_ignore_ascii_case(stem)) {
+                name.insert(stem.len(), '_');
+            }
+        }
+        if name.trim().is_empty() {
+            name = "_".to_string();
+        }
+        name
+    }
+
+    /// Where `file` under `root` should live, or None if it already complies.
+    /// Folders below `root` are renamed along with the file.
+    pub fn target_path(&self, root: &Path, file: &Path) -> Option<PathBuf> {
+        let relative = file.strip_prefix(root).ok()?;
+        let components: Vec<String> = relative.components()
+            .filter_map(|component| match component {
+                Component::Normal(name) => Some(name.to_string_lossy().into_owned()),
+                _ => None,
+            })
+            .collect();
+        let (file_name, folders) = components.split_last()?;
+
+        let mut target = root.to_path_buf();
+        for folder in folders {
+            target.push(self.sanitize_component(folder));
+        }
+
+        let (stem, extension) = match file_name.rsplit_once('.') {
+            Some((stem, extension)) if !stem.is_empty() => (stem, Some(extension)),
+            _ => (file_name.as_str(), None),
+        };
+        let mut stem = self.sanitize_component(stem);
+        if let Some(max_length) = self.max_path_length {
+            let fixed = target.to_string_lossy().chars().count() + 1 + extension.map(|ext| ext.len() + 1).unwrap_or(0);
+            let room = max_length.saturating_sub(fixed).max(1);
+            if stem.chars().count() > room {
+                stem = stem.chars().take(room).collect::<String>().trim_end_matches(['.', ' ']).to_string();
+            }
+        }
+        target.push(match extension {
+            Some(extension) => format!("{}.{}", stem, extension),
+            None => stem,
+        });
+
+        (target != file).then_some(target)
+    }
+}
+
+/// Pictographs (flags included), symbols and the joiners and selectors emoji are built from
+fn is_emoji(c: char) -> bool {
+    matches!(c as u32,
+        0x1F000..=0x1FAFF
+        | 0x2600..=0x27BF
+        | 0x2B00..=0x2BFF
+        | 0xFE00..=0xFE0F
+        | 0x200D
+        | 0xE0020..=0xE007F)
+}
+
+/// Rename `files` and the files next to them sharing their stem (lyrics,
+/// covers). Returns the old and new path of every renamed media file.
+pub fn apply(rules: &FilenameRules, root: &Path, files: &[PathBuf]) -> io::Result<Vec<(PathBuf, PathBuf)>> {
+    let mut renamed = Vec::new();
+    for file in files {
+        let Some(target) = rules.target_path(root, file) else {
+            continue;
+        };
+        let target_dir = target.parent().unwrap_or(root).to_path_buf();
+        fs::create_dir_all(&target_dir)?;
+
+        let target_name = target.file_name().unwrap_or_default();
+        let destination = unique_destination(&target_dir, target_name);
+        for (sibling, sibling_target) in siblings(file, &destination)? {
+            if !sibling_target.exists() {
+                fs::rename(&sibling, &sibling_target)?;
+            }
+        }
+        fs::rename(file, &destination)?;
+
+        if let Some(old_dir) = file.parent() {
+            remove_empty_dirs(old_dir, root);
+        }
+        renamed.push((file.clone(), destination));
+    }
+    Ok(renamed)
+}
+
+/// Files next to `file` with its stem and another extension, paired with
+/// their path next to `destination`
+fn siblings(file: &Path, destination: &Path) -> io::Result<Vec<(PathBuf, PathBuf)>> {
+    let (Some(dir), Some(stem), Some(new_stem)) = (file.parent(), file.file_stem(), destination.file_stem()) else {
+        return Ok(Vec::new());
+    };
+    let new_dir = destination.parent().unwrap_or(dir);
+    let mut found = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path == file || path.file_stem() != Some(stem) {
+            continue;
+        }
+        let Some(extension) = path.extension() else {
+            continue;
+        };
+        let mut name = new_stem.to_os_string();
+        name.push(".");
+        name.push(extension);
+        found.push((path.clone(), new_dir.join(name)));
+    }
+    Ok(found)
+}
+
+/// Remove `dir` and its parents up to `root` while they are empty
+fn remove_empty_dirs(dir: &Path, root: &Path) {
+    let mut current = dir;
+    while current != root && current.starts_with(root) {
+        if fs::remove_dir(current).is_err() {
+            break;
+        }
+        match current.parent() {
+            Some(parent) => current = parent,
+            None => break,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn all_rules() -> FilenameRules {
+        FilenameRules {
+            windows_safe: true,
+            strip_emoji: true,
+            transliterate: true,
+            max_path_length: None,
+        }
+    }
+
+    #[test]
+    fn test_sanitize_component() {
+        let windows = FilenameRules { windows_safe: true, ..FilenameRules::default() };
+        assert_eq!(windows.sanitize_component("What? Why: \"Now\"."), "What_ Why_ _Now_");
+        assert_eq!(windows.sanitize_component("con"), "con_");
+        assert_eq!(windows.sanitize_component("Aux.Intro"), "Aux_.Intro");
+
+        let rules = all_rules();
+        assert_eq!(rules.sanitize_component("Café 🔥 Beyoncé ❤️"), "Cafe Beyonce");
+        assert_eq!(rules.sanitize_component("🔥"), "_");
+        assert_eq!(rules.sanitize_component("東京"), "Dong Jing");
+    }
+
+    #[test]
+    fn test_apply_renames_files_and_folders() {
+        let root = tempfile::tempdir().unwrap();
+        let album = root.path().join("Artist").join("Album: Live?");
+        fs::create_dir_all(&album).unwrap();
+        let track = album.join("01 Song?.m4a");
+        fs::write(&track, b"audio").unwrap();
+        fs::write(album.join("01 Song?.lrc"), b"lyrics").unwrap();
+
+        let rules = FilenameRules {
+            windows_safe: true,
+            max_path_length: Some(root.path().to_string_lossy().chars().count() + 30),
+            ..FilenameRules::default()
+        };
+        let renamed = apply(&rules, root.path(), std::slice::from_ref(&track)).unwrap();
+        let new_album = root.path().join("Artist").join("Album_ Live_");
+        // The name is shortened to the 5 characters left under the path limit
+        assert_eq!(renamed, vec![(track, new_album.join("01 So.m4a"))]);
+        assert!(new_album.join("01 So.m4a").exists());
+        assert!(new_album.join("01 So.lrc").exists());
+        assert!(!album.exists());
+
+        assert!(rules.target_path(root.path(), &new_album.join("01 So.m4a")).is_none());
+    }
+}
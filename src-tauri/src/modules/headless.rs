@@ -0,0 +1,98 @@
+use std::fmt;
+
+/// Printed for `--help` and after a usage error
+pub const USAGE: &str = "\
+Usage: gytmdl-gui --headless [--add URL]... [--wait]
+
+Drives the same queue, config and cookies as the app, without opening a window.
+
+Options:
+  --add URL   Queue a YouTube Music URL (repeatable)
+  --wait      Run the queue until every due job has finished; exits with 1 if any failed
+  --help      Show this message
+
+Without --wait, URLs are only added to the queue and run the next time the
+app or `--headless --wait` starts. Don't run this while the app is open: both
+write the same state file.";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum HeadlessError {
+    MissingValue(String),
+    UnknownArgument(String),
+}
+
+impl fmt::Display for HeadlessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HeadlessError::MissingValue(flag) => write!(f, "{} requires a value", flag),
+            HeadlessError::UnknownArgument(arg) => write!(f, "Unknown argument: '{}'", arg),
+        }
+    }
+}
+
+impl std::error::Error for HeadlessError {}
+
+/// Options for a headless run
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HeadlessArgs {
+    pub add: Vec<String>,
+    pub wait: bool,
+    pub help: bool,
+}
+
+/// Parse the process arguments (including the executable). Returns `None`
+/// when `--headless` isn't among them and the app should start normally.
+pub fn parse_args(args: &[String]) -> Result<Option<HeadlessArgs>, HeadlessError> {
+    if !args.iter().skip(1).any(|arg| arg == "--headless") {
+        return Ok(None);
+    }
+
+    let mut parsed = HeadlessArgs::default();
+    let mut args = args.iter().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--headless" => {}
+            "--add" => {
+                let url = args.next().ok_or_else(|| HeadlessError::MissingValue(arg.clone()))?;
+                parsed.add.push(url.clone());
+            }
+            "--wait" => parsed.wait = true,
+            "--help" | "-h" => parsed.help = true,
+            _ => return Err(HeadlessError::UnknownArgument(arg.clone())),
+        }
+    }
+    Ok(Some(parsed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(parse_args(&args(&["gytmdl-gui"])), Ok(None));
+        assert_eq!(parse_args(&args(&["gytmdl-gui", "https://music.youtube.com/watch?v=a"])), Ok(None));
+
+        let parsed = parse_args(&args(&["gytmdl-gui", "--headless", "--add", "a", "--wait", "--add", "b"]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(parsed.add, vec!["a", "b"]);
+        assert!(parsed.wait);
+    }
+
+    #[test]
+    fn test_rejects_bad_args() {
+        assert_eq!(
+            parse_args(&args(&["gytmdl-gui", "--headless", "--add"])),
+            Err(HeadlessError::MissingValue("--add".to_string()))
+        );
+        assert_eq!(
+            parse_args(&args(&["gytmdl-gui", "--headless", "--bogus"])),
+            Err(HeadlessError::UnknownArgument("--bogus".to_string()))
+        );
+    }
+}
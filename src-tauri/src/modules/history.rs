@@ -0,0 +1,248 @@
+use crate::modules::session_report::SessionSummary;
+use crate::modules::state::{AppState, DownloadJob};
+use chrono::{Duration, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Summary of the cleanup performed when state is loaded
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CleanupSummary {
+    /// Terminal jobs moved from the queue into history
+    pub archived_jobs: usize,
+    /// Queued jobs removed because another queued job had the same URL
+    pub deduplicated_jobs: usize,
+    pub retention_days: u32,
+}
+
+impl CleanupSummary {
+    /// Check if the cleanup changed anything
+    pub fn is_empty(&self) -> bool {
+        self.archived_jobs == 0 && self.deduplicated_jobs == 0
+    }
+}
+
+/// Append-only store of finished jobs that have left the queue.
+/// Each line of the history file is one JSON-encoded `DownloadJob`; session
+/// summaries go to `sessions.jsonl` next to it the same way.
+pub struct HistoryStore {
+    history_file: PathBuf,
+}
+
+impl HistoryStore {
+    /// Create a HistoryStore in the app data directory
+    pub fn new() -> Self {
+        let history_file = std::env::current_dir()
+            .unwrap_or_else(|_| PathBuf::from("."))
+            .join(".gytmdl-gui")
+            .join("history.jsonl");
+
+        Self { history_file }
+    }
+
+    /// Create a HistoryStore backed by a specific file
+    pub fn with_history_file(history_file: PathBuf) -> Self {
+        Self { history_file }
+    }
+
+    /// Get the path to the history file
+    pub fn get_history_file_path(&self) -> &PathBuf {
+        &self.history_file
+    }
+
+    /// Get the path to the session summaries file
+    pub fn get_sessions_file_path(&self) -> PathBuf {
+        self.history_file.with_file_name("sessions.jsonl")
+    }
+
+    /// Append jobs to the history file
+    pub fn append(&self, jobs: &[DownloadJob]) -> Result<(), io::Error> {
+        append_lines(&self.history_file, jobs)
+    }
+
+    /// Load all jobs from history, skipping unreadable lines
+    pub fn load(&self) -> Result<Vec<DownloadJob>, io::Error> {
+        load_lines(&self.history_file)
+    }
+
+    /// Append the summary of a finished session
+    pub fn append_session(&self, summary: &SessionSummary) -> Result<(), io::Error> {
+        append_lines(&self.get_sessions_file_path(), std::slice::from_ref(summary))
+    }
+
+    /// Load all session summaries, oldest first, skipping unreadable lines
+    pub fn load_sessions(&self) -> Result<Vec<SessionSummary>, io::Error> {
+        load_lines(&self.get_sessions_file_path())
+    }
+
+    /// Move old terminal jobs into history and drop duplicate queued URLs.
+    /// Archived jobs are only removed from the state once history has been written.
+    pub fn prune_state(&self, state: &mut AppState, retention_days: u32) -> Result<CleanupSummary, io::Error> {
+        let cutoff = Utc::now() - Duration::days(retention_days as i64);
+
+        let archived = state.take_terminal_jobs_before(cutoff);
+        if let Err(e) = self.append(&archived) {
+            state.jobs.extend(archived);
+            return Err(e);
+        }
+
+        let deduplicated_jobs = state.dedupe_queued_jobs();
+
+        Ok(CleanupSummary {
+            archived_jobs: archived.len(),
+            deduplicated_jobs,
+            retention_days,
+        })
+    }
+
+    /// Move finished jobs beyond the `keep` most recent into history. They are
+    /// put back in the state if history can't be written. Returns the number moved.
+    pub fn archive_overflow(&self, state: &mut AppState, keep: usize) -> Result<usize, io::Error> {
+        let archived = state.take_finished_jobs_over(keep);
+        if let Err(e) = self.append(archived.jobs()) {
+            state.restore_jobs(archived);
+            return Err(e);
+        }
+        Ok(archived.len())
+    }
+}
+
+/// Append one JSON line per item to `path`
+fn append_lines<T: Serialize>(path: &Path, items: &[T]) -> Result<(), io::Error> {
+    if items.is_empty() {
+        return Ok(());
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut buffer = String::new();
+    for item in items {
+        let line = serde_json::to_string(item)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        buffer.push_str(&line);
+        buffer.push('\n');
+    }
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    file.write_all(buffer.as_bytes())
+}
+
+/// Read the JSON lines of `path`, skipping unreadable ones
+fn load_lines<T: DeserializeOwned>(path: &Path) -> Result<Vec<T>, io::Error> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = fs::read_to_string(path)?;
+    Ok(content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+impl Default for HistoryStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::state::JobStatus;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_append_and_load() {
+        let temp_dir = tempdir().unwrap();
+        let store = HistoryStore::with_history_file(temp_dir.path().join("history.jsonl"));
+        assert!(store.load().unwrap().is_empty());
+
+        let job = DownloadJob::new("https://music.youtube.com/watch?v=1".to_string());
+        store.append(std::slice::from_ref(&job)).unwrap();
+        store.append(&[DownloadJob::new("https://music.youtube.com/watch?v=2".to_string())]).unwrap();
+
+        let history = store.load().unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].id, job.id);
+    }
+
+    #[test]
+    fn test_append_and_load_sessions() {
+        let temp_dir = tempdir().unwrap();
+        let store = HistoryStore::with_history_file(temp_dir.path().join("history.jsonl"));
+        assert!(store.load_sessions().unwrap().is_empty());
+
+        let mut tracker = crate::modules::session_report::SessionTracker::new();
+        tracker.record_cancelled(&DownloadJob::new("https://music.youtube.com/watch?v=1".to_string()));
+        store.append_session(&tracker.finish(Utc::now()).unwrap()).unwrap();
+
+        let sessions = store.load_sessions().unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].cancelled, 1);
+        assert!(store.load().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_prune_state() {
+        let temp_dir = tempdir().unwrap();
+        let store = HistoryStore::with_history_file(temp_dir.path().join("history.jsonl"));
+        let mut state = AppState::new();
+
+        // Old completed job gets archived
+        let old_id = state.add_job("https://music.youtube.com/watch?v=old".to_string());
+        state.update_job_status(&old_id, JobStatus::Completed);
+        state.get_job_mut(&old_id).unwrap().completed_at = Some(Utc::now() - Duration::days(40));
+
+        // Recent completed job stays
+        let recent_id = state.add_job("https://music.youtube.com/watch?v=recent".to_string());
+        state.update_job_status(&recent_id, JobStatus::Completed);
+
+        // Duplicate queued URLs collapse to the first one
+        let first_id = state.add_job("https://music.youtube.com/watch?v=dup".to_string());
+        state.add_job("https://music.youtube.com/watch?v=dup".to_string());
+
+        let summary = store.prune_state(&mut state, 30).unwrap();
+        assert_eq!(summary.archived_jobs, 1);
+        assert_eq!(summary.deduplicated_jobs, 1);
+        assert!(!summary.is_empty());
+
+        let remaining: Vec<&str> = state.jobs.iter().map(|job| job.id.as_str()).collect();
+        assert_eq!(remaining, vec![recent_id.as_str(), first_id.as_str()]);
+
+        let history = store.load().unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].id, old_id);
+    }
+
+    #[test]
+    fn test_archive_overflow_keeps_most_recent() {
+        let temp_dir = tempdir().unwrap();
+        let store = HistoryStore::with_history_file(temp_dir.path().join("history.jsonl"));
+        let mut state = AppState::new();
+
+        let mut finished = Vec::new();
+        for (i, days_ago) in [3, 1, 2].into_iter().enumerate() {
+            let id = state.add_job(format!("https://music.youtube.com/watch?v={}", i));
+            state.update_job_status(&id, if i == 0 { JobStatus::Failed } else { JobStatus::Completed });
+            state.get_job_mut(&id).unwrap().completed_at = Some(Utc::now() - Duration::days(days_ago));
+            finished.push(id);
+        }
+        let queued_id = state.add_job("https://music.youtube.com/watch?v=queued".to_string());
+
+        assert_eq!(store.archive_overflow(&mut state, 1).unwrap(), 2);
+        let remaining: Vec<&str> = state.jobs.iter().map(|job| job.id.as_str()).collect();
+        assert_eq!(remaining, vec![finished[1].as_str(), queued_id.as_str()]);
+        assert_eq!(store.load().unwrap().len(), 2);
+
+        assert_eq!(store.archive_overflow(&mut state, 1).unwrap(), 0);
+    }
+}
@@ -0,0 +1,132 @@
+use crate::modules::error_classifier::ClassifiedError;
+use crate::modules::events::EventEmitter;
+use crate::modules::state::{AppState, DownloadJob, JobStatus, Progress};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// A change to a job, published on a [`JobBus`]
+#[derive(Debug, Clone)]
+pub enum JobEvent {
+    Added(Box<DownloadJob>),
+    StatusChanged {
+        job_id: String,
+        status: JobStatus,
+    },
+    /// The job failed; `details` are classified from `error` when unset
+    Failed {
+        job_id: String,
+        error: String,
+        details: Option<ClassifiedError>,
+    },
+    ProgressUpdated {
+        job_id: String,
+        progress: Progress,
+    },
+}
+
+impl JobEvent {
+    pub fn status(job_id: &str, status: JobStatus) -> Self {
+        Self::StatusChanged { job_id: job_id.to_string(), status }
+    }
+
+    pub fn failed(job_id: &str, error: String) -> Self {
+        Self::Failed { job_id: job_id.to_string(), error, details: None }
+    }
+
+    pub fn progress(job_id: &str, progress: Progress) -> Self {
+        Self::ProgressUpdated { job_id: job_id.to_string(), progress }
+    }
+
+    /// Apply the change to `state`. Returns false if the job doesn't exist,
+    /// or already does for `Added`.
+    pub fn apply(&self, state: &mut AppState) -> bool {
+        match self {
+            Self::Added(job) => {
+                if state.get_job(&job.id).is_some() {
+                    return false;
+                }
+                let mut job = (**job).clone();
+                state.drop_completed_dependencies(&mut job.depends_on);
+                state.jobs.push(job);
+                true
+            }
+            Self::StatusChanged { job_id, status } => state.update_job_status(job_id, status.clone()),
+            Self::Failed { job_id, error, details: Some(details) } => {
+                state.set_job_failure(job_id, error.clone(), details.clone())
+            }
+            Self::Failed { job_id, error, details: None } => state.set_job_error(job_id, error.clone()),
+            Self::ProgressUpdated { job_id, progress } => state.update_job_progress(job_id, progress.clone()),
+        }
+    }
+}
+
+/// Single path for job changes: each event is applied to the app state, then
+/// sent to the frontend through the emitter, which coalesces progress.
+/// Progress of a running job only touches its live progress cell, so the
+/// state write lock isn't taken for every line gytmdl prints.
+#[derive(Clone)]
+pub struct JobBus {
+    state: Arc<RwLock<AppState>>,
+    events: EventEmitter,
+}
+
+impl JobBus {
+    pub fn new(state: Arc<RwLock<AppState>>, events: EventEmitter) -> Self {
+        Self { state, events }
+    }
+
+    /// Apply and emit an event
+    pub async fn publish(&self, event: JobEvent) {
+        if let JobEvent::ProgressUpdated { job_id, progress } = &event {
+            let cell = self.state.read().await.live_progress.cell(job_id);
+            if let Some(cell) = cell {
+                cell.set(progress.clone());
+                self.events.job_event(&event);
+                return;
+            }
+        }
+        let mut state_guard = self.state.write().await;
+        self.publish_locked(&mut state_guard, event);
+    }
+
+    /// Apply and emit an event while the caller holds the state write lock
+    pub fn publish_locked(&self, state: &mut AppState, event: JobEvent) {
+        if event.apply(state) {
+            state.mark_changed();
+            self.events.job_event(&event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_events_update_state() {
+        let state = Arc::new(RwLock::new(AppState::new()));
+        let bus = JobBus::new(Arc::clone(&state), EventEmitter::disabled());
+
+        let job = DownloadJob::new("https://music.youtube.com/watch?v=abc".to_string());
+        let job_id = job.id.clone();
+        bus.publish(JobEvent::Added(Box::new(job.clone()))).await;
+        assert!(!JobEvent::Added(Box::new(job)).apply(&mut *state.write().await));
+
+        bus.publish(JobEvent::status(&job_id, JobStatus::Downloading)).await;
+        let cell = state.read().await.live_progress.track(&job_id, Progress::default());
+        bus.publish(JobEvent::progress(&job_id, Progress {
+            percentage: Some(40.0),
+            ..Progress::default()
+        })).await;
+        // Running jobs' progress goes to the live cell only
+        assert_eq!(cell.get().percentage, Some(40.0));
+        assert_eq!(state.read().await.get_job(&job_id).unwrap().progress.percentage, None);
+
+        bus.publish(JobEvent::failed(&job_id, "HTTP Error 403: Forbidden".to_string())).await;
+        let state_guard = state.read().await;
+        let job = state_guard.get_job(&job_id).unwrap();
+        assert_eq!(job.status, JobStatus::Failed);
+        assert!(job.started_at.is_some());
+        assert!(job.error_details.is_some());
+    }
+}
@@ -0,0 +1,193 @@
+use crate::modules::atomic_file;
+use crate::modules::job_query::JobQuery;
+use crate::modules::state::{DownloadJob, JobStatus};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::io;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+/// Which jobs are exported
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Default)]
+pub enum ExportSource {
+    /// History followed by the current queue
+    #[default]
+    All,
+    Queue,
+    History,
+}
+
+/// Jobs to export: a source plus the usual job query filters
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ExportFilter {
+    pub source: ExportSource,
+    #[serde(flatten)]
+    pub query: JobQuery,
+}
+
+/// One exported job, flattened for spreadsheets
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ExportRow {
+    pub url: String,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub status: JobStatus,
+    pub error: Option<String>,
+    /// Output files, separated by "; " when a job produced several
+    pub output_path: Option<String>,
+    pub created_at: String,
+    pub started_at: Option<String>,
+    pub completed_at: Option<String>,
+    /// "queue" or "history"
+    pub source: &'static str,
+}
+
+const CSV_HEADER: [&str; 11] = [
+    "url", "title", "artist", "album", "status", "error", "output_path", "created_at", "started_at", "completed_at", "source",
+];
+
+impl ExportRow {
+    pub fn new(job: &DownloadJob, from_history: bool) -> Self {
+        let metadata = job.metadata.as_ref();
+        let output_path = (!job.output_files.is_empty()).then(|| {
+            job.output_files.iter()
+                .map(|path| path.to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+                .join("; ")
+        });
+
+        Self {
+            url: job.url.clone(),
+            title: metadata.and_then(|metadata| metadata.title.clone()),
+            artist: metadata.and_then(|metadata| metadata.artist.clone()),
+            album: metadata.and_then(|metadata| metadata.album.clone()),
+            status: job.status.clone(),
+            error: job.error.clone(),
+            output_path,
+            created_at: job.created_at.to_rfc3339(),
+            started_at: job.started_at.map(|time| time.to_rfc3339()),
+            completed_at: job.completed_at.map(|time| time.to_rfc3339()),
+            source: if from_history { "history" } else { "queue" },
+        }
+    }
+
+    fn csv_fields(&self) -> [String; 11] {
+        let text = |value: &Option<String>| value.clone().unwrap_or_default();
+        [
+            self.url.clone(),
+            text(&self.title),
+            text(&self.artist),
+            text(&self.album),
+            format!("{:?}", self.status),
+            text(&self.error),
+            text(&self.output_path),
+            self.created_at.clone(),
+            text(&self.started_at),
+            text(&self.completed_at),
+            self.source.to_string(),
+        ]
+    }
+}
+
+/// Rows for the history and queue jobs that pass `filter`, in the order it asks for
+pub fn select_rows(history: Vec<DownloadJob>, queue: &[DownloadJob], filter: &ExportFilter) -> Vec<ExportRow> {
+    let mut jobs = match filter.source {
+        ExportSource::Queue => Vec::new(),
+        _ => history,
+    };
+    let history_ids: HashSet<String> = jobs.iter().map(|job| job.id.clone()).collect();
+    if filter.source != ExportSource::History {
+        jobs.extend(queue.iter().cloned());
+    }
+
+    filter.query.apply(&jobs).jobs.into_iter()
+        .map(|job| ExportRow::new(job, history_ids.contains(&job.id)))
+        .collect()
+}
+
+/// Quote a CSV field when it holds a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+pub fn to_csv(rows: &[ExportRow]) -> String {
+    let mut content = CSV_HEADER.join(",");
+    content.push_str("\r\n");
+    for row in rows {
+        let fields: Vec<String> = row.csv_fields().iter().map(|field| csv_field(field)).collect();
+        content.push_str(&fields.join(","));
+        content.push_str("\r\n");
+    }
+    content
+}
+
+/// Write `rows` to `path` in `format`
+pub fn write_export(path: &Path, format: ExportFormat, rows: &[ExportRow]) -> io::Result<()> {
+    let content = match format {
+        ExportFormat::Csv => to_csv(rows),
+        ExportFormat::Json => serde_json::to_string_pretty(rows)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+    };
+    atomic_file::write_atomic(path, content.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::state::JobMetadata;
+
+    fn job(url: &str, status: JobStatus) -> DownloadJob {
+        DownloadJob {
+            status,
+            metadata: Some(JobMetadata {
+                title: Some("Song, \"Live\"".to_string()),
+                ..JobMetadata::default()
+            }),
+            ..DownloadJob::new(url.to_string())
+        }
+    }
+
+    #[test]
+    fn test_select_rows_and_csv() {
+        let history = vec![job("https://music.youtube.com/watch?v=old", JobStatus::Completed)];
+        let queue = vec![
+            job("https://music.youtube.com/watch?v=new", JobStatus::Completed),
+            job("https://music.youtube.com/watch?v=failed", JobStatus::Failed),
+        ];
+
+        let filter = ExportFilter {
+            query: JobQuery {
+                statuses: vec![JobStatus::Completed],
+                ..JobQuery::default()
+            },
+            ..ExportFilter::default()
+        };
+        let rows = select_rows(history.clone(), &queue, &filter);
+        let sources: Vec<_> = rows.iter().map(|row| (row.url.as_str(), row.source)).collect();
+        assert_eq!(sources, vec![
+            ("https://music.youtube.com/watch?v=old", "history"),
+            ("https://music.youtube.com/watch?v=new", "queue"),
+        ]);
+
+        let queue_only = ExportFilter { source: ExportSource::Queue, ..ExportFilter::default() };
+        assert_eq!(select_rows(history, &queue, &queue_only).len(), 2);
+
+        let csv = to_csv(&rows[..1]);
+        let mut lines = csv.split("\r\n");
+        assert_eq!(lines.next(), Some(CSV_HEADER.join(",").as_str()));
+        let row = lines.next().unwrap();
+        assert!(row.starts_with("https://music.youtube.com/watch?v=old,\"Song, \"\"Live\"\"\",,,Completed,"));
+        assert!(row.ends_with(",history"));
+    }
+}
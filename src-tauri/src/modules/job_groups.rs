@@ -0,0 +1,130 @@
+use crate::modules::state::{DownloadJob, JobStatus, Progress};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Tracks queued together from one playlist, album or subscription check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobGroup {
+    pub id: String,
+    /// Playlist, album or channel the tracks were expanded from
+    pub source_url: String,
+    pub name: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl JobGroup {
+    pub fn new(source_url: String, name: Option<String>) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            source_url,
+            name,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// A group with the combined progress of its jobs
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupProgress {
+    #[serde(flatten)]
+    pub group: JobGroup,
+    pub total_jobs: usize,
+    pub queued: usize,
+    pub downloading: usize,
+    pub completed: usize,
+    pub failed: usize,
+    pub cancelled: usize,
+    /// Average progress of the jobs that weren't cancelled, finished jobs counting as 100
+    pub percentage: f32,
+    /// Nothing in the group is queued or downloading
+    pub finished: bool,
+}
+
+impl GroupProgress {
+    /// Combine the jobs of `group`, each paired with its current progress
+    pub fn summarize<'a>(group: &JobGroup, jobs: impl IntoIterator<Item = (&'a DownloadJob, Progress)>) -> Self {
+        let mut summary = Self {
+            group: group.clone(),
+            total_jobs: 0,
+            queued: 0,
+            downloading: 0,
+            completed: 0,
+            failed: 0,
+            cancelled: 0,
+            percentage: 0.0,
+            finished: true,
+        };
+
+        let mut percentage_sum = 0.0;
+        for (job, progress) in jobs {
+            summary.total_jobs += 1;
+            let percentage = match job.status {
+                JobStatus::Queued => {
+                    summary.queued += 1;
+                    0.0
+                }
+                JobStatus::Downloading => {
+                    summary.downloading += 1;
+                    progress.percentage.unwrap_or(0.0).clamp(0.0, 100.0)
+                }
+                JobStatus::Completed => {
+                    summary.completed += 1;
+                    100.0
+                }
+                JobStatus::Failed => {
+                    summary.failed += 1;
+                    100.0
+                }
+                JobStatus::Cancelled => {
+                    summary.cancelled += 1;
+                    continue;
+                }
+            };
+            percentage_sum += percentage;
+        }
+
+        let counted = summary.total_jobs - summary.cancelled;
+        if counted > 0 {
+            summary.percentage = percentage_sum / counted as f32;
+        }
+        summary.finished = summary.queued == 0 && summary.downloading == 0;
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(status: JobStatus) -> DownloadJob {
+        DownloadJob {
+            status,
+            ..DownloadJob::new("https://music.youtube.com/watch?v=abc".to_string())
+        }
+    }
+
+    #[test]
+    fn test_summarize_group() {
+        let group = JobGroup::new("https://music.youtube.com/playlist?list=abc".to_string(), None);
+        let jobs = [
+            job(JobStatus::Completed),
+            job(JobStatus::Downloading),
+            job(JobStatus::Queued),
+            job(JobStatus::Failed),
+            job(JobStatus::Cancelled),
+        ];
+        let half = Progress {
+            percentage: Some(50.0),
+            ..Progress::default()
+        };
+
+        let summary = GroupProgress::summarize(&group, jobs.iter().map(|job| (job, half.clone())));
+        assert_eq!(summary.total_jobs, 5);
+        assert_eq!((summary.completed, summary.downloading, summary.queued), (1, 1, 1));
+        assert_eq!((summary.failed, summary.cancelled), (1, 1));
+        // (100 + 50 + 0 + 100) / 4
+        assert_eq!(summary.percentage, 62.5);
+        assert!(!summary.finished);
+    }
+}
@@ -0,0 +1,216 @@
+use crate::modules::state::DownloadJob;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
+use std::ops::Deref;
+
+/// Jobs in queue order with an index from job id to position, so lookups by
+/// id don't scan the whole list. Serialized as a plain array, the same as the
+/// `Vec<DownloadJob>` it replaces.
+///
+/// Derefs to a slice for reading; every change goes through methods that keep
+/// the index in sync.
+#[derive(Debug, Clone, Default)]
+pub struct JobList {
+    jobs: Vec<DownloadJob>,
+    index: HashMap<String, usize>,
+}
+
+impl JobList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn reindex(&mut self) {
+        self.index.clear();
+        for (position, job) in self.jobs.iter().enumerate() {
+            // Lookups find the first job with an id, as a scan would
+            self.index.entry(job.id.clone()).or_insert(position);
+        }
+    }
+
+    pub fn position(&self, job_id: &str) -> Option<usize> {
+        self.index.get(job_id).copied()
+    }
+
+    pub fn get(&self, job_id: &str) -> Option<&DownloadJob> {
+        self.position(job_id).map(|position| &self.jobs[position])
+    }
+
+    /// The job with `job_id`. Its id must not be changed through the reference.
+    pub fn get_mut(&mut self, job_id: &str) -> Option<&mut DownloadJob> {
+        self.position(job_id).map(|position| &mut self.jobs[position])
+    }
+
+    /// All jobs in order. Ids must not be changed through the references.
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, DownloadJob> {
+        self.jobs.iter_mut()
+    }
+
+    pub fn push(&mut self, job: DownloadJob) {
+        self.index.entry(job.id.clone()).or_insert(self.jobs.len());
+        self.jobs.push(job);
+    }
+
+    /// Insert at `position`, shifting later jobs back
+    pub fn insert(&mut self, position: usize, job: DownloadJob) {
+        self.jobs.insert(position, job);
+        self.reindex();
+    }
+
+    pub fn remove(&mut self, position: usize) -> DownloadJob {
+        let job = self.jobs.remove(position);
+        self.reindex();
+        job
+    }
+
+    pub fn retain(&mut self, keep: impl FnMut(&DownloadJob) -> bool) {
+        let initial_len = self.jobs.len();
+        self.jobs.retain(keep);
+        if self.jobs.len() != initial_len {
+            self.reindex();
+        }
+    }
+
+    /// Remove and return the jobs matching `take`, keeping the order of the rest
+    pub fn extract(&mut self, mut take: impl FnMut(&DownloadJob) -> bool) -> Vec<DownloadJob> {
+        let (taken, kept): (Vec<_>, Vec<_>) = self.jobs.drain(..).partition(|job| take(job));
+        self.jobs = kept;
+        if !taken.is_empty() {
+            self.reindex();
+        }
+        taken
+    }
+
+    /// `extract`, also returning the position each taken job had, for `restore`
+    pub fn extract_positioned(&mut self, mut take: impl FnMut(&DownloadJob) -> bool) -> Vec<(usize, DownloadJob)> {
+        let mut taken = Vec::new();
+        let mut kept = Vec::with_capacity(self.jobs.len());
+        for (position, job) in self.jobs.drain(..).enumerate() {
+            if take(&job) {
+                taken.push((position, job));
+            } else {
+                kept.push(job);
+            }
+        }
+        self.jobs = kept;
+        if !taken.is_empty() {
+            self.reindex();
+        }
+        taken
+    }
+
+    /// Put back jobs from `extract_positioned` where they were, or at the end
+    /// when the list has since become shorter
+    pub fn restore(&mut self, mut jobs: Vec<(usize, DownloadJob)>) {
+        if jobs.is_empty() {
+            return;
+        }
+        jobs.sort_by_key(|(position, _)| *position);
+        for (position, job) in jobs {
+            let position = position.min(self.jobs.len());
+            self.jobs.insert(position, job);
+        }
+        self.reindex();
+    }
+}
+
+impl Deref for JobList {
+    type Target = [DownloadJob];
+
+    fn deref(&self) -> &[DownloadJob] {
+        &self.jobs
+    }
+}
+
+impl From<Vec<DownloadJob>> for JobList {
+    fn from(jobs: Vec<DownloadJob>) -> Self {
+        let mut list = Self { jobs, index: HashMap::new() };
+        list.reindex();
+        list
+    }
+}
+
+impl Extend<DownloadJob> for JobList {
+    fn extend<I: IntoIterator<Item = DownloadJob>>(&mut self, jobs: I) {
+        for job in jobs {
+            self.push(job);
+        }
+    }
+}
+
+impl Serialize for JobList {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.jobs.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for JobList {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Vec::<DownloadJob>::deserialize(deserializer).map(Self::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::state::AppState;
+
+    fn job(state: &mut AppState, url: &str) -> DownloadJob {
+        let job_id = state.add_job(url.to_string());
+        state.get_job(&job_id).unwrap().clone()
+    }
+
+    #[test]
+    fn test_index_follows_changes() {
+        let mut state = AppState::new();
+        let (a, b, c) = (job(&mut state, "a"), job(&mut state, "b"), job(&mut state, "c"));
+
+        let mut list = JobList::new();
+        list.extend([a.clone(), b.clone(), c.clone()]);
+        assert_eq!(list.position(&c.id), Some(2));
+
+        let moved = list.remove(0);
+        list.insert(2, moved);
+        assert_eq!(list.position(&b.id), Some(0));
+        assert_eq!(list.position(&a.id), Some(2));
+
+        list.retain(|job| job.id != b.id);
+        assert!(list.get(&b.id).is_none());
+        assert_eq!(list.get(&a.id).unwrap().url, "a");
+
+        let taken = list.extract(|job| job.id == c.id);
+        assert_eq!(taken.len(), 1);
+        assert_eq!(list.position(&a.id), Some(0));
+        assert_eq!(list.len(), 1);
+    }
+
+    #[test]
+    fn test_restore_puts_jobs_back_in_place() {
+        let mut state = AppState::new();
+        let jobs: Vec<_> = ["a", "b", "c", "d"].iter().map(|url| job(&mut state, url)).collect();
+        let mut list = JobList::from(jobs);
+
+        let taken = list.extract_positioned(|job| job.url == "a" || job.url == "c");
+        assert_eq!(taken.iter().map(|(position, _)| *position).collect::<Vec<_>>(), vec![0, 2]);
+        assert_eq!(list.len(), 2);
+
+        list.restore(taken);
+        let urls: Vec<&str> = list.iter().map(|job| job.url.as_str()).collect();
+        assert_eq!(urls, vec!["a", "b", "c", "d"]);
+        assert_eq!(list.position(&list[2].id), Some(2));
+    }
+
+    #[test]
+    fn test_serializes_as_array() {
+        let mut state = AppState::new();
+        let list = JobList::from(vec![job(&mut state, "a"), job(&mut state, "b")]);
+
+        let value = serde_json::to_value(&list).unwrap();
+        assert!(value.is_array());
+
+        let restored: JobList = serde_json::from_value(value).unwrap();
+        let urls: Vec<&str> = restored.iter().map(|job| job.url.as_str()).collect();
+        assert_eq!(urls, vec!["a", "b"]);
+        assert_eq!(restored.position(&list[1].id), Some(1));
+    }
+}
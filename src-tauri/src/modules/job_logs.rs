@@ -0,0 +1,134 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+/// Maximum number of output lines kept per job
+const DEFAULT_LINE_CAPACITY: usize = 1000;
+
+/// Maximum number of jobs whose output is kept; the oldest is dropped first
+const DEFAULT_JOB_CAPACITY: usize = 200;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum LogStream {
+    Stdout,
+    Stderr,
+}
+
+/// A raw line of gytmdl output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogLine {
+    pub timestamp: DateTime<Utc>,
+    pub stream: LogStream,
+    pub line: String,
+}
+
+/// Per-job ring buffers of raw process output
+pub struct JobLogs {
+    line_capacity: usize,
+    job_capacity: usize,
+    logs: HashMap<String, VecDeque<LogLine>>,
+    /// Job IDs in the order their logs were started
+    order: VecDeque<String>,
+}
+
+impl JobLogs {
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_LINE_CAPACITY, DEFAULT_JOB_CAPACITY)
+    }
+
+    pub fn with_capacity(line_capacity: usize, job_capacity: usize) -> Self {
+        Self {
+            line_capacity,
+            job_capacity,
+            logs: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    /// Start a fresh log for a job, discarding output from earlier attempts
+    pub fn start(&mut self, job_id: &str) {
+        self.remove(job_id);
+        while self.order.len() >= self.job_capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.logs.remove(&oldest);
+            }
+        }
+        self.order.push_back(job_id.to_string());
+        self.logs.insert(job_id.to_string(), VecDeque::new());
+    }
+
+    /// Append a line to a job's log, dropping the oldest line when full
+    pub fn append(&mut self, job_id: &str, stream: LogStream, line: &str) {
+        if !self.logs.contains_key(job_id) {
+            self.start(job_id);
+        }
+        let Some(ring) = self.logs.get_mut(job_id) else {
+            return;
+        };
+        if ring.len() >= self.line_capacity {
+            ring.pop_front();
+        }
+        ring.push_back(LogLine {
+            timestamp: Utc::now(),
+            stream,
+            line: line.to_string(),
+        });
+    }
+
+    /// Get a job's captured output, oldest line first
+    pub fn get(&self, job_id: &str) -> Vec<LogLine> {
+        self.logs.get(job_id)
+            .map(|ring| ring.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Forget a job's output
+    pub fn remove(&mut self, job_id: &str) {
+        if self.logs.remove(job_id).is_some() {
+            self.order.retain(|id| id != job_id);
+        }
+    }
+}
+
+impl Default for JobLogs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_capacity() {
+        let mut logs = JobLogs::with_capacity(3, 10);
+        for i in 0..5 {
+            logs.append("job", LogStream::Stdout, &format!("line {}", i));
+        }
+        logs.append("job", LogStream::Stderr, "ERROR: boom");
+
+        let lines = logs.get("job");
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].line, "line 3");
+        assert_eq!(lines[2].stream, LogStream::Stderr);
+        assert!(logs.get("other").is_empty());
+    }
+
+    #[test]
+    fn test_job_capacity_and_restart() {
+        let mut logs = JobLogs::with_capacity(10, 2);
+        logs.append("a", LogStream::Stdout, "a1");
+        logs.append("b", LogStream::Stdout, "b1");
+        logs.append("c", LogStream::Stdout, "c1");
+
+        // Oldest job is evicted
+        assert!(logs.get("a").is_empty());
+        assert_eq!(logs.get("c").len(), 1);
+
+        // Restarting a job clears its previous attempt
+        logs.start("b");
+        assert!(logs.get("b").is_empty());
+        assert_eq!(logs.get("c").len(), 1);
+    }
+}
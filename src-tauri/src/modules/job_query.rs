@@ -0,0 +1,188 @@
+use crate::modules::state::{DownloadJob, JobStatus};
+use serde::Deserialize;
+use std::cmp::Ordering;
+
+/// Order of the returned jobs
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Default)]
+pub enum JobSortField {
+    /// Queue order, as shown when nothing else is picked
+    #[default]
+    Queue,
+    Created,
+    /// Unfinished jobs come last in either direction
+    Completed,
+    /// Metadata title, or the URL while metadata isn't known
+    Title,
+}
+
+/// Filter, sort and page over the job list. Every field is optional, so an
+/// empty query returns all jobs in queue order.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct JobQuery {
+    /// Only jobs with one of these statuses; all statuses when empty
+    pub statuses: Vec<JobStatus>,
+    /// Case-insensitive text matched against the URL, title, artist and album
+    pub search: Option<String>,
+    pub sort_by: JobSortField,
+    pub descending: bool,
+    pub offset: usize,
+    pub limit: Option<usize>,
+}
+
+/// One page of jobs matching a query
+#[derive(Debug)]
+pub struct JobPage<'a> {
+    pub jobs: Vec<&'a DownloadJob>,
+    /// Jobs matching the filters, before paging
+    pub total: usize,
+}
+
+impl JobQuery {
+    /// Whether a job passes the status and search filters
+    pub fn matches(&self, job: &DownloadJob) -> bool {
+        if !self.statuses.is_empty() && !self.statuses.contains(&job.status) {
+            return false;
+        }
+
+        let Some(search) = self.search.as_deref().map(str::trim).filter(|search| !search.is_empty()) else {
+            return true;
+        };
+        let search = search.to_lowercase();
+        let metadata = job.metadata.as_ref();
+        std::iter::once(Some(job.url.as_str()))
+            .chain([
+                metadata.and_then(|metadata| metadata.title.as_deref()),
+                metadata.and_then(|metadata| metadata.artist.as_deref()),
+                metadata.and_then(|metadata| metadata.album.as_deref()),
+            ])
+            .flatten()
+            .any(|text| text.to_lowercase().contains(&search))
+    }
+
+    /// Filter, sort and page `jobs`
+    pub fn apply<'a>(&self, jobs: &'a [DownloadJob]) -> JobPage<'a> {
+        let mut matching: Vec<&DownloadJob> = jobs.iter().filter(|job| self.matches(job)).collect();
+        let total = matching.len();
+
+        // Stable, so ties stay in queue order
+        match self.sort_by {
+            JobSortField::Queue => {
+                if self.descending {
+                    matching.reverse();
+                }
+            }
+            JobSortField::Created => matching.sort_by(|a, b| self.directed(a.created_at.cmp(&b.created_at))),
+            JobSortField::Completed => matching.sort_by(|a, b| match (a.completed_at, b.completed_at) {
+                (Some(a), Some(b)) => self.directed(a.cmp(&b)),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            }),
+            JobSortField::Title => matching.sort_by(|a, b| self.directed(Self::title_key(a).cmp(&Self::title_key(b)))),
+        }
+
+        let jobs = matching.into_iter()
+            .skip(self.offset)
+            .take(self.limit.unwrap_or(usize::MAX))
+            .collect();
+        JobPage { jobs, total }
+    }
+
+    fn directed(&self, ordering: Ordering) -> Ordering {
+        if self.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+
+    fn title_key(job: &DownloadJob) -> String {
+        job.metadata.as_ref()
+            .and_then(|metadata| metadata.title.as_deref())
+            .unwrap_or(&job.url)
+            .to_lowercase()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::state::{AppState, JobMetadata};
+
+    fn state_with_jobs() -> AppState {
+        let mut state = AppState::new();
+        for (url, title, status) in [
+            ("https://music.youtube.com/watch?v=a", "Banana", JobStatus::Completed),
+            ("https://music.youtube.com/watch?v=b", "apple", JobStatus::Queued),
+            ("https://music.youtube.com/watch?v=c", "Cherry", JobStatus::Completed),
+        ] {
+            let job_id = state.add_job(url.to_string());
+            state.update_job_metadata(&job_id, JobMetadata {
+                title: Some(title.to_string()),
+                ..JobMetadata::default()
+            });
+            state.update_job_status(&job_id, status);
+        }
+        state
+    }
+
+    fn titles(page: &JobPage) -> Vec<String> {
+        page.jobs.iter()
+            .map(|job| job.metadata.as_ref().unwrap().title.clone().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_filter_and_search() {
+        let state = state_with_jobs();
+        let query = JobQuery {
+            statuses: vec![JobStatus::Completed],
+            ..JobQuery::default()
+        };
+        let page = query.apply(&state.jobs);
+        assert_eq!(page.total, 2);
+        assert_eq!(titles(&page), vec!["Banana", "Cherry"]);
+
+        let query = JobQuery {
+            search: Some("APPLE".to_string()),
+            ..JobQuery::default()
+        };
+        assert_eq!(titles(&query.apply(&state.jobs)), vec!["apple"]);
+
+        let query = JobQuery {
+            search: Some("watch?v=c".to_string()),
+            ..JobQuery::default()
+        };
+        assert_eq!(titles(&query.apply(&state.jobs)), vec!["Cherry"]);
+    }
+
+    #[test]
+    fn test_sort_and_page() {
+        let state = state_with_jobs();
+        let query = JobQuery {
+            sort_by: JobSortField::Title,
+            ..JobQuery::default()
+        };
+        assert_eq!(titles(&query.apply(&state.jobs)), vec!["apple", "Banana", "Cherry"]);
+
+        let query = JobQuery {
+            sort_by: JobSortField::Title,
+            descending: true,
+            offset: 1,
+            limit: Some(1),
+            ..JobQuery::default()
+        };
+        let page = query.apply(&state.jobs);
+        assert_eq!(page.total, 3);
+        assert_eq!(titles(&page), vec!["Banana"]);
+
+        // The queued job hasn't finished, so it sorts last either way
+        let query = JobQuery {
+            sort_by: JobSortField::Completed,
+            descending: true,
+            ..JobQuery::default()
+        };
+        assert_eq!(titles(&query.apply(&state.jobs)).last().unwrap(), "apple");
+    }
+}
@@ -0,0 +1,214 @@
+use crate::modules::state::JobMetadata;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+const UNKNOWN_ARTIST: &str = "Unknown Artist";
+const UNKNOWN_ALBUM: &str = "Unknown Album";
+const UNKNOWN_TITLE: &str = "Unknown Title";
+
+/// Whether organizing leaves the original download in the output folder
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub enum OrganizeMode {
+    #[default]
+    Move,
+    Copy,
+}
+
+/// Where each of a job's files ended up
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrganizedFile {
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+/// Check that a library template only uses known placeholders and stays inside the library root
+pub fn validate_template(template: &str) -> Result<(), String> {
+    if template.trim().is_empty() {
+        return Err("Library template cannot be empty".to_string());
+    }
+
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..].find('}')
+            .ok_or_else(|| format!("Unclosed placeholder in library template: '{}'", template))?;
+        let name = &rest[start + 1..start + end];
+        if !matches!(name, "artist" | "album" | "title") {
+            return Err(format!("Unknown library template placeholder: '{{{}}}'", name));
+        }
+        rest = &rest[start + end + 1..];
+    }
+
+    let escapes_root = Path::new(template).components()
+        .any(|c| !matches!(c, Component::Normal(_)));
+    if escapes_root {
+        return Err(format!("Library template must be a relative path inside the library: '{}'", template));
+    }
+
+    Ok(())
+}
+
+/// Folder inside the library for a job, e.g. `Library/Artist/Album`
+pub fn target_directory(root: &Path, template: &str, metadata: Option<&JobMetadata>) -> PathBuf {
+    let field = |value: Option<&String>, fallback: &str| {
+        value.map(|v| sanitize_component(v))
+            .filter(|v| !v.is_empty())
+            .unwrap_or_else(|| fallback.to_string())
+    };
+    let artist = field(metadata.and_then(|m| m.artist.as_ref()), UNKNOWN_ARTIST);
+    let album = field(metadata.and_then(|m| m.album.as_ref()), UNKNOWN_ALBUM);
+    let title = field(metadata.and_then(|m| m.title.as_ref()), UNKNOWN_TITLE);
+
+    // Substitute per path segment so a '/' in a tag can't add directory levels
+    template.split(['/', '\\'])
+        .filter(|segment| !segment.is_empty())
+        .fold(root.to_path_buf(), |dir, segment| {
+            let segment = segment
+                .replace("{artist}", &artist)
+                .replace("{album}", &album)
+                .replace("{title}", &title);
+            dir.join(segment)
+        })
+}
+
+/// Make a tag value safe to use as a single file or folder name
+pub fn sanitize_component(value: &str) -> String {
+    let replaced: String = value.chars()
+        .map(|c| match c {
+            '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    // Windows rejects names ending in a dot or space
+    replaced.trim().trim_end_matches('.').trim_end().to_string()
+}
+
+/// First path in `dir` named like `file_name` that doesn't exist yet,
+/// adding " (2)", " (3)", ... before the extension on collision
+pub fn unique_destination(dir: &Path, file_name: &std::ffi::OsStr) -> PathBuf {
+    let candidate = dir.join(file_name);
+    if !candidate.exists() {
+        return candidate;
+    }
+
+    let original = Path::new(file_name);
+    let stem = original.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    let extension = original.extension().map(|e| e.to_string_lossy().into_owned());
+
+    (2..)
+        .map(|n| {
+            let name = match &extension {
+                Some(ext) => format!("{} ({}).{}", stem, n, ext),
+                None => format!("{} ({})", stem, n),
+            };
+            dir.join(name)
+        })
+        .find(|path| !path.exists())
+        .expect("unbounded range always yields a free name")
+}
+
+/// Move or copy files into `target_dir`, never overwriting anything already there
+pub fn organize_files(files: &[PathBuf], target_dir: &Path, mode: OrganizeMode) -> Result<Vec<OrganizedFile>, io::Error> {
+    fs::create_dir_all(target_dir)?;
+
+    let mut organized = Vec::with_capacity(files.len());
+    for from in files {
+        let file_name = from.file_name().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, format!("Not a file: {:?}", from))
+        })?;
+
+        // Already in place, e.g. organizing the same job twice
+        if from.parent() == Some(target_dir) {
+            organized.push(OrganizedFile { from: from.clone(), to: from.clone() });
+            continue;
+        }
+
+        let to = unique_destination(target_dir, file_name);
+        match mode {
+            OrganizeMode::Move => move_file(from, &to)?,
+            OrganizeMode::Copy => {
+                fs::copy(from, &to)?;
+            }
+        }
+        organized.push(OrganizedFile { from: from.clone(), to });
+    }
+
+    Ok(organized)
+}
+
+/// Rename, falling back to copy and delete when the library is on another volume
+fn move_file(from: &Path, to: &Path) -> Result<(), io::Error> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    fs::copy(from, to)?;
+    fs::remove_file(from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn metadata(artist: &str, album: &str) -> JobMetadata {
+        JobMetadata {
+            title: Some("Song".to_string()),
+            artist: Some(artist.to_string()),
+            album: Some(album.to_string()),
+            duration: None,
+            thumbnail: None,
+            has_synced_lyrics: None,
+            track_count: None,
+        }
+    }
+
+    #[test]
+    fn test_target_directory() {
+        let root = Path::new("/music");
+        let meta = metadata("AC/DC", "Back in Black");
+        assert_eq!(
+            target_directory(root, "{artist}/{album}", Some(&meta)),
+            Path::new("/music/AC_DC/Back in Black")
+        );
+        assert_eq!(
+            target_directory(root, "{artist}/{album}", None),
+            Path::new("/music/Unknown Artist/Unknown Album")
+        );
+        assert_eq!(sanitize_component("What?. "), "What_");
+    }
+
+    #[test]
+    fn test_validate_template() {
+        assert!(validate_template("{artist}/{album}").is_ok());
+        assert!(validate_template("Singles/{artist} - {title}").is_ok());
+        assert!(validate_template("{genre}").is_err());
+        assert!(validate_template("{artist").is_err());
+        assert!(validate_template("../{artist}").is_err());
+        assert!(validate_template("/{artist}").is_err());
+    }
+
+    #[test]
+    fn test_organize_handles_collisions() {
+        let temp = TempDir::new().unwrap();
+        let source = temp.path().join("downloads");
+        let target = temp.path().join("Library").join("Artist").join("Album");
+        fs::create_dir_all(&source).unwrap();
+        fs::create_dir_all(&target).unwrap();
+        fs::write(target.join("01 Song.m4a"), b"existing").unwrap();
+
+        let file = source.join("01 Song.m4a");
+        fs::write(&file, b"new").unwrap();
+        let moved = organize_files(std::slice::from_ref(&file), &target, OrganizeMode::Move).unwrap();
+        assert_eq!(moved[0].to, target.join("01 Song (2).m4a"));
+        assert!(!file.exists());
+        assert_eq!(fs::read(target.join("01 Song.m4a")).unwrap(), b"existing");
+
+        let other = source.join("02 Other.m4a");
+        fs::write(&other, b"copy").unwrap();
+        let copied = organize_files(std::slice::from_ref(&other), &target, OrganizeMode::Copy).unwrap();
+        assert!(other.exists());
+        assert!(copied[0].to.exists());
+    }
+}
@@ -0,0 +1,460 @@
+use crate::modules::disk_space;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// Maximum number of files verified at once
+const VERIFY_WORKERS: usize = 4;
+
+/// Allowed drift between the recorded and re-probed duration
+const DURATION_TOLERANCE_SECS: f64 = 1.0;
+
+/// What is known about a file in the library
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibraryEntry {
+    /// URL the file was downloaded from, when a job produced exactly this file
+    pub source_url: Option<String>,
+    pub size: Option<u64>,
+    pub sha256: Option<String>,
+    pub duration_secs: Option<f64>,
+    pub verified_at: Option<DateTime<Utc>>,
+}
+
+/// Result of checking one file
+#[derive(Debug, Clone, PartialEq)]
+pub enum FileCheck {
+    Ok { size: u64, sha256: String, duration_secs: Option<f64> },
+    /// Readable, but the content differs from the last verification (e.g. retagged)
+    Changed { size: u64, sha256: String, duration_secs: Option<f64> },
+    Missing,
+    Corrupt(String),
+}
+
+/// A missing or corrupt file found during verification
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibraryIssue {
+    pub path: PathBuf,
+    pub reason: String,
+    /// URL to re-download from, if known
+    pub source_url: Option<String>,
+}
+
+/// Summary of a library verification pass
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    pub checked: usize,
+    pub ok: usize,
+    pub changed: usize,
+    pub missing: Vec<LibraryIssue>,
+    pub corrupt: Vec<LibraryIssue>,
+    /// Jobs queued to replace missing or corrupt files
+    #[serde(default)]
+    pub queued_job_ids: Vec<String>,
+}
+
+impl IntegrityReport {
+    /// Source URLs of every missing or corrupt file that can be re-downloaded
+    pub fn redownload_urls(&self) -> Vec<String> {
+        let mut urls: Vec<String> = self.missing.iter()
+            .chain(self.corrupt.iter())
+            .filter_map(|issue| issue.source_url.clone())
+            .collect();
+        urls.sort();
+        urls.dedup();
+        urls
+    }
+}
+
+/// Persistent record of downloaded files, their checksums and durations
+pub struct LibraryManifest {
+    manifest_file: PathBuf,
+    entries: HashMap<PathBuf, LibraryEntry>,
+}
+
+impl LibraryManifest {
+    /// Load the manifest from the app data directory
+    pub fn new() -> Self {
+        let manifest_file = std::env::current_dir()
+            .unwrap_or_else(|_| PathBuf::from("."))
+            .join(".gytmdl-gui")
+            .join("library.json");
+
+        Self::with_manifest_file(manifest_file)
+    }
+
+    /// Load the manifest from a specific file. A missing or unreadable file starts empty.
+    pub fn with_manifest_file(manifest_file: PathBuf) -> Self {
+        let entries = fs::read_to_string(&manifest_file)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+
+        Self { manifest_file, entries }
+    }
+
+    pub fn save(&self) -> Result<(), io::Error> {
+        if let Some(parent) = self.manifest_file.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_string_pretty(&self.entries)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        fs::write(&self.manifest_file, content)
+    }
+
+    /// Record the files a download produced and where they came from
+    pub fn record_download(&mut self, paths: &[PathBuf], source_url: &str) -> Result<(), io::Error> {
+        for path in paths {
+            let entry = self.entries.entry(path.clone()).or_insert(LibraryEntry {
+                source_url: None,
+                size: None,
+                sha256: None,
+                duration_secs: None,
+                verified_at: None,
+            });
+            entry.source_url = Some(source_url.to_string());
+        }
+        self.save()
+    }
+
+    /// Follow a file that was moved, e.g. when organizing it into the music library
+    pub fn record_move(&mut self, from: &Path, to: PathBuf) -> Result<(), io::Error> {
+        match self.entries.remove(from) {
+            Some(entry) => {
+                self.entries.insert(to, entry);
+                self.save()
+            }
+            None => Ok(()),
+        }
+    }
+
+    pub fn entries(&self) -> &HashMap<PathBuf, LibraryEntry> {
+        &self.entries
+    }
+
+    /// Fold verification results back into the manifest and build the report.
+    /// Missing and corrupt files keep their last known good values.
+    pub fn apply_results(&mut self, results: Vec<(PathBuf, FileCheck)>) -> IntegrityReport {
+        let mut report = IntegrityReport { checked: results.len(), ..Default::default() };
+        let now = Utc::now();
+
+        for (path, check) in results {
+            let source_url = self.entries.get(&path).and_then(|entry| entry.source_url.clone());
+            let (size, sha256, duration_secs) = match check {
+                FileCheck::Ok { size, sha256, duration_secs } => {
+                    report.ok += 1;
+                    (size, sha256, duration_secs)
+                }
+                FileCheck::Changed { size, sha256, duration_secs } => {
+                    report.changed += 1;
+                    (size, sha256, duration_secs)
+                }
+                FileCheck::Missing => {
+                    report.missing.push(LibraryIssue {
+                        path,
+                        reason: "File not found".to_string(),
+                        source_url,
+                    });
+                    continue;
+                }
+                FileCheck::Corrupt(reason) => {
+                    report.corrupt.push(LibraryIssue { path, reason, source_url });
+                    continue;
+                }
+            };
+
+            self.entries.insert(path, LibraryEntry {
+                source_url,
+                size: Some(size),
+                sha256: Some(sha256),
+                duration_secs,
+                verified_at: Some(now),
+            });
+        }
+
+        report
+    }
+}
+
+impl Default for LibraryManifest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Hash a file, returning its size and hex SHA-256
+fn hash_file(path: &Path) -> Result<(u64, String), io::Error> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    let mut size = 0u64;
+
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        size += read as u64;
+    }
+
+    Ok((size, format!("{:x}", hasher.finalize())))
+}
+
+/// Read a box header at the current position: (type, payload start, box end)
+fn read_box_header<R: Read + Seek>(reader: &mut R, limit: u64) -> Result<Option<([u8; 4], u64, u64)>, io::Error> {
+    let start = reader.stream_position()?;
+    if start + 8 > limit {
+        return Ok(None);
+    }
+
+    let mut header = [0u8; 8];
+    reader.read_exact(&mut header)?;
+    let size = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as u64;
+    let box_type = [header[4], header[5], header[6], header[7]];
+
+    let (payload_start, end) = match size {
+        0 => (start + 8, limit),
+        1 => {
+            let mut large = [0u8; 8];
+            reader.read_exact(&mut large)?;
+            (start + 16, start + u64::from_be_bytes(large))
+        }
+        size => (start + 8, start + size),
+    };
+
+    if end < payload_start || end > limit {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "Malformed MP4 box"));
+    }
+    Ok(Some((box_type, payload_start, end)))
+}
+
+/// Find a child box within `[start, end)`, returning its payload range
+fn find_box<R: Read + Seek>(reader: &mut R, start: u64, end: u64, name: &[u8; 4]) -> Result<Option<(u64, u64)>, io::Error> {
+    reader.seek(SeekFrom::Start(start))?;
+    while let Some((box_type, payload_start, box_end)) = read_box_header(reader, end)? {
+        if &box_type == name {
+            return Ok(Some((payload_start, box_end)));
+        }
+        reader.seek(SeekFrom::Start(box_end))?;
+    }
+    Ok(None)
+}
+
+/// Read the duration of an MP4/M4A file from its `moov/mvhd` box
+pub fn mp4_duration(path: &Path) -> Result<f64, io::Error> {
+    let mut file = File::open(path)?;
+    let file_len = file.metadata()?.len();
+
+    let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+    let (moov_start, moov_end) = find_box(&mut file, 0, file_len, b"moov")?
+        .ok_or_else(|| invalid("No moov box"))?;
+    let (mvhd_start, _) = find_box(&mut file, moov_start, moov_end, b"mvhd")?
+        .ok_or_else(|| invalid("No mvhd box"))?;
+
+    file.seek(SeekFrom::Start(mvhd_start))?;
+    let mut version = [0u8; 4];
+    file.read_exact(&mut version)?;
+
+    let (timescale, duration) = if version[0] == 1 {
+        let mut fields = [0u8; 28];
+        file.read_exact(&mut fields)?;
+        let timescale = u32::from_be_bytes(fields[16..20].try_into().unwrap());
+        let duration = u64::from_be_bytes(fields[20..28].try_into().unwrap());
+        (timescale, duration)
+    } else {
+        let mut fields = [0u8; 16];
+        file.read_exact(&mut fields)?;
+        let timescale = u32::from_be_bytes(fields[8..12].try_into().unwrap());
+        let duration = u32::from_be_bytes(fields[12..16].try_into().unwrap()) as u64;
+        (timescale, duration)
+    };
+
+    if timescale == 0 {
+        return Err(invalid("Zero timescale"));
+    }
+    Ok(duration as f64 / timescale as f64)
+}
+
+/// Probe a file's duration where the container is understood
+fn probe_duration(path: &Path) -> Result<Option<f64>, String> {
+    let extension = path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_lowercase);
+
+    match extension.as_deref() {
+        Some("m4a") | Some("mp4") => mp4_duration(path)
+            .map(Some)
+            .map_err(|e| format!("Could not read duration: {}", e)),
+        _ => Ok(None),
+    }
+}
+
+/// Check a single file against its last known state
+pub fn verify_file(path: &Path, expected: Option<&LibraryEntry>) -> FileCheck {
+    if !path.exists() {
+        return FileCheck::Missing;
+    }
+
+    let (size, sha256) = match hash_file(path) {
+        Ok(hashed) => hashed,
+        Err(e) => return FileCheck::Corrupt(format!("Unreadable: {}", e)),
+    };
+    if size == 0 {
+        return FileCheck::Corrupt("File is empty".to_string());
+    }
+
+    let duration_secs = match probe_duration(path) {
+        Ok(duration) => duration,
+        Err(reason) => return FileCheck::Corrupt(reason),
+    };
+
+    let expected_duration = expected.and_then(|entry| entry.duration_secs);
+    if let (Some(expected), Some(actual)) = (expected_duration, duration_secs) {
+        if (expected - actual).abs() > DURATION_TOLERANCE_SECS {
+            return FileCheck::Corrupt(format!("Duration changed from {:.0}s to {:.0}s", expected, actual));
+        }
+    }
+
+    match expected.and_then(|entry| entry.sha256.as_deref()) {
+        Some(previous) if previous != sha256 => FileCheck::Changed { size, sha256, duration_secs },
+        _ => FileCheck::Ok { size, sha256, duration_secs },
+    }
+}
+
+/// Verify every file in `known` plus any media file under `output_root`,
+/// using a bounded pool of blocking workers
+pub async fn verify_library(
+    output_root: PathBuf,
+    known: HashMap<PathBuf, LibraryEntry>,
+) -> Vec<(PathBuf, FileCheck)> {
+    let on_disk = tokio::task::spawn_blocking(move || {
+        disk_space::media_files_since(&output_root, SystemTime::UNIX_EPOCH)
+    }).await.unwrap_or_default();
+
+    // Media files on disk that the manifest has no entry for
+    let mut targets: Vec<(PathBuf, Option<LibraryEntry>)> = on_disk.into_iter()
+        .filter(|(path, _)| !known.contains_key(path))
+        .map(|(path, _)| (path, None))
+        .collect();
+    targets.extend(known.into_iter().map(|(path, entry)| (path, Some(entry))));
+
+    let semaphore = Arc::new(Semaphore::new(VERIFY_WORKERS));
+    let mut workers = JoinSet::new();
+    for (path, expected) in targets {
+        let semaphore = Arc::clone(&semaphore);
+        workers.spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            let check_path = path.clone();
+            let check = tokio::task::spawn_blocking(move || verify_file(&check_path, expected.as_ref()))
+                .await
+                .unwrap_or_else(|e| FileCheck::Corrupt(format!("Verification failed: {}", e)));
+            (path, check)
+        });
+    }
+
+    let mut results = Vec::new();
+    while let Some(result) = workers.join_next().await {
+        if let Ok(result) = result {
+            results.push(result);
+        }
+    }
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    /// Build a minimal M4A with an `mvhd` box of the given timescale and duration
+    fn minimal_m4a(timescale: u32, duration: u32) -> Vec<u8> {
+        let mut mvhd = Vec::new();
+        mvhd.extend_from_slice(&[0, 0, 0, 0]); // version + flags
+        mvhd.extend_from_slice(&[0; 8]); // creation + modification time
+        mvhd.extend_from_slice(&timescale.to_be_bytes());
+        mvhd.extend_from_slice(&duration.to_be_bytes());
+
+        let mut mvhd_box = ((mvhd.len() + 8) as u32).to_be_bytes().to_vec();
+        mvhd_box.extend_from_slice(b"mvhd");
+        mvhd_box.extend_from_slice(&mvhd);
+
+        let mut file = Vec::new();
+        file.extend_from_slice(&16u32.to_be_bytes());
+        file.extend_from_slice(b"ftypM4A \0\0\0\0");
+        file.extend_from_slice(&((mvhd_box.len() + 8) as u32).to_be_bytes());
+        file.extend_from_slice(b"moov");
+        file.extend_from_slice(&mvhd_box);
+        file
+    }
+
+    #[test]
+    fn test_mp4_duration() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("track.m4a");
+        fs::write(&path, minimal_m4a(44100, 44100 * 180)).unwrap();
+        assert_eq!(mp4_duration(&path).unwrap(), 180.0);
+
+        fs::write(&path, b"not an mp4 file").unwrap();
+        assert!(mp4_duration(&path).is_err());
+    }
+
+    #[test]
+    fn test_verify_file() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("track.m4a");
+        assert_eq!(verify_file(&path, None), FileCheck::Missing);
+
+        fs::write(&path, minimal_m4a(1000, 200_000)).unwrap();
+        let FileCheck::Ok { sha256, duration_secs, .. } = verify_file(&path, None) else {
+            panic!("expected a healthy file");
+        };
+        assert_eq!(duration_secs, Some(200.0));
+
+        // A truncated download loses its moov box
+        let recorded = LibraryEntry {
+            source_url: None,
+            size: None,
+            sha256: Some(sha256),
+            duration_secs,
+            verified_at: None,
+        };
+        fs::write(&path, &minimal_m4a(1000, 200_000)[..20]).unwrap();
+        assert!(matches!(verify_file(&path, Some(&recorded)), FileCheck::Corrupt(_)));
+
+        // Same duration, different bytes: changed, not corrupt
+        let mut retagged = minimal_m4a(1000, 200_000);
+        retagged.extend_from_slice(b"\0\0\0\x10freetagdata!");
+        fs::write(&path, retagged).unwrap();
+        assert!(matches!(verify_file(&path, Some(&recorded)), FileCheck::Changed { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_verify_library_report() {
+        let temp_dir = tempdir().unwrap();
+        let output_root = temp_dir.path().join("library");
+        fs::create_dir_all(&output_root).unwrap();
+        fs::write(output_root.join("good.m4a"), minimal_m4a(1000, 5000)).unwrap();
+        fs::write(output_root.join("empty.m4a"), b"").unwrap();
+
+        let mut manifest = LibraryManifest::with_manifest_file(temp_dir.path().join("library.json"));
+        manifest.record_download(&[output_root.join("moved.m4a")], "https://music.youtube.com/watch?v=moved").unwrap();
+
+        let results = verify_library(output_root.clone(), manifest.entries().clone()).await;
+        let report = manifest.apply_results(results);
+
+        assert_eq!(report.checked, 3);
+        assert_eq!(report.ok, 1);
+        assert_eq!(report.missing.len(), 1);
+        assert_eq!(report.corrupt.len(), 1);
+        assert_eq!(report.redownload_urls(), vec!["https://music.youtube.com/watch?v=moved".to_string()]);
+        assert!(manifest.entries()[&output_root.join("good.m4a")].sha256.is_some());
+    }
+}
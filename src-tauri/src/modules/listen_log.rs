@@ -0,0 +1,349 @@
+use crate::modules::state::{AppConfig, DownloadJob};
+use chrono::{DateTime, Utc};
+use md5::{Digest, Md5};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+
+const SUBMIT_TIMEOUT: Duration = Duration::from_secs(15);
+const CLIENT_NAME: &str = "gytmdl-gui";
+
+/// Service finished downloads are logged to. Self-hosted servers speaking
+/// the same API work through `listen_log_endpoint`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ListenLogService {
+    /// ListenBrainz `submit-listens`, authorized with a user token
+    ListenBrainz,
+    /// Last.fm `track.scrobble`, signed with the API secret and a session key
+    LastFm,
+}
+
+impl ListenLogService {
+    pub fn default_endpoint(&self) -> &'static str {
+        match self {
+            ListenLogService::ListenBrainz => "https://api.listenbrainz.org/1/submit-listens",
+            ListenLogService::LastFm => "https://ws.audioscrobbler.com/2.0/",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ListenLogError {
+    NotConfigured(String),
+    RequestFailed(String),
+    /// The service answered but refused the listen
+    Rejected(String),
+    StorageError(io::Error),
+}
+
+impl std::fmt::Display for ListenLogError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ListenLogError::NotConfigured(msg) => write!(f, "Listen logging is not set up: {}", msg),
+            ListenLogError::RequestFailed(msg) => write!(f, "Listen submission failed: {}", msg),
+            ListenLogError::Rejected(msg) => write!(f, "Listen was rejected: {}", msg),
+            ListenLogError::StorageError(e) => write!(f, "Failed to store listen log credentials: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ListenLogError {}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SubmissionStatus {
+    Submitted,
+    Failed,
+    /// Nothing was sent, e.g. the job has no artist or title
+    Skipped,
+}
+
+/// Outcome of logging one finished job
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ListenSubmission {
+    pub service: ListenLogService,
+    pub status: SubmissionStatus,
+    pub attempted_at: DateTime<Utc>,
+    pub error: Option<String>,
+}
+
+/// Secrets for the listen log. They are kept out of `config.json`, so they
+/// never end up in config exports or the config changelog.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ListenLogCredentials {
+    /// ListenBrainz user token, or Last.fm session key
+    #[serde(default)]
+    pub token: Option<String>,
+    /// Last.fm API secret used to sign requests
+    #[serde(default)]
+    pub api_secret: Option<String>,
+}
+
+/// Credentials file in the app data directory, readable only by the user
+pub struct CredentialStore {
+    credentials_file: PathBuf,
+}
+
+impl CredentialStore {
+    pub fn new() -> Self {
+        let credentials_file = std::env::current_dir()
+            .unwrap_or_else(|_| PathBuf::from("."))
+            .join(".gytmdl-gui")
+            .join("listen_log_credentials.json");
+
+        Self { credentials_file }
+    }
+
+    pub fn with_credentials_file(credentials_file: PathBuf) -> Self {
+        Self { credentials_file }
+    }
+
+    /// Stored credentials; empty when none were saved
+    pub fn load(&self) -> ListenLogCredentials {
+        fs::read_to_string(&self.credentials_file)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, credentials: &ListenLogCredentials) -> Result<(), ListenLogError> {
+        if let Some(parent) = self.credentials_file.parent() {
+            fs::create_dir_all(parent).map_err(ListenLogError::StorageError)?;
+        }
+        let content = serde_json::to_string_pretty(credentials)
+            .map_err(|e| ListenLogError::StorageError(io::Error::new(io::ErrorKind::InvalidData, e)))?;
+        fs::write(&self.credentials_file, content).map_err(ListenLogError::StorageError)?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&self.credentials_file, fs::Permissions::from_mode(0o600))
+                .map_err(ListenLogError::StorageError)?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for CredentialStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// What the frontend can know about the listen log setup; never the secrets
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ListenLogStatus {
+    pub service: Option<ListenLogService>,
+    pub has_token: bool,
+    pub has_api_secret: bool,
+}
+
+impl ListenLogStatus {
+    pub fn new(config: &AppConfig, credentials: &ListenLogCredentials) -> Self {
+        Self {
+            service: config.listen_log_service,
+            has_token: credentials.token.is_some(),
+            has_api_secret: credentials.api_secret.is_some(),
+        }
+    }
+}
+
+/// A finished download as a listen
+#[derive(Debug, Clone, PartialEq)]
+pub struct Listen {
+    pub artist: String,
+    pub title: String,
+    pub album: Option<String>,
+    pub duration: Option<u32>,
+    pub origin_url: String,
+    pub listened_at: DateTime<Utc>,
+}
+
+impl Listen {
+    /// A listen for a job, if its metadata has an artist and title
+    pub fn for_job(job: &DownloadJob, listened_at: DateTime<Utc>) -> Option<Self> {
+        let metadata = job.metadata.as_ref()?;
+        Some(Self {
+            artist: metadata.artist.clone()?,
+            title: metadata.title.clone()?,
+            album: metadata.album.clone(),
+            duration: metadata.duration,
+            origin_url: job.url.clone(),
+            listened_at,
+        })
+    }
+}
+
+/// Body of a ListenBrainz `submit-listens` request
+pub fn listenbrainz_payload(listen: &Listen) -> serde_json::Value {
+    let mut track_metadata = serde_json::json!({
+        "artist_name": listen.artist,
+        "track_name": listen.title,
+        "additional_info": {
+            "media_player": CLIENT_NAME,
+            "submission_client": CLIENT_NAME,
+            "submission_client_version": env!("CARGO_PKG_VERSION"),
+            "origin_url": listen.origin_url,
+        },
+    });
+    if let Some(album) = &listen.album {
+        track_metadata["release_name"] = album.clone().into();
+    }
+    if let Some(duration) = listen.duration {
+        track_metadata["additional_info"]["duration"] = duration.into();
+    }
+
+    serde_json::json!({
+        "listen_type": "single",
+        "payload": [{
+            "listened_at": listen.listened_at.timestamp(),
+            "track_metadata": track_metadata,
+        }],
+    })
+}
+
+/// Form fields of a signed Last.fm `track.scrobble` request
+pub fn lastfm_params(listen: &Listen, api_key: &str, session_key: &str, api_secret: &str) -> Vec<(String, String)> {
+    let mut params = vec![
+        ("method", "track.scrobble".to_string()),
+        ("artist", listen.artist.clone()),
+        ("track", listen.title.clone()),
+        ("timestamp", listen.listened_at.timestamp().to_string()),
+        ("api_key", api_key.to_string()),
+        ("sk", session_key.to_string()),
+    ];
+    if let Some(album) = &listen.album {
+        params.push(("album", album.clone()));
+    }
+    if let Some(duration) = listen.duration {
+        params.push(("duration", duration.to_string()));
+    }
+
+    // The signature is the MD5 of every field sorted by name, then the secret
+    params.sort();
+    let mut signed = String::new();
+    for (name, value) in &params {
+        signed.push_str(name);
+        signed.push_str(value);
+    }
+    signed.push_str(api_secret);
+    let api_sig: String = Md5::digest(signed.as_bytes()).iter().map(|byte| format!("{:02x}", byte)).collect();
+
+    let mut params: Vec<(String, String)> = params.into_iter().map(|(name, value)| (name.to_string(), value)).collect();
+    params.push(("api_sig".to_string(), api_sig));
+    params.push(("format".to_string(), "json".to_string()));
+    params
+}
+
+/// Error message in a service's JSON response, if any
+fn response_error(body: &str) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_str(body).ok()?;
+    let message = value.get("message").or_else(|| value.get("error"))?;
+    Some(message.as_str().map_or_else(|| message.to_string(), str::to_string))
+}
+
+/// Send a listen to the configured service
+pub async fn submit(
+    config: &AppConfig,
+    credentials: &ListenLogCredentials,
+    listen: &Listen,
+) -> Result<(), ListenLogError> {
+    let service = config.listen_log_service
+        .ok_or_else(|| ListenLogError::NotConfigured("no service selected".to_string()))?;
+    let endpoint = config.listen_log_endpoint.as_deref().unwrap_or(service.default_endpoint());
+    let token = credentials.token.as_deref()
+        .ok_or_else(|| ListenLogError::NotConfigured("no token saved".to_string()))?;
+
+    let client = reqwest::Client::builder()
+        .timeout(SUBMIT_TIMEOUT)
+        .build()
+        .map_err(|e| ListenLogError::RequestFailed(e.to_string()))?;
+    let request = match service {
+        ListenLogService::ListenBrainz => client.post(endpoint)
+            .header("Authorization", format!("Token {}", token))
+            .header("Content-Type", "application/json")
+            .body(listenbrainz_payload(listen).to_string()),
+        ListenLogService::LastFm => {
+            let api_key = config.listen_log_api_key.as_deref()
+                .ok_or_else(|| ListenLogError::NotConfigured("no Last.fm API key set".to_string()))?;
+            let api_secret = credentials.api_secret.as_deref()
+                .ok_or_else(|| ListenLogError::NotConfigured("no Last.fm API secret saved".to_string()))?;
+            client.post(endpoint).form(&lastfm_params(listen, api_key, token, api_secret))
+        }
+    };
+
+    let response = request.send().await
+        .map_err(|e| ListenLogError::RequestFailed(e.to_string()))?;
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+
+    // Last.fm reports some errors with a 200 and an error field
+    match response_error(&body) {
+        Some(message) if !status.is_success() || service == ListenLogService::LastFm => {
+            Err(ListenLogError::Rejected(message))
+        }
+        _ if !status.is_success() => Err(ListenLogError::Rejected(format!("HTTP {}", status))),
+        _ => Ok(()),
+    }
+}
+
+/// Log a finished job to the configured service. Returns None when listen
+/// logging is off.
+pub async fn submit_job(config: &AppConfig, job: &DownloadJob) -> Option<ListenSubmission> {
+    let service = config.listen_log_service?;
+    let attempted_at = Utc::now();
+    let submission = |status, error| ListenSubmission { service, status, attempted_at, error };
+
+    let Some(listen) = Listen::for_job(job, job.completed_at.unwrap_or(attempted_at)) else {
+        return Some(submission(SubmissionStatus::Skipped, Some("No artist or title known".to_string())));
+    };
+    let credentials = tokio::task::spawn_blocking(|| CredentialStore::new().load())
+        .await
+        .unwrap_or_default();
+
+    Some(match submit(config, &credentials, &listen).await {
+        Ok(()) => submission(SubmissionStatus::Submitted, None),
+        Err(e) => submission(SubmissionStatus::Failed, Some(e.to_string())),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn listen() -> Listen {
+        Listen {
+            artist: "Artist".to_string(),
+            title: "Song".to_string(),
+            album: Some("Album".to_string()),
+            duration: Some(200),
+            origin_url: "https://music.youtube.com/watch?v=abc".to_string(),
+            listened_at: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_listenbrainz_payload() {
+        let payload = listenbrainz_payload(&listen());
+        assert_eq!(payload["listen_type"], "single");
+        let entry = &payload["payload"][0];
+        assert_eq!(entry["listened_at"], 1_700_000_000);
+        assert_eq!(entry["track_metadata"]["release_name"], "Album");
+        assert_eq!(entry["track_metadata"]["additional_info"]["duration"], 200);
+        assert_eq!(entry["track_metadata"]["additional_info"]["origin_url"], "https://music.youtube.com/watch?v=abc");
+    }
+
+    #[test]
+    fn test_lastfm_params_are_signed() {
+        let params = lastfm_params(&listen(), "key", "session", "secret");
+        let field = |name: &str| params.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str());
+
+        let signed = "albumAlbumapi_keykeyartistArtistduration200methodtrack.scrobblesksessiontimestamp1700000000trackSongsecret";
+        let expected: String = Md5::digest(signed.as_bytes()).iter().map(|byte| format!("{:02x}", byte)).collect();
+        assert_eq!(field("api_sig"), Some(expected.as_str()));
+        assert_eq!(field("format"), Some("json"));
+    }
+}
@@ -0,0 +1,88 @@
+use crate::modules::state::Progress;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// Latest progress of one running job, shared between its worker and readers
+#[derive(Debug, Clone)]
+pub struct ProgressCell(Arc<RwLock<Progress>>);
+
+impl ProgressCell {
+    pub fn set(&self, progress: Progress) {
+        if let Ok(mut current) = self.0.write() {
+            *current = progress;
+        }
+    }
+
+    pub fn get(&self) -> Progress {
+        self.0.read().map(|progress| progress.clone()).unwrap_or_default()
+    }
+}
+
+/// Progress of running jobs, kept outside `AppState` so the many updates a
+/// download reports per second don't each take the app state write lock.
+/// A worker tracks its job while it runs, then settles the last value back
+/// into the job.
+#[derive(Debug, Default)]
+pub struct LiveProgress {
+    jobs: RwLock<HashMap<String, ProgressCell>>,
+}
+
+impl LiveProgress {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start tracking a job, or update it if it already is, and return its cell
+    pub fn track(&self, job_id: &str, progress: Progress) -> ProgressCell {
+        if let Some(cell) = self.cell(job_id) {
+            cell.set(progress);
+            return cell;
+        }
+        let cell = ProgressCell(Arc::new(RwLock::new(progress)));
+        if let Ok(mut jobs) = self.jobs.write() {
+            jobs.insert(job_id.to_string(), cell.clone());
+        }
+        cell
+    }
+
+    pub fn cell(&self, job_id: &str) -> Option<ProgressCell> {
+        self.jobs.read().ok()?.get(job_id).cloned()
+    }
+
+    pub fn get(&self, job_id: &str) -> Option<Progress> {
+        self.cell(job_id).map(|cell| cell.get())
+    }
+
+    /// Stop tracking a job and return its last progress
+    pub fn untrack(&self, job_id: &str) -> Option<Progress> {
+        self.jobs.write().ok()?.remove(job_id).map(|cell| cell.get())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn progress(percentage: f32) -> Progress {
+        Progress {
+            percentage: Some(percentage),
+            ..Progress::default()
+        }
+    }
+
+    #[test]
+    fn test_cells_share_tracked_progress() {
+        let live = LiveProgress::new();
+        let cell = live.track("job", progress(10.0));
+        cell.set(progress(40.0));
+        assert_eq!(live.get("job").unwrap().percentage, Some(40.0));
+
+        // Tracking again updates the same cell
+        live.track("job", progress(50.0));
+        assert_eq!(cell.get().percentage, Some(50.0));
+
+        assert_eq!(live.untrack("job").unwrap().percentage, Some(50.0));
+        assert!(live.get("job").is_none());
+        assert!(live.untrack("job").is_none());
+    }
+}
@@ -0,0 +1,265 @@
+use crate::modules::debug_logger::{LogEntry, LogLevel};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+const FILE_PREFIX: &str = "gytmdl-gui-";
+const FILE_EXTENSION: &str = "log";
+
+/// Hard cap on kept log files, so size-based rotation can't fill the disk
+/// within the retention period
+const MAX_LOG_FILES: usize = 20;
+
+/// Where and how much to keep of the persisted debug log
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogFileSettings {
+    pub dir: PathBuf,
+    /// Start a new file once the current one reaches this size
+    pub max_file_bytes: u64,
+    /// Delete files last written more than this many days ago
+    pub retention_days: u32,
+}
+
+impl LogFileSettings {
+    pub fn new(dir: PathBuf, max_file_size_mb: u64, retention_days: u32) -> Self {
+        Self {
+            dir,
+            max_file_bytes: max_file_size_mb.max(1) * 1024 * 1024,
+            retention_days,
+        }
+    }
+}
+
+fn level_label(level: LogLevel) -> &'static str {
+    match level {
+        LogLevel::Debug => "DEBUG",
+        LogLevel::Info => "INFO",
+        LogLevel::Warn => "WARN",
+        LogLevel::Error => "ERROR",
+    }
+}
+
+/// One line of a log file
+fn format_entry(entry: &LogEntry) -> String {
+    format!(
+        "{} {:<5} [{}] {}\n",
+        entry.timestamp.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+        level_label(entry.level),
+        entry.component,
+        entry.message,
+    )
+}
+
+struct OpenLogFile {
+    writer: BufWriter<File>,
+    size: u64,
+    opened_on: NaiveDate,
+}
+
+/// Writes log entries to `gytmdl-gui-<timestamp>.log` files, starting a new
+/// file when the current one is full or the day changes
+pub struct RotatingLogFile {
+    settings: LogFileSettings,
+    current: Option<OpenLogFile>,
+}
+
+impl RotatingLogFile {
+    pub fn new(settings: LogFileSettings) -> Self {
+        Self { settings, current: None }
+    }
+
+    pub fn settings(&self) -> &LogFileSettings {
+        &self.settings
+    }
+
+    /// Append one entry as a line of text
+    pub fn write_entry(&mut self, entry: &LogEntry) -> io::Result<()> {
+        let line = format_entry(entry);
+
+        let needs_rotation = self.current.as_ref().is_none_or(|current| {
+            current.size >= self.settings.max_file_bytes || current.opened_on != entry.timestamp.date_naive()
+        });
+        if needs_rotation {
+            self.rotate(entry.timestamp)?;
+        }
+
+        let Some(current) = self.current.as_mut() else {
+            return Ok(());
+        };
+        current.writer.write_all(line.as_bytes())?;
+        current.size += line.len() as u64;
+        // Problems should reach the disk even if the app crashes right after
+        if entry.level >= LogLevel::Warn {
+            current.writer.flush()?;
+        }
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        match self.current.as_mut() {
+            Some(current) => current.writer.flush(),
+            None => Ok(()),
+        }
+    }
+
+    /// Close the current file, open a new one and drop files past retention
+    fn rotate(&mut self, now: DateTime<Utc>) -> io::Result<()> {
+        self.flush()?;
+        self.current = None;
+        fs::create_dir_all(&self.settings.dir)?;
+
+        let mut path = self.settings.dir.join(format!(
+            "{}{}.{}", FILE_PREFIX, now.format("%Y%m%d-%H%M%S"), FILE_EXTENSION
+        ));
+        // Several rotations within one second
+        let mut counter = 1;
+        while path.exists() {
+            path = self.settings.dir.join(format!(
+                "{}{}_{:03}.{}", FILE_PREFIX, now.format("%Y%m%d-%H%M%S"), counter, FILE_EXTENSION
+            ));
+            counter += 1;
+        }
+
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        self.current = Some(OpenLogFile {
+            writer: BufWriter::new(file),
+            size: 0,
+            opened_on: now.date_naive(),
+        });
+
+        prune_log_files(&self.settings.dir, self.settings.retention_days, now)?;
+        Ok(())
+    }
+}
+
+impl Drop for RotatingLogFile {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+/// Log files in `dir`, oldest first
+pub fn list_log_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| {
+                    path.extension().is_some_and(|extension| extension == FILE_EXTENSION)
+                        && path.file_name()
+                            .and_then(|name| name.to_str())
+                            .is_some_and(|name| name.starts_with(FILE_PREFIX))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    // Timestamped names sort chronologically
+    files.sort();
+    files
+}
+
+/// Delete log files last written before the retention period, and the oldest
+/// files beyond `MAX_LOG_FILES`. The newest file is always kept.
+pub fn prune_log_files(dir: &Path, retention_days: u32, now: DateTime<Utc>) -> io::Result<usize> {
+    let cutoff = now - Duration::days(i64::from(retention_days));
+    let files = list_log_files(dir);
+    let excess = files.len().saturating_sub(MAX_LOG_FILES);
+
+    let mut removed = 0;
+    for (index, file) in files.iter().enumerate() {
+        if index + 1 == files.len() {
+            break;
+        }
+        let expired = fs::metadata(file)
+            .and_then(|metadata| metadata.modified())
+            .is_ok_and(|modified| DateTime::<Utc>::from(modified) < cutoff);
+        if index < excess || expired {
+            fs::remove_file(file)?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// Zip the log files in `dir` into `destination` for attaching to a bug report.
+/// `session_entries` are added as `session.log` when there are no files, e.g.
+/// because writing logs to disk is turned off.
+pub fn export_logs(dir: &Path, destination: &Path, session_entries: &[LogEntry]) -> io::Result<()> {
+    let files = list_log_files(dir);
+    let mut archive = zip::ZipWriter::new(File::create(destination)?);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    for file in &files {
+        let name = file.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+        archive.start_file(name, options).map_err(io::Error::other)?;
+        io::copy(&mut File::open(file)?, &mut archive)?;
+    }
+    if files.is_empty() {
+        archive.start_file("session.log", options).map_err(io::Error::other)?;
+        for entry in session_entries {
+            archive.write_all(format_entry(entry).as_bytes())?;
+        }
+    }
+
+    archive.finish().map_err(io::Error::other)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn entry(timestamp: DateTime<Utc>, message: &str) -> LogEntry {
+        LogEntry {
+            timestamp,
+            level: LogLevel::Info,
+            component: "queue".to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_rotates_on_size_and_day() {
+        let temp_dir = tempdir().unwrap();
+        let mut settings = LogFileSettings::new(temp_dir.path().to_path_buf(), 1, 7);
+        settings.max_file_bytes = 100;
+        let mut log_file = RotatingLogFile::new(settings);
+
+        let now = Utc::now();
+        log_file.write_entry(&entry(now, "first")).unwrap();
+        log_file.write_entry(&entry(now, "second")).unwrap();
+        assert_eq!(list_log_files(temp_dir.path()).len(), 1);
+
+        // The first file is now over 100 bytes
+        log_file.write_entry(&entry(now, &"x".repeat(64))).unwrap();
+        log_file.write_entry(&entry(now, "third")).unwrap();
+        assert_eq!(list_log_files(temp_dir.path()).len(), 2);
+
+        log_file.write_entry(&entry(now + Duration::days(1), "tomorrow")).unwrap();
+        log_file.flush().unwrap();
+        let files = list_log_files(temp_dir.path());
+        assert_eq!(files.len(), 3);
+        let first = fs::read_to_string(&files[0]).unwrap();
+        assert!(first.contains("INFO  [queue] first"));
+    }
+
+    #[test]
+    fn test_prune_keeps_newest_file() {
+        let temp_dir = tempdir().unwrap();
+        for name in ["gytmdl-gui-20260101-000000.log", "gytmdl-gui-20260102-000000.log", "other.log"] {
+            fs::write(temp_dir.path().join(name), "").unwrap();
+        }
+
+        // Everything is older than a retention period that ends in the future
+        let removed = prune_log_files(temp_dir.path(), 0, Utc::now() + Duration::days(1)).unwrap();
+        assert_eq!(removed, 1);
+        assert_eq!(
+            list_log_files(temp_dir.path()),
+            vec![temp_dir.path().join("gytmdl-gui-20260102-000000.log")]
+        );
+        assert!(temp_dir.path().join("other.log").exists());
+    }
+}
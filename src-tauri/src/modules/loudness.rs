@@ -0,0 +1,152 @@
+use crate::modules::converter::ConvertError;
+use serde::{Deserialize, Serialize};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::process::Command;
+
+/// File types ReplayGain tags are written to
+const TAGGABLE_EXTENSIONS: &[&str] = &["m4a", "mp3", "flac", "opus", "ogg"];
+
+/// ReplayGain values measured for one track
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct LoudnessAnalysis {
+    pub track_gain_db: f64,
+    pub track_peak: f64,
+}
+
+pub fn is_taggable(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| TAGGABLE_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+/// Measure a file with ffmpeg's replaygain filter
+pub async fn analyze(ffmpeg: &Path, input: &Path) -> Result<LoudnessAnalysis, ConvertError> {
+    let output = Command::new(ffmpeg)
+        .args(["-hide_banner", "-nostdin", "-nostats", "-i"])
+        .arg(input)
+        .args(["-map", "0:a", "-af", "replaygain", "-f", "null", "-"])
+        .stdin(Stdio::null())
+        .kill_on_drop(true)
+        .output()
+        .await?;
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+        let message = stderr.lines().last().unwrap_or("").trim().to_string();
+        return Err(ConvertError::FfmpegFailed(message));
+    }
+
+    parse_replaygain_output(&stderr).ok_or_else(|| {
+        ConvertError::FfmpegFailed("replaygain filter reported no values".to_string())
+    })
+}
+
+/// Read the values printed by the replaygain filter, e.g.
+/// `[Parsed_replaygain_0 @ 0x...] track_gain = -7.34 dB`
+pub fn parse_replaygain_output(output: &str) -> Option<LoudnessAnalysis> {
+    let value_of = |key: &str| {
+        output.lines()
+            .rev()
+            .filter_map(|line| line.split_once(key))
+            .filter_map(|(_, rest)| rest.trim_start().strip_prefix('='))
+            .find_map(|rest| rest.split_whitespace().next()?.parse::<f64>().ok())
+    };
+
+    Some(LoudnessAnalysis {
+        track_gain_db: value_of("track_gain")?,
+        track_peak: value_of("track_peak")?,
+    })
+}
+
+/// ffmpeg arguments that copy `input` to `output` with ReplayGain tags added
+pub fn build_tag_args(input: &Path, output: &Path, analysis: &LoudnessAnalysis) -> Vec<OsString> {
+    let mut args: Vec<OsString> = ["-hide_banner", "-nostdin", "-loglevel", "error", "-y", "-i"]
+        .iter()
+        .map(OsString::from)
+        .collect();
+    args.push(input.as_os_str().to_owned());
+    args.extend(["-map", "0", "-c", "copy", "-map_metadata", "0"].iter().map(OsString::from));
+    args.push("-metadata".into());
+    args.push(format!("REPLAYGAIN_TRACK_GAIN={:.2} dB", analysis.track_gain_db).into());
+    args.push("-metadata".into());
+    args.push(format!("REPLAYGAIN_TRACK_PEAK={:.6}", analysis.track_peak).into());
+
+    let extension = input.extension().and_then(|ext| ext.to_str()).unwrap_or("").to_lowercase();
+    match extension.as_str() {
+        // The mp4 muxer drops keys it doesn't know unless told otherwise
+        "m4a" => args.extend(["-movflags", "use_metadata_tags"].iter().map(OsString::from)),
+        "mp3" => args.extend(["-id3v2_version", "3"].iter().map(OsString::from)),
+        _ => {}
+    }
+
+    args.push(output.as_os_str().to_owned());
+    args
+}
+
+/// Write ReplayGain tags by remuxing into a sibling file and replacing the original
+pub async fn write_tags(ffmpeg: &Path, input: &Path, analysis: &LoudnessAnalysis) -> Result<(), ConvertError> {
+    let temp_output = tagged_temp_path(input);
+    let output = Command::new(ffmpeg)
+        .args(build_tag_args(input, &temp_output, analysis))
+        .stdin(Stdio::null())
+        .kill_on_drop(true)
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        let _ = std::fs::remove_file(&temp_output);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(ConvertError::FfmpegFailed(stderr.lines().last().unwrap_or("").trim().to_string()));
+    }
+
+    std::fs::rename(&temp_output, input)?;
+    Ok(())
+}
+
+/// `song.m4a` -> `song.replaygain.m4a`; ffmpeg picks the muxer from the extension
+fn tagged_temp_path(input: &Path) -> PathBuf {
+    let stem = input.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    match input.extension() {
+        Some(ext) => input.with_file_name(format!("{}.replaygain.{}", stem, ext.to_string_lossy())),
+        None => input.with_file_name(format!("{}.replaygain", stem)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_replaygain_output() {
+        let output = "\
+Input #0, mov,mp4,m4a,3gp,3g2,mj2, from 'song.m4a':
+[Parsed_replaygain_0 @ 0x600001c8c000] track_gain = -7.34 dB
+[Parsed_replaygain_0 @ 0x600001c8c000] track_peak = 0.987654
+";
+        assert_eq!(
+            parse_replaygain_output(output),
+            Some(LoudnessAnalysis { track_gain_db: -7.34, track_peak: 0.987654 })
+        );
+        assert_eq!(parse_replaygain_output("no filter output"), None);
+    }
+
+    #[test]
+    fn test_build_tag_args() {
+        let input = Path::new("/music/song.m4a");
+        let analysis = LoudnessAnalysis { track_gain_db: -7.3412, track_peak: 0.5 };
+        let args: Vec<String> = build_tag_args(input, &tagged_temp_path(input), &analysis)
+            .iter()
+            .map(|a| a.to_string_lossy().into_owned())
+            .collect();
+
+        assert!(args.contains(&"REPLAYGAIN_TRACK_GAIN=-7.34 dB".to_string()));
+        assert!(args.contains(&"REPLAYGAIN_TRACK_PEAK=0.500000".to_string()));
+        assert!(args.contains(&"use_metadata_tags".to_string()));
+        assert_eq!(args.last().unwrap(), "/music/song.replaygain.m4a");
+        assert!(is_taggable(input));
+        assert!(!is_taggable(Path::new("/music/cover.jpg")));
+    }
+}
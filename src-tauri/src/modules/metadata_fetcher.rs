@@ -0,0 +1,139 @@
+use crate::modules::state::JobMetadata;
+use serde::Deserialize;
+use std::time::Duration;
+
+const OEMBED_ENDPOINT: &str = "https://www.youtube.com/oembed";
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug)]
+pub enum MetadataError {
+    RequestError(String),
+    ParseError(String),
+}
+
+impl std::fmt::Display for MetadataError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MetadataError::RequestError(msg) => write!(f, "Metadata request failed: {}", msg),
+            MetadataError::ParseError(msg) => write!(f, "Failed to parse metadata: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for MetadataError {}
+
+/// Subset of the oEmbed response we care about
+#[derive(Debug, Deserialize)]
+struct OEmbedResponse {
+    title: Option<String>,
+    author_name: Option<String>,
+    thumbnail_url: Option<String>,
+}
+
+/// Lightweight metadata lookup used to label jobs before they download
+pub struct MetadataFetcher {
+    client: reqwest::Client,
+}
+
+impl MetadataFetcher {
+    pub fn new() -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(FETCH_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+
+        Self { client }
+    }
+
+    /// Fetch title, artist and thumbnail for a YouTube Music URL
+    pub async fn fetch(&self, url: &str) -> Result<JobMetadata, MetadataError> {
+        let response = self.client
+            .get(OEMBED_ENDPOINT)
+            .query(&[("url", Self::normalize_url(url).as_str()), ("format", "json")])
+            .send()
+            .await
+            .map_err(|e| MetadataError::RequestError(e.to_string()))?;
+
+        if !response.status().is_success() {
+            return Err(MetadataError::RequestError(format!("HTTP {}", response.status())));
+        }
+
+        let body = response.text().await
+            .map_err(|e| MetadataError::RequestError(e.to_string()))?;
+
+        Self::parse_oembed(&body)
+    }
+
+    /// oEmbed only knows www.youtube.com, so rewrite music.youtube.com links
+    fn normalize_url(url: &str) -> String {
+        url.replacen("music.youtube.com", "www.youtube.com", 1)
+    }
+
+    /// Convert an oEmbed JSON body into JobMetadata
+    fn parse_oembed(body: &str) -> Result<JobMetadata, MetadataError> {
+        let response: OEmbedResponse = serde_json::from_str(body)
+            .map_err(|e| MetadataError::ParseError(e.to_string()))?;
+
+        Ok(JobMetadata {
+            title: response.title,
+            artist: response.author_name.map(|name| Self::clean_artist_name(&name)),
+            album: None,
+            duration: None,
+            thumbnail: response.thumbnail_url,
+            has_synced_lyrics: None,
+            track_count: None,
+        })
+    }
+
+    /// Auto-generated YouTube Music channels are named "Artist - Topic"
+    fn clean_artist_name(name: &str) -> String {
+        name.trim_end_matches(" - Topic").trim().to_string()
+    }
+}
+
+impl Default for MetadataFetcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_url() {
+        assert_eq!(
+            MetadataFetcher::normalize_url("https://music.youtube.com/watch?v=abc"),
+            "https://www.youtube.com/watch?v=abc"
+        );
+        assert_eq!(
+            MetadataFetcher::normalize_url("https://youtu.be/abc"),
+            "https://youtu.be/abc"
+        );
+    }
+
+    #[test]
+    fn test_parse_oembed() {
+        let body = r#"{
+            "title": "Song Title",
+            "author_name": "Some Artist - Topic",
+            "thumbnail_url": "https://i.ytimg.com/vi/abc/hqdefault.jpg",
+            "type": "video"
+        }"#;
+
+        let metadata = MetadataFetcher::parse_oembed(body).unwrap();
+        assert_eq!(metadata.title, Some("Song Title".to_string()));
+        assert_eq!(metadata.artist, Some("Some Artist".to_string()));
+        assert_eq!(metadata.thumbnail, Some("https://i.ytimg.com/vi/abc/hqdefault.jpg".to_string()));
+        assert!(metadata.album.is_none());
+    }
+
+    #[test]
+    fn test_parse_oembed_invalid() {
+        assert!(matches!(
+            MetadataFetcher::parse_oembed("Not Found"),
+            Err(MetadataError::ParseError(_))
+        ));
+    }
+}
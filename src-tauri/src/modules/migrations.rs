@@ -0,0 +1,184 @@
+use crate::modules::state::{AppConfig, AppState, DownloadJob};
+use serde_json::{Map, Value};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Version written to `state.json`; files from before versioning count as 0
+pub const STATE_SCHEMA_VERSION: u32 = 1;
+
+/// Version written to `config.json`; files from before versioning count as 0
+pub const CONFIG_SCHEMA_VERSION: u32 = 1;
+
+/// Upgrades a document by one schema version
+type Migration = fn(&mut Map<String, Value>);
+
+/// `STATE_MIGRATIONS[n]` upgrades a state file from version n to n + 1
+const STATE_MIGRATIONS: &[Migration] = &[state_v0_to_v1];
+
+/// `CONFIG_MIGRATIONS[n]` upgrades a config file from version n to n + 1
+const CONFIG_MIGRATIONS: &[Migration] = &[config_v0_to_v1];
+
+#[derive(Debug)]
+pub enum MigrationError {
+    /// Written by a newer version of the app; loading it could lose data
+    UnsupportedVersion { found: u32, supported: u32 },
+    InvalidFormat(String),
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MigrationError::UnsupportedVersion { found, supported } => write!(
+                f,
+                "File has schema version {} but this version of the app only supports up to {}",
+                found, supported
+            ),
+            MigrationError::InvalidFormat(msg) => write!(f, "Invalid file format: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for MigrationError {}
+
+/// Upgrade a parsed `state.json` in place. Returns the version it was upgraded from.
+pub fn migrate_state(document: &mut Value) -> Result<u32, MigrationError> {
+    migrate(document, STATE_MIGRATIONS, STATE_SCHEMA_VERSION)
+}
+
+/// Upgrade a parsed `config.json` in place. Returns the version it was upgraded from.
+pub fn migrate_config(document: &mut Value) -> Result<u32, MigrationError> {
+    migrate(document, CONFIG_MIGRATIONS, CONFIG_SCHEMA_VERSION)
+}
+
+/// Keep a copy of a file before it is rewritten in a newer format, e.g.
+/// `state.json` -> `state.json.v0.bak`
+pub fn backup_before_upgrade(path: &Path, from_version: u32) -> Result<PathBuf, io::Error> {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(format!(".v{}.bak", from_version));
+    let backup = PathBuf::from(backup);
+    fs::copy(path, &backup)?;
+    Ok(backup)
+}
+
+fn migrate(document: &mut Value, migrations: &[Migration], current: u32) -> Result<u32, MigrationError> {
+    let object = document.as_object_mut()
+        .ok_or_else(|| MigrationError::InvalidFormat("expected a JSON object".to_string()))?;
+
+    let found = match object.get("schema_version") {
+        None => 0,
+        Some(version) => version.as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or_else(|| MigrationError::InvalidFormat(format!("invalid schema_version: {}", version)))?,
+    };
+    if found > current {
+        return Err(MigrationError::UnsupportedVersion { found, supported: current });
+    }
+
+    for migration in &migrations[found as usize..current as usize] {
+        migration(object);
+    }
+    object.insert("schema_version".to_string(), Value::from(current));
+    Ok(found)
+}
+
+/// Add every key `defaults` has and `target` lacks, descending into nested objects
+fn fill_missing(target: &mut Map<String, Value>, defaults: &Map<String, Value>) {
+    for (key, default) in defaults {
+        match (target.get_mut(key), default) {
+            (None, _) => {
+                target.insert(key.clone(), default.clone());
+            }
+            (Some(Value::Object(nested)), Value::Object(nested_defaults)) => {
+                fill_missing(nested, nested_defaults);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn defaults_of<T: serde::Serialize>(value: &T) -> Map<String, Value> {
+    match serde_json::to_value(value) {
+        Ok(Value::Object(map)) => map,
+        _ => Map::new(),
+    }
+}
+
+/// Unversioned configs predate serde defaults on most fields, so any setting
+/// added since they were written made the whole file unreadable
+fn config_v0_to_v1(config: &mut Map<String, Value>) {
+    fill_missing(config, &defaults_of(&AppConfig::default()));
+}
+
+/// Same as the config upgrade, for the embedded config and every job. Jobs
+/// without an id or URL can't be recovered and are dropped.
+fn state_v0_to_v1(state: &mut Map<String, Value>) {
+    if let Some(Value::Object(config)) = state.get_mut("config") {
+        config_v0_to_v1(config);
+    }
+
+    if let Some(Value::Array(jobs)) = state.get_mut("jobs") {
+        let job_defaults = defaults_of(&DownloadJob::new(String::new()));
+        jobs.retain(|job| job.get("id").is_some() && job.get("url").is_some());
+        for job in jobs.iter_mut().filter_map(Value::as_object_mut) {
+            fill_missing(job, &job_defaults);
+        }
+    }
+
+    fill_missing(state, &defaults_of(&AppState::default()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_migrates_unversioned_config() {
+        let mut document = json!({
+            "output_path": "/music",
+            "temp_path": "/tmp/gytmdl",
+            "itag": "251",
+            "cover_size": 600
+        });
+
+        assert_eq!(migrate_config(&mut document).unwrap(), 0);
+        let config: AppConfig = serde_json::from_value(document).unwrap();
+        assert_eq!(config.itag, "251");
+        assert_eq!(config.cover_size, 600);
+        assert_eq!(config.template_file, AppConfig::default().template_file);
+        assert_eq!(config.schema_version, CONFIG_SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_migrates_unversioned_state() {
+        let mut document = json!({
+            "jobs": [
+                { "id": "a", "url": "https://music.youtube.com/watch?v=a", "status": "Completed" },
+                { "status": "Queued" }
+            ],
+            "config": { "itag": "251" }
+        });
+
+        assert_eq!(migrate_state(&mut document).unwrap(), 0);
+        let state: AppState = serde_json::from_value(document).unwrap();
+        assert_eq!(state.jobs.len(), 1);
+        assert_eq!(state.jobs[0].status, crate::modules::state::JobStatus::Completed);
+        assert_eq!(state.config.itag, "251");
+        assert_eq!(state.schema_version, STATE_SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_current_and_newer_versions() {
+        let mut current = serde_json::to_value(AppConfig::default()).unwrap();
+        assert_eq!(migrate_config(&mut current).unwrap(), CONFIG_SCHEMA_VERSION);
+
+        let mut newer = json!({ "schema_version": CONFIG_SCHEMA_VERSION + 1 });
+        assert!(matches!(
+            migrate_config(&mut newer),
+            Err(MigrationError::UnsupportedVersion { .. })
+        ));
+        assert!(matches!(migrate_config(&mut json!([])), Err(MigrationError::InvalidFormat(_))));
+    }
+}
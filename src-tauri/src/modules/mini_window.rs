@@ -0,0 +1,135 @@
+use crate::modules::events::EventEmitter;
+use crate::modules::state::{AppState, DownloadStage, JobStatus};
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder};
+use tokio::sync::RwLock;
+
+/// Label of the always-on-top progress window
+pub const MINI_WINDOW_LABEL: &str = "mini";
+
+/// Frontend route the mini window loads
+const MINI_WINDOW_URL: &str = "index.html#/mini";
+const MINI_WINDOW_WIDTH: f64 = 360.0;
+const MINI_WINDOW_HEIGHT: f64 = 220.0;
+
+/// How often the mini window's summary is refreshed while it is open
+const REFRESH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// One running download as the mini window shows it
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct MiniJob {
+    pub job_id: String,
+    /// Metadata title, or the URL while metadata isn't known
+    pub title: String,
+    pub stage: DownloadStage,
+    pub percentage: Option<f32>,
+    pub speed_bytes_per_sec: Option<u64>,
+    pub eta_seconds: Option<u64>,
+}
+
+/// Everything the mini window renders
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct MiniProgress {
+    pub active: Vec<MiniJob>,
+    pub queued: usize,
+    pub paused: bool,
+}
+
+impl MiniProgress {
+    pub fn from_state(state: &AppState) -> Self {
+        let active = state.jobs.iter()
+            .filter(|job| job.status == JobStatus::Downloading)
+            .map(|job| {
+                let progress = state.job_progress(job);
+                MiniJob {
+                    job_id: job.id.clone(),
+                    title: job.metadata.as_ref()
+                        .and_then(|metadata| metadata.title.clone())
+                        .unwrap_or_else(|| job.url.clone()),
+                    stage: progress.stage,
+                    percentage: progress.percentage,
+                    speed_bytes_per_sec: progress.speed_bytes_per_sec,
+                    eta_seconds: progress.eta_seconds,
+                }
+            })
+            .collect();
+
+        Self {
+            active,
+            queued: state.count_jobs_by_status(&JobStatus::Queued),
+            paused: state.is_paused,
+        }
+    }
+}
+
+/// Open the mini window, or close it if it is already open. Returns whether
+/// it is open afterwards.
+pub fn toggle(app_handle: &AppHandle, state: Arc<RwLock<AppState>>, events: EventEmitter) -> Result<bool, String> {
+    if let Some(window) = app_handle.get_webview_window(MINI_WINDOW_LABEL) {
+        window.close().map_err(|e| e.to_string())?;
+        return Ok(false);
+    }
+
+    WebviewWindowBuilder::new(app_handle, MINI_WINDOW_LABEL, WebviewUrl::App(MINI_WINDOW_URL.into()))
+        .title("Downloads")
+        .inner_size(MINI_WINDOW_WIDTH, MINI_WINDOW_HEIGHT)
+        .always_on_top(true)
+        .resizable(false)
+        .skip_taskbar(true)
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    tauri::async_runtime::spawn(run_feed(app_handle.clone(), state, events));
+    Ok(true)
+}
+
+/// Close the mini window if it is open
+pub fn close(app_handle: &AppHandle) {
+    if let Some(window) = app_handle.get_webview_window(MINI_WINDOW_LABEL) {
+        let _ = window.close();
+    }
+}
+
+/// Send the mini window a fresh summary whenever it changes, until the window closes
+async fn run_feed(app_handle: AppHandle, state: Arc<RwLock<AppState>>, events: EventEmitter) {
+    let mut last = None;
+    let mut ticker = tokio::time::interval(REFRESH_INTERVAL);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    loop {
+        ticker.tick().await;
+        if app_handle.get_webview_window(MINI_WINDOW_LABEL).is_none() {
+            break;
+        }
+        let summary = MiniProgress::from_state(&*state.read().await);
+        if last.as_ref() != Some(&summary) {
+            events.mini_progress(&summary);
+            last = Some(summary);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::state::Progress;
+
+    #[test]
+    fn test_summary_lists_running_jobs() {
+        let mut state = AppState::new();
+        let running = state.add_job("https://music.youtube.com/watch?v=running".to_string());
+        state.add_job("https://music.youtube.com/watch?v=queued".to_string());
+        state.update_job_status(&running, JobStatus::Downloading);
+        state.live_progress.track(&running, Progress {
+            percentage: Some(30.0),
+            ..Progress::default()
+        });
+
+        let summary = MiniProgress::from_state(&state);
+        assert_eq!(summary.queued, 1);
+        assert_eq!(summary.active.len(), 1);
+        assert_eq!(summary.active[0].title, "https://music.youtube.com/watch?v=running");
+        assert_eq!(summary.active[0].percentage, Some(30.0));
+    }
+}
@@ -5,6 +5,72 @@ pub mod progress_parser;
 pub mod config_manager;
 pub mod cookie_manager;
 pub mod sidecar_manager;
+pub mod events;
+pub mod sidecar_isolation;
+pub mod disk_space;
+pub mod metadata_fetcher;
+pub mod progress_journal;
+pub mod url_rewriter;
+pub mod history;
+pub mod download_cache;
+pub mod duplicate_detector;
+pub mod timezone;
+pub mod api_tokens;
+pub mod self_test;
+pub mod library_integrity;
+pub mod job_logs;
+pub mod throughput;
+pub mod debug_logger;
+pub mod log_files;
+pub mod binary_locator;
+pub mod process_runner;
+pub mod queue_metrics;
+pub mod event_batcher;
+pub mod browser_cookies;
+pub mod cookie_monitor;
+pub mod library;
+pub mod converter;
+pub mod loudness;
+pub mod notifier;
+pub mod autosave;
+pub mod migrations;
+pub mod template;
+pub mod subscriptions;
+pub mod clipboard_watcher;
+pub mod deep_link;
+pub mod error_classifier;
+pub mod watchdog;
+pub mod job_query;
+pub mod setup_diagnostics;
+pub mod app_updater;
+pub mod atomic_file;
+pub mod dry_run;
+pub mod speed_schedule;
+pub mod dispatch_slots;
+pub mod job_list;
+pub mod live_progress;
+pub mod job_events;
+pub mod job_groups;
+pub mod verification;
+pub mod cover_art;
+pub mod tagging;
+pub mod playlist_file;
+pub mod job_export;
+pub mod mini_window;
+pub mod filename_rules;
+pub mod connectivity;
+pub mod track_selection;
+pub mod app_lock;
+pub mod app_error;
+pub mod runtime_settings;
+pub mod output_routing;
+pub mod config_history;
+pub mod temp_janitor;
+pub mod listen_log;
+pub mod process_priority;
+pub mod session_report;
+#[cfg(feature = "headless")]
+pub mod headless;
 
 #[cfg(test)]
 pub mod tests;
\ No newline at end of file
@@ -0,0 +1,111 @@
+use crate::modules::cookie_monitor::CookieExpiryWarning;
+use crate::modules::debug_logger::DEBUG_LOGGER;
+use crate::modules::events::EventEmitter;
+use crate::modules::state::DownloadJob;
+use tauri::AppHandle;
+use tauri_plugin_notification::NotificationExt;
+
+/// Sends native desktop notifications.
+///
+/// Like `EventEmitter`, it holds an optional `AppHandle` so the queue can run
+/// without a Tauri runtime; notifying is then a no-op. Clicks are handled by
+/// [`handle_clicks`].
+#[derive(Clone, Default)]
+pub struct Notifier {
+    app_handle: Option<AppHandle>,
+}
+
+impl Notifier {
+    pub fn new(app_handle: AppHandle) -> Self {
+        Self { app_handle: Some(app_handle) }
+    }
+
+    pub fn disabled() -> Self {
+        Self { app_handle: None }
+    }
+
+    pub fn job_completed(&self, job: &DownloadJob) {
+        self.show(&job.id, "Download complete", &job_label(job));
+    }
+
+    pub fn job_failed(&self, job: &DownloadJob, error: &str) {
+        self.show(&job.id, "Download failed", &format!("{}\n{}", job_label(job), error));
+    }
+
+    pub fn cookie_expiry(&self, warning: &CookieExpiryWarning) {
+        let title = if warning.expired { "Cookies expired" } else { "Cookies expiring soon" };
+        self.show("", title, &warning.message);
+    }
+
+    pub fn cookies_suspect(&self, message: &str) {
+        self.show("", "Downloads paused", message);
+    }
+
+    fn show(&self, job_id: &str, title: &str, body: &str) {
+        let Some(app_handle) = &self.app_handle else {
+            return;
+        };
+
+        let mut builder = app_handle.notification().builder()
+            .title(title)
+            .body(body);
+        if !job_id.is_empty() {
+            builder = builder.extra("job_id", job_id);
+        }
+        if let Err(e) = builder.show() {
+            DEBUG_LOGGER.warn("notifications", format!("Failed to show notification '{}': {}", title, e));
+        }
+    }
+}
+
+/// Bring the app forward with `focus_window` whenever one of its notifications
+/// is clicked, and tell the frontend which job, if any, it was about
+pub fn handle_clicks(app_handle: &AppHandle, focus_window: fn(&AppHandle)) {
+    let window_handle = app_handle.clone();
+    let events = EventEmitter::new(app_handle.clone());
+    let result = app_handle.notification().on_action(move |action| {
+        if action.action_id() != "tap" {
+            return;
+        }
+        focus_window(&window_handle);
+        let job_id = action.notification()
+            .and_then(|notification| notification.extra().get("job_id"))
+            .and_then(|job_id| job_id.as_str());
+        events.notification_clicked(job_id);
+    });
+    if let Err(e) = result {
+        DEBUG_LOGGER.warn("notifications", format!("Failed to handle notification clicks: {}", e));
+    }
+}
+
+/// "Artist - Title" when known, otherwise the job's URL
+pub fn job_label(job: &DownloadJob) -> String {
+    let metadata = job.metadata.as_ref();
+    match (
+        metadata.and_then(|m| m.title.as_deref()),
+        metadata.and_then(|m| m.artist.as_deref()),
+    ) {
+        (Some(title), Some(artist)) => format!("{} - {}", artist, title),
+        (Some(title), None) => title.to_string(),
+        _ => job.url.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::state::JobMetadata;
+
+    #[test]
+    fn test_job_label() {
+        let mut job = DownloadJob::new("https://music.youtube.com/watch?v=abc".to_string());
+        assert_eq!(job_label(&job), "https://music.youtube.com/watch?v=abc");
+
+        job.metadata = Some(JobMetadata {
+            title: Some("Song".to_string()),
+            artist: Some("Artist".to_string()),
+            ..JobMetadata::default()
+        });
+        assert_eq!(job_label(&job), "Artist - Song");
+    }
+}
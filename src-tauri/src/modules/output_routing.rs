@@ -0,0 +1,189 @@
+use crate::modules::state::{AppConfig, DownloadJob};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Sends matching jobs to another folder or names them with other templates,
+/// e.g. compilations to `Compilations` or one artist to a separate drive.
+/// Patterns are case-insensitive regexes and every one that is set must match.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OutputRoute {
+    #[serde(default)]
+    pub artist: Option<String>,
+    #[serde(default)]
+    pub album: Option<String>,
+    #[serde(default)]
+    pub url: Option<String>,
+    /// Output folder for matching jobs; relative paths are under the configured output folder
+    #[serde(default)]
+    pub output_path: Option<PathBuf>,
+    #[serde(default)]
+    pub template_folder: Option<String>,
+    #[serde(default)]
+    pub template_file: Option<String>,
+    #[serde(default = "default_route_enabled")]
+    pub enabled: bool,
+}
+
+fn default_route_enabled() -> bool {
+    true
+}
+
+/// Output folder and templates a job was given when it was dispatched, so
+/// metadata arriving later can't move it to another route
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RoutedOutput {
+    pub output_path: PathBuf,
+    pub template_folder: String,
+    pub template_file: String,
+}
+
+impl OutputRoute {
+    /// Whether the route matches on metadata rather than only on the URL
+    pub fn needs_metadata(&self) -> bool {
+        self.artist.is_some() || self.album.is_some()
+    }
+
+    fn patterns(&self) -> [(&'static str, Option<&str>); 3] {
+        [
+            ("artist", self.artist.as_deref()),
+            ("album", self.album.as_deref()),
+            ("URL", self.url.as_deref()),
+        ]
+    }
+
+    /// Whether the job's URL and metadata match every pattern. Artist and
+    /// album patterns never match before the job's metadata is known.
+    pub fn matches(&self, job: &DownloadJob) -> bool {
+        let metadata = job.metadata.as_ref();
+        let values = [
+            metadata.and_then(|metadata| metadata.artist.as_deref()),
+            metadata.and_then(|metadata| metadata.album.as_deref()),
+            Some(job.url.as_str()),
+        ];
+        self.patterns().iter().zip(values).all(|((_, pattern), value)| match pattern {
+            None => true,
+            Some(pattern) => match (compile(pattern), value) {
+                (Ok(regex), Some(value)) => regex.is_match(value),
+                _ => false,
+            },
+        })
+    }
+}
+
+fn compile(pattern: &str) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(pattern).case_insensitive(true).build()
+}
+
+/// Check every route has valid patterns, something to match on and something to change
+pub fn validate_routes(routes: &[OutputRoute]) -> Result<(), String> {
+    for (index, route) in routes.iter().enumerate() {
+        let mut has_pattern = false;
+        for (field, pattern) in route.patterns() {
+            if let Some(pattern) = pattern {
+                compile(pattern)
+                    .map_err(|e| format!("Invalid {} pattern in output route {}: {}", field, index + 1, e))?;
+                has_pattern = true;
+            }
+        }
+        if !has_pattern {
+            return Err(format!("Output route {} has nothing to match on", index + 1));
+        }
+        if route.output_path.is_none() && route.template_folder.is_none() && route.template_file.is_none() {
+            return Err(format!("Output route {} sets no output folder or template", index + 1));
+        }
+    }
+    Ok(())
+}
+
+/// The first enabled route matching the job
+pub fn route_for<'a>(config: &'a AppConfig, job: &DownloadJob) -> Option<&'a OutputRoute> {
+    config.output_routes.iter().find(|route| route.enabled && route.matches(job))
+}
+
+/// Where the job goes under the first route matching it now
+pub fn resolve(config: &AppConfig, job: &DownloadJob) -> RoutedOutput {
+    let route = route_for(config, job);
+    RoutedOutput {
+        output_path: match route.and_then(|route| route.output_path.as_ref()) {
+            Some(output_path) => config.output_path.join(output_path),
+            None => config.output_path.clone(),
+        },
+        template_folder: route.and_then(|route| route.template_folder.clone())
+            .unwrap_or_else(|| config.template_folder.clone()),
+        template_file: route.and_then(|route| route.template_file.clone())
+            .unwrap_or_else(|| config.template_file.clone()),
+    }
+}
+
+/// The output the job was dispatched with, or where it would go now
+fn routed_output(config: &AppConfig, job: &DownloadJob) -> RoutedOutput {
+    job.routed_output.clone().unwrap_or_else(|| resolve(config, job))
+}
+
+/// Apply the job's routed output to its config
+pub fn apply(config: &mut AppConfig, job: &DownloadJob) {
+    let routed = routed_output(config, job);
+    config.output_path = routed.output_path;
+    config.template_folder = routed.template_folder;
+    config.template_file = routed.template_file;
+}
+
+/// Folder the job downloads into
+pub fn output_path(config: &AppConfig, job: &DownloadJob) -> PathBuf {
+    routed_output(config, job).output_path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::state::JobMetadata;
+
+    fn route(artist: Option<&str>, album: Option<&str>, output_path: &str) -> OutputRoute {
+        OutputRoute {
+            artist: artist.map(str::to_string),
+            album: album.map(str::to_string),
+            url: None,
+            output_path: Some(PathBuf::from(output_path)),
+            template_folder: None,
+            template_file: None,
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn test_first_matching_route_applies() {
+        let mut config = AppConfig {
+            output_path: PathBuf::from("/music"),
+            output_routes: vec![
+                route(Some("^various artists$"), None, "Compilations"),
+                route(None, Some("live"), "/mnt/live"),
+            ],
+            ..AppConfig::default()
+        };
+        assert!(validate_routes(&config.output_routes).is_ok());
+
+        let mut job = DownloadJob::new("https://music.youtube.com/playlist?list=abc".to_string());
+        assert_eq!(output_path(&config, &job), PathBuf::from("/music"));
+
+        job.metadata = Some(JobMetadata {
+            artist: Some("Various Artists".to_string()),
+            album: Some("Live Hits".to_string()),
+            ..JobMetadata::default()
+        });
+        assert_eq!(output_path(&config, &job), PathBuf::from("/music/Compilations"));
+
+        config.output_routes[0].enabled = false;
+        apply(&mut config, &job);
+        assert_eq!(config.output_path, PathBuf::from("/mnt/live"));
+
+        // Once dispatched, the job keeps its output even when its metadata changes
+        job.routed_output = Some(resolve(&config, &job));
+        job.metadata = None;
+        config.output_path = PathBuf::from("/music");
+        assert_eq!(output_path(&config, &job), PathBuf::from("/mnt/live"));
+
+        assert!(validate_routes(&[route(Some("("), None, "x")]).is_err());
+        assert!(validate_routes(&[route(None, None, "x")]).is_err());
+    }
+}
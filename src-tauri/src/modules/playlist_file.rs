@@ -0,0 +1,128 @@
+use crate::modules::atomic_file;
+use crate::modules::disk_space;
+use crate::modules::job_groups::JobGroup;
+use crate::modules::library::sanitize_component;
+use crate::modules::state::{DownloadJob, JobStatus};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// One track line of a playlist file
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlaylistEntry {
+    pub path: PathBuf,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub duration_secs: Option<u32>,
+}
+
+impl PlaylistEntry {
+    /// The downloaded tracks of completed jobs, in the order given. Metadata
+    /// is only used for jobs that produced a single track.
+    pub fn for_jobs(jobs: &[&DownloadJob]) -> Vec<Self> {
+        let mut entries = Vec::new();
+        for job in jobs.iter().filter(|job| job.status == JobStatus::Completed) {
+            let files: Vec<&PathBuf> = job.output_files.iter()
+                .filter(|path| disk_space::is_media_file(path) && path.exists())
+                .collect();
+            let metadata = job.metadata.as_ref().filter(|_| files.len() == 1);
+            entries.extend(files.into_iter().map(|path| Self {
+                path: path.clone(),
+                title: metadata.and_then(|metadata| metadata.title.clone()),
+                artist: metadata.and_then(|metadata| metadata.artist.clone()),
+                duration_secs: metadata.and_then(|metadata| metadata.duration),
+            }));
+        }
+        entries
+    }
+}
+
+/// The deepest folder holding every entry, e.g. the album folder when a
+/// group is one album
+pub fn common_directory(entries: &[PlaylistEntry]) -> Option<PathBuf> {
+    let mut common = entries.first()?.path.parent()?.to_path_buf();
+    for entry in &entries[1..] {
+        while !entry.path.starts_with(&common) {
+            common = common.parent()?.to_path_buf();
+        }
+    }
+    Some(common)
+}
+
+/// `<group name>.m3u8` in the folder holding the group's tracks
+pub fn default_playlist_path(entries: &[PlaylistEntry], fallback_dir: &Path, group: &JobGroup) -> PathBuf {
+    let name = group.name.clone()
+        .unwrap_or_else(|| format!("Playlist {}", group.id.chars().take(8).collect::<String>()));
+    let dir = common_directory(entries).unwrap_or_else(|| fallback_dir.to_path_buf());
+    dir.join(format!("{}.m3u8", sanitize_component(&name)))
+}
+
+/// Extended M3U with UTF-8 paths. Tracks under `base_dir` are written
+/// relative to it so the playlist survives moving the folder.
+pub fn render_m3u8(name: Option<&str>, entries: &[PlaylistEntry], base_dir: &Path) -> String {
+    let mut content = String::from("#EXTM3U\n");
+    if let Some(name) = name {
+        content.push_str(&format!("#PLAYLIST:{}\n", name));
+    }
+    for entry in entries {
+        let label = match (&entry.artist, &entry.title) {
+            (Some(artist), Some(title)) => Some(format!("{} - {}", artist, title)),
+            (None, Some(title)) => Some(title.clone()),
+            _ => None,
+        };
+        if let Some(label) = label {
+            let duration = entry.duration_secs.map(i64::from).unwrap_or(-1);
+            content.push_str(&format!("#EXTINF:{},{}\n", duration, label));
+        }
+        let path = entry.path.strip_prefix(base_dir).unwrap_or(&entry.path);
+        content.push_str(&path.to_string_lossy());
+        content.push('\n');
+    }
+    content
+}
+
+/// Write the playlist to `path`, replacing any earlier version
+pub fn write_playlist(path: &Path, name: Option<&str>, entries: &[PlaylistEntry]) -> io::Result<()> {
+    let base_dir = path.parent().unwrap_or(Path::new(""));
+    atomic_file::write_atomic(path, render_m3u8(name, entries, base_dir).as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str, title: Option<&str>) -> PlaylistEntry {
+        PlaylistEntry {
+            path: PathBuf::from(path),
+            title: title.map(str::to_string),
+            artist: title.map(|_| "Artist".to_string()),
+            duration_secs: title.map(|_| 215),
+        }
+    }
+
+    #[test]
+    fn test_render_m3u8() {
+        let entries = vec![
+            entry("/music/Artist/Album/01 Intro.m4a", Some("Intro")),
+            entry("/music/Artist/Album/02 Song.m4a", None),
+            entry("/music/Other/Single.m4a", Some("Single")),
+        ];
+        assert_eq!(common_directory(&entries), Some(PathBuf::from("/music")));
+        assert_eq!(common_directory(&entries[..2]), Some(PathBuf::from("/music/Artist/Album")));
+
+        let content = render_m3u8(Some("Mix"), &entries, Path::new("/music"));
+        let expected = [
+            "#EXTM3U",
+            "#PLAYLIST:Mix",
+            "#EXTINF:215,Artist - Intro",
+            "Artist/Album/01 Intro.m4a",
+            "Artist/Album/02 Song.m4a",
+            "#EXTINF:215,Artist - Single",
+            "Other/Single.m4a",
+        ].join("\n") + "\n";
+        assert_eq!(content, expected);
+
+        // Tracks outside the playlist's folder keep their full path
+        let content = render_m3u8(None, &entries[2..], Path::new("/elsewhere"));
+        assert!(content.ends_with("/music/Other/Single.m4a\n"));
+    }
+}
@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+
+/// CPU and disk priority for gytmdl and ffmpeg. Processes they start
+/// themselves inherit it.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessPriority {
+    #[default]
+    Normal,
+    /// `nice 10` and best-effort I/O at the lowest level on Unix,
+    /// below normal priority on Windows
+    Low,
+    /// `nice 19` and idle I/O on Unix, idle priority on Windows; only runs
+    /// when nothing else wants the machine
+    Idle,
+}
+
+impl ProcessPriority {
+    /// Unix niceness of the child
+    #[cfg(unix)]
+    fn niceness(&self) -> libc::c_int {
+        match self {
+            ProcessPriority::Normal => 0,
+            ProcessPriority::Low => 10,
+            ProcessPriority::Idle => 19,
+        }
+    }
+
+    /// Linux I/O priority: class in the top bits, level below
+    #[cfg(target_os = "linux")]
+    fn io_priority(&self) -> libc::c_int {
+        const CLASS_SHIFT: libc::c_int = 13;
+        const CLASS_BEST_EFFORT: libc::c_int = 2;
+        const CLASS_IDLE: libc::c_int = 3;
+        match self {
+            ProcessPriority::Normal => 0,
+            ProcessPriority::Low => (CLASS_BEST_EFFORT << CLASS_SHIFT) | 7,
+            ProcessPriority::Idle => CLASS_IDLE << CLASS_SHIFT,
+        }
+    }
+
+    /// Windows priority class creation flag
+    #[cfg(windows)]
+    fn creation_flags(&self) -> u32 {
+        const BELOW_NORMAL_PRIORITY_CLASS: u32 = 0x0000_4000;
+        const IDLE_PRIORITY_CLASS: u32 = 0x0000_0040;
+        match self {
+            ProcessPriority::Normal => 0,
+            ProcessPriority::Low => BELOW_NORMAL_PRIORITY_CLASS,
+            ProcessPriority::Idle => IDLE_PRIORITY_CLASS,
+        }
+    }
+}
+
+/// Start `command` at `priority`. On Windows this sets the command's
+/// creation flags, replacing any set before.
+pub fn apply(command: &mut Command, priority: ProcessPriority) {
+    if priority == ProcessPriority::Normal {
+        return;
+    }
+
+    #[cfg(unix)]
+    {
+        let niceness = priority.niceness();
+        #[cfg(target_os = "linux")]
+        let io_priority = priority.io_priority();
+
+        // Runs in the child between fork and exec, so it only makes plain
+        // syscalls. Failures are ignored: the download still works at
+        // normal priority. An app already niced further is left as it is.
+        unsafe {
+            command.pre_exec(move || {
+                if libc::getpriority(libc::PRIO_PROCESS, 0) < niceness {
+                    libc::setpriority(libc::PRIO_PROCESS, 0, niceness);
+                }
+                #[cfg(target_os = "linux")]
+                {
+                    const IOPRIO_WHO_PROCESS: libc::c_int = 1;
+                    libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, 0, io_priority);
+                }
+                Ok(())
+            });
+        }
+    }
+
+    #[cfg(windows)]
+    command.creation_flags(priority.creation_flags());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    async fn niceness_of(priority: ProcessPriority) -> i32 {
+        let mut command = Command::new("nice");
+        apply(&mut command, priority);
+        let output = command.output().await.unwrap();
+        String::from_utf8_lossy(&output.stdout).trim().parse().unwrap()
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_low_priority_raises_niceness() {
+        let normal = niceness_of(ProcessPriority::Normal).await;
+        assert_eq!(niceness_of(ProcessPriority::Low).await, normal.max(10));
+        assert_eq!(niceness_of(ProcessPriority::Idle).await, 19);
+    }
+}
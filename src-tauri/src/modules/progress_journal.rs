@@ -0,0 +1,147 @@
+use crate::modules::state::Progress;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// Maximum number of progress entries kept per job
+const DEFAULT_CAPACITY: usize = 500;
+
+/// A single parsed progress update with the time it was seen
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub timestamp: DateTime<Utc>,
+    pub progress: Progress,
+}
+
+/// Per-job ring of progress updates for diagnosing stalls after the fact.
+/// Journals live in memory while a job runs and are written to disk if it fails.
+pub struct ProgressJournal {
+    journals_dir: PathBuf,
+    capacity: usize,
+    entries: HashMap<String, VecDeque<JournalEntry>>,
+}
+
+impl ProgressJournal {
+    /// Create a ProgressJournal persisting to the app data directory
+    pub fn new() -> Self {
+        let journals_dir = std::env::current_dir()
+            .unwrap_or_else(|_| PathBuf::from("."))
+            .join(".gytmdl-gui")
+            .join("journals");
+
+        Self::with_journals_dir(journals_dir)
+    }
+
+    /// Create a ProgressJournal persisting to a specific directory
+    pub fn with_journals_dir(journals_dir: PathBuf) -> Self {
+        Self {
+            journals_dir,
+            capacity: DEFAULT_CAPACITY,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Record a progress update for a job, dropping the oldest entry when full
+    pub fn record(&mut self, job_id: &str, progress: &Progress) {
+        let ring = self.entries.entry(job_id.to_string()).or_default();
+        if ring.len() >= self.capacity {
+            ring.pop_front();
+        }
+        ring.push_back(JournalEntry {
+            timestamp: Utc::now(),
+            progress: progress.clone(),
+        });
+    }
+
+    /// Get the journal for a job, from memory or from a persisted failure
+    pub fn history(&self, job_id: &str) -> Vec<JournalEntry> {
+        if let Some(ring) = self.entries.get(job_id) {
+            return ring.iter().cloned().collect();
+        }
+
+        fs::read_to_string(self.journal_path(job_id))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Write a job's journal to disk and release it from memory
+    pub fn persist(&mut self, job_id: &str) -> Result<(), io::Error> {
+        let Some(ring) = self.entries.remove(job_id) else {
+            return Ok(());
+        };
+
+        fs::create_dir_all(&self.journals_dir)?;
+        let entries: Vec<JournalEntry> = ring.into_iter().collect();
+        let content = serde_json::to_string_pretty(&entries)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        fs::write(self.journal_path(job_id), content)
+    }
+
+    /// Drop a job's in-memory journal without persisting it
+    pub fn discard(&mut self, job_id: &str) {
+        self.entries.remove(job_id);
+    }
+
+    fn journal_path(&self, job_id: &str) -> PathBuf {
+        self.journals_dir.join(format!("{}.json", job_id))
+    }
+}
+
+impl Default for ProgressJournal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_record_is_bounded() {
+        let temp_dir = tempdir().unwrap();
+        let mut journal = ProgressJournal::with_journals_dir(temp_dir.path().to_path_buf());
+
+        for i in 0..DEFAULT_CAPACITY + 10 {
+            let progress = Progress {
+                percentage: Some(i as f32),
+                ..Progress::default()
+            };
+            journal.record("job-1", &progress);
+        }
+
+        let history = journal.history("job-1");
+        assert_eq!(history.len(), DEFAULT_CAPACITY);
+        assert_eq!(history[0].progress.percentage, Some(10.0));
+    }
+
+    #[test]
+    fn test_persist_and_reload() {
+        let temp_dir = tempdir().unwrap();
+        let mut journal = ProgressJournal::with_journals_dir(temp_dir.path().to_path_buf());
+
+        journal.record("job-1", &Progress::default());
+        journal.record("job-1", &Progress::default());
+        journal.persist("job-1").unwrap();
+
+        // Reloaded from disk by a fresh journal
+        let reloaded = ProgressJournal::with_journals_dir(temp_dir.path().to_path_buf());
+        assert_eq!(reloaded.history("job-1").len(), 2);
+    }
+
+    #[test]
+    fn test_discard() {
+        let temp_dir = tempdir().unwrap();
+        let mut journal = ProgressJournal::with_journals_dir(temp_dir.path().to_path_buf());
+
+        journal.record("job-1", &Progress::default());
+        journal.discard("job-1");
+        assert!(journal.history("job-1").is_empty());
+        assert!(!temp_dir.path().join("job-1.json").exists());
+    }
+}
@@ -1,16 +1,47 @@
-use crate::modules::state::{Progress, DownloadStage};
+use crate::modules::state::{Progress, DownloadStage, PlaylistProgress};
 use regex::Regex;
 use std::sync::OnceLock;
 
 /// Progress parser for gytmdl output
 pub struct ProgressParser;
 
+/// Parses the output of one process, keeping what later lines need to be
+/// read, like the length of the track being remuxed
+#[derive(Debug, Default)]
+pub struct ProgressStream {
+    /// Length of the track being downloaded, for ffmpeg remux progress
+    track_secs: Option<f64>,
+}
+
+impl ProgressStream {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Turn ffmpeg `time=` lines into a percentage of a track this long
+    pub fn with_track_duration(mut self, track_secs: Option<u32>) -> Self {
+        self.track_secs = track_secs.filter(|secs| *secs > 0).map(f64::from);
+        self
+    }
+
+    /// Parse a line of output, from stdout or stderr
+    pub fn parse_line(&self, line: &str) -> Option<Progress> {
+        // ffmpeg stats gytmdl passes through while remuxing
+        ProgressParser::parse_ffmpeg_progress(line, self.track_secs)
+            .or_else(|| ProgressParser::parse_output(line))
+    }
+}
+
 impl ProgressParser {
     /// Parse a line of gytmdl output and extract progress information
     pub fn parse_output(output: &str) -> Option<Progress> {
         let line = output.trim();
         
         // Try different parsing strategies in order of specificity
+        if let Some(progress) = Self::parse_track_progress(line) {
+            return Some(progress);
+        }
+
         if let Some(progress) = Self::parse_download_progress(line) {
             return Some(progress);
         }
@@ -34,23 +65,150 @@ impl ProgressParser {
     fn parse_download_progress(line: &str) -> Option<Progress> {
         static DOWNLOAD_REGEX: OnceLock<Regex> = OnceLock::new();
         let regex = DOWNLOAD_REGEX.get_or_init(|| {
-            Regex::new(r"\[download\]\s+(\d+(?:\.\d+)?)%\s+of\s+[\d.]+\w+(?:\s+at\s+[\d.]+\w+/s)?(?:\s+ETA\s+[\d:]+)?(?:\s+in\s+[\d:]+)?").unwrap()
+            Regex::new(r"\[download\]\s+(\d+(?:\.\d+)?)%\s+of\s+~?\s*([\d.]+)\s*(\w+)(?:\s+at\s+([\d.]+)\s*(\w+)/s)?(?:\s+ETA\s+([\d:]+))?(?:\s+in\s+[\d:]+)?").unwrap()
         });
 
-        if let Some(captures) = regex.captures(line) {
-            if let Some(percentage_str) = captures.get(1) {
-                if let Ok(percentage) = percentage_str.as_str().parse::<f32>() {
-                    return Some(Progress {
-                        stage: DownloadStage::DownloadingAudio,
-                        percentage: Some(percentage),
-                        current_step: line.to_string(),
-                        total_steps: None,
-                        current_step_index: None,
-                    });
-                }
-            }
+        let captures = regex.captures(line)?;
+        let percentage = captures.get(1)?.as_str().parse::<f32>().ok()?;
+
+        let total_bytes = Self::parse_size(&captures[2], &captures[3]);
+        let downloaded_bytes = total_bytes
+            .map(|total| (total as f64 * (percentage.clamp(0.0, 100.0) as f64) / 100.0).round() as u64);
+        let speed_bytes_per_sec = match (captures.get(4), captures.get(5)) {
+            (Some(value), Some(unit)) => Self::parse_size(value.as_str(), unit.as_str()),
+            _ => None,
+        };
+        let eta_seconds = captures.get(6).and_then(|eta| Self::parse_clock(eta.as_str()));
+
+        Some(Progress {
+            stage: DownloadStage::DownloadingAudio,
+            percentage: Some(percentage),
+            current_step: line.to_string(),
+            total_steps: None,
+            current_step_index: None,
+            speed_bytes_per_sec,
+            eta_seconds,
+            downloaded_bytes,
+            total_bytes,
+            playlist: None,
+        })
+    }
+
+    /// Parse an ffmpeg stats line printed while remuxing. The percentage is
+    /// the position reached in a track of `track_secs`, when known.
+    /// Example:
+    /// "size=    3072kB time=00:01:23.45 bitrate= 302.1kbits/s speed=45.6x"
+    pub fn parse_ffmpeg_progress(line: &str, track_secs: Option<f64>) -> Option<Progress> {
+        static FFMPEG_REGEX: OnceLock<Regex> = OnceLock::new();
+        let regex = FFMPEG_REGEX.get_or_init(|| {
+            Regex::new(r"(?:^|\s)time=\s*(\d+):(\d{2}):(\d{2}(?:\.\d+)?)\b.*\bbitrate=(?:.*\bspeed=\s*([\d.]+)x)?").unwrap()
+        });
+
+        let captures = regex.captures(line.trim())?;
+        let hours = captures[1].parse::<f64>().ok()?;
+        let minutes = captures[2].parse::<f64>().ok()?;
+        let seconds = captures[3].parse::<f64>().ok()?;
+        let position = hours * 3600.0 + minutes * 60.0 + seconds;
+        let speed = captures.get(4).and_then(|speed| speed.as_str().parse::<f64>().ok()).filter(|speed| *speed > 0.0);
+
+        let percentage = track_secs.map(|track_secs| (position / track_secs * 100.0).clamp(0.0, 100.0) as f32);
+        let eta_seconds = track_secs.zip(speed)
+            .map(|(track_secs, speed)| ((track_secs - position).max(0.0) / speed).round() as u64);
+        let current_step = match percentage {
+            Some(percentage) => format!("Remuxing ({:.1}%)", percentage),
+            None => format!("Remuxing ({:.0}s processed)", position),
+        };
+
+        Some(Progress {
+            stage: DownloadStage::Remuxing,
+            percentage,
+            current_step,
+            total_steps: None,
+            current_step_index: None,
+            speed_bytes_per_sec: None,
+            eta_seconds,
+            downloaded_bytes: None,
+            total_bytes: None,
+            playlist: None,
+        })
+    }
+
+    /// Parse the start of a track within a playlist or album job
+    /// Examples:
+    /// "Downloading track 3 of 40: Song Title"
+    /// "(Track 3/40 from URL 1/1) Downloading \"Song Title\""
+    fn parse_track_progress(line: &str) -> Option<Progress> {
+        static TRACK_REGEX: OnceLock<Regex> = OnceLock::new();
+        let regex = TRACK_REGEX.get_or_init(|| {
+            Regex::new(r#"(?i)(?:Downloading\s+track\s+(\d+)\s+of\s+(\d+)(?::\s*(.+))?|\(Track\s+(\d+)/(\d+)[^)]*\)\s*Downloading(?:\s+"(.+)")?)"#).unwrap()
+        });
+
+        let captures = regex.captures(line)?;
+        let (current, total, title) = match (captures.get(1), captures.get(2)) {
+            (Some(current), Some(total)) => (current, total, captures.get(3)),
+            _ => (captures.get(4)?, captures.get(5)?, captures.get(6)),
+        };
+        let current_track = current.as_str().parse::<u32>().ok()?;
+        let total_tracks = total.as_str().parse::<u32>().ok()?;
+        let track_title = title
+            .map(|title| title.as_str().trim().to_string())
+            .filter(|title| !title.is_empty());
+
+        // Share of the job finished before this track started
+        let percentage = if total_tracks > 0 {
+            Some(current_track.saturating_sub(1) as f32 / total_tracks as f32 * 100.0)
+        } else {
+            None
+        };
+
+        Some(Progress {
+            stage: DownloadStage::DownloadingAudio,
+            percentage,
+            current_step: line.to_string(),
+            total_steps: Some(total_tracks),
+            current_step_index: Some(current_track),
+            speed_bytes_per_sec: None,
+            eta_seconds: None,
+            downloaded_bytes: None,
+            total_bytes: None,
+            playlist: Some(PlaylistProgress {
+                current_track,
+                total_tracks,
+                track_title,
+            }),
+        })
+    }
+
+    /// Keep the last seen playlist position on progress lines that don't
+    /// carry one, such as the per-track `[download]` percentages
+    pub fn carry_playlist(progress: &mut Progress, last: &mut Option<PlaylistProgress>) {
+        match &progress.playlist {
+            Some(playlist) => *last = Some(playlist.clone()),
+            None => progress.playlist = last.clone(),
         }
-        None
+    }
+
+    /// Convert a yt-dlp size such as "3.45" + "MiB" to bytes
+    fn parse_size(value: &str, unit: &str) -> Option<u64> {
+        let value = value.parse::<f64>().ok()?;
+        let multiplier = match unit {
+            "B" => 1.0,
+            "KiB" => 1024.0,
+            "MiB" => 1024.0 * 1024.0,
+            "GiB" => 1024.0 * 1024.0 * 1024.0,
+            "KB" | "kB" => 1000.0,
+            "MB" => 1000.0 * 1000.0,
+            "GB" => 1000.0 * 1000.0 * 1000.0,
+            _ => return None,
+        };
+        Some((value * multiplier).round() as u64)
+    }
+
+    /// Convert an "MM:SS" or "HH:MM:SS" clock to seconds
+    fn parse_clock(clock: &str) -> Option<u64> {
+        clock.split(':').try_fold(0u64, |total, part| {
+            part.parse::<u64>().ok().map(|value| total * 60 + value)
+        })
     }
 
     /// Parse stage indicators and progress from various gytmdl output patterns
@@ -63,6 +221,11 @@ impl ProgressParser {
                 current_step: line.to_string(),
                 total_steps: None,
                 current_step_index: None,
+                speed_bytes_per_sec: None,
+                eta_seconds: None,
+                downloaded_bytes: None,
+                total_bytes: None,
+                playlist: None,
             });
         }
 
@@ -75,6 +238,11 @@ impl ProgressParser {
                 current_step: line.to_string(),
                 total_steps: None,
                 current_step_index: None,
+                speed_bytes_per_sec: None,
+                eta_seconds: None,
+                downloaded_bytes: None,
+                total_bytes: None,
+                playlist: None,
             });
         }
 
@@ -86,6 +254,11 @@ impl ProgressParser {
                 current_step: line.to_string(),
                 total_steps: None,
                 current_step_index: None,
+                speed_bytes_per_sec: None,
+                eta_seconds: None,
+                downloaded_bytes: None,
+                total_bytes: None,
+                playlist: None,
             });
         }
 
@@ -98,6 +271,11 @@ impl ProgressParser {
                 current_step: line.to_string(),
                 total_steps: None,
                 current_step_index: None,
+                speed_bytes_per_sec: None,
+                eta_seconds: None,
+                downloaded_bytes: None,
+                total_bytes: None,
+                playlist: None,
             });
         }
 
@@ -111,6 +289,11 @@ impl ProgressParser {
                 current_step: line.to_string(),
                 total_steps: None,
                 current_step_index: None,
+                speed_bytes_per_sec: None,
+                eta_seconds: None,
+                downloaded_bytes: None,
+                total_bytes: None,
+                playlist: None,
             });
         }
 
@@ -124,6 +307,11 @@ impl ProgressParser {
                 current_step: line.to_string(),
                 total_steps: None,
                 current_step_index: None,
+                speed_bytes_per_sec: None,
+                eta_seconds: None,
+                downloaded_bytes: None,
+                total_bytes: None,
+                playlist: None,
             });
         }
 
@@ -166,6 +354,11 @@ impl ProgressParser {
                     current_step: line.to_string(),
                     total_steps: Some(total_steps),
                     current_step_index: Some(current_step),
+                    speed_bytes_per_sec: None,
+                    eta_seconds: None,
+                    downloaded_bytes: None,
+                    total_bytes: None,
+                    playlist: None,
                 });
             }
         }
@@ -202,6 +395,11 @@ impl ProgressParser {
             current_step: line.to_string(),
             total_steps: None,
             current_step_index: None,
+            speed_bytes_per_sec: None,
+            eta_seconds: None,
+            downloaded_bytes: None,
+            total_bytes: None,
+            playlist: None,
         })
     }
 
@@ -237,6 +435,11 @@ impl ProgressParser {
             current_step: format!("Error: {}", error_line),
             total_steps: None,
             current_step_index: None,
+            speed_bytes_per_sec: None,
+            eta_seconds: None,
+            downloaded_bytes: None,
+            total_bytes: None,
+            playlist: None,
         }
     }
 
@@ -248,6 +451,11 @@ impl ProgressParser {
             current_step: "Download completed successfully".to_string(),
             total_steps: None,
             current_step_index: None,
+            speed_bytes_per_sec: None,
+            eta_seconds: None,
+            downloaded_bytes: None,
+            total_bytes: None,
+            playlist: None,
         }
     }
 
@@ -259,6 +467,11 @@ impl ProgressParser {
             current_step: "Initializing download...".to_string(),
             total_steps: None,
             current_step_index: None,
+            speed_bytes_per_sec: None,
+            eta_seconds: None,
+            downloaded_bytes: None,
+            total_bytes: None,
+            playlist: None,
         }
     }
 
@@ -299,6 +512,15 @@ impl ProgressParser {
         lower_line.starts_with("fatal:")
     }
 
+    /// Check if an error comes from YouTube's age gate
+    pub fn is_age_restricted_error(line: &str) -> bool {
+        let lower_line = line.to_lowercase();
+        lower_line.contains("confirm your age") ||
+        lower_line.contains("age-restricted") ||
+        lower_line.contains("age restricted") ||
+        lower_line.contains("inappropriate for some users")
+    }
+
     /// Check if a line indicates successful completion
     pub fn is_completion_line(line: &str) -> bool {
         let lower_line = line.to_lowercase();
@@ -348,6 +570,58 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_transfer_stats() {
+        let progress = ProgressParser::parse_download_progress(
+            "[download]  50.0% of ~  4.00MiB at  1.50MiB/s ETA 01:05 (frag 3/10)"
+        ).unwrap();
+        assert_eq!(progress.total_bytes, Some(4 * 1024 * 1024));
+        assert_eq!(progress.downloaded_bytes, Some(2 * 1024 * 1024));
+        assert_eq!(progress.speed_bytes_per_sec, Some(1572864));
+        assert_eq!(progress.eta_seconds, Some(65));
+
+        // Unknown speed and ETA are left empty
+        let progress = ProgressParser::parse_download_progress(
+            "[download]   1.0% of 3.45MiB at Unknown B/s ETA Unknown"
+        ).unwrap();
+        assert_eq!(progress.total_bytes, Some(3617587));
+        assert!(progress.speed_bytes_per_sec.is_none());
+        assert!(progress.eta_seconds.is_none());
+
+        assert_eq!(ProgressParser::parse_clock("1:02:03"), Some(3723));
+        assert_eq!(ProgressParser::parse_size("1.5", "MB"), Some(1_500_000));
+    }
+
+    #[test]
+    fn test_parse_track_progress() {
+        let progress = ProgressParser::parse_output("Downloading track 3 of 40: Song Title").unwrap();
+        let playlist = progress.playlist.clone().unwrap();
+        assert_eq!(playlist.current_track, 3);
+        assert_eq!(playlist.total_tracks, 40);
+        assert_eq!(playlist.track_title.as_deref(), Some("Song Title"));
+        assert_eq!(progress.percentage, Some(5.0));
+
+        let progress = ProgressParser::parse_output(
+            "[INFO     12:00:00] (Track 2/4 from URL 1/1) Downloading \"Another Song\""
+        ).unwrap();
+        assert_eq!(progress.playlist, Some(PlaylistProgress {
+            current_track: 2,
+            total_tracks: 4,
+            track_title: Some("Another Song".to_string()),
+        }));
+
+        let progress = ProgressParser::parse_output("Downloading track 1 of 2").unwrap();
+        assert!(progress.playlist.unwrap().track_title.is_none());
+
+        // Later per-track lines inherit the playlist position
+        let mut last = None;
+        let mut track = ProgressParser::parse_output("Downloading track 7 of 9").unwrap();
+        ProgressParser::carry_playlist(&mut track, &mut last);
+        let mut download = ProgressParser::parse_output("[download] 50.0% of 3.00MiB").unwrap();
+        ProgressParser::carry_playlist(&mut download, &mut last);
+        assert_eq!(download.playlist.unwrap().current_track, 7);
+    }
+
     #[test]
     fn test_parse_stage_indicators() {
         let test_cases = vec![
@@ -449,6 +723,14 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_is_age_restricted_error() {
+        assert!(ProgressParser::is_age_restricted_error(
+            "ERROR: [youtube] abc123: Sign in to confirm your age. This video may be inappropriate for some users."
+        ));
+        assert!(!ProgressParser::is_age_restricted_error("ERROR: Network timeout"));
+    }
+
     #[test]
     fn test_is_error_line() {
         let error_lines = vec![
@@ -548,6 +830,25 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_ffmpeg_progress() {
+        let line = "size=    3072kB time=00:01:40.00 bitrate= 302.1kbits/s speed=20.0x";
+        let progress = ProgressParser::parse_ffmpeg_progress(line, Some(200.0)).unwrap();
+        assert!(matches!(progress.stage, DownloadStage::Remuxing));
+        assert_eq!(progress.percentage, Some(50.0));
+        assert_eq!(progress.eta_seconds, Some(5));
+
+        // Without the track length the stage is still reported
+        let progress = ProgressParser::parse_ffmpeg_progress(line, None).unwrap();
+        assert_eq!(progress.percentage, None);
+        assert!(ProgressParser::parse_ffmpeg_progress("size=N/A time=N/A bitrate=N/A speed=N/A", Some(200.0)).is_none());
+        assert!(ProgressParser::parse_ffmpeg_progress("Remuxing audio stream", Some(200.0)).is_none());
+
+        let stream = ProgressStream::new().with_track_duration(Some(100));
+        let progress = stream.parse_line("size=1024kB time=00:00:25.00 bitrate=320.0kbits/s speed=10x").unwrap();
+        assert_eq!(progress.percentage, Some(25.0));
+    }
+
     #[test]
     fn test_create_progress_states() {
         let completed = ProgressParser::create_completed_progress();
@@ -1,10 +1,49 @@
-use crate::modules::state::{AppState, DownloadJob, JobStatus};
-use crate::modules::gytmdl_wrapper::{GytmdlWrapper, GytmdlError};
-use crate::modules::progress_parser::ProgressParser;
+use crate::modules::state::{AppConfig, AppState, CoverFormat, DependencyState, DownloadJob, DownloadStage, JobStatus, Progress};
+use crate::modules::gytmdl_wrapper::GytmdlError;
+use crate::modules::binary_locator::BinaryLocator;
+use crate::modules::process_runner::{ProcessRunner, GytmdlProcess, OutputLine, OutputStream};
+use crate::modules::progress_parser::{ProgressParser, ProgressStream};
+use crate::modules::events::EventEmitter;
+use crate::modules::job_events::{JobBus, JobEvent};
+use crate::modules::notifier::Notifier;
+use crate::modules::disk_space::{self, DiskSpaceMonitor, SpaceProjection};
+use crate::modules::progress_journal::{JournalEntry, ProgressJournal};
+use crate::modules::download_cache::{CacheKey, DownloadCache};
+use crate::modules::library_integrity::{self, IntegrityReport, LibraryManifest};
+use crate::modules::library::{self, OrganizedFile};
+use crate::modules::converter::Converter;
+use crate::modules::loudness;
+use crate::modules::verification::{self, ExpectedAudio, VerificationReport, VerificationStatus};
+use crate::modules::cover_art::{self, CoverError, CoverOptions, CoverSource, CoverUpdate};
+use crate::modules::playlist_file::{self, PlaylistEntry};
+use crate::modules::job_groups::JobGroup;
+use crate::modules::history::HistoryStore;
+use crate::modules::runtime_settings::{self, RuntimeSettings, RuntimeSettingsResult};
+use crate::modules::config_manager::ConfigManager;
+use crate::modules::output_routing;
+use crate::modules::listen_log;
+use crate::modules::track_selection;
+use crate::modules::filename_rules::{self, FilenameRules};
+use crate::modules::sidecar_manager::SidecarManager;
+use crate::modules::sidecar_isolation::SidecarIsolation;
+use crate::modules::job_logs::{JobLogs, LogLine, LogStream};
+use crate::modules::session_report::{self, SessionTracker};
+use crate::modules::throughput::{QueueForecast, RemainingWork, ThroughputTracker};
+use crate::modules::queue_metrics::{MetricsCollector, QueueMetrics};
+use crate::modules::event_batcher::DEFAULT_BATCH_INTERVAL;
+use crate::modules::debug_logger::DEBUG_LOGGER;
+use crate::modules::self_test::DegradedFlags;
+use crate::modules::watchdog::Watchdog;
+use crate::modules::dry_run;
+use crate::modules::dispatch_slots::DispatchSlots;
+use crate::modules::speed_schedule::{self, ScheduleWindow};
+use crate::modules::metadata_fetcher::MetadataFetcher;
+use crate::modules::error_classifier::{self, ClassifiedError, JobErrorKind};
 use std::sync::Arc;
-use tokio::sync::{Mutex, mpsc, RwLock};
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use tokio::sync::{Mutex, Notify, OwnedSemaphorePermit, mpsc, oneshot, watch, RwLock};
 use tokio::task::JoinSet;
-use tokio::time::{sleep, Duration};
+use tokio::time::{sleep, Duration, Instant};
 use std::collections::HashMap;
 
 /// Represents a job submission request
@@ -14,6 +53,14 @@ pub struct JobSubmission {
     pub retry_count: u32,
 }
 
+/// Requests handled by the queue's control task, one at a time
+enum QueueControl {
+    ApplySettings {
+        settings: RuntimeSettings,
+        reply: oneshot::Sender<Result<RuntimeSettingsResult, String>>,
+    },
+}
+
 /// Represents the result of a job execution
 #[derive(Debug)]
 pub enum JobResult {
@@ -22,332 +69,1779 @@ pub enum JobResult {
     Cancelled(String),
 }
 
+/// How long a cancelled worker gets to kill its process before the task is aborted
+const CANCEL_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long output may still arrive after gytmdl exits before the rest is dropped
+const OUTPUT_DRAIN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Longest the dispatcher waits before looking at the speed schedule and
+/// scheduled jobs again
+const SCHEDULE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How often the dispatcher looks again at jobs waiting on other jobs
+const DEPENDENCY_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A dispatched job's worker task and the signal used to cancel it
+struct RunningJob {
+    handle: tokio::task::JoinHandle<()>,
+    cancel_tx: watch::Sender<bool>,
+}
+
+impl RunningJob {
+    /// Ask the worker to kill its process, aborting the task if it doesn't finish in time.
+    /// Returns whether the worker finished on its own, and so recorded the job.
+    async fn cancel(self) -> bool {
+        let _ = self.cancel_tx.send(true);
+        let abort_handle = self.handle.abort_handle();
+        match tokio::time::timeout(CANCEL_TIMEOUT, self.handle).await {
+            Ok(joined) => joined.is_ok(),
+            Err(_) => {
+                abort_handle.abort();
+                false
+            }
+        }
+    }
+}
+
+type RunningJobs = Arc<Mutex<HashMap<String, RunningJob>>>;
+
+/// Shared services handed to every worker task
+#[derive(Clone)]
+struct WorkerServices {
+    events: EventEmitter,
+    /// Applies job status and progress changes and emits them
+    bus: JobBus,
+    notifier: Notifier,
+    disk_monitor: Arc<Mutex<DiskSpaceMonitor>>,
+    journal: Arc<Mutex<ProgressJournal>>,
+    download_cache: Arc<Mutex<DownloadCache>>,
+    library: Arc<Mutex<LibraryManifest>>,
+    job_logs: Arc<Mutex<JobLogs>>,
+    throughput: Arc<Mutex<ThroughputTracker>>,
+    metrics: Arc<Mutex<MetricsCollector>>,
+    /// Locked after the state lock; see `QueueManager::session`
+    session: Arc<Mutex<SessionTracker>>,
+    is_paused: Arc<watch::Sender<bool>>,
+    /// Downloads in a row that failed with 403/sign-in errors
+    auth_failures: Arc<AtomicU32>,
+    job_sender: mpsc::UnboundedSender<JobSubmission>,
+}
+
 /// Manages the download queue with concurrent processing
 pub struct QueueManager {
     state: Arc<RwLock<AppState>>,
-    gytmdl_wrapper: Arc<GytmdlWrapper>,
-    concurrent_limit: usize,
+    process_runner: Arc<ProcessRunner>,
+    /// Configured limit, applied to `slots` by the dispatch loop unless a
+    /// speed schedule window overrides it
+    concurrent_limit: Arc<AtomicUsize>,
+    slots: Arc<DispatchSlots>,
+    /// Wakes the dispatch loop to re-read limits and schedules
+    wake: Arc<Notify>,
     job_sender: mpsc::UnboundedSender<JobSubmission>,
     job_receiver: Arc<Mutex<mpsc::UnboundedReceiver<JobSubmission>>>,
-    worker_pool: Arc<Mutex<JoinSet<JobResult>>>,
-    running_jobs: Arc<Mutex<HashMap<String, tokio::task::JoinHandle<()>>>>,
-    is_paused: Arc<RwLock<bool>>,
-    is_shutdown: Arc<RwLock<bool>>,
+    control_sender: mpsc::UnboundedSender<QueueControl>,
+    control_receiver: Arc<Mutex<mpsc::UnboundedReceiver<QueueControl>>>,
+    running_jobs: RunningJobs,
+    is_paused: Arc<watch::Sender<bool>>,
+    is_shutdown: Arc<watch::Sender<bool>>,
+    events: EventEmitter,
+    notifier: Notifier,
+    disk_monitor: Arc<Mutex<DiskSpaceMonitor>>,
+    journal: Arc<Mutex<ProgressJournal>>,
+    download_cache: Arc<Mutex<DownloadCache>>,
+    library: Arc<Mutex<LibraryManifest>>,
+    job_logs: Arc<Mutex<JobLogs>>,
+    throughput: Arc<Mutex<ThroughputTracker>>,
+    metrics: Arc<Mutex<MetricsCollector>>,
+    /// Jobs finished since the queue last drained. Always locked after the
+    /// state lock, never before it: a finished job is recorded under the same
+    /// state lock that settles it, so `finish_session_if_drained` can't close
+    /// the session between the job settling and it being counted.
+    session: Arc<Mutex<SessionTracker>>,
+    auth_failures: Arc<AtomicU32>,
 }
 
 impl QueueManager {
     /// Create a new QueueManager with the specified concurrent limit
     pub fn new(state: Arc<RwLock<AppState>>, concurrent_limit: usize) -> Result<Self, GytmdlError> {
-        let gytmdl_wrapper = Arc::new(GytmdlWrapper::new()?);
+        let process_runner = Arc::new(ProcessRunner::new(&BinaryLocator::detect()?));
         let (job_sender, job_receiver) = mpsc::unbounded_channel();
+        let (control_sender, control_receiver) = mpsc::unbounded_channel();
         
         Ok(Self {
             state,
-            gytmdl_wrapper,
-            concurrent_limit,
+            process_runner,
+            concurrent_limit: Arc::new(AtomicUsize::new(concurrent_limit)),
+            slots: Arc::new(DispatchSlots::new(concurrent_limit)),
+            wake: Arc::new(Notify::new()),
             job_sender,
             job_receiver: Arc::new(Mutex::new(job_receiver)),
-            worker_pool: Arc::new(Mutex::new(JoinSet::new())),
+            control_sender,
+            control_receiver: Arc::new(Mutex::new(control_receiver)),
             running_jobs: Arc::new(Mutex::new(HashMap::new())),
-            is_paused: Arc::new(RwLock::new(false)),
-            is_shutdown: Arc::new(RwLock::new(false)),
+            is_paused: Arc::new(watch::Sender::new(false)),
+            is_shutdown: Arc::new(watch::Sender::new(false)),
+            events: EventEmitter::disabled(),
+            notifier: Notifier::disabled(),
+            disk_monitor: Arc::new(Mutex::new(DiskSpaceMonitor::new())),
+            journal: Arc::new(Mutex::new(ProgressJournal::new())),
+            download_cache: Arc::new(Mutex::new(DownloadCache::new())),
+            library: Arc::new(Mutex::new(LibraryManifest::new())),
+            job_logs: Arc::new(Mutex::new(JobLogs::new())),
+            throughput: Arc::new(Mutex::new(ThroughputTracker::new())),
+            metrics: Arc::new(Mutex::new(MetricsCollector::new())),
+            session: Arc::new(Mutex::new(SessionTracker::new())),
+            auth_failures: Arc::new(AtomicU32::new(0)),
         })
     }
 
-    /// Start the queue manager processing loop
+    /// Create a new QueueManager that emits job events to the frontend
+    pub fn with_app_handle(
+        state: Arc<RwLock<AppState>>,
+        concurrent_limit: usize,
+        app_handle: tauri::AppHandle,
+    ) -> Result<Self, GytmdlError> {
+        let mut manager = Self::new(state, concurrent_limit)?;
+        manager.events = EventEmitter::new(app_handle.clone()).with_batching(DEFAULT_BATCH_INTERVAL);
+        manager.notifier = Notifier::new(app_handle);
+        Ok(manager)
+    }
+
+    /// Run sidecar processes in `isolation`; call before `start`
+    pub fn with_isolation(mut self, isolation: SidecarIsolation) -> Self {
+        self.process_runner = Arc::new((*self.process_runner).clone().with_isolation(isolation));
+        self
+    }
+
+    /// Start the queue manager dispatch loop. The loop sleeps until something
+    /// can change what it does: a submission, a freed concurrency slot, a
+    /// pause/resume/shutdown signal, or a schedule coming due.
     pub async fn start(&self) -> Result<(), GytmdlError> {
         let state = Arc::clone(&self.state);
         let job_receiver = Arc::clone(&self.job_receiver);
-        let _worker_pool = Arc::clone(&self.worker_pool);
         let running_jobs = Arc::clone(&self.running_jobs);
         let is_paused = Arc::clone(&self.is_paused);
         let is_shutdown = Arc::clone(&self.is_shutdown);
-        let gytmdl_wrapper = Arc::clone(&self.gytmdl_wrapper);
-        let concurrent_limit = self.concurrent_limit;
+        let slots = Arc::clone(&self.slots);
+        let wake = Arc::clone(&self.wake);
+        let process_runner = Arc::clone(&self.process_runner);
+        let concurrent_limit = Arc::clone(&self.concurrent_limit);
+        let events = self.events.clone();
+        let notifier = self.notifier.clone();
+        let disk_monitor = Arc::clone(&self.disk_monitor);
+        let journal = Arc::clone(&self.journal);
+        let download_cache = Arc::clone(&self.download_cache);
+        let library = Arc::clone(&self.library);
+        let job_logs = Arc::clone(&self.job_logs);
+        let throughput = Arc::clone(&self.throughput);
+        let metrics = Arc::clone(&self.metrics);
+        let session = Arc::clone(&self.session);
+        let auth_failures = Arc::clone(&self.auth_failures);
+        let job_sender = self.job_sender.clone();
+        let bus = self.bus();
 
         tokio::spawn(async move {
+            let mut paused_rx = is_paused.subscribe();
+            let mut shutdown_rx = is_shutdown.subscribe();
+            let mut receiver = job_receiver.lock().await;
+            // Submissions received but not yet dispatched
+            let mut pending: Vec<JobSubmission> = Vec::new();
+            let mut active_window: Option<ScheduleWindow> = None;
+
             loop {
-                // Check if we should shutdown
-                if *is_shutdown.read().await {
+                if *shutdown_rx.borrow_and_update() {
                     break;
                 }
 
-                // Check if we're paused
-                if *is_paused.read().await {
-                    sleep(Duration::from_millis(100)).await;
-                    continue;
-                }
+                // Wait for resume
+                if *paused_rx.borrow_and_update() {
+                    tokio::select! {
+                        _ = paused_rx.changed() => {}
+                        _ = shutdown_rx.changed() => {}
+                    }
+                    continue;
+                }
+
+                // Hold everything while offline, even if the user resumed: jobs
+                // requeued after an outage would otherwise fail again at once
+                if state.read().await.degraded.contains(DegradedFlags::OFFLINE) {
+                    tokio::select! {
+                        _ = sleep(SCHEDULE_CHECK_INTERVAL) => {}
+                        _ = wake.notified() => {}
+                        _ = paused_rx.changed() => {}
+                        _ = shutdown_rx.changed() => {}
+                    }
+                    continue;
+                }
+
+                // Hold everything until the scheduled queue start
+                let now = chrono::Utc::now();
+                let queue_start_at = state.read().await.queue_start_at.filter(|start_at| *start_at > now);
+                if let Some(start_at) = queue_start_at {
+                    tokio::select! {
+                        _ = sleep(Self::until(start_at)) => {}
+                        _ = wake.notified() => {}
+                        _ = paused_rx.changed() => {}
+                        _ = shutdown_rx.changed() => {}
+                    }
+                    continue;
+                }
+
+                // The speed schedule's active window overrides the configured limit
+                let limit = {
+                    let state_guard = state.read().await;
+                    let window = speed_schedule::active_window(&state_guard.config, now);
+                    if window != active_window.as_ref() {
+                        match window {
+                            Some(window) => DEBUG_LOGGER.info("queue", format!(
+                                "Speed schedule window {}-{} started: {} concurrent",
+                                window.start.format("%H:%M"), window.end.format("%H:%M"), window.concurrent_limit
+                            )),
+                            None => DEBUG_LOGGER.info("queue", "Speed schedule window ended, using the configured limits"),
+                        }
+                        active_window = window.cloned();
+                    }
+                    window.map_or(concurrent_limit.load(Ordering::Relaxed), |window| window.concurrent_limit)
+                };
+                slots.set_limit(limit);
+
+                // Take a slot before a submission, so nothing is picked until it can start
+                let permit = tokio::select! {
+                    permit = slots.acquire() => permit,
+                    _ = sleep(SCHEDULE_CHECK_INTERVAL) => continue,
+                    _ = wake.notified() => continue,
+                    _ = paused_rx.changed() => continue,
+                    _ = shutdown_rx.changed() => continue,
+                };
+
+                // Collect pending submissions, waiting for one if there are none
+                if pending.is_empty() {
+                    tokio::select! {
+                        submission = receiver.recv() => match submission {
+                            Some(submission) => pending.push(submission),
+                            None => break, // Channel closed
+                        },
+                        _ = wake.notified() => continue,
+                        _ = paused_rx.changed() => continue,
+                        _ = shutdown_rx.changed() => continue,
+                    }
+                }
+                while let Ok(submission) = receiver.try_recv() {
+                    pending.push(submission);
+                }
+
+                // Fail jobs whose dependencies can no longer complete
+                let blocked: Vec<(String, String)> = {
+                    let state_guard = state.read().await;
+                    pending.iter()
+                        .filter_map(|submission| state_guard.get_job(&submission.job_id))
+                        .filter_map(|job| match state_guard.dependency_state(job) {
+                            DependencyState::Blocked(dependency) => Some((job.id.clone(), dependency)),
+                            _ => None,
+                        })
+                        .collect()
+                };
+                for (job_id, dependency) in &blocked {
+                    DEBUG_LOGGER.info("queue", format!("Not starting job {}: dependency {} did not complete", job_id, dependency));
+                    let error = format!("Dependency {} did not complete", dependency);
+                    let mut state_guard = state.write().await;
+                    bus.publish_locked(&mut state_guard, JobEvent::failed(job_id, error.clone()));
+                    if let Some(job) = state_guard.get_job(job_id) {
+                        session.lock().await.record_failed(job, &error);
+                    }
+                }
+                if !blocked.is_empty() {
+                    Self::finish_session_if_drained(&state.read().await, &session, &events).await;
+                }
+
+                // Pick the highest priority submission that is still queued
+                let next_submission = {
+                    let state_guard = state.read().await;
+                    pending.retain(|submission| {
+                        state_guard.get_job(&submission.job_id)
+                            .map(|job| matches!(job.status, JobStatus::Queued))
+                            .unwrap_or(false)
+                    });
+
+                    let candidates: Vec<&str> = pending.iter().map(|s| s.job_id.as_str()).collect();
+                    state_guard.pick_next_job(&candidates)
+                        .and_then(|job_id| pending.iter().position(|s| s.job_id == job_id))
+                        .map(|index| pending.remove(index))
+                };
+
+                let Some(submission) = next_submission else {
+                    // Everything pending is scheduled for later or waiting on other jobs
+                    let (next_due, waiting) = {
+                        let state_guard = state.read().await;
+                        let jobs: Vec<&DownloadJob> = pending.iter()
+                            .filter_map(|submission| state_guard.get_job(&submission.job_id))
+                            .collect();
+                        let next_due = jobs.iter().filter_map(|job| job.scheduled_at).min();
+                        let waiting = jobs.iter()
+                            .any(|job| state_guard.dependency_state(job) == DependencyState::Waiting);
+                        (next_due, waiting)
+                    };
+                    let recheck_in = match next_due {
+                        Some(next_due) => Self::until(next_due).min(SCHEDULE_CHECK_INTERVAL),
+                        None => SCHEDULE_CHECK_INTERVAL,
+                    };
+                    let recheck_in = if waiting { recheck_in.min(DEPENDENCY_CHECK_INTERVAL) } else { recheck_in };
+                    if next_due.is_some() || waiting {
+                        tokio::select! {
+                            _ = sleep(recheck_in) => {}
+                            submission = receiver.recv() => pending.extend(submission),
+                            _ = wake.notified() => {}
+                            _ = paused_rx.changed() => {}
+                            _ = shutdown_rx.changed() => {}
+                        }
+                    }
+                    continue;
+                };
+
+                // Get the job from state
+                let job = {
+                    let state_guard = state.read().await;
+                    state_guard.get_job(&submission.job_id).cloned()
+                };
+                let Some(job) = job else {
+                    continue;
+                };
+                let job = Self::resolve_routed_output(&state, job).await;
+
+                // Skip the download if another job already produced this track
+                if !job.dry_run && Self::try_complete_from_cache(&state, &download_cache, &bus, &job).await {
+                    continue;
+                }
+
+                // Fail fast rather than let gytmdl run out of space mid-download
+                let disk_space = if job.dry_run { Ok(()) } else { Self::check_disk_space(&state).await };
+                if let Err(reason) = disk_space {
+                    if state.read().await.config.pause_on_low_disk_space {
+                        DEBUG_LOGGER.warn("queue", format!("Pausing queue: {}", reason));
+                        is_paused.send_replace(true);
+                        state.write().await.pause();
+                        events.queue_paused(&reason);
+                        pending.push(submission);
+                    } else {
+                        DEBUG_LOGGER.warn("queue", format!("Not starting job {}: {}", job.id, reason));
+                        bus.publish(JobEvent::failed(&job.id, reason)).await;
+                    }
+                    continue;
+                }
+
+                bus.publish(JobEvent::status(&job.id, JobStatus::Downloading)).await;
+
+                // Spawn worker task; it holds the slot until it finishes
+                let (cancel_tx, cancel_rx) = watch::channel(false);
+                let job_handle = Self::spawn_worker_task(
+                    Arc::clone(&state),
+                    Arc::clone(&process_runner),
+                    WorkerServices {
+                        events: events.clone(),
+                        bus: bus.clone(),
+                        notifier: notifier.clone(),
+                        disk_monitor: Arc::clone(&disk_monitor),
+                        journal: Arc::clone(&journal),
+                        download_cache: Arc::clone(&download_cache),
+                        library: Arc::clone(&library),
+                        job_logs: Arc::clone(&job_logs),
+                        throughput: Arc::clone(&throughput),
+                        metrics: Arc::clone(&metrics),
+                        session: Arc::clone(&session),
+                        is_paused: Arc::clone(&is_paused),
+                        auth_failures: Arc::clone(&auth_failures),
+                        job_sender: job_sender.clone(),
+                    },
+                    job,
+                    submission.retry_count,
+                    cancel_rx,
+                    permit,
+                ).await;
+
+                // Drop handles of finished jobs and store the new one
+                let mut running_jobs_guard = running_jobs.lock().await;
+                running_jobs_guard.retain(|_, running_job| !running_job.handle.is_finished());
+                running_jobs_guard.insert(
+                    submission.job_id.clone(),
+                    RunningJob { handle: job_handle, cancel_tx },
+                );
+            }
+
+            // Cleanup all running jobs on shutdown
+            Self::cleanup_all_jobs(Arc::clone(&running_jobs)).await;
+        });
+
+        self.start_control_task();
+        Ok(())
+    }
+
+    /// Handle control requests in order, so concurrent reconfigurations
+    /// can't interleave
+    fn start_control_task(&self) {
+        let state = Arc::clone(&self.state);
+        let control_receiver = Arc::clone(&self.control_receiver);
+        let concurrent_limit = Arc::clone(&self.concurrent_limit);
+        let wake = Arc::clone(&self.wake);
+
+        tokio::spawn(async move {
+            let mut receiver = control_receiver.lock().await;
+            while let Some(request) = receiver.recv().await {
+                match request {
+                    QueueControl::ApplySettings { settings, reply } => {
+                        let result = Self::apply_settings(&state, &concurrent_limit, &settings).await;
+                        wake.notify_one();
+                        let _ = reply.send(result);
+                    }
+                }
+            }
+        });
+    }
+
+    /// Validate and apply `settings` to the config under one state lock
+    async fn apply_settings(
+        state: &Arc<RwLock<AppState>>,
+        concurrent_limit: &AtomicUsize,
+        settings: &RuntimeSettings,
+    ) -> Result<RuntimeSettingsResult, String> {
+        let mut state_guard = state.write().await;
+        let mut config = state_guard.config.clone();
+        let result = runtime_settings::apply(&mut config, settings);
+        ConfigManager::with_default_path().validate_config(&config)
+            .map_err(|e| format!("Configuration validation failed: {}", e))?;
+
+        concurrent_limit.store(config.concurrent_limit, Ordering::Relaxed);
+        state_guard.concurrent_limit = config.concurrent_limit;
+        state_guard.config = config;
+        DEBUG_LOGGER.info("queue", format!("Applied runtime settings: {:?}", result.applied));
+        Ok(result)
+    }
+
+    /// Bus for job changes made outside the worker tasks
+    fn bus(&self) -> JobBus {
+        JobBus::new(Arc::clone(&self.state), self.events.clone())
+    }
+
+    /// Time left until `at`, zero if it has passed
+    fn until(at: chrono::DateTime<chrono::Utc>) -> Duration {
+        (at - chrono::Utc::now()).to_std().unwrap_or_default()
+    }
+
+    /// Spawn a worker task for processing a download job
+    async fn spawn_worker_task(
+        state: Arc<RwLock<AppState>>,
+        process_runner: Arc<ProcessRunner>,
+        services: WorkerServices,
+        job: DownloadJob,
+        retry_count: u32,
+        cancel_rx: watch::Receiver<bool>,
+        slot: OwnedSemaphorePermit,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            // Released when the task ends, which lets the dispatcher start the next job
+            let _slot = slot;
+
+            if job.dry_run {
+                Self::run_dry_run(&state, &process_runner, &services.bus, &job).await;
+                return;
+            }
+
+            let source_url = job.url.clone();
+            let job_id = job.id.clone();
+            services.job_logs.lock().await.start(&job_id);
+            let started_at = std::time::SystemTime::now();
+            let cache_key = {
+                let state_guard = state.read().await;
+                CacheKey::for_url(&job.url, &state_guard.config)
+            };
+            let mut result = Self::process_job(
+                Arc::clone(&state),
+                Arc::clone(&process_runner),
+                &services,
+                job.clone(),
+                retry_count,
+                cancel_rx.clone(),
+            ).await;
+
+            // Age-gated content gets one more attempt with the age-verified cookies
+            if let JobResult::Failed(_, error) = &result {
+                if !job.age_restricted && ProgressParser::is_age_restricted_error(error) {
+                    let has_verified_cookies = {
+                        let mut state_guard = state.write().await;
+                        if let Some(job) = state_guard.get_job_mut(&job_id) {
+                            job.age_restricted = true;
+                        }
+                        state_guard.config.age_verified_cookies_path.as_ref()
+                            .map(|path| path.exists())
+                            .unwrap_or(false)
+                    };
+
+                    if has_verified_cookies {
+                        DEBUG_LOGGER.info("queue", format!("Job {} is age-restricted, retrying with age-verified cookies", job_id));
+                        let job = DownloadJob { age_restricted: true, ..job };
+                        result = Self::process_job(
+                            Arc::clone(&state),
+                            Arc::clone(&process_runner),
+                            &services,
+                            job,
+                            retry_count,
+                            cancel_rx.clone(),
+                        ).await;
+                    }
+                }
+            }
+
+            // Cancelled jobs' staging folders are removed by `cancel_job`; on
+            // shutdown they stay and are published after the next attempt
+            if !matches!(result, JobResult::Cancelled(_)) {
+                Self::publish_job_files(&state, &job_id).await;
+            }
+
+            // Post-process before the job is marked completed so it still counts as running
+            if matches!(result, JobResult::Success(_)) {
+                let mut cancel_rx = cancel_rx;
+                tokio::select! {
+                    processed = Self::post_process_job(&state, &services.bus, &job_id) => {
+                        if let Err(e) = processed {
+                            result = JobResult::Failed(job_id.clone(), e);
+                        }
+                    }
+                    _ = Self::cancelled(&mut cancel_rx) => {
+                        result = JobResult::Cancelled(job_id.clone());
+                    }
+                }
+            }
+
+            let WorkerServices {
+                events, bus, notifier, disk_monitor, journal, download_cache, library, job_logs, throughput, metrics, session, is_paused,
+                auth_failures, job_sender,
+            } = services;
+
+            // Keep the progress journal only for failed jobs
+            {
+                let mut journal_guard = journal.lock().await;
+                if matches!(result, JobResult::Failed(..)) {
+                    if let Err(e) = journal_guard.persist(&job_id) {
+                        DEBUG_LOGGER.error("queue", format!("Failed to persist progress journal for job {}: {}", job_id, e));
+                    }
+                } else {
+                    journal_guard.discard(&job_id);
+                }
+            }
+
+            // Cancelled jobs' folders are removed by `cancel_job`; on shutdown
+            // they stay so the download can continue on the next launch. A
+            // failed job's folder goes once it is known not to be retried.
+            if matches!(result, JobResult::Success(_)) {
+                let temp_path = state.read().await.config.temp_path.clone();
+                Self::remove_temp_dir(temp_path, &job_id).await;
+            }
+
+            // Update job status based on result
+            let mut state_guard = state.write().await;
+            state_guard.settle_live_progress(&job_id);
+            match result {
+                JobResult::Success(_) => {
+                    auth_failures.store(0, Ordering::SeqCst);
+                    let progress = ProgressParser::create_completed_progress();
+                    bus.publish_locked(&mut state_guard, JobEvent::progress(&job_id, progress));
+                    bus.publish_locked(&mut state_guard, JobEvent::status(&job_id, JobStatus::Completed));
+
+                    let output_path = Self::job_output_path(&state_guard, &job_id);
+                    let queued_tracks = state_guard.queued_track_count();
+                    let output_files = state_guard.get_job(&job_id)
+                        .map(|job| job.output_files.clone())
+                        .unwrap_or_default();
+                    drop(state_guard);
+
+                    let new_files: Vec<(std::path::PathBuf, u64)> = tokio::task::spawn_blocking(move || {
+                        output_files.into_iter()
+                            .filter_map(|path| Some((path.clone(), std::fs::metadata(&path).ok()?.len())))
+                            .collect()
+                    }).await.unwrap_or_default();
+
+                    // Only single-track jobs have a cache key, and so a single file
+                    if let ([(path, _)], Some(key)) = (new_files.as_slice(), cache_key) {
+                        download_cache.lock().await.record(key, path.clone(), output_path.clone());
+                    }
+                    let paths: Vec<_> = new_files.iter().map(|(path, _)| path.clone()).collect();
+                    if !paths.is_empty() {
+                        if let Err(e) = library.lock().await.record_download(&paths, &source_url) {
+                            DEBUG_LOGGER.error("queue", format!("Failed to record library entries for job {}: {}", job_id, e));
+                        }
+                    }
+
+                    Self::record_lyrics(&state, &job_id).await;
+                    Self::log_listen(&state, &job_id);
+
+                    if state.read().await.config.auto_organize {
+                        if let Err(e) = Self::organize_job_files(&state, &library, &job_id).await {
+                            DEBUG_LOGGER.warn("queue", format!("Failed to organize job {} into the library: {}", job_id, e));
+                        }
+                    }
+
+                    let elapsed = started_at.elapsed().unwrap_or_default();
+                    let total_bytes = new_files.iter().map(|(_, size)| *size).sum();
+                    throughput.lock().await.record(total_bytes, new_files.len() as u32, elapsed);
+                    metrics.lock().await.record_success(total_bytes, elapsed);
+                    let track_secs = state.read().await.get_job(&job_id)
+                        .and_then(|job| job.metadata.as_ref()?.duration);
+                    if let ([(_, size)], Some(track_secs)) = (new_files.as_slice(), track_secs) {
+                        metrics.lock().await.record_track_length(*size, track_secs);
+                    }
+
+                    let sizes = new_files.iter().map(|(_, size)| *size).collect();
+                    Self::update_disk_projection(&disk_monitor, &events, output_path, sizes, queued_tracks).await;
+
+                    let mut state_guard = state.write().await;
+                    if state_guard.config.notify_on_completion {
+                        if let Some(job) = state_guard.get_job(&job_id) {
+                            notifier.job_completed(job);
+                        }
+                    }
+                    if let Some(job) = state_guard.get_job(&job_id) {
+                        session.lock().await.record_completed(job, total_bytes, &job.output_files);
+                    }
+                    Self::finish_group(&state_guard, &job_id);
+                    Self::archive_finished_jobs(&state, &mut state_guard);
+                    Self::emit_queue_forecast(&state_guard, &throughput, &metrics, &disk_monitor, &events).await;
+                    Self::finish_session_if_drained(&state_guard, &session, &events).await;
+                }
+                JobResult::Failed(_, error) => {
+                    let age_restricted = state_guard.get_job(&job_id)
+                        .map(|job| job.age_restricted)
+                        .unwrap_or(false);
+                    let details = if age_restricted {
+                        ClassifiedError::from(JobErrorKind::AgeRestricted)
+                    } else {
+                        let logs = job_logs.lock().await.get(&job_id);
+                        error_classifier::classify_output(&error, logs.iter()
+                            .filter(|log| log.stream == LogStream::Stderr)
+                            .map(|log| log.line.as_str()))
+                    };
+                    if state_guard.config.notify_on_failure {
+                        if let Some(job) = state_guard.get_job(&job_id) {
+                            notifier.job_failed(job, &details.to_string());
+                        }
+                    }
+                    let kind = details.kind;
+                    bus.publish_locked(&mut state_guard, JobEvent::Failed {
+                        job_id: job_id.clone(),
+                        error,
+                        details: Some(details),
+                    });
+                    metrics.lock().await.record_failure(started_at.elapsed().unwrap_or_default());
+
+                    let lost_connection = state_guard.get_job(&job_id).is_some_and(|job| job.retry_when_online)
+                        || (kind == JobErrorKind::Network && state_guard.degraded.contains(DegradedFlags::OFFLINE));
+                    if lost_connection {
+                        Self::requeue_after_outage(&mut state_guard, &events, &job_sender, &job_id);
+                    } else if kind == JobErrorKind::Timeout && state_guard.config.retry_on_timeout {
+                        Self::retry_timed_out_job(&mut state_guard, &events, &job_sender, &job_id);
+                    }
+                    // Only count it if it wasn't queued again
+                    let mut failed_temp_path = None;
+                    if let Some(job) = state_guard.get_job(&job_id).filter(|job| job.status == JobStatus::Failed) {
+                        session.lock().await.record_failed(job, job.error.as_deref().unwrap_or_default());
+                        if !state_guard.config.keep_failed_temp_dirs {
+                            failed_temp_path = Some(state_guard.config.temp_path.clone());
+                        }
+                    }
+                    Self::finish_group(&state_guard, &job_id);
+                    Self::archive_finished_jobs(&state, &mut state_guard);
+                    Self::emit_queue_forecast(&state_guard, &throughput, &metrics, &disk_monitor, &events).await;
+                    Self::finish_session_if_drained(&state_guard, &session, &events).await;
+
+                    let mut paused_after = None;
+                    if kind != JobErrorKind::AuthRequired {
+                        auth_failures.store(0, Ordering::SeqCst);
+                    } else {
+                        let failures = auth_failures.fetch_add(1, Ordering::SeqCst) + 1;
+                        let threshold = state_guard.config.auth_failure_pause_threshold;
+                        if threshold > 0 && failures >= threshold {
+                            auth_failures.store(0, Ordering::SeqCst);
+                            state_guard.pause();
+                            state_guard.degraded.insert(DegradedFlags::COOKIES_SUSPECT);
+                            paused_after = Some(failures);
+                        }
+                    }
+                    drop(state_guard);
+
+                    if let Some(temp_path) = failed_temp_path {
+                        Self::remove_temp_dir(temp_path, &job_id).await;
+                    }
+                    if let Some(failures) = paused_after {
+                        Self::pause_for_auth_failures(&is_paused, &events, &notifier, failures).await;
+                    }
+                }
+                JobResult::Cancelled(_) => {
+                    bus.publish_locked(&mut state_guard, JobEvent::status(&job_id, JobStatus::Cancelled));
+                    metrics.lock().await.record_cancelled();
+                    if let Some(job) = state_guard.get_job(&job_id) {
+                        session.lock().await.record_cancelled(job);
+                    }
+                    Self::finish_group(&state_guard, &job_id);
+                    Self::archive_finished_jobs(&state, &mut state_guard);
+                    Self::emit_queue_forecast(&state_guard, &throughput, &metrics, &disk_monitor, &events).await;
+                    Self::finish_session_if_drained(&state_guard, &session, &events).await;
+                }
+            }
+        })
+    }
+
+    /// Send the frontend a fresh queue forecast after a job finished
+    async fn emit_queue_forecast(
+        state_guard: &AppState,
+        throughput: &Mutex<ThroughputTracker>,
+        metrics: &Mutex<MetricsCollector>,
+        disk_monitor: &Mutex<DiskSpaceMonitor>,
+        events: &EventEmitter,
+    ) {
+        let forecast = Self::queue_forecast(state_guard, throughput, metrics, disk_monitor).await;
+        events.queue_forecast(&forecast);
+    }
+
+    /// Forecast the queued and running jobs from the recent throughput, with
+    /// as many running together as the speed schedule currently allows
+    async fn queue_forecast(
+        state_guard: &AppState,
+        throughput: &Mutex<ThroughputTracker>,
+        metrics: &Mutex<MetricsCollector>,
+        disk_monitor: &Mutex<DiskSpaceMonitor>,
+    ) -> QueueForecast {
+        let now = chrono::Utc::now();
+        let average_track_bytes = disk_monitor.lock().await.average_track_size();
+        let bytes_per_track_second = metrics.lock().await.bytes_per_track_second();
+        let work = RemainingWork::of_queue(state_guard, average_track_bytes, bytes_per_track_second);
+        let parallelism = speed_schedule::active_window(&state_guard.config, now)
+            .map_or(state_guard.concurrent_limit, |window| window.concurrent_limit);
+        throughput.lock().await.forecast(work, parallelism, state_guard.config.data_price_per_gb, now)
+    }
+
+    /// Close the session once nothing is queued or downloading: store its
+    /// summary in history, write the report file when one is configured and
+    /// tell the frontend
+    async fn finish_session_if_drained(
+        state_guard: &AppState,
+        session: &Mutex<SessionTracker>,
+        events: &EventEmitter,
+    ) {
+        let drained = state_guard.count_jobs_by_status(&JobStatus::Queued) == 0
+            && state_guard.count_jobs_by_status(&JobStatus::Downloading) == 0;
+        if !drained {
+            return;
+        }
+        let Some(mut summary) = session.lock().await.finish(chrono::Utc::now()) else {
+            return;
+        };
+
+        let report_format = state_guard.config.session_report_format;
+        let output_path = state_guard.config.output_path.clone();
+        let events = events.clone();
+        tokio::spawn(async move {
+            if let Some(format) = report_format {
+                let report = summary.clone();
+                match tokio::task::spawn_blocking(move || session_report::write_report(&output_path, &report, format)).await {
+                    Ok(Ok(path)) => summary.report_path = Some(path),
+                    Ok(Err(e)) => DEBUG_LOGGER.warn("queue", format!("Failed to write session report: {}", e)),
+                    Err(e) => DEBUG_LOGGER.warn("queue", format!("Session report task failed: {}", e)),
+                }
+            }
+            if let Err(e) = HistoryStore::new().append_session(&summary) {
+                DEBUG_LOGGER.error("queue", format!("Failed to store session summary: {}", e));
+            }
+            DEBUG_LOGGER.info("queue", format!(
+                "Queue drained: {} completed, {} failed, {} cancelled",
+                summary.completed, summary.failed, summary.cancelled
+            ));
+            events.session_summary(&summary);
+        });
+    }
+
+    /// Write the playlist file of the job's group once its last job has
+    /// finished, when group playlists are turned on
+    fn finish_group(state_guard: &AppState, job_id: &str) {
+        if !state_guard.config.write_group_playlists {
+            return;
+        }
+        let Some(group_id) = state_guard.get_job(job_id).and_then(|job| job.group_id.clone()) else {
+            return;
+        };
+        let finished = state_guard.group_progress(&group_id)
+            .is_some_and(|progress| progress.finished && progress.completed > 0);
+        if !finished {
+            return;
+        }
+
+        // Collected now, since the group's jobs may move to history before the write runs
+        let playlist = Self::group_playlist(state_guard, &group_id);
+        tokio::spawn(async move {
+            match Self::write_group_playlist(playlist, None).await {
+                Ok(path) => DEBUG_LOGGER.info("queue", format!("Wrote playlist of group {} to {}", group_id, path.display())),
+                Err(e) => DEBUG_LOGGER.warn("queue", format!("Failed to write playlist of group {}: {}", group_id, e)),
+            }
+        });
+    }
+
+    /// A group, its downloaded tracks in queue order and the output folder
+    fn group_playlist(
+        state_guard: &AppState,
+        group_id: &str,
+    ) -> Result<(JobGroup, Vec<PlaylistEntry>, std::path::PathBuf), String> {
+        let group = state_guard.get_group(group_id).ok_or("Group not found")?.clone();
+        let entries = PlaylistEntry::for_jobs(&state_guard.group_jobs(group_id));
+        Ok((group, entries, state_guard.config.output_path.clone()))
+    }
+
+    /// Write an .m3u8 of a group's downloaded tracks. Without a `path` it goes
+    /// next to the tracks, named after the group.
+    async fn write_group_playlist(
+        playlist: Result<(JobGroup, Vec<PlaylistEntry>, std::path::PathBuf), String>,
+        path: Option<std::path::PathBuf>,
+    ) -> Result<std::path::PathBuf, String> {
+        let (group, entries, output_path) = playlist?;
+        if entries.is_empty() {
+            return Err("No downloaded tracks in this group".to_string());
+        }
+
+        let path = path.unwrap_or_else(|| playlist_file::default_playlist_path(&entries, &output_path, &group));
+        let target = path.clone();
+        tokio::task::spawn_blocking(move || playlist_file::write_playlist(&target, group.name.as_deref(), &entries))
+            .await
+            .map_err(|e| e.to_string())?
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        Ok(path)
+    }
+
+    /// Move the oldest finished jobs to history once more than the configured
+    /// number are in the queue. They are taken out under the lock and written
+    /// to history after it is released; if that fails they are put back.
+    fn archive_finished_jobs(state: &Arc<RwLock<AppState>>, state_guard: &mut AppState) {
+        let Some(keep) = state_guard.config.max_completed_jobs_in_memory else {
+            return;
+        };
+        let archived = state_guard.take_finished_jobs_over(keep as usize);
+        if archived.is_empty() {
+            return;
+        }
+        state_guard.mark_changed();
+
+        let state = Arc::clone(state);
+        tokio::spawn(async move {
+            let count = archived.len();
+            if let Err(e) = Self::append_history(archived.jobs().to_vec()).await {
+                DEBUG_LOGGER.warn("queue", format!("Failed to move finished jobs to history: {}", e));
+                let mut state_guard = state.write().await;
+                state_guard.restore_jobs(archived);
+                state_guard.mark_changed();
+                return;
+            }
+            DEBUG_LOGGER.info("queue", format!("Moved {} finished job(s) to history", count));
+        });
+    }
+
+    /// Write jobs to history off the async runtime
+    async fn append_history(jobs: Vec<DownloadJob>) -> Result<(), String> {
+        tokio::task::spawn_blocking(move || HistoryStore::new().append(&jobs))
+            .await
+            .map_err(|e| e.to_string())?
+            .map_err(|e| e.to_string())
+    }
+
+    /// Resolve what a dry-run job would download and complete it with the report
+    async fn run_dry_run(
+        state: &Arc<RwLock<AppState>>,
+        process_runner: &ProcessRunner,
+        bus: &JobBus,
+        job: &DownloadJob,
+    ) {
+        let config = Self::job_config(&state.read().await.config, job);
+        let result = dry_run::resolve(process_runner, &MetadataFetcher::new(), &config, job).await;
+
+        let mut state_guard = state.write().await;
+        match result {
+            Ok(report) => {
+                if let Some(metadata) = report.metadata.clone() {
+                    state_guard.update_job_metadata(&job.id, metadata);
+                }
+                if let Some(job) = state_guard.get_job_mut(&job.id) {
+                    job.dry_run_report = Some(report);
+                }
+                let progress = ProgressParser::create_completed_progress();
+                bus.publish_locked(&mut state_guard, JobEvent::progress(&job.id, progress));
+                bus.publish_locked(&mut state_guard, JobEvent::status(&job.id, JobStatus::Completed));
+            }
+            Err(e) => {
+                let error = format!("Dry run failed: {}", e);
+                bus.publish_locked(&mut state_guard, JobEvent::failed(&job.id, error));
+            }
+        }
+    }
+
+    /// Queue a job the watchdog killed again, after the usual retry backoff
+    fn retry_timed_out_job(
+        state: &mut AppState,
+        events: &EventEmitter,
+        job_sender: &mpsc::UnboundedSender<JobSubmission>,
+        job_id: &str,
+    ) {
+        let Some(job) = state.get_job_mut(job_id).filter(|job| job.has_retries_left()) else {
+            return;
+        };
+        job.reset_for_retry();
+        let submission = JobSubmission {
+            job_id: job_id.to_string(),
+            retry_count: job.retry_count,
+        };
+        DEBUG_LOGGER.info("queue", format!("Retrying timed-out job {} (attempt {})", job_id, submission.retry_count));
+        events.job_status(job_id, JobStatus::Queued, None);
+        Self::submit_after_backoff(job_sender, submission);
+    }
+
+    /// Queue a job that failed because the connection dropped again, without
+    /// counting a retry. Nothing is dispatched while offline, so it waits
+    /// until the connection is back.
+    fn requeue_after_outage(
+        state: &mut AppState,
+        events: &EventEmitter,
+        job_sender: &mpsc::UnboundedSender<JobSubmission>,
+        job_id: &str,
+    ) {
+        let Some(job) = state.get_job_mut(job_id) else {
+            return;
+        };
+        job.requeue();
+        let submission = JobSubmission {
+            job_id: job_id.to_string(),
+            retry_count: job.retry_count,
+        };
+        DEBUG_LOGGER.info("queue", format!("Job {} failed while offline, queued again", job_id));
+        events.job_status(job_id, JobStatus::Queued, None);
+        let _ = job_sender.send(submission);
+    }
+
+    /// Delete a job's temp directory off the async runtime
+    async fn remove_temp_dir(temp_path: std::path::PathBuf, job_id: &str) {
+        let removing_job = job_id.to_string();
+        let removed = tokio::task::spawn_blocking(move || ProcessRunner::remove_job_temp_dir(&temp_path, &removing_job)).await;
+        if let Ok(Err(e)) = removed {
+            DEBUG_LOGGER.warn("queue", format!("Failed to remove temp directory for job {}: {}", job_id, e));
+        }
+    }
+
+    /// Send a retried job to the dispatcher once its backoff delay has passed
+    fn submit_after_backoff(job_sender: &mpsc::UnboundedSender<JobSubmission>, submission: JobSubmission) {
+        let delay = Duration::from_millis(Self::calculate_backoff_delay(submission.retry_count));
+        let job_sender = job_sender.clone();
+        tokio::spawn(async move {
+            sleep(delay).await;
+            let _ = job_sender.send(submission);
+        });
+    }
+
+    /// Pause after repeated 403/sign-in failures instead of failing the rest
+    /// of the queue the same way
+    async fn pause_for_auth_failures(is_paused: &watch::Sender<bool>, events: &EventEmitter, notifier: &Notifier, failures: u32) {
+        let message = format!(
+            "{} downloads in a row were refused by YouTube, so the queue was paused. Re-import cookies, then resume.",
+            failures
+        );
+        DEBUG_LOGGER.warn("queue", &message);
+        is_paused.send_replace(true);
+        events.queue_paused(&message);
+        events.cookies_suspect(failures, &message);
+        notifier.cookies_suspect(&message);
+    }
+
+    /// Move the files gytmdl finished into the output folder and add them to
+    /// the job's output files. Everything in the job's staging folder is its
+    /// own, whatever other downloads ran alongside it.
+    async fn publish_job_files(state: &Arc<RwLock<AppState>>, job_id: &str) {
+        let (output_path, overwrite) = {
+            let state_guard = state.read().await;
+            (Self::job_output_path(&state_guard, job_id), state_guard.config.overwrite)
+        };
+        let staged_job = job_id.to_string();
+        let published = tokio::task::spawn_blocking(move || {
+            ProcessRunner::publish_staged_files(&output_path, &staged_job, overwrite)
+        }).await;
+        let files = match published {
+            Ok(Ok(files)) => files,
+            Ok(Err(e)) => {
+                DEBUG_LOGGER.error("queue", format!("Failed to move the files of job {} into the output folder: {}", job_id, e));
+                return;
+            }
+            Err(e) => {
+                DEBUG_LOGGER.error("queue", format!("Moving the files of job {} panicked: {}", job_id, e));
+                return;
+            }
+        };
+
+        let mut state_guard = state.write().await;
+        if let Some(job) = state_guard.get_job_mut(job_id) {
+            job.output_files.retain(|path| path.exists());
+            for path in files {
+                if !job.output_files.contains(&path) {
+                    job.output_files.push(path);
+                }
+            }
+        }
+    }
+
+    /// Run the optional renaming, conversion and loudness stages on a finished job
+    async fn post_process_job(
+        state: &Arc<RwLock<AppState>>,
+        bus: &JobBus,
+        job_id: &str,
+    ) -> Result<(), String> {
+        Self::sanitize_job_filenames(state, job_id).await;
+        Self::convert_job_files(state, bus, job_id).await?;
+        Self::analyze_job_files(state, bus, job_id).await?;
+        Self::verify_job_files(state, bus, job_id).await;
+        Self::archive_job_cover(state, job_id).await;
+        Ok(())
+    }
+
+    /// Rename a finished job's files and the folders gytmdl made for them to
+    /// follow the filename rules, and point the job at the new paths. Files
+    /// keep their names when renaming fails; the download itself succeeded.
+    async fn sanitize_job_filenames(state: &Arc<RwLock<AppState>>, job_id: &str) {
+        let (rules, output_path) = {
+            let state_guard = state.read().await;
+            (FilenameRules::for_config(&state_guard.config), Self::job_output_path(&state_guard, job_id))
+        };
+        if !rules.is_enabled() {
+            return;
+        }
+
+        let files = Self::job_media_files(state, job_id).await;
+        let renamed = tokio::task::spawn_blocking(move || filename_rules::apply(&rules, &output_path, &files)).await;
+        let renamed = match renamed {
+            Ok(Ok(renamed)) => renamed,
+            Ok(Err(e)) => {
+                DEBUG_LOGGER.warn("queue", format!("Failed to rename files of job {}: {}", job_id, e));
+                return;
+            }
+            Err(e) => {
+                DEBUG_LOGGER.warn("queue", format!("Renaming files of job {} panicked: {}", job_id, e));
+                return;
+            }
+        };
+
+        let mut state_guard = state.write().await;
+        if let Some(job) = state_guard.get_job_mut(job_id) {
+            for (from, to) in renamed {
+                match job.output_files.iter_mut().find(|path| **path == from) {
+                    Some(path) => *path = to,
+                    None => job.output_files.push(to),
+                }
+            }
+        }
+    }
+
+    /// The job's own files that still exist. Nothing else in the output
+    /// folder is counted; a file another download wrote isn't this job's.
+    async fn job_media_files(state: &Arc<RwLock<AppState>>, job_id: &str) -> Vec<std::path::PathBuf> {
+        let state_guard = state.read().await;
+        state_guard.get_job(job_id)
+            .map(|job| job.output_files.iter().filter(|path| path.exists()).cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Transcode a finished job's m4a files to the configured format and swap the
+    /// converted files into the job's output list
+    async fn convert_job_files(
+        state: &Arc<RwLock<AppState>>,
+        bus: &JobBus,
+        job_id: &str,
+    ) -> Result<(), String> {
+        let (format, bitrate_kbps, keep_original, duration, priority) = {
+            let state_guard = state.read().await;
+            let config = &state_guard.config;
+            let Some(format) = config.convert_to else {
+                return Ok(());
+            };
+            let duration = state_guard.get_job(job_id)
+                .and_then(|job| job.metadata.as_ref())
+                .and_then(|m| m.duration)
+                .map(f64::from);
+            (format, config.convert_bitrate_kbps, config.keep_original_after_convert, duration, config.process_priority)
+        };
+
+        let mut files = Self::job_media_files(state, job_id).await;
+        files.retain(|path| path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("m4a")));
+        if files.is_empty() {
+            return Ok(());
+        }
+
+        let converter = Converter::detect().map_err(|e| e.to_string())?.with_priority(priority);
+        // The job's duration only describes the file when there is exactly one
+        let duration = if files.len() == 1 { duration } else { None };
+        let total = files.len();
+
+        for (index, input) in files.iter().enumerate() {
+            let output = Converter::output_path_for(input, format);
+            let current_step = format!("Converting to {}", format);
+            let progress_for = |percentage: Option<f32>| Progress {
+                stage: DownloadStage::Converting,
+                percentage,
+                current_step: current_step.clone(),
+                total_steps: Some(total as u32),
+                current_step_index: Some(index as u32 + 1),
+                ..Progress::default()
+            };
+
+            let initial = progress_for(Some(index as f32 / total as f32 * 100.0));
+            bus.publish(JobEvent::progress(job_id, initial)).await;
+
+            let (progress_tx, mut progress_rx) = mpsc::unbounded_channel();
+            let conversion = converter.convert(input, &output, format, bitrate_kbps, duration, move |percentage| {
+                let _ = progress_tx.send(percentage);
+            });
+            let forward_progress = async {
+                while let Some(percentage) = progress_rx.recv().await {
+                    let overall = (index as f32 + percentage / 100.0) / total as f32 * 100.0;
+                    bus.publish(JobEvent::progress(job_id, progress_for(Some(overall)))).await;
+                }
+            };
+            let (converted, _) = tokio::join!(conversion, forward_progress);
+            converted.map_err(|e| format!("Failed to convert {:?}: {}", input, e))?;
+
+            {
+                let mut state_guard = state.write().await;
+                if let Some(job) = state_guard.get_job_mut(job_id) {
+                    match job.output_files.iter_mut().find(|path| *path == input) {
+                        Some(path) if !keep_original => *path = output.clone(),
+                        _ => job.output_files.push(output.clone()),
+                    }
+                }
+            }
+
+            if !keep_original {
+                if let Err(e) = tokio::fs::remove_file(input).await {
+                    DEBUG_LOGGER.warn("queue", format!("Failed to remove original {:?} after conversion: {}", input, e));
+                }
+            }
+        }
+
+        DEBUG_LOGGER.info("queue", format!("Converted {} file(s) of job {} to {}", total, job_id, format));
+        Ok(())
+    }
+
+    /// Measure each of a finished job's tracks and write ReplayGain tags
+    async fn analyze_job_files(
+        state: &Arc<RwLock<AppState>>,
+        bus: &JobBus,
+        job_id: &str,
+    ) -> Result<(), String> {
+        if !state.read().await.config.replaygain {
+            return Ok(());
+        }
+
+        let mut files = Self::job_media_files(state, job_id).await;
+        files.retain(|path| loudness::is_taggable(path));
+        if files.is_empty() {
+            return Ok(());
+        }
+
+        let converter = Converter::detect().map_err(|e| e.to_string())?;
+        let ffmpeg = converter.get_ffmpeg_path();
+        let total = files.len();
+
+        for (index, file) in files.iter().enumerate() {
+            let progress = Progress {
+                stage: DownloadStage::Analyzing,
+                percentage: Some(index as f32 / total as f32 * 100.0),
+                current_step: "Analyzing loudness".to_string(),
+                total_steps: Some(total as u32),
+                current_step_index: Some(index as u32 + 1),
+                ..Progress::default()
+            };
+            bus.publish(JobEvent::progress(job_id, progress)).await;
+
+            let analysis = loudness::analyze(ffmpeg, file).await
+                .map_err(|e| format!("Failed to analyze {:?}: {}", file, e))?;
+            loudness::write_tags(ffmpeg, file, &analysis).await
+                .map_err(|e| format!("Failed to write ReplayGain tags to {:?}: {}", file, e))?;
+            DEBUG_LOGGER.debug("queue", format!(
+                "ReplayGain for {:?}: {:.2} dB, peak {:.6}", file, analysis.track_gain_db, analysis.track_peak
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Probe a finished job's files with ffprobe and store the report on the
+    /// job. Suspect files don't fail the job; they are flagged on it instead.
+    async fn verify_job_files(
+        state: &Arc<RwLock<AppState>>,
+        bus: &JobBus,
+        job_id: &str,
+    ) {
+        let (expected, duration, tolerance_secs) = {
+            let state_guard = state.read().await;
+            let config = &state_guard.config;
+            if !config.verify_downloads {
+                return;
+            }
+            let duration = state_guard.get_job(job_id)
+                .and_then(|job| job.metadata.as_ref()?.duration);
+            (ExpectedAudio::for_config(config), duration, config.verify_duration_tolerance_secs)
+        };
+
+        let Some(ffprobe) = verification::locate_ffprobe() else {
+            DEBUG_LOGGER.warn("queue", format!("Not verifying job {}: ffprobe was not found in the sidecar folder or on PATH", job_id));
+            return;
+        };
+        let files = Self::job_media_files(state, job_id).await;
+        if files.is_empty() {
+            return;
+        }
+        // The metadata duration is for a single track
+        let duration = if files.len() == 1 { duration } else { None };
+
+        let total = files.len();
+        let mut results = Vec::with_capacity(total);
+        for (index, file) in files.iter().enumerate() {
+            let progress = Progress {
+                stage: DownloadStage::Verifying,
+                percentage: Some(index as f32 / total as f32 * 100.0),
+                current_step: "Verifying audio".to_string(),
+                total_steps: Some(total as u32),
+                current_step_index: Some(index as u32 + 1),
+                ..Progress::default()
+            };
+            bus.publish(JobEvent::progress(job_id, progress)).await;
+            results.push(verification::verify_file(&ffprobe, file, &expected, duration, tolerance_secs).await);
+        }
+
+        let report = VerificationReport::new(results);
+        if report.status == VerificationStatus::Suspect {
+            DEBUG_LOGGER.warn("queue", format!("Job {} is suspect: {}", job_id, report.issues().join("; ")));
+        }
+        if let Some(job) = state.write().await.get_job_mut(job_id) {
+            job.verification = Some(report);
+        }
+    }
+
+    /// Copy a finished job's cover into the central cover folder, from the
+    /// cover gytmdl saved next to the track or else from the job's thumbnail.
+    /// Failures are logged; the download itself succeeded.
+    async fn archive_job_cover(state: &Arc<RwLock<AppState>>, job_id: &str) {
+        let (job, config) = {
+            let state_guard = state.read().await;
+            let Some(job) = state_guard.get_job(job_id).cloned() else {
+                return;
+            };
+            (job, state_guard.config.clone())
+        };
+        let Some(folder) = config.cover_folder.as_deref() else {
+            return;
+        };
+
+        let dest = cover_art::central_cover_path(folder, job.metadata.as_ref(), job_id, &config.cover_format);
+        let files = Self::job_media_files(state, job_id).await;
+        let saved_cover = files.iter()
+            .map(|file| cover_art::track_cover_path(file, &config.cover_format))
+            .find(|cover| cover.exists());
+
+        let result = match saved_cover {
+            Some(cover) => async {
+                if let Some(parent) = dest.parent() {
+                    tokio::fs::create_dir_all(parent).await?;
+                }
+                tokio::fs::copy(&cover, &dest).await?;
+                Ok(())
+            }.await.map_err(CoverError::IoError),
+            None => Self::apply_cover(&job, &config, None, &[], CoverOptions {
+                size: config.cover_size,
+                format: config.cover_format.clone(),
+                quality: config.cover_quality,
+                save_next_to_tracks: false,
+                central_path: Some(dest.clone()),
+            }).await.map(|_| ()),
+        };
+
+        match result {
+            Ok(()) => DEBUG_LOGGER.debug("queue", format!("Saved cover of job {} to {}", job_id, dest.display())),
+            Err(e) => DEBUG_LOGGER.warn("queue", format!("Failed to save cover of job {} to the cover folder: {}", job_id, e)),
+        }
+    }
+
+    /// Render a cover and embed it into `files`, using a scratch folder under
+    /// the temp path. Without a `source`, the job's thumbnail is downloaded.
+    async fn apply_cover(
+        job: &DownloadJob,
+        config: &AppConfig,
+        source: Option<CoverSource>,
+        files: &[std::path::PathBuf],
+        options: CoverOptions,
+    ) -> Result<CoverUpdate, CoverError> {
+        let source = match source {
+            Some(source) => source,
+            None => Self::thumbnail_source(job).await?,
+        };
+        let converter = Converter::detect()?;
+        let work_dir = config.temp_path.join(format!("cover-{}", job.id));
+        let result = cover_art::apply(converter.get_ffmpeg_path(), &source, files, &options, &work_dir).await;
+        let _ = tokio::fs::remove_dir_all(&work_dir).await;
+        result
+    }
+
+    /// The job's thumbnail, looked up when the job has no metadata yet, or
+    /// else the video's own thumbnail
+    async fn thumbnail_source(job: &DownloadJob) -> Result<CoverSource, CoverError> {
+        let thumbnail = match job.metadata.as_ref().and_then(|metadata| metadata.thumbnail.clone()) {
+            Some(thumbnail) => Some(thumbnail),
+            None => MetadataFetcher::new().fetch(&job.url).await.ok().and_then(|metadata| metadata.thumbnail),
+        };
+        thumbnail
+            .or_else(|| cover_art::video_thumbnail_url(&job.url))
+            .map(CoverSource::Url)
+            .ok_or(CoverError::NoCoverSource)
+    }
+
+    /// Move or copy a completed job's files into the music library and point the
+    /// job and library manifest at their new location
+    async fn organize_job_files(
+        state: &Arc<RwLock<AppState>>,
+        library: &Arc<Mutex<LibraryManifest>>,
+        job_id: &str,
+    ) -> Result<Vec<OrganizedFile>, String> {
+        let (files, target_dir, mode) = {
+            let state_guard = state.read().await;
+            let job = state_guard.get_job(job_id)
+                .ok_or_else(|| format!("Job not found: {}", job_id))?;
+            if job.status != JobStatus::Completed {
+                return Err("Only completed jobs can be organized".to_string());
+            }
+            let config = &state_guard.config;
+            let root = config.library_root.as_ref()
+                .ok_or_else(|| "No library folder is configured".to_string())?;
+            let mut files: Vec<_> = job.output_files.iter().filter(|path| path.exists()).cloned().collect();
+            if files.is_empty() {
+                return Err("The job has no downloaded files left to organize".to_string());
+            }
+            // Keep synced lyrics next to their tracks
+            let lyrics: Vec<_> = files.iter()
+                .map(|path| path.with_extension("lrc"))
+                .filter(|path| path.exists())
+                .collect();
+            files.extend(lyrics);
+            let target_dir = library::target_directory(root, &config.library_template, job.metadata.as_ref());
+            (files, target_dir, config.organize_mode)
+        };
+
+        let organized = tokio::task::spawn_blocking(move || {
+            library::organize_files(&files, &target_dir, mode)
+        }).await
+            .map_err(|e| format!("Organize task failed: {}", e))?
+            .map_err(|e| format!("Failed to organize files: {}", e))?;
+
+        if mode == library::OrganizeMode::Move {
+            {
+                let mut state_guard = state.write().await;
+                if let Some(job) = state_guard.get_job_mut(job_id) {
+                    for file in &organized {
+                        if let Some(path) = job.output_files.iter_mut().find(|path| **path == file.from) {
+                            *path = file.to.clone();
+                        }
+                    }
+                }
+            }
+
+            let mut library = library.lock().await;
+            for file in &organized {
+                if let Err(e) = library.record_move(&file.from, file.to.clone()) {
+                    DEBUG_LOGGER.error("queue", format!("Failed to update library entry for {:?}: {}", file.to, e));
+                }
+            }
+        }
+
+        DEBUG_LOGGER.info("queue", format!("Organized {} file(s) of job {} into the library", organized.len(), job_id));
+        Ok(organized)
+    }
+
+    /// Note whether gytmdl saved a synced .lrc file next to any of the job's tracks
+    async fn record_lyrics(state: &Arc<RwLock<AppState>>, job_id: &str) {
+        let mut state_guard = state.write().await;
+        if let Some(job) = state_guard.get_job_mut(job_id) {
+            if job.output_files.is_empty() {
+                return;
+            }
+            let has_synced_lyrics = job.output_files.iter()
+                .any(|path| path.with_extension("lrc").exists());
+            job.metadata.get_or_insert_with(Default::default).has_synced_lyrics = Some(has_synced_lyrics);
+        }
+    }
+
+    /// Log a finished job to the listen log service in the background, so a
+    /// slow service doesn't hold up the queue
+    fn log_listen(state: &Arc<RwLock<AppState>>, job_id: &str) {
+        let state = Arc::clone(state);
+        let job_id = job_id.to_string();
+        tokio::spawn(async move {
+            let (config, job) = {
+                let state_guard = state.read().await;
+                if state_guard.config.listen_log_service.is_none() {
+                    return;
+                }
+                let Some(job) = state_guard.get_job(&job_id).cloned() else {
+                    return;
+                };
+                (state_guard.config.clone(), job)
+            };
+
+            let Some(submission) = listen_log::submit_job(&config, &job).await else {
+                return;
+            };
+            if let Some(error) = &submission.error {
+                DEBUG_LOGGER.warn("listen-log", format!("Did not log job {}: {}", job_id, error));
+            }
+            if let Some(job) = state.write().await.get_job_mut(&job_id) {
+                job.listen_submission = Some(submission);
+            }
+        });
+    }
+
+    /// Check the output and temp volumes against the configured free space minimum
+    async fn check_disk_space(state: &Arc<RwLock<AppState>>) -> Result<(), String> {
+        let (output_path, temp_path, min_free_bytes) = {
+            let state_guard = state.read().await;
+            let config = &state_guard.config;
+            (config.output_path.clone(), config.temp_path.clone(), config.min_free_space_mb * 1024 * 1024)
+        };
+        if min_free_bytes == 0 {
+            return Ok(());
+        }
+
+        tokio::task::spawn_blocking(move || {
+            disk_space::preflight(&[&output_path, &temp_path], min_free_bytes)
+        }).await.unwrap_or(Ok(()))
+    }
+
+    /// Record the sizes of a finished job's files and warn if the queue won't fit
+    async fn update_disk_projection(
+        disk_monitor: &Arc<Mutex<DiskSpaceMonitor>>,
+        events: &EventEmitter,
+        output_path: std::path::PathBuf,
+        sizes: Vec<u64>,
+        queued_tracks: usize,
+    ) {
+        let mut monitor = disk_monitor.lock().await;
+        for size in sizes {
+            monitor.record_track_size(size);
+        }
 
-                // Check if we have capacity for more jobs
-                let running_count = running_jobs.lock().await.len();
-                if running_count >= concurrent_limit {
-                    sleep(Duration::from_millis(100)).await;
-                    continue;
-                }
+        if let Ok(available_bytes) = disk_space::available_space(&output_path) {
+            let projection = monitor.project(output_path, available_bytes, queued_tracks);
+            if !projection.will_fit {
+                events.disk_space_warning(&projection);
+            }
+        }
+    }
 
-                // Try to get a job from the queue
-                let job_submission = {
-                    let mut receiver = job_receiver.lock().await;
-                    receiver.recv().await
-                };
+    /// Complete a single-track job from a file another job produced this
+    /// session. Returns true if the job was satisfied without downloading.
+    async fn try_complete_from_cache(
+        state: &Arc<RwLock<AppState>>,
+        download_cache: &Arc<Mutex<DownloadCache>>,
+        bus: &JobBus,
+        job: &DownloadJob,
+    ) -> bool {
+        let (key, output_path) = {
+            let state_guard = state.read().await;
+            match CacheKey::for_url(&job.url, &state_guard.config) {
+                Some(key) => (key, output_routing::output_path(&state_guard.config, job)),
+                None => return false,
+            }
+        };
 
-                if let Some(submission) = job_submission {
-                    // Get the job from state
-                    let job = {
-                        let state_guard = state.read().await;
-                        state_guard.get_job(&submission.job_id).cloned()
-                    };
+        let Some(cached) = download_cache.lock().await.lookup(&key) else {
+            return false;
+        };
 
-                    if let Some(job) = job {
-                        // Check if job is still in a valid state to process
-                        if matches!(job.status, JobStatus::Queued) {
-                            // Update job status to downloading
-                            {
-                                let mut state_guard = state.write().await;
-                                state_guard.update_job_status(&job.id, JobStatus::Downloading);
-                            }
-
-                            // Spawn worker task
-                            let job_handle = Self::spawn_worker_task(
-                                Arc::clone(&state),
-                                Arc::clone(&gytmdl_wrapper),
-                                job,
-                                submission.retry_count,
-                            ).await;
-
-                            // Store the job handle
-                            running_jobs.lock().await.insert(submission.job_id.clone(), job_handle);
-                        }
-                    }
-                } else {
-                    // Channel closed, break the loop
-                    break;
-                }
+        let materialized = tokio::task::spawn_blocking(move || cached.materialize(&output_path))
+            .await
+            .map_err(|e| e.to_string())
+            .and_then(|result| result.map_err(|e| e.to_string()));
+        if let Err(e) = materialized {
+            DEBUG_LOGGER.warn("queue", format!("Failed to reuse cached download for job {}: {}", job.id, e));
+            return false;
+        }
+
+        let progress = ProgressParser::create_completed_progress();
+        let mut state_guard = state.write().await;
+        if let Some(job) = state_guard.get_job_mut(&job.id) {
+            job.from_cache = true;
+        }
+        bus.publish_locked(&mut state_guard, JobEvent::progress(&job.id, progress));
+        bus.publish_locked(&mut state_guard, JobEvent::status(&job.id, JobStatus::Completed));
+
+        true
+    }
 
-                // Clean up completed jobs
-                Self::cleanup_completed_jobs(Arc::clone(&running_jobs)).await;
+    /// The config with the job's own settings applied
+    fn job_config(config: &AppConfig, job: &DownloadJob) -> AppConfig {
+        let mut config = config.clone();
 
-                // Small delay to prevent busy waiting
-                sleep(Duration::from_millis(10)).await;
+        // Age-restricted jobs use the age-verified cookies when configured
+        if job.age_restricted {
+            if let Some(cookies_path) = config.age_verified_cookies_path.clone() {
+                config.cookies_path = Some(cookies_path);
             }
+        }
 
-            // Cleanup all running jobs on shutdown
-            Self::cleanup_all_jobs(Arc::clone(&running_jobs)).await;
-        });
+        // A per-job proxy carries its own credentials, if any
+        if let Some(proxy) = job.proxy.clone() {
+            config.proxy_url = Some(proxy);
+            config.proxy_username = job.proxy_credentials.as_ref().map(|credentials| credentials.username.clone());
+            config.proxy_password = job.proxy_credentials.as_ref().and_then(|credentials| credentials.password.clone());
+        }
 
-        Ok(())
+        // Output routes may send the job to another folder or templates
+        output_routing::apply(&mut config, job);
+        config
     }
 
-    /// Spawn a worker task for processing a download job
-    async fn spawn_worker_task(
-        state: Arc<RwLock<AppState>>,
-        gytmdl_wrapper: Arc<GytmdlWrapper>,
-        job: DownloadJob,
-        retry_count: u32,
-    ) -> tokio::task::JoinHandle<()> {
-        tokio::spawn(async move {
-            let job_id = job.id.clone();
-            let result = Self::process_job(
-                Arc::clone(&state),
-                Arc::clone(&gytmdl_wrapper),
-                job,
-                retry_count,
-            ).await;
+    /// Fix the job's output folder and templates before it starts. When a
+    /// route matches on artist or album, the metadata is fetched now rather
+    /// than left to the background prefetch, which may not have finished.
+    async fn resolve_routed_output(state: &Arc<RwLock<AppState>>, mut job: DownloadJob) -> DownloadJob {
+        if job.routed_output.is_some() {
+            return job;
+        }
+        let needs_metadata = job.metadata.is_none() && state.read().await.config.output_routes.iter()
+            .any(|route| route.enabled && route.needs_metadata());
+        if needs_metadata {
+            match MetadataFetcher::new().fetch(&job.url).await {
+                Ok(metadata) => job.metadata = Some(metadata),
+                Err(e) => DEBUG_LOGGER.warn("queue", format!("Could not fetch metadata to route job {}: {}", job.id, e)),
+            }
+        }
 
-            // Update job status based on result
-            let mut state_guard = state.write().await;
-            match result {
-                JobResult::Success(_) => {
-                    state_guard.update_job_status(&job_id, JobStatus::Completed);
-                    state_guard.update_job_progress(&job_id, ProgressParser::create_completed_progress());
-                }
-                JobResult::Failed(_, error) => {
-                    state_guard.set_job_error(&job_id, error);
-                }
-                JobResult::Cancelled(_) => {
-                    state_guard.update_job_status(&job_id, JobStatus::Cancelled);
-                }
+        let mut state_guard = state.write().await;
+        let routed = output_routing::resolve(&state_guard.config, &job);
+        if let Some(stored) = state_guard.get_job_mut(&job.id) {
+            if stored.metadata.is_none() {
+                stored.metadata = job.metadata.clone();
             }
-        })
+            stored.routed_output = Some(routed.clone());
+        }
+        job.routed_output = Some(routed);
+        job
+    }
+
+    /// Folder a job downloads into, after output routing
+    fn job_output_path(state_guard: &AppState, job_id: &str) -> std::path::PathBuf {
+        match state_guard.get_job(job_id) {
+            Some(job) => output_routing::output_path(&state_guard.config, job),
+            None => state_guard.config.output_path.clone(),
+        }
     }
 
     /// Process a single download job
     async fn process_job(
         state: Arc<RwLock<AppState>>,
-        gytmdl_wrapper: Arc<GytmdlWrapper>,
+        process_runner: Arc<ProcessRunner>,
+        services: &WorkerServices,
         job: DownloadJob,
         _retry_count: u32,
+        mut cancel_rx: watch::Receiver<bool>,
     ) -> JobResult {
+        let WorkerServices { bus, journal, job_logs, .. } = services;
         let job_id = job.id.clone();
 
         // Get current config
-        let config = {
-            let state_guard = state.read().await;
-            state_guard.config.clone()
-        };
+        let config = Self::job_config(&state.read().await.config, &job);
+        if job.proxy_requires_auth && job.proxy_credentials.is_none() {
+            return JobResult::Failed(job_id, "The job's proxy credentials aren't kept across restarts; queue it again with its proxy".to_string());
+        }
 
-        // Update progress to initializing
+        // Update progress to initializing; later updates only touch the live cell
         {
+            let progress = ProgressParser::create_initializing_progress();
+            if config.progress_journal {
+                journal.lock().await.record(&job_id, &progress);
+            }
             let mut state_guard = state.write().await;
-            state_guard.update_job_progress(&job_id, ProgressParser::create_initializing_progress());
+            state_guard.live_progress.track(&job_id, progress.clone());
+            bus.publish_locked(&mut state_guard, JobEvent::progress(&job_id, progress));
         }
 
         // Debug: Log the binary path and command being used
-        println!("DEBUG: Attempting to spawn gytmdl process for job {}", job_id);
-        println!("DEBUG: Binary path: {:?}", gytmdl_wrapper.get_binary_path());
+        DEBUG_LOGGER.debug("queue", format!("Attempting to spawn gytmdl process for job {}", job_id));
+        DEBUG_LOGGER.debug("queue", format!("Binary path: {:?}", process_runner.get_binary_path()));
         
         // Test binary first
-        match gytmdl_wrapper.test_binary().await {
+        match process_runner.test_binary().await {
             Ok(version) => {
-                println!("DEBUG: Binary test successful, version: {}", version);
+                DEBUG_LOGGER.debug("queue", format!("Binary test successful, version: {}", version));
             }
             Err(e) => {
-                let error_msg = format!("Binary test failed: {}. Binary path: {:?}", e, gytmdl_wrapper.get_binary_path());
-                println!("DEBUG: {}", error_msg);
+                let error_msg = format!("Binary test failed: {}. Binary path: {:?}", e, process_runner.get_binary_path());
+                DEBUG_LOGGER.error("queue", &error_msg);
                 return JobResult::Failed(job_id, error_msg);
             }
         }
 
+        // gytmdl writes into the job's staging folder, so without overwrite it
+        // needs stand-ins there for the tracks already downloaded to skip them
+        if !config.overwrite {
+            let output_path = config.output_path.clone();
+            let staged_job = job_id.clone();
+            match tokio::task::spawn_blocking(move || ProcessRunner::seed_staging_dir(&output_path, &staged_job)).await {
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => DEBUG_LOGGER.warn("queue", format!("Failed to list existing files for job {}: {}", job_id, e)),
+                Err(e) => DEBUG_LOGGER.warn("queue", format!("Listing existing files for job {} panicked: {}", job_id, e)),
+            }
+        }
+
         // Spawn the gytmdl process
-        let mut process = match gytmdl_wrapper.spawn_download_process(&config, &job).await {
+        let mut process = match process_runner.spawn_download_process(&config, &job).await {
             Ok(process) => {
-                println!("DEBUG: Process spawned successfully with PID: {:?}", process.process_id());
+                DEBUG_LOGGER.debug("queue", format!("Process spawned successfully with PID: {:?}", process.process_id()));
                 process
             },
             Err(e) => {
                 let error_msg = match e {
-                    crate::modules::gytmdl_wrapper::GytmdlError::BinaryNotFound(_) => {
+                    GytmdlError::BinaryNotFound(_) => {
                         format!("gytmdl binary not found. Please build sidecar binaries or install gytmdl. Error: {}", e)
                     }
                     _ => format!("Failed to spawn process: {}", e)
                 };
-                println!("DEBUG: Process spawn failed: {}", error_msg);
+                DEBUG_LOGGER.error("queue", format!("Process spawn failed: {}", error_msg));
                 return JobResult::Failed(job_id, error_msg);
             }
         };
 
         // Process output and update progress
-        let mut stdout_done = false;
-        let mut stderr_done = false;
-        
+        let mut output = process.output_lines();
+        let mut exit_status = None;
+        // Last track position reported by a playlist or album job
+        let mut playlist = None;
+        // The job's track length only describes what ffmpeg remuxes for single tracks
+        let track_secs = job.metadata.as_ref()
+            .and_then(|metadata| metadata.duration)
+            .filter(|_| !track_selection::is_collection_url(&job.url));
+        let progress_stream = ProgressStream::new().with_track_duration(track_secs);
+        // Stuck processes would otherwise hold a concurrency slot forever
+        let mut watchdog = Watchdog::new(&config, Instant::now());
+
         loop {
-            // Check if process has finished first
-            match process.try_wait() {
-                Ok(Some(exit_status)) => {
-                    println!("DEBUG: Process exited with status: {:?}", exit_status);
-                    if exit_status.success() {
-                        println!("DEBUG: Process completed successfully");
-                        return JobResult::Success(job_id);
-                    } else {
-                        let error_msg = match exit_status.code() {
-                            Some(2) => {
-                                let msg = format!("gytmdl process failed with exit code 2. Binary path: {:?}. This usually means the binary is not working correctly or missing dependencies.", gytmdl_wrapper.get_binary_path());
-                                println!("DEBUG: {}", msg);
-                                msg
-                            },
-                            Some(code) => {
-                                let msg = format!("Process exited with code: {}. Binary path: {:?}", code, gytmdl_wrapper.get_binary_path());
-                                println!("DEBUG: {}", msg);
-                                msg
-                            },
-                            None => {
-                                let msg = format!("Process was terminated by signal. Binary path: {:?}", gytmdl_wrapper.get_binary_path());
-                                println!("DEBUG: {}", msg);
-                                msg
-                            },
-                        };
-                        return JobResult::Failed(job_id, error_msg);
-                    }
-                }
-                Ok(None) => {
-                    // Process is still running, continue reading output
+            if let Some(reason) = watchdog.expired(Instant::now()) {
+                return Self::kill_timed_out_process(&mut process, job_id, reason).await;
+            }
+
+            let output_line = tokio::select! {
+                _ = Self::cancelled(&mut cancel_rx) => {
+                    return Self::kill_cancelled_process(&mut process, job_id).await;
                 }
-                Err(e) => {
-                    return JobResult::Failed(job_id, format!("Error checking process status: {}", e));
+                _ = watchdog.wait() => continue,
+                status = process.wait(), if exit_status.is_none() => {
+                    DEBUG_LOGGER.debug("queue", format!("Process exited with status: {:?}", status));
+                    exit_status = Some(status);
+                    continue;
                 }
+                // A child process of gytmdl can keep the pipes open after it exits
+                _ = sleep(OUTPUT_DRAIN_TIMEOUT), if exit_status.is_some() => break,
+                output_line = output.recv() => output_line,
+            };
+            // Both streams are closed
+            let Some(OutputLine { stream, line }) = output_line else {
+                break;
+            };
+            let (log_stream, stream_name) = match stream {
+                OutputStream::Stdout => (LogStream::Stdout, "stdout"),
+                OutputStream::Stderr => (LogStream::Stderr, "stderr"),
+            };
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => return JobResult::Failed(job_id, format!("Error reading {}: {}", stream_name, e)),
+            };
+
+            watchdog.output_received(Instant::now());
+            if stream == OutputStream::Stderr {
+                DEBUG_LOGGER.debug("queue", format!("gytmdl stderr: {}", line));
             }
+            let sanitized_line = ProgressParser::sanitize_output(&line);
+            job_logs.lock().await.append(&job_id, log_stream, &sanitized_line);
 
-            // Read stdout if not done
-            if !stdout_done {
-                match process.read_stdout_line().await {
-                    Ok(Some(line)) => {
-                        let sanitized_line = ProgressParser::sanitize_output(&line);
-                        
-                        // Check for completion
-                        if ProgressParser::is_completion_line(&sanitized_line) {
-                            // Don't break immediately, let the process finish naturally
-                        }
-                        
-                        // Parse progress and update state
-                        if let Some(progress) = ProgressParser::parse_output(&sanitized_line) {
-                            let mut state_guard = state.write().await;
-                            state_guard.update_job_progress(&job_id, progress);
-                        }
-                    }
-                    Ok(None) => {
-                        // EOF on stdout
-                        stdout_done = true;
-                    }
-                    Err(e) => {
-                        return JobResult::Failed(job_id, format!("Error reading stdout: {}", e));
-                    }
-                }
+            // Check for errors
+            if stream == OutputStream::Stderr && ProgressParser::is_error_line(&sanitized_line) {
+                DEBUG_LOGGER.warn("queue", format!("Error detected in stderr: {}", sanitized_line));
+                return JobResult::Failed(job_id, sanitized_line);
             }
 
-            // Read stderr if not done
-            if !stderr_done {
-                match process.read_stderr_line().await {
-                    Ok(Some(line)) => {
-                        println!("DEBUG: gytmdl stderr: {}", line);
-                        let sanitized_line = ProgressParser::sanitize_output(&line);
-                        
-                        // Check for errors
-                        if ProgressParser::is_error_line(&sanitized_line) {
-                            println!("DEBUG: Error detected in stderr: {}", sanitized_line);
-                            return JobResult::Failed(job_id, sanitized_line);
-                        }
-                        
-                        // Parse progress from stderr as well
-                        if let Some(progress) = ProgressParser::parse_output(&sanitized_line) {
-                            let mut state_guard = state.write().await;
-                            state_guard.update_job_progress(&job_id, progress);
-                        }
-                    }
-                    Ok(None) => {
-                        // EOF on stderr
-                        stderr_done = true;
-                    }
-                    Err(e) => {
-                        return JobResult::Failed(job_id, format!("Error reading stderr: {}", e));
-                    }
+            // Parse progress from either stream and update state
+            if let Some(mut progress) = progress_stream.parse_line(&sanitized_line) {
+                ProgressParser::carry_playlist(&mut progress, &mut playlist);
+                if config.progress_journal {
+                    journal.lock().await.record(&job_id, &progress);
                 }
+                bus.publish(JobEvent::progress(&job_id, progress)).await;
             }
+        }
 
-            // If both streams are done, wait for process to finish
-            if stdout_done && stderr_done {
-                break;
-            }
+        let exit_status = match exit_status {
+            Some(status) => status,
+            None => process.wait().await,
+        };
+        match exit_status {
+            Ok(exit_status) => Self::exit_result(job_id, exit_status, &process_runner),
+            Err(e) => JobResult::Failed(job_id, format!("Error waiting for process: {}", e)),
+        }
+    }
 
-            // Small delay to prevent busy waiting
-            sleep(Duration::from_millis(10)).await;
+    /// Map the exit status of a finished gytmdl process to the job's result
+    fn exit_result(job_id: String, exit_status: std::process::ExitStatus, process_runner: &ProcessRunner) -> JobResult {
+        if exit_status.success() {
+            DEBUG_LOGGER.debug("queue", "Process completed successfully");
+            return JobResult::Success(job_id);
         }
 
-        // If we reach here, check the final process status
-        match process.wait().await {
-            Ok(exit_status) => {
-                if exit_status.success() {
-                    JobResult::Success(job_id)
-                } else {
-                    let error_msg = format!("Process exited with code: {:?}", exit_status.code());
-                    JobResult::Failed(job_id, error_msg)
+        let error_msg = match exit_status.code() {
+            Some(2) => {
+                let mut msg = format!("gytmdl process failed with exit code 2. Binary path: {:?}. This usually means the binary is not working correctly or missing dependencies.", process_runner.get_binary_path());
+                if !SidecarManager::is_ffmpeg_reachable() {
+                    msg.push_str(" ffmpeg was not found on PATH; install it or add the bundled copy to PATH.");
                 }
-            }
-            Err(e) => {
-                JobResult::Failed(job_id, format!("Error waiting for process: {}", e))
+                msg
+            },
+            Some(code) => format!("Process exited with code: {}. Binary path: {:?}", code, process_runner.get_binary_path()),
+            None => format!("Process was terminated by signal. Binary path: {:?}", process_runner.get_binary_path()),
+        };
+        DEBUG_LOGGER.error("queue", &error_msg);
+        JobResult::Failed(job_id, error_msg)
+    }
+
+    /// Resolve once the job has been cancelled
+    async fn cancelled(cancel_rx: &mut watch::Receiver<bool>) {
+        while !*cancel_rx.borrow() {
+            if cancel_rx.changed().await.is_err() {
+                // Sender dropped without cancelling; never resolve
+                std::future::pending::<()>().await;
             }
         }
     }
 
+    /// Kill a cancelled job's gytmdl process
+    async fn kill_cancelled_process(process: &mut GytmdlProcess, job_id: String) -> JobResult {
+        if let Err(e) = process.kill().await {
+            DEBUG_LOGGER.error("queue", format!("Failed to kill gytmdl process for job {}: {}", job_id, e));
+        }
+        JobResult::Cancelled(job_id)
+    }
+
+    /// Kill a job's gytmdl process after the watchdog ran out
+    async fn kill_timed_out_process(process: &mut GytmdlProcess, job_id: String, reason: String) -> JobResult {
+        DEBUG_LOGGER.warn("queue", format!("Killing job {}: {}", job_id, reason));
+        if let Err(e) = process.kill().await {
+            DEBUG_LOGGER.error("queue", format!("Failed to kill gytmdl process for job {}: {}", job_id, e));
+        }
+        JobResult::Failed(job_id, reason)
+    }
+
     /// Submit a job to the queue for processing
     pub async fn submit_job(&self, job_id: String) -> Result<(), String> {
         let submission = JobSubmission {
@@ -370,33 +1864,17 @@ impl QueueManager {
                     return Err("Job cannot be retried".to_string());
                 }
                 
-                // Get current retry count from job metadata or default to 0
-                let current_retry_count = job.error.as_ref()
-                    .and_then(|error| {
-                        // Try to extract retry count from error message
-                        if error.contains("retry_count:") {
-                            error.split("retry_count:").nth(1)
-                                .and_then(|s| s.split_whitespace().next())
-                                .and_then(|s| s.parse::<u32>().ok())
-                        } else {
-                            None
-                        }
-                    })
-                    .unwrap_or(0);
-                
-                let new_retry_count = current_retry_count + 1;
-                
-                // Check maximum retry limit
-                if new_retry_count > 3 {
+                if !job.has_retries_left() {
                     return Err("Maximum retry attempts exceeded".to_string());
                 }
                 
                 job.reset_for_retry();
-                new_retry_count
+                job.retry_count
             } else {
                 return Err("Job not found".to_string());
             }
         };
+        self.events.job_status(&job_id, JobStatus::Queued, None);
 
         // Apply exponential backoff delay
         let delay_ms = Self::calculate_backoff_delay(retry_count);
@@ -428,24 +1906,138 @@ impl QueueManager {
     /// Cancel a specific job
     pub async fn cancel_job(&self, job_id: &str) -> Result<(), String> {
         // Update job status to cancelled
-        {
+        let (temp_path, output_path) = {
             let mut state_guard = self.state.write().await;
-            state_guard.update_job_status(job_id, JobStatus::Cancelled);
+            self.bus().publish_locked(&mut state_guard, JobEvent::status(job_id, JobStatus::Cancelled));
+            (state_guard.config.temp_path.clone(), Self::job_output_path(&state_guard, job_id))
+        };
+        let aborted = self.stop_job(job_id, &temp_path, &output_path).await;
+        // An aborted worker never settles its progress itself
+        self.state.write().await.settle_live_progress(job_id);
+        self.record_cancel(job_id, aborted).await;
+
+        Ok(())
+    }
+
+    /// Count a cancelled job whose worker was aborted before it could, and
+    /// close the session if the cancel left the queue drained
+    async fn record_cancel(&self, job_id: &str, aborted: bool) {
+        let state_guard = self.state.read().await;
+        if aborted {
+            self.metrics.lock().await.record_cancelled();
+            if let Some(job) = state_guard.get_job(job_id) {
+                self.session.lock().await.record_cancelled(job);
+            }
         }
+        Self::finish_session_if_drained(&state_guard, &self.session, &self.events).await;
+    }
 
-        // Kill the running process if it exists
-        let mut running_jobs = self.running_jobs.lock().await;
-        if let Some(handle) = running_jobs.remove(job_id) {
-            handle.abort();
+    /// Kill a job's process if it is running and drop its partial and staged files.
+    /// Returns whether its worker was aborted before it could record the job.
+    async fn stop_job(&self, job_id: &str, temp_path: &std::path::Path, output_path: &std::path::Path) -> bool {
+        let running_job = self.running_jobs.lock().await.remove(job_id);
+        let aborted = match running_job {
+            Some(running_job) => !running_job.cancel().await,
+            None => false,
+        };
+        match ProcessRunner::remove_job_temp_dir(temp_path, job_id) {
+            Ok(true) => DEBUG_LOGGER.info("queue", format!("Removed partial files for cancelled job {}", job_id)),
+            Ok(false) => {}
+            Err(e) => DEBUG_LOGGER.warn("queue", format!("Failed to remove temp directory for job {}: {}", job_id, e)),
+        }
+        if let Err(e) = ProcessRunner::remove_job_staging_dir(output_path, job_id) {
+            DEBUG_LOGGER.warn("queue", format!("Failed to remove staging directory for job {}: {}", job_id, e));
         }
+        self.journal.lock().await.discard(job_id);
+        aborted
+    }
 
-        Ok(())
+    /// Apply one action to several jobs. State changes for the whole batch
+    /// happen under one lock, so the queue is never seen half-updated; jobs
+    /// the action doesn't apply to are reported and skipped.
+    pub async fn batch_job_action(&self, job_ids: &[String], action: BatchAction) -> Vec<BatchActionResult> {
+        let mut results = Vec::with_capacity(job_ids.len());
+        let mut applied = Vec::new();
+        let temp_path = {
+            let mut state_guard = self.state.write().await;
+            for job_id in job_ids {
+                // Resolved before a removal takes the job out of the queue
+                let output_path = Self::job_output_path(&state_guard, job_id);
+                let outcome = Self::apply_batch_action(&mut state_guard, job_id, action);
+                if let Ok(retry_count) = outcome {
+                    applied.push((job_id.clone(), retry_count, output_path));
+                }
+                results.push(BatchActionResult {
+                    job_id: job_id.clone(),
+                    success: outcome.is_ok(),
+                    error: outcome.err(),
+                });
+            }
+            state_guard.config.temp_path.clone()
+        };
+
+        for (job_id, retry_count, output_path) in applied {
+            match action {
+                BatchAction::Cancel => {
+                    self.events.job_status(&job_id, JobStatus::Cancelled, None);
+                    let aborted = self.stop_job(&job_id, &temp_path, &output_path).await;
+                    self.record_cancel(&job_id, aborted).await;
+                }
+                BatchAction::Remove => {
+                    self.stop_job(&job_id, &temp_path, &output_path).await;
+                }
+                BatchAction::Retry => {
+                    self.events.job_status(&job_id, JobStatus::Queued, None);
+                    Self::submit_after_backoff(&self.job_sender, JobSubmission { job_id, retry_count });
+                }
+                BatchAction::SetPriority(_) => {}
+            }
+        }
+        results
+    }
+
+    /// Change a job's state for a batch action. Returns its retry count.
+    fn apply_batch_action(state: &mut AppState, job_id: &str, action: BatchAction) -> Result<u32, String> {
+        let job = state.get_job_mut(job_id).ok_or_else(|| "Job not found".to_string())?;
+        let retry_count = job.retry_count;
+        match action {
+            BatchAction::Cancel => {
+                if job.is_terminal() {
+                    return Err("Job has already finished".to_string());
+                }
+                state.update_job_status(job_id, JobStatus::Cancelled);
+            }
+            BatchAction::Retry => {
+                if !job.can_retry() {
+                    return Err("Job cannot be retried".to_string());
+                }
+                if !job.has_retries_left() {
+                    return Err("Maximum retry attempts exceeded".to_string());
+                }
+                job.reset_for_retry();
+                return Ok(job.retry_count);
+            }
+            BatchAction::Remove => {
+                state.trash_job(job_id);
+            }
+            BatchAction::SetPriority(priority) => job.priority = priority,
+        }
+        Ok(retry_count)
+    }
+
+    /// Get the recorded progress journal for a job
+    pub async fn get_progress_history(&self, job_id: &str) -> Vec<JournalEntry> {
+        self.journal.lock().await.history(job_id)
+    }
+
+    /// Get the raw gytmdl output captured for a job
+    pub async fn get_job_logs(&self, job_id: &str) -> Vec<LogLine> {
+        self.job_logs.lock().await.get(job_id)
     }
 
     /// Pause the queue processing
     pub async fn pause(&self) {
-        let mut is_paused = self.is_paused.write().await;
-        *is_paused = true;
+        self.is_paused.send_replace(true);
 
         // Update state
         let mut state_guard = self.state.write().await;
@@ -454,17 +2046,54 @@ impl QueueManager {
 
     /// Resume the queue processing
     pub async fn resume(&self) {
-        let mut is_paused = self.is_paused.write().await;
-        *is_paused = false;
+        self.is_paused.send_replace(false);
 
         // Update state
         let mut state_guard = self.state.write().await;
         state_guard.resume();
     }
 
+    /// Pause the queue because the connection dropped and mark running jobs
+    /// to be queued again when they fail. Returns false when the queue was
+    /// already paused, which leaves resuming to the user.
+    pub async fn pause_for_outage(&self) -> bool {
+        let mut state_guard = self.state.write().await;
+        state_guard.degraded.insert(DegradedFlags::OFFLINE);
+        for job in state_guard.jobs.iter_mut().filter(|job| job.status == JobStatus::Downloading) {
+            job.retry_when_online = true;
+        }
+        if state_guard.is_paused() {
+            return false;
+        }
+        state_guard.pause();
+        self.is_paused.send_replace(true);
+        self.events.queue_paused("Lost the connection");
+        true
+    }
+
+    /// The connection is back: clear the offline flag and resume the queue if
+    /// `resume` and nobody resumed it in the meantime
+    pub async fn end_outage(&self, resume: bool) {
+        let mut state_guard = self.state.write().await;
+        state_guard.degraded.remove(DegradedFlags::OFFLINE);
+        for job in state_guard.jobs.iter_mut() {
+            job.retry_when_online = false;
+        }
+        if resume && state_guard.is_paused() {
+            state_guard.resume();
+            self.is_paused.send_replace(false);
+        }
+        self.wake.notify_one();
+    }
+
     /// Check if the queue is paused
     pub async fn is_paused(&self) -> bool {
-        *self.is_paused.read().await
+        *self.is_paused.borrow()
+    }
+
+    /// Have the dispatcher re-read the queue and job schedules now
+    pub fn reschedule(&self) {
+        self.wake.notify_one();
     }
 
     /// Get the number of currently running jobs
@@ -478,38 +2107,39 @@ impl QueueManager {
         state_guard.count_jobs_by_status(&JobStatus::Queued)
     }
 
-    /// Shutdown the queue manager
+    /// Stop dispatching and kill every running gytmdl process
     pub async fn shutdown(&self) {
-        let mut is_shutdown = self.is_shutdown.write().await;
-        *is_shutdown = true;
+        self.is_shutdown.send_replace(true);
 
         // Cancel all running jobs
+        let interrupted: Vec<String> = self.running_jobs.lock().await.keys().cloned().collect();
         Self::cleanup_all_jobs(Arc::clone(&self.running_jobs)).await;
-    }
-
-    /// Clean up completed job handles
-    async fn cleanup_completed_jobs(running_jobs: Arc<Mutex<HashMap<String, tokio::task::JoinHandle<()>>>>) {
-        let mut jobs = running_jobs.lock().await;
-        let mut completed_jobs = Vec::new();
 
-        for (job_id, handle) in jobs.iter() {
-            if handle.is_finished() {
-                completed_jobs.push(job_id.clone());
+        // Keep interrupted downloads queued so they run again on the next launch
+        let mut state_guard = self.state.write().await;
+        for job_id in &interrupted {
+            if let Some(job) = state_guard.get_job_mut(job_id) {
+                job.requeue();
             }
         }
-
-        for job_id in completed_jobs {
-            jobs.remove(&job_id);
+        if !interrupted.is_empty() {
+            DEBUG_LOGGER.info("queue", format!("Stopped {} running job(s) for shutdown", interrupted.len()));
         }
     }
 
     /// Clean up all running jobs (for shutdown)
-    async fn cleanup_all_jobs(running_jobs: Arc<Mutex<HashMap<String, tokio::task::JoinHandle<()>>>>) {
-        let mut jobs = running_jobs.lock().await;
-        
-        for (_, handle) in jobs.drain() {
-            handle.abort();
+    async fn cleanup_all_jobs(running_jobs: RunningJobs) {
+        let jobs: Vec<RunningJob> = running_jobs.lock().await
+            .drain()
+            .map(|(_, running_job)| running_job)
+            .collect();
+
+        // Cancel concurrently so every child process is killed
+        let mut cancellations = JoinSet::new();
+        for running_job in jobs {
+            cancellations.spawn(running_job.cancel());
         }
+        while cancellations.join_next().await.is_some() {}
     }
 
     /// Process all queued jobs (convenience method)
@@ -529,15 +2159,15 @@ impl QueueManager {
         Ok(())
     }
 
-    /// Remove a job from the queue and clean up resources
+    /// Move a job from the queue to the trash and clean up resources
     pub async fn remove_job(&self, job_id: &str) -> Result<(), String> {
         // First cancel the job if it's running
         self.cancel_job(job_id).await?;
 
-        // Remove from state
+        // Move to the trash
         {
             let mut state_guard = self.state.write().await;
-            if !state_guard.remove_job(job_id) {
+            if !state_guard.trash_job(job_id) {
                 return Err("Job not found".to_string());
             }
         }
@@ -555,6 +2185,25 @@ impl QueueManager {
         Ok(initial_count - final_count)
     }
 
+    /// Move finished jobs beyond the `keep` most recent to history. Returns
+    /// the number moved.
+    pub async fn archive_old_jobs(&self, keep: usize) -> Result<usize, String> {
+        let archived = self.state.write().await.take_finished_jobs_over(keep);
+        if archived.is_empty() {
+            return Ok(0);
+        }
+
+        let count = archived.len();
+        let written = Self::append_history(archived.jobs().to_vec()).await;
+        let mut state_guard = self.state.write().await;
+        state_guard.mark_changed();
+        if let Err(e) = written {
+            state_guard.restore_jobs(archived);
+            return Err(format!("Failed to write history: {}", e));
+        }
+        Ok(count)
+    }
+
     /// Cancel all jobs in the queue
     pub async fn cancel_all_jobs(&self) -> Result<usize, String> {
         let job_ids = {
@@ -601,36 +2250,150 @@ impl QueueManager {
         state_guard.get_job(job_id).cloned()
     }
 
-    /// Update the concurrent limit for the queue
-    pub async fn set_concurrent_limit(&mut self, limit: usize) -> Result<(), String> {
+    /// Update the concurrent limit for the queue. Takes effect immediately;
+    /// when lowered below the running count, running jobs finish but nothing new
+    /// starts until the count drops under the new limit.
+    pub async fn set_concurrent_limit(&self, limit: usize) -> Result<(), String> {
         if limit == 0 {
             return Err("Concurrent limit must be greater than 0".to_string());
         }
 
-        self.concurrent_limit = limit;
+        self.concurrent_limit.store(limit, Ordering::Relaxed);
+        self.wake.notify_one();
 
         // Update the config in state as well
         {
             let mut state_guard = self.state.write().await;
             state_guard.config.concurrent_limit = limit;
+            state_guard.concurrent_limit = limit;
         }
 
         Ok(())
     }
 
+    /// Change concurrency and speed schedule while the queue
+    /// runs. Requests go through the control task started by `start`.
+    pub async fn apply_runtime_settings(&self, settings: RuntimeSettings) -> Result<RuntimeSettingsResult, String> {
+        let (reply, response) = oneshot::channel();
+        self.control_sender.send(QueueControl::ApplySettings { settings, reply })
+            .map_err(|_| "Queue manager is not running".to_string())?;
+        response.await.map_err(|_| "Queue manager stopped before applying the settings".to_string())?
+    }
+
+    /// Organize a completed job's files into the configured music library
+    pub async fn organize_completed(&self, job_id: &str) -> Result<Vec<OrganizedFile>, String> {
+        Self::organize_job_files(&self.state, &self.library, job_id).await
+    }
+
+    /// Re-fetch or replace a completed job's cover art: embed it into the
+    /// job's files, save it next to them when covers are saved and copy it to
+    /// the cover folder. Without a `source` the thumbnail is fetched again;
+    /// size and format default to the cover settings.
+    pub async fn update_cover(
+        &self,
+        job_id: &str,
+        source: Option<CoverSource>,
+        size: Option<u32>,
+        format: Option<CoverFormat>,
+    ) -> Result<CoverUpdate, String> {
+        let (job, config) = {
+            let state_guard = self.state.read().await;
+            let job = state_guard.get_job(job_id).ok_or("Job not found")?.clone();
+            (job, state_guard.config.clone())
+        };
+        if job.status != JobStatus::Completed {
+            return Err("Cover art can only be changed on completed jobs".to_string());
+        }
+        let files: Vec<_> = job.output_files.iter().filter(|path| path.exists()).cloned().collect();
+        if files.is_empty() {
+            return Err("No downloaded files found for this job".to_string());
+        }
+
+        let format = format.unwrap_or_else(|| config.cover_format.clone());
+        let options = CoverOptions {
+            size: size.unwrap_or(config.cover_size),
+            central_path: config.cover_folder.as_deref()
+                .map(|folder| cover_art::central_cover_path(folder, job.metadata.as_ref(), job_id, &format)),
+            format,
+            quality: config.cover_quality,
+            save_next_to_tracks: config.save_cover,
+        };
+        let update = Self::apply_cover(&job, &config, source, &files, options).await
+            .map_err(|e| e.to_string())?;
+        DEBUG_LOGGER.info("queue", format!("Updated cover of job {} ({} files embedded)", job_id, update.embedded.len()));
+        Ok(update)
+    }
+
+    /// Write an .m3u8 playlist of a group's downloaded tracks
+    pub async fn generate_playlist_file(&self, group_id: &str, path: Option<std::path::PathBuf>) -> Result<std::path::PathBuf, String> {
+        let playlist = Self::group_playlist(&*self.state.read().await, group_id);
+        Self::write_group_playlist(playlist, path).await
+    }
+
+    /// Verify every file in the library: existence, checksum and duration
+    pub async fn verify_library(&self) -> IntegrityReport {
+        let output_root = self.state.read().await.config.output_path.clone();
+        let known = self.library.lock().await.entries().clone();
+
+        // Verification can take minutes; don't hold the manifest lock while it runs
+        let results = library_integrity::verify_library(output_root, known).await;
+
+        let mut library = self.library.lock().await;
+        let report = library.apply_results(results);
+        if let Err(e) = library.save() {
+            DEBUG_LOGGER.error("queue", format!("Failed to save library manifest: {}", e));
+        }
+        report
+    }
+
     /// Get the current concurrent limit
     pub fn get_concurrent_limit(&self) -> usize {
-        self.concurrent_limit
+        self.concurrent_limit.load(Ordering::Relaxed)
     }
 
     /// Check if the queue manager is healthy (binary available, etc.)
     pub async fn health_check(&self) -> Result<String, String> {
-        match self.gytmdl_wrapper.test_binary().await {
+        match self.process_runner.test_binary().await {
             Ok(version) => Ok(format!("Queue manager healthy. gytmdl version: {}", version)),
             Err(e) => Err(format!("Health check failed: {}", e)),
         }
     }
 
+    /// Project how many queued tracks fit on the output volume
+    pub async fn get_disk_space_projection(&self) -> Result<SpaceProjection, String> {
+        let (output_path, queued_tracks) = {
+            let state_guard = self.state.read().await;
+            (state_guard.config.output_path.clone(), state_guard.queued_track_count())
+        };
+
+        let available_bytes = disk_space::available_space(&output_path)
+            .map_err(|e| format!("Failed to read free space for {:?}: {}", output_path, e))?;
+
+        let monitor = self.disk_monitor.lock().await;
+        Ok(monitor.project(output_path, available_bytes, queued_tracks))
+    }
+
+    /// Estimate the data, time and cost needed to finish the queue, and when
+    /// it will be done
+    pub async fn get_queue_forecast(&self) -> QueueForecast {
+        let state_guard = self.state.read().await;
+        Self::queue_forecast(&state_guard, &self.throughput, &self.metrics, &self.disk_monitor).await
+    }
+
+    /// Get session-wide download totals and the live combined speed
+    pub async fn get_queue_metrics(&self) -> QueueMetrics {
+        let current_speed = {
+            let state_guard = self.state.read().await;
+            state_guard.jobs.iter()
+                .filter(|job| matches!(job.status, JobStatus::Downloading))
+                .filter_map(|job| state_guard.job_progress(job).speed_bytes_per_sec)
+                .sum()
+        };
+        let running_count = self.running_jobs.lock().await.len();
+
+        self.metrics.lock().await.snapshot(current_speed, running_count)
+    }
+
     /// Get queue statistics
     pub async fn get_queue_stats(&self) -> QueueStats {
         let state_guard = self.state.read().await;
@@ -643,13 +2406,30 @@ impl QueueManager {
             failed: state_guard.count_jobs_by_status(&JobStatus::Failed),
             cancelled: state_guard.count_jobs_by_status(&JobStatus::Cancelled),
             total: state_guard.jobs.len(),
-            is_paused: *self.is_paused.read().await,
+            is_paused: *self.is_paused.borrow(),
         }
     }
 }
 
+/// Action applied to every job selected in the queue
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize)]
+pub enum BatchAction {
+    Cancel,
+    Retry,
+    Remove,
+    SetPriority(u32),
+}
+
+/// Outcome of a batch action for one job
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct BatchActionResult {
+    pub job_id: String,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
 /// Queue statistics
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize)]
 pub struct QueueStats {
     pub queued: usize,
     pub downloading: usize,
@@ -750,13 +2530,14 @@ mod tests {
     async fn test_concurrent_limit_update() {
         let state = Arc::new(RwLock::new(AppState::new()));
         
-        if let Ok(mut manager) = QueueManager::new(Arc::clone(&state), 2) {
+        if let Ok(manager) = QueueManager::new(Arc::clone(&state), 2) {
             assert_eq!(manager.get_concurrent_limit(), 2);
             
             // Update concurrent limit
             let result = manager.set_concurrent_limit(5).await;
             assert!(result.is_ok());
             assert_eq!(manager.get_concurrent_limit(), 5);
+            assert_eq!(state.read().await.config.concurrent_limit, 5);
             
             // Test invalid limit
             let result = manager.set_concurrent_limit(0).await;
@@ -815,6 +2596,33 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_shutdown_requeues_running_jobs() {
+        let state = Arc::new(RwLock::new(AppState::new()));
+
+        if let Ok(manager) = QueueManager::new(Arc::clone(&state), 2) {
+            let job_id = {
+                let mut state_guard = state.write().await;
+                let job_id = state_guard.add_job("https://test.com".to_string());
+                state_guard.update_job_status(&job_id, JobStatus::Downloading);
+                job_id
+            };
+
+            let (cancel_tx, mut cancel_rx) = watch::channel(false);
+            let handle = tokio::spawn(async move {
+                QueueManager::cancelled(&mut cancel_rx).await;
+            });
+            manager.running_jobs.lock().await.insert(job_id.clone(), RunningJob { handle, cancel_tx });
+
+            manager.shutdown().await;
+
+            let job = manager.get_job_info(&job_id).await.unwrap();
+            assert_eq!(job.status, JobStatus::Queued);
+            assert_eq!(job.retry_count, 0);
+            assert!(manager.running_jobs.lock().await.is_empty());
+        }
+    }
+
     #[tokio::test]
     async fn test_clear_completed_jobs() {
         let state = Arc::new(RwLock::new(AppState::new()));
@@ -869,6 +2677,34 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_batch_job_action() {
+        let state = Arc::new(RwLock::new(AppState::new()));
+
+        if let Ok(manager) = QueueManager::new(Arc::clone(&state), 2) {
+            let (queued, completed) = {
+                let mut state_guard = state.write().await;
+                let queued = state_guard.add_job("https://music.youtube.com/watch?v=a".to_string());
+                let completed = state_guard.add_job("https://music.youtube.com/watch?v=b".to_string());
+                state_guard.update_job_status(&completed, JobStatus::Completed);
+                (queued, completed)
+            };
+            let job_ids = vec![queued.clone(), completed.clone(), "missing".to_string()];
+
+            let results = manager.batch_job_action(&job_ids, BatchAction::SetPriority(5)).await;
+            assert_eq!(results.iter().filter(|result| result.success).count(), 2);
+            assert_eq!(results[2].error.as_deref(), Some("Job not found"));
+
+            let results = manager.batch_job_action(&job_ids, BatchAction::Cancel).await;
+            assert!(results[0].success);
+            assert_eq!(results[1].error.as_deref(), Some("Job has already finished"));
+
+            let state_guard = state.read().await;
+            assert_eq!(state_guard.get_job(&queued).unwrap().status, JobStatus::Cancelled);
+            assert_eq!(state_guard.get_job(&completed).unwrap().priority, 5);
+        }
+    }
+
     #[tokio::test]
     async fn test_health_check() {
         let state = Arc::new(RwLock::new(AppState::new()));
@@ -884,4 +2720,35 @@ mod tests {
             }
         }
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_cancel_signal_resolves_waiter() {
+        let (cancel_tx, mut cancel_rx) = watch::channel(false);
+
+        let waiter = tokio::spawn(async move {
+            QueueManager::cancelled(&mut cancel_rx).await;
+        });
+
+        sleep(Duration::from_millis(20)).await;
+        assert!(!waiter.is_finished());
+
+        cancel_tx.send(true).unwrap();
+        tokio::time::timeout(Duration::from_secs(1), waiter).await
+            .expect("waiter should resolve after cancel")
+            .unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_kill_cancelled_process() {
+        let child = tokio::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .expect("Failed to spawn sleep");
+        let mut process = GytmdlProcess::new(child, "job-1".to_string());
+
+        let result = QueueManager::kill_cancelled_process(&mut process, "job-1".to_string()).await;
+        assert!(matches!(result, JobResult::Cancelled(id) if id == "job-1"));
+        assert!(process.try_wait().unwrap().is_some());
+    }
+}
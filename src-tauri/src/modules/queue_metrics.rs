@@ -0,0 +1,148 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Session totals for jobs the queue has finished
+#[derive(Debug)]
+pub struct MetricsCollector {
+    session_started_at: DateTime<Utc>,
+    total_bytes: u64,
+    succeeded: u64,
+    failed: u64,
+    cancelled: u64,
+    /// Combined run time of succeeded and failed jobs
+    total_duration: Duration,
+    /// Size and track length of downloads whose length was known
+    track_bytes: u64,
+    track_seconds: u64,
+}
+
+/// Snapshot of queue activity since the app started
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueueMetrics {
+    pub session_started_at: DateTime<Utc>,
+    pub total_bytes_downloaded: u64,
+    pub jobs_succeeded: u64,
+    pub jobs_failed: u64,
+    pub jobs_cancelled: u64,
+    /// Share of finished jobs that succeeded; cancelled jobs are not counted
+    pub success_rate: Option<f64>,
+    pub failure_rate: Option<f64>,
+    pub average_job_duration_secs: Option<f64>,
+    /// Sum of the reported speeds of all running jobs
+    pub current_speed_bytes_per_sec: u64,
+    pub running_jobs: usize,
+}
+
+impl MetricsCollector {
+    pub fn new() -> Self {
+        Self {
+            session_started_at: Utc::now(),
+            total_bytes: 0,
+            succeeded: 0,
+            failed: 0,
+            cancelled: 0,
+            total_duration: Duration::ZERO,
+            track_bytes: 0,
+            track_seconds: 0,
+        }
+    }
+
+    pub fn record_success(&mut self, bytes: u64, duration: Duration) {
+        self.succeeded += 1;
+        self.total_bytes += bytes;
+        self.total_duration += duration;
+    }
+
+    /// Record the size of a download whose track length is known
+    pub fn record_track_length(&mut self, bytes: u64, track_seconds: u32) {
+        if bytes == 0 || track_seconds == 0 {
+            return;
+        }
+        self.track_bytes += bytes;
+        self.track_seconds += u64::from(track_seconds);
+    }
+
+    /// Average file size per second of audio
+    pub fn bytes_per_track_second(&self) -> Option<f64> {
+        (self.track_seconds > 0).then(|| self.track_bytes as f64 / self.track_seconds as f64)
+    }
+
+    /// Average run time of succeeded and failed jobs
+    pub fn average_job_duration_secs(&self) -> Option<f64> {
+        let finished = self.succeeded + self.failed;
+        (finished > 0).then(|| self.total_duration.as_secs_f64() / finished as f64)
+    }
+
+    pub fn record_failure(&mut self, duration: Duration) {
+        self.failed += 1;
+        self.total_duration += duration;
+    }
+
+    pub fn record_cancelled(&mut self) {
+        self.cancelled += 1;
+    }
+
+    /// Combine the session totals with the live state of running jobs
+    pub fn snapshot(&self, current_speed_bytes_per_sec: u64, running_jobs: usize) -> QueueMetrics {
+        let finished = self.succeeded + self.failed;
+        let (success_rate, failure_rate) = if finished > 0 {
+            (
+                Some(self.succeeded as f64 / finished as f64),
+                Some(self.failed as f64 / finished as f64),
+            )
+        } else {
+            (None, None)
+        };
+
+        QueueMetrics {
+            session_started_at: self.session_started_at,
+            total_bytes_downloaded: self.total_bytes,
+            jobs_succeeded: self.succeeded,
+            jobs_failed: self.failed,
+            jobs_cancelled: self.cancelled,
+            success_rate,
+            failure_rate,
+            average_job_duration_secs: self.average_job_duration_secs(),
+            current_speed_bytes_per_sec,
+            running_jobs,
+        }
+    }
+}
+
+impl Default for MetricsCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_snapshot() {
+        let metrics = MetricsCollector::new().snapshot(0, 0);
+        assert_eq!(metrics.total_bytes_downloaded, 0);
+        assert!(metrics.success_rate.is_none());
+        assert!(metrics.average_job_duration_secs.is_none());
+    }
+
+    #[test]
+    fn test_rates_and_averages() {
+        let mut collector = MetricsCollector::new();
+        collector.record_success(3_000_000, Duration::from_secs(10));
+        collector.record_success(5_000_000, Duration::from_secs(20));
+        collector.record_failure(Duration::from_secs(30));
+        collector.record_cancelled();
+
+        let metrics = collector.snapshot(1_500_000, 2);
+        assert_eq!(metrics.total_bytes_downloaded, 8_000_000);
+        assert_eq!(metrics.jobs_cancelled, 1);
+        assert!((metrics.success_rate.unwrap() - 2.0 / 3.0).abs() < 1e-9);
+        assert!((metrics.failure_rate.unwrap() - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(metrics.average_job_duration_secs, Some(20.0));
+        assert_eq!(metrics.current_speed_bytes_per_sec, 1_500_000);
+        assert_eq!(metrics.running_jobs, 2);
+    }
+}
@@ -0,0 +1,59 @@
+use crate::modules::speed_schedule::ScheduleWindow;
+use crate::modules::state::AppConfig;
+use serde::{Deserialize, Serialize};
+
+/// Queue settings that can change while downloads are running. Unset fields
+/// are left as they are.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct RuntimeSettings {
+    pub concurrent_limit: Option<usize>,
+    pub speed_schedule: Option<Vec<ScheduleWindow>>,
+}
+
+/// Which of the requested settings took effect
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct RuntimeSettingsResult {
+    /// In effect for the whole queue right away
+    pub applied: Vec<&'static str>,
+}
+
+/// Write `settings` into `config` and report which ones took effect
+pub fn apply(config: &mut AppConfig, settings: &RuntimeSettings) -> RuntimeSettingsResult {
+    let mut result = RuntimeSettingsResult::default();
+
+    // The dispatch loop re-reads the limit and schedule whenever it is woken
+    if let Some(limit) = settings.concurrent_limit {
+        config.concurrent_limit = limit;
+        result.applied.push("concurrent_limit");
+    }
+    if let Some(windows) = &settings.speed_schedule {
+        config.speed_schedule = windows.clone();
+        result.applied.push("speed_schedule");
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_reports_applied_settings() {
+        let mut config = AppConfig::default();
+        let settings = RuntimeSettings {
+            concurrent_limit: Some(5),
+            ..RuntimeSettings::default()
+        };
+
+        let result = apply(&mut config, &settings);
+        assert_eq!(config.concurrent_limit, 5);
+        assert_eq!(result.applied, vec!["concurrent_limit"]);
+
+        let cleared = RuntimeSettings { speed_schedule: Some(Vec::new()), ..RuntimeSettings::default() };
+        let result = apply(&mut config, &cleared);
+        assert!(config.speed_schedule.is_empty());
+        assert_eq!(result.applied, vec!["speed_schedule"]);
+    }
+}
@@ -0,0 +1,284 @@
+use crate::modules::config_manager::ConfigManager;
+use crate::modules::cookie_manager::CookieManager;
+use crate::modules::disk_space;
+use crate::modules::binary_locator::BinaryLocator;
+use crate::modules::process_runner::ProcessRunner;
+use crate::modules::state::{AppConfig, AppState};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// Time budget for each individual check
+const CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Free space below which the output volume is reported as low
+const LOW_DISK_SPACE_BYTES: u64 = 500 * 1024 * 1024;
+
+/// Bitmask of features that are unavailable or impaired
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct DegradedFlags(u32);
+
+impl DegradedFlags {
+    pub const NONE: Self = Self(0);
+    /// The gytmdl sidecar is missing or doesn't run
+    pub const DOWNLOADS_UNAVAILABLE: Self = Self(1);
+    /// No valid cookies, so age-restricted and premium content will fail
+    pub const AUTHENTICATION_UNAVAILABLE: Self = Self(1 << 1);
+    /// The saved configuration doesn't pass validation
+    pub const CONFIG_INVALID: Self = Self(1 << 2);
+    /// Saved state couldn't be read or the state directory isn't writable
+    pub const PERSISTENCE_UNAVAILABLE: Self = Self(1 << 3);
+    /// The output volume is nearly full or couldn't be checked
+    pub const LOW_DISK_SPACE: Self = Self(1 << 4);
+    /// YouTube kept refusing downloads, so the cookies are probably stale.
+    /// Set by the queue rather than the self-test; cleared by importing cookies.
+    pub const COOKIES_SUSPECT: Self = Self(1 << 5);
+    /// The connectivity monitor can't reach its endpoint. Set and cleared by
+    /// the monitor rather than the self-test.
+    pub const OFFLINE: Self = Self(1 << 6);
+
+    const NAMES: &'static [(Self, &'static str)] = &[
+        (Self::DOWNLOADS_UNAVAILABLE, "downloads_unavailable"),
+        (Self::AUTHENTICATION_UNAVAILABLE, "authentication_unavailable"),
+        (Self::CONFIG_INVALID, "config_invalid"),
+        (Self::PERSISTENCE_UNAVAILABLE, "persistence_unavailable"),
+        (Self::LOW_DISK_SPACE, "low_disk_space"),
+        (Self::COOKIES_SUSPECT, "cookies_suspect"),
+        (Self::OFFLINE, "offline"),
+    ];
+
+    pub fn bits(&self) -> u32 {
+        self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn insert(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+
+    pub fn remove(&mut self, other: Self) {
+        self.0 &= !other.0;
+    }
+
+    /// Names of the set flags, for display
+    pub fn names(&self) -> Vec<&'static str> {
+        Self::NAMES.iter()
+            .filter(|(flag, _)| self.contains(*flag))
+            .map(|(_, name)| *name)
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CheckStatus {
+    Passed,
+    Degraded,
+    TimedOut,
+}
+
+/// Outcome of a single startup check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckResult {
+    pub name: String,
+    pub status: CheckStatus,
+    pub message: String,
+    /// Flags this check set; empty when it passed
+    pub degraded: DegradedFlags,
+    pub duration_ms: u64,
+}
+
+/// Outcome of the startup self-test
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelfTestReport {
+    pub checks: Vec<CheckResult>,
+    pub degraded: DegradedFlags,
+    pub duration_ms: u64,
+    pub completed_at: DateTime<Utc>,
+}
+
+/// Run `check` within `timeout`. An `Err` or a timeout marks `flag` as degraded.
+async fn run_check<F>(name: &str, flag: DegradedFlags, timeout: Duration, check: F) -> CheckResult
+where
+    F: Future<Output = Result<String, String>>,
+{
+    let started = Instant::now();
+    let (status, message, degraded) = match tokio::time::timeout(timeout, check).await {
+        Ok(Ok(message)) => (CheckStatus::Passed, message, DegradedFlags::NONE),
+        Ok(Err(message)) => (CheckStatus::Degraded, message, flag),
+        Err(_) => (
+            CheckStatus::TimedOut,
+            format!("Check did not finish within {}s", timeout.as_secs_f32()),
+            flag,
+        ),
+    };
+
+    CheckResult {
+        name: name.to_string(),
+        status,
+        message,
+        degraded,
+        duration_ms: started.elapsed().as_millis() as u64,
+    }
+}
+
+/// Run blocking filesystem work off the async runtime so timeouts apply to it
+async fn blocking<F>(work: F) -> Result<String, String>
+where
+    F: FnOnce() -> Result<String, String> + Send + 'static,
+{
+    tokio::task::spawn_blocking(work)
+        .await
+        .unwrap_or_else(|e| Err(format!("Check panicked: {}", e)))
+}
+
+/// Check that saved state parses and its directory is writable
+fn check_state_file(state_file: PathBuf) -> Result<String, String> {
+    if state_file.exists() {
+        AppState::load_from_file(&state_file)
+            .map_err(|e| format!("Saved state could not be read: {}", e))?;
+    }
+
+    let state_dir = state_file.parent()
+        .ok_or_else(|| "State file has no parent directory".to_string())?;
+    std::fs::create_dir_all(state_dir)
+        .map_err(|e| format!("State directory is not writable: {}", e))?;
+    let probe = state_dir.join(".write-test");
+    std::fs::write(&probe, b"")
+        .and_then(|_| std::fs::remove_file(&probe))
+        .map_err(|e| format!("State directory is not writable: {}", e))?;
+
+    Ok("Saved state is readable".to_string())
+}
+
+/// Check that the output volume has room for downloads
+fn check_disk_space(output_path: PathBuf) -> Result<String, String> {
+    let available = disk_space::available_space(&output_path)
+        .map_err(|e| format!("Could not check free space for {:?}: {}", output_path, e))?;
+    let available_mb = available / (1024 * 1024);
+
+    if available < LOW_DISK_SPACE_BYTES {
+        Err(format!("Only {} MB free on the output volume", available_mb))
+    } else {
+        Ok(format!("{} MB free on the output volume", available_mb))
+    }
+}
+
+async fn check_sidecar() -> Result<String, String> {
+    let locator = BinaryLocator::detect().map_err(|e| e.to_string())?;
+    // test_binary verifies the binary against its manifest before running it
+    let version = ProcessRunner::new(&locator).test_binary().await.map_err(|e| e.to_string())?;
+    Ok(format!("gytmdl {} is available", version))
+}
+
+async fn check_cookies(cookie_manager: &CookieManager) -> Result<String, String> {
+    let info = cookie_manager.validate_cookies().await.map_err(|e| e.to_string())?;
+    if info.is_valid {
+        Ok(info.expiration_warning.unwrap_or_else(|| "Cookies are valid".to_string()))
+    } else {
+        Err(info.expiration_warning.unwrap_or_else(|| "Cookies are invalid".to_string()))
+    }
+}
+
+/// Run every startup check concurrently, each bounded by its own timeout
+pub async fn run(state_file: PathBuf, config: &AppConfig, cookie_manager: &CookieManager) -> SelfTestReport {
+    let started = Instant::now();
+    let output_path = config.output_path.clone();
+
+    let (state, config_check, sidecar, cookies, disk) = tokio::join!(
+        run_check("state", DegradedFlags::PERSISTENCE_UNAVAILABLE, CHECK_TIMEOUT,
+            blocking(move || check_state_file(state_file))),
+        run_check("config", DegradedFlags::CONFIG_INVALID, CHECK_TIMEOUT, async {
+            ConfigManager::with_default_path()
+                .validate_config(config)
+                .map(|_| "Configuration is valid".to_string())
+                .map_err(|e| e.to_string())
+        }),
+        run_check("sidecar", DegradedFlags::DOWNLOADS_UNAVAILABLE, CHECK_TIMEOUT, check_sidecar()),
+        run_check("cookies", DegradedFlags::AUTHENTICATION_UNAVAILABLE, CHECK_TIMEOUT,
+            check_cookies(cookie_manager)),
+        run_check("disk", DegradedFlags::LOW_DISK_SPACE, CHECK_TIMEOUT,
+            blocking(move || check_disk_space(output_path))),
+    );
+
+    let checks = vec![state, config_check, sidecar, cookies, disk];
+    let mut degraded = DegradedFlags::NONE;
+    for check in &checks {
+        degraded.insert(check.degraded);
+    }
+
+    SelfTestReport {
+        checks,
+        degraded,
+        duration_ms: started.elapsed().as_millis() as u64,
+        completed_at: Utc::now(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_degraded_flags() {
+        let mut flags = DegradedFlags::NONE;
+        assert!(flags.is_empty());
+
+        flags.insert(DegradedFlags::DOWNLOADS_UNAVAILABLE);
+        flags.insert(DegradedFlags::LOW_DISK_SPACE);
+        assert!(flags.contains(DegradedFlags::DOWNLOADS_UNAVAILABLE));
+        assert!(!flags.contains(DegradedFlags::CONFIG_INVALID));
+        assert_eq!(flags.bits(), 0b10001);
+        assert_eq!(flags.names(), vec!["downloads_unavailable", "low_disk_space"]);
+        assert_eq!(serde_json::to_string(&flags).unwrap(), "17");
+
+        flags.remove(DegradedFlags::LOW_DISK_SPACE);
+        assert_eq!(flags, DegradedFlags::DOWNLOADS_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn test_run_check_outcomes() {
+        let flag = DegradedFlags::CONFIG_INVALID;
+        let timeout = Duration::from_millis(50);
+
+        let passed = run_check("ok", flag, timeout, async { Ok("fine".to_string()) }).await;
+        assert_eq!(passed.status, CheckStatus::Passed);
+        assert!(passed.degraded.is_empty());
+
+        let failed = run_check("bad", flag, timeout, async { Err("broken".to_string()) }).await;
+        assert_eq!(failed.status, CheckStatus::Degraded);
+        assert_eq!(failed.degraded, flag);
+
+        let slow = run_check("slow", flag, timeout, async {
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            Ok(String::new())
+        }).await;
+        assert_eq!(slow.status, CheckStatus::TimedOut);
+        assert_eq!(slow.degraded, flag);
+    }
+
+    #[test]
+    fn test_check_state_file() {
+        let temp_dir = tempdir().unwrap();
+        let state_file = temp_dir.path().join("state").join("state.json");
+
+        // A missing state file is fine
+        assert!(check_state_file(state_file.clone()).is_ok());
+
+        AppState::default().save_to_file(&state_file).unwrap();
+        assert!(check_state_file(state_file.clone()).is_ok());
+
+        std::fs::write(&state_file, "{ not json").unwrap();
+        assert!(check_state_file(state_file).is_err());
+    }
+}
@@ -0,0 +1,291 @@
+use crate::modules::atomic_file;
+use crate::modules::state::DownloadJob;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// File format of the report written into the output folder when the queue drains
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum SessionReportFormat {
+    Markdown,
+    Html,
+}
+
+impl SessionReportFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::Html => "html",
+        }
+    }
+}
+
+/// A job that failed during a session
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SessionFailure {
+    pub job_id: String,
+    pub url: String,
+    pub title: Option<String>,
+    pub error: String,
+}
+
+/// What the queue did from picking up work until it ran out of it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSummary {
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub duration_secs: u64,
+    pub completed: usize,
+    pub failed: usize,
+    pub cancelled: usize,
+    pub total_bytes: u64,
+    pub failures: Vec<SessionFailure>,
+    /// Files written by the session's completed jobs
+    pub new_files: Vec<PathBuf>,
+    /// Report file written into the output folder, if one was
+    #[serde(default)]
+    pub report_path: Option<PathBuf>,
+}
+
+impl SessionSummary {
+    fn new(started_at: DateTime<Utc>) -> Self {
+        Self {
+            started_at,
+            finished_at: started_at,
+            duration_secs: 0,
+            completed: 0,
+            failed: 0,
+            cancelled: 0,
+            total_bytes: 0,
+            failures: Vec::new(),
+            new_files: Vec::new(),
+            report_path: None,
+        }
+    }
+}
+
+/// Collects finished jobs into a session until the queue drains
+#[derive(Debug, Default)]
+pub struct SessionTracker {
+    current: Option<SessionSummary>,
+}
+
+impl SessionTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The open session, started at the earliest start of the jobs in it
+    fn session(&mut self, job: &DownloadJob) -> &mut SessionSummary {
+        let started_at = job.started_at.unwrap_or_else(Utc::now);
+        let session = self.current.get_or_insert_with(|| SessionSummary::new(started_at));
+        session.started_at = session.started_at.min(started_at);
+        session
+    }
+
+    pub fn record_completed(&mut self, job: &DownloadJob, bytes: u64, files: &[PathBuf]) {
+        let session = self.session(job);
+        session.completed += 1;
+        session.total_bytes += bytes;
+        session.new_files.extend(files.iter().cloned());
+    }
+
+    pub fn record_failed(&mut self, job: &DownloadJob, error: &str) {
+        let session = self.session(job);
+        session.failed += 1;
+        session.failures.push(SessionFailure {
+            job_id: job.id.clone(),
+            url: job.url.clone(),
+            title: job.metadata.as_ref().and_then(|metadata| metadata.title.clone()),
+            error: error.to_string(),
+        });
+    }
+
+    pub fn record_cancelled(&mut self, job: &DownloadJob) {
+        self.session(job).cancelled += 1;
+    }
+
+    /// Close the open session, if any job finished since the last one
+    pub fn finish(&mut self, now: DateTime<Utc>) -> Option<SessionSummary> {
+        let mut session = self.current.take()?;
+        session.finished_at = now;
+        session.duration_secs = (now - session.started_at).num_seconds().max(0) as u64;
+        Some(session)
+    }
+}
+
+/// e.g. "1.5 MB"
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// e.g. "1h 02m 05s"
+fn format_duration(secs: u64) -> String {
+    match (secs / 3600, secs % 3600 / 60, secs % 60) {
+        (0, 0, seconds) => format!("{}s", seconds),
+        (0, minutes, seconds) => format!("{}m {:02}s", minutes, seconds),
+        (hours, minutes, seconds) => format!("{}h {:02}m {:02}s", hours, minutes, seconds),
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Label and value of each line of the totals section
+fn totals(summary: &SessionSummary) -> [(&'static str, String); 6] {
+    [
+        ("Started", summary.started_at.to_rfc3339()),
+        ("Duration", format_duration(summary.duration_secs)),
+        ("Completed", summary.completed.to_string()),
+        ("Failed", summary.failed.to_string()),
+        ("Cancelled", summary.cancelled.to_string()),
+        ("Downloaded", format_bytes(summary.total_bytes)),
+    ]
+}
+
+pub fn render_markdown(summary: &SessionSummary) -> String {
+    let mut content = String::from("# Download session\n\n");
+    for (label, value) in totals(summary) {
+        content.push_str(&format!("- **{}:** {}\n", label, value));
+    }
+
+    if !summary.failures.is_empty() {
+        content.push_str("\n## Failures\n\n");
+        for failure in &summary.failures {
+            let name = failure.title.as_deref().unwrap_or(&failure.url);
+            content.push_str(&format!("- {}: {}\n", name, failure.error));
+        }
+    }
+
+    if !summary.new_files.is_empty() {
+        content.push_str("\n## New files\n\n");
+        for path in &summary.new_files {
+            content.push_str(&format!("- `{}`\n", path.display()));
+        }
+    }
+    content
+}
+
+pub fn render_html(summary: &SessionSummary) -> String {
+    let mut content = String::from(
+        "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>Download session</title></head>\n<body>\n<h1>Download session</h1>\n<ul>\n",
+    );
+    for (label, value) in totals(summary) {
+        content.push_str(&format!("<li><strong>{}:</strong> {}</li>\n", label, escape_html(&value)));
+    }
+    content.push_str("</ul>\n");
+
+    if !summary.failures.is_empty() {
+        content.push_str("<h2>Failures</h2>\n<ul>\n");
+        for failure in &summary.failures {
+            let name = failure.title.as_deref().unwrap_or(&failure.url);
+            content.push_str(&format!("<li>{}: {}</li>\n", escape_html(name), escape_html(&failure.error)));
+        }
+        content.push_str("</ul>\n");
+    }
+
+    if !summary.new_files.is_empty() {
+        content.push_str("<h2>New files</h2>\n<ul>\n");
+        for path in &summary.new_files {
+            content.push_str(&format!("<li><code>{}</code></li>\n", escape_html(&path.to_string_lossy())));
+        }
+        content.push_str("</ul>\n");
+    }
+    content.push_str("</body>\n</html>\n");
+    content
+}
+
+/// `gytmdl-session-<finish time>.<ext>` in `dir`
+pub fn report_path(dir: &Path, summary: &SessionSummary, format: SessionReportFormat) -> PathBuf {
+    dir.join(format!(
+        "gytmdl-session-{}.{}",
+        summary.finished_at.format("%Y%m%d-%H%M%S"),
+        format.extension(),
+    ))
+}
+
+/// Write the report into `dir` and return its path
+pub fn write_report(dir: &Path, summary: &SessionSummary, format: SessionReportFormat) -> io::Result<PathBuf> {
+    let path = report_path(dir, summary, format);
+    let content = match format {
+        SessionReportFormat::Markdown => render_markdown(summary),
+        SessionReportFormat::Html => render_html(summary),
+    };
+    atomic_file::write_atomic(&path, content.as_bytes())?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(url: &str) -> DownloadJob {
+        DownloadJob {
+            started_at: Some(Utc::now()),
+            ..DownloadJob::new(url.to_string())
+        }
+    }
+
+    #[test]
+    fn test_session_tracker_collects_until_finished() {
+        let mut tracker = SessionTracker::new();
+        assert!(tracker.finish(Utc::now()).is_none());
+
+        let first = job("https://music.youtube.com/watch?v=1");
+        tracker.record_completed(&first, 2048, &[PathBuf::from("/music/a.m4a")]);
+        tracker.record_failed(&job("https://music.youtube.com/watch?v=2"), "HTTP Error 403: Forbidden");
+        tracker.record_cancelled(&job("https://music.youtube.com/watch?v=3"));
+
+        let summary = tracker.finish(Utc::now()).unwrap();
+        assert_eq!(summary.started_at, first.started_at.unwrap());
+        assert_eq!((summary.completed, summary.failed, summary.cancelled), (1, 1, 1));
+        assert_eq!(summary.total_bytes, 2048);
+        assert_eq!(summary.failures[0].error, "HTTP Error 403: Forbidden");
+        assert_eq!(summary.new_files, vec![PathBuf::from("/music/a.m4a")]);
+        assert!(tracker.finish(Utc::now()).is_none());
+    }
+
+    #[test]
+    fn test_render_reports() {
+        let mut tracker = SessionTracker::new();
+        tracker.record_completed(&job("https://music.youtube.com/watch?v=1"), 1536 * 1024, &[PathBuf::from("/music/a.m4a")]);
+        tracker.record_failed(&job("https://music.youtube.com/watch?v=2"), "<unavailable>");
+        let summary = tracker.finish(Utc::now()).unwrap();
+
+        let markdown = render_markdown(&summary);
+        assert!(markdown.contains("- **Downloaded:** 1.5 MB\n"));
+        assert!(markdown.contains("- https://music.youtube.com/watch?v=2: <unavailable>\n"));
+        assert!(markdown.contains("- `/music/a.m4a`\n"));
+
+        let html = render_html(&summary);
+        assert!(html.contains("&lt;unavailable&gt;"));
+        assert!(html.contains("<li><code>/music/a.m4a</code></li>"));
+
+        let path = report_path(Path::new("/music"), &summary, SessionReportFormat::Html);
+        assert!(path.to_string_lossy().starts_with("/music/gytmdl-session-"));
+        assert_eq!(path.extension().unwrap(), "html");
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(5), "5s");
+        assert_eq!(format_duration(65), "1m 05s");
+        assert_eq!(format_duration(3725), "1h 02m 05s");
+    }
+}
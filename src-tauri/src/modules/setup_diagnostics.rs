@@ -0,0 +1,221 @@
+use crate::modules::binary_locator::BinaryLocator;
+use crate::modules::cookie_manager::CookieManager;
+use crate::modules::process_runner::ProcessRunner;
+use crate::modules::sidecar_manager::SidecarManager;
+use crate::modules::state::AppConfig;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Time budget for each check, long enough for a slow network request
+const CHECK_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Page requested to confirm YouTube Music can be reached
+const REACHABILITY_URL: &str = "https://music.youtube.com/";
+
+const SIDECAR_FIX: &str = "Reinstall the app, or pick another gytmdl binary in the sidecar settings";
+const FFMPEG_FIX: &str = "Install ffmpeg and make sure it is on PATH, then restart the app";
+const COOKIES_FIX: &str = "Import cookies from a browser signed in to YouTube Music";
+const PATH_FIX: &str = "Choose a folder you can write to in the settings";
+const NETWORK_FIX: &str = "Check your internet connection and proxy settings";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum DiagnosticStatus {
+    Ok,
+    /// Downloads work, but some content or features won't
+    Warning,
+    /// Downloads can't work until this is fixed
+    Error,
+}
+
+/// One line of the setup report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiagnosticItem {
+    pub id: String,
+    pub status: DiagnosticStatus,
+    pub message: String,
+    /// What the user should do, when the item isn't ok
+    pub fix: Option<String>,
+}
+
+/// Everything a first-run wizard needs to show, in display order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetupReport {
+    pub items: Vec<DiagnosticItem>,
+    /// No item has an error
+    pub ready: bool,
+    pub completed_at: DateTime<Utc>,
+}
+
+impl DiagnosticItem {
+    fn ok(id: &str, message: String) -> Self {
+        Self {
+            id: id.to_string(),
+            status: DiagnosticStatus::Ok,
+            message,
+            fix: None,
+        }
+    }
+
+    fn failed(id: &str, status: DiagnosticStatus, message: String, fix: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            status,
+            message,
+            fix: Some(fix.to_string()),
+        }
+    }
+}
+
+/// Run `check`, reporting a timeout as an error
+async fn with_timeout<F>(id: &str, fix: &str, check: F) -> DiagnosticItem
+where
+    F: Future<Output = DiagnosticItem>,
+{
+    tokio::time::timeout(CHECK_TIMEOUT, check).await.unwrap_or_else(|_| {
+        DiagnosticItem::failed(
+            id,
+            DiagnosticStatus::Error,
+            format!("Check did not finish within {}s", CHECK_TIMEOUT.as_secs()),
+            fix,
+        )
+    })
+}
+
+async fn check_sidecar() -> DiagnosticItem {
+    let version = match BinaryLocator::detect() {
+        Ok(locator) => ProcessRunner::new(&locator).test_binary().await.map_err(|e| e.to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+    match version {
+        Ok(version) => DiagnosticItem::ok("sidecar", format!("gytmdl {} is available", version)),
+        Err(e) => DiagnosticItem::failed("sidecar", DiagnosticStatus::Error, e, SIDECAR_FIX),
+    }
+}
+
+async fn check_ffmpeg() -> DiagnosticItem {
+    let ffmpeg = SidecarManager::check_dependencies().await
+        .into_iter()
+        .find(|dependency| dependency.name == "ffmpeg");
+    match ffmpeg {
+        Some(ffmpeg) if ffmpeg.reachable_from_sidecar => DiagnosticItem::ok("ffmpeg", match ffmpeg.version {
+            Some(version) => format!("ffmpeg {} is available", version),
+            None => "ffmpeg is available".to_string(),
+        }),
+        Some(ffmpeg) if ffmpeg.binary_path.is_some() => DiagnosticItem::failed(
+            "ffmpeg",
+            DiagnosticStatus::Error,
+            "ffmpeg is installed but gytmdl can't find it".to_string(),
+            FFMPEG_FIX,
+        ),
+        _ => DiagnosticItem::failed("ffmpeg", DiagnosticStatus::Error, "ffmpeg could not be found".to_string(), FFMPEG_FIX),
+    }
+}
+
+async fn check_cookies(cookie_manager: &CookieManager) -> DiagnosticItem {
+    match cookie_manager.validate_cookies().await {
+        Ok(info) if info.is_valid => DiagnosticItem::ok(
+            "cookies",
+            info.expiration_warning.unwrap_or_else(|| "Cookies are valid".to_string()),
+        ),
+        // Most content downloads without cookies
+        Ok(info) => DiagnosticItem::failed(
+            "cookies",
+            DiagnosticStatus::Warning,
+            info.expiration_warning.unwrap_or_else(|| "No valid cookies; age-restricted and premium content will fail".to_string()),
+            COOKIES_FIX,
+        ),
+        Err(e) => DiagnosticItem::failed("cookies", DiagnosticStatus::Warning, e.to_string(), COOKIES_FIX),
+    }
+}
+
+/// Create `path` if needed and write a probe file to it
+fn check_writable(id: &str, path: PathBuf) -> DiagnosticItem {
+    let probe = path.join(".gytmdl-write-test");
+    let result = std::fs::create_dir_all(&path)
+        .and_then(|_| std::fs::write(&probe, b""))
+        .and_then(|_| std::fs::remove_file(&probe));
+    match result {
+        Ok(()) => DiagnosticItem::ok(id, format!("{} is writable", path.display())),
+        Err(e) => DiagnosticItem::failed(id, DiagnosticStatus::Error, format!("{} is not writable: {}", path.display(), e), PATH_FIX),
+    }
+}
+
+async fn check_writable_async(id: &'static str, path: &Path) -> DiagnosticItem {
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || check_writable(id, path))
+        .await
+        .unwrap_or_else(|e| DiagnosticItem::failed(id, DiagnosticStatus::Error, format!("Check panicked: {}", e), PATH_FIX))
+}
+
+/// Reach YouTube Music through the configured proxy, like gytmdl would
+async fn check_network(config: &AppConfig) -> DiagnosticItem {
+    let mut builder = reqwest::Client::builder()
+        .timeout(CHECK_TIMEOUT)
+        .user_agent(concat!("gytmdl-gui/", env!("CARGO_PKG_VERSION")));
+    if let Some(proxy) = ProcessRunner::proxy_url(config) {
+        match reqwest::Proxy::all(&proxy) {
+            Ok(proxy) => builder = builder.proxy(proxy),
+            Err(e) => return DiagnosticItem::failed("network", DiagnosticStatus::Error, format!("Invalid proxy: {}", e), NETWORK_FIX),
+        }
+    }
+
+    let result = match builder.build() {
+        Ok(client) => client.head(REACHABILITY_URL).send().await.map_err(|e| e.to_string()),
+        Err(e) => Err(format!("Failed to create HTTP client: {}", e)),
+    };
+    match result {
+        Ok(response) if !response.status().is_server_error() => {
+            DiagnosticItem::ok("network", "YouTube Music is reachable".to_string())
+        }
+        Ok(response) => DiagnosticItem::failed(
+            "network",
+            DiagnosticStatus::Error,
+            format!("YouTube Music responded with {}", response.status()),
+            NETWORK_FIX,
+        ),
+        Err(e) => DiagnosticItem::failed("network", DiagnosticStatus::Error, format!("YouTube Music is unreachable: {}", e), NETWORK_FIX),
+    }
+}
+
+/// Run every setup check concurrently
+pub async fn run(config: &AppConfig, cookie_manager: &CookieManager) -> SetupReport {
+    let (sidecar, ffmpeg, cookies, output, temp, network) = tokio::join!(
+        with_timeout("sidecar", SIDECAR_FIX, check_sidecar()),
+        with_timeout("ffmpeg", FFMPEG_FIX, check_ffmpeg()),
+        with_timeout("cookies", COOKIES_FIX, check_cookies(cookie_manager)),
+        with_timeout("output_path", PATH_FIX, check_writable_async("output_path", &config.output_path)),
+        with_timeout("temp_path", PATH_FIX, check_writable_async("temp_path", &config.temp_path)),
+        with_timeout("network", NETWORK_FIX, check_network(config)),
+    );
+
+    let items = vec![sidecar, ffmpeg, cookies, output, temp, network];
+    SetupReport {
+        ready: items.iter().all(|item| item.status != DiagnosticStatus::Error),
+        items,
+        completed_at: Utc::now(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_check_writable() {
+        let temp_dir = tempdir().unwrap();
+        let item = check_writable("output_path", temp_dir.path().join("new"));
+        assert_eq!(item.status, DiagnosticStatus::Ok);
+        assert!(temp_dir.path().join("new").exists());
+
+        // A file where the folder should be
+        let blocked = temp_dir.path().join("file");
+        std::fs::write(&blocked, b"").unwrap();
+        let item = check_writable("output_path", blocked.join("sub"));
+        assert_eq!(item.status, DiagnosticStatus::Error);
+        assert!(item.fix.is_some());
+    }
+}
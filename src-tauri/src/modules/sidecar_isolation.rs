@@ -0,0 +1,213 @@
+use serde::Serialize;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use tokio::process::Command;
+
+/// Environment variables pointing config/cache discovery at the sandbox.
+/// gytmdl reads `~/.gytmdl/config.json` unless passed `--no-config-file`, so
+/// the home directory is redirected into the app's own directory as well.
+const CONFIG_HOME_VARIABLES: &[&str] = &["XDG_CONFIG_HOME", "APPDATA"];
+const CACHE_HOME_VARIABLES: &[&str] = &["XDG_CACHE_HOME", "LOCALAPPDATA"];
+const HOME_VARIABLES: &[&str] = &["HOME", "USERPROFILE"];
+
+/// Variables that would make Python pick up user site-packages or startup
+/// scripts from outside the sidecar bundle
+const REMOVED_VARIABLES: &[&str] = &["PYTHONPATH", "PYTHONSTARTUP", "PYTHONHOME", "PYTHONUSERBASE"];
+
+/// gytmdl's config file, relative to the user's home; ignored while
+/// sandboxed via `--no-config-file`
+const GYTMDL_CONFIG_FILE: &str = ".gytmdl/config.json";
+
+/// Where the sandbox lives and what it changes, for display
+#[derive(Debug, Clone, Serialize)]
+pub struct IsolationInfo {
+    pub root_dir: PathBuf,
+    pub home_dir: PathBuf,
+    pub config_dir: PathBuf,
+    pub cache_dir: PathBuf,
+    pub environment: Vec<(String, String)>,
+    pub removed_variables: Vec<String>,
+    /// System config files that sandboxed downloads don't see
+    pub system_config_files: Vec<PathBuf>,
+}
+
+/// Isolates sidecar processes from the user's gytmdl configuration
+#[derive(Debug, Clone)]
+pub struct SidecarIsolation {
+    root_dir: PathBuf,
+}
+
+impl SidecarIsolation {
+    /// Create a SidecarIsolation rooted in the app data directory
+    pub fn new() -> Self {
+        let root_dir = std::env::current_dir()
+            .unwrap_or_else(|_| PathBuf::from("."))
+            .join(".gytmdl-gui")
+            .join("sidecar");
+
+        Self { root_dir }
+    }
+
+    /// Create a SidecarIsolation rooted at a specific directory
+    pub fn with_root_dir(root_dir: PathBuf) -> Self {
+        Self { root_dir }
+    }
+
+    /// Get the sandbox root directory
+    pub fn root_dir(&self) -> &PathBuf {
+        &self.root_dir
+    }
+
+    /// Directory used as the sidecar's home
+    pub fn home_dir(&self) -> PathBuf {
+        self.root_dir.join("home")
+    }
+
+    /// Directory used for config discovery
+    pub fn config_dir(&self) -> PathBuf {
+        self.root_dir.join("config")
+    }
+
+    /// Directory used for caches
+    pub fn cache_dir(&self) -> PathBuf {
+        self.root_dir.join("cache")
+    }
+
+    /// Get the environment overrides applied to every sidecar spawn
+    pub fn environment(&self) -> Vec<(String, String)> {
+        let home = self.home_dir().to_string_lossy().to_string();
+        let config = self.config_dir().to_string_lossy().to_string();
+        let cache = self.cache_dir().to_string_lossy().to_string();
+
+        let mut env = Vec::new();
+        for name in HOME_VARIABLES {
+            env.push((name.to_string(), home.clone()));
+        }
+        for name in CONFIG_HOME_VARIABLES {
+            env.push((name.to_string(), config.clone()));
+        }
+        for name in CACHE_HOME_VARIABLES {
+            env.push((name.to_string(), cache.clone()));
+        }
+        env.push(("PYTHONNOUSERSITE".to_string(), "1".to_string()));
+        env
+    }
+
+    /// Get the variables removed from the inherited environment
+    pub fn removed_variables() -> &'static [&'static str] {
+        REMOVED_VARIABLES
+    }
+
+    /// Arguments that stop gytmdl from loading its own config file
+    pub fn ignore_config_args() -> Vec<String> {
+        vec!["--no-config-file".to_string()]
+    }
+
+    /// Create the sandbox directories if they don't exist
+    pub fn ensure_directories(&self) -> Result<(), io::Error> {
+        fs::create_dir_all(self.home_dir())?;
+        fs::create_dir_all(self.config_dir())?;
+        fs::create_dir_all(self.cache_dir())?;
+        Ok(())
+    }
+
+    /// Apply the sandboxed environment to a command.
+    /// When `honor_system_config` is set the inherited environment is left untouched.
+    pub fn apply_to_command(&self, command: &mut Command, honor_system_config: bool) -> Result<(), io::Error> {
+        if honor_system_config {
+            return Ok(());
+        }
+
+        self.ensure_directories()?;
+
+        for name in Self::removed_variables() {
+            command.env_remove(name);
+        }
+        command.envs(self.environment());
+
+        Ok(())
+    }
+}
+
+impl SidecarIsolation {
+    /// Describe the sandbox and the system config files it hides
+    pub fn info(&self) -> IsolationInfo {
+        let system_config_files: Vec<PathBuf> = std::iter::once(system_home().join(GYTMDL_CONFIG_FILE))
+            .filter(|path| path.is_file())
+            .collect();
+
+        IsolationInfo {
+            root_dir: self.root_dir.clone(),
+            home_dir: self.home_dir(),
+            config_dir: self.config_dir(),
+            cache_dir: self.cache_dir(),
+            environment: self.environment(),
+            removed_variables: Self::removed_variables().iter().map(|v| v.to_string()).collect(),
+            system_config_files,
+        }
+    }
+}
+
+/// The real home directory, as the sidecar would see it unsandboxed
+fn system_home() -> PathBuf {
+    HOME_VARIABLES.iter()
+        .find_map(|name| std::env::var_os(name).filter(|v| !v.is_empty()))
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+impl Default for SidecarIsolation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_environment_points_into_sandbox() {
+        let temp_dir = tempdir().unwrap();
+        let isolation = SidecarIsolation::with_root_dir(temp_dir.path().to_path_buf());
+
+        let env = isolation.environment();
+        let lookup = |name: &str| env.iter().find(|(k, _)| k == name).map(|(_, v)| v.clone());
+
+        assert_eq!(lookup("XDG_CONFIG_HOME"), Some(isolation.config_dir().to_string_lossy().to_string()));
+        assert_eq!(lookup("XDG_CACHE_HOME"), Some(isolation.cache_dir().to_string_lossy().to_string()));
+        assert_eq!(lookup("HOME"), Some(isolation.home_dir().to_string_lossy().to_string()));
+        assert_eq!(lookup("PYTHONNOUSERSITE"), Some("1".to_string()));
+    }
+
+    #[test]
+    fn test_apply_to_command_creates_directories() {
+        let temp_dir = tempdir().unwrap();
+        let isolation = SidecarIsolation::with_root_dir(temp_dir.path().join("sidecar"));
+
+        let mut command = Command::new("gytmdl");
+        isolation.apply_to_command(&mut command, false).unwrap();
+
+        assert!(isolation.home_dir().exists());
+        assert!(isolation.config_dir().exists());
+        assert!(isolation.cache_dir().exists());
+
+        let envs: Vec<_> = command.as_std().get_envs().collect();
+        assert!(envs.iter().any(|(k, v)| *k == "XDG_CONFIG_HOME" && v.is_some()));
+        assert!(envs.iter().any(|(k, v)| *k == "PYTHONPATH" && v.is_none()));
+    }
+
+    #[test]
+    fn test_honor_system_config_leaves_command_untouched() {
+        let temp_dir = tempdir().unwrap();
+        let isolation = SidecarIsolation::with_root_dir(temp_dir.path().join("sidecar"));
+
+        let mut command = Command::new("gytmdl");
+        isolation.apply_to_command(&mut command, true).unwrap();
+
+        assert_eq!(command.as_std().get_envs().count(), 0);
+        assert!(!isolation.root_dir().exists());
+    }
+}
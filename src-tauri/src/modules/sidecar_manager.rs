@@ -1,6 +1,33 @@
-use crate::modules::gytmdl_wrapper::{GytmdlWrapper, GytmdlError, BinaryManifest};
-use std::path::PathBuf;
+use crate::modules::binary_locator::{BinaryLocator, BinaryManifest};
+use crate::modules::debug_logger::DEBUG_LOGGER;
+use crate::modules::process_runner::ProcessRunner;
+use crate::modules::sidecar_isolation::SidecarIsolation;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use minisign_verify::{PublicKey, Signature};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::process::Command;
+
+/// External tools gytmdl shells out to, with the flag that prints their version.
+/// yt-dlp isn't one: gytmdl bundles it as a library.
+const DEPENDENCIES: &[(&str, &str)] = &[("ffmpeg", "-version")];
+
+/// How long a dependency gets to print its version
+const VERSION_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Release feed carrying the sidecar builds, with their manifests and signatures
+const SIDECAR_RELEASES_URL: &str = "https://api.github.com/repos/seungkilee-cs/gytmdl-gui/releases/latest";
+
+/// minisign public key the sidecar builds are signed with, set at build time;
+/// see "Enabling updates" in the README. Without it sidecar updates are off,
+/// since a checksum from the same release only catches corruption.
+const SIDECAR_PUBLIC_KEY: Option<&str> = option_env!("GYTMDL_SIDECAR_PUBKEY");
+
+const UPDATE_CHECK_TIMEOUT: Duration = Duration::from_secs(30);
+const UPDATE_DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(600);
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SidecarInfo {
@@ -12,12 +39,95 @@ pub struct SidecarInfo {
     pub error: Option<String>,
 }
 
+/// Where a dependency binary was found
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum DependencySource {
+    /// In the sidecar directory next to gytmdl
+    Bundled,
+    /// On the system PATH
+    System,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DependencyInfo {
+    pub name: String,
+    pub binary_path: Option<String>,
+    pub source: Option<DependencySource>,
+    pub version: Option<String>,
+    /// Whether gytmdl will find it when spawned: on PATH, or next to gytmdl on Windows
+    pub reachable_from_sidecar: bool,
+    pub error: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SidecarStatus {
     pub current_binary: Option<SidecarInfo>,
     pub available_binaries: Vec<SidecarInfo>,
     pub platform_binary_name: String,
     pub sidecar_directory: String,
+    #[serde(default)]
+    pub dependencies: Vec<DependencyInfo>,
+}
+
+/// Sidecar binaries that can be updated in place
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum SidecarComponent {
+    Gytmdl,
+}
+
+impl SidecarComponent {
+    /// File name of the installed binary in the sidecar directory
+    fn installed_binary_name(&self) -> String {
+        match self {
+            SidecarComponent::Gytmdl => BinaryLocator::get_platform_binary_name(),
+        }
+    }
+
+    /// Name of this platform's binary among the release assets
+    fn release_asset_name(&self) -> String {
+        match self {
+            SidecarComponent::Gytmdl => BinaryLocator::get_platform_binary_name(),
+        }
+    }
+}
+
+/// A newer (or different) sidecar build found in a release feed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SidecarUpdate {
+    pub component: SidecarComponent,
+    pub installed_path: String,
+    pub installed_version: Option<String>,
+    pub latest_version: String,
+    pub update_available: bool,
+    pub download_url: String,
+    /// minisign signature of the binary, checked against the pinned key
+    pub signature_url: String,
+    /// What the downloaded binary is verified against before it is installed
+    pub manifest: BinaryManifest,
+}
+
+/// A downloaded sidecar build that passed verification, ready to install
+pub struct VerifiedSidecar {
+    update: SidecarUpdate,
+    content: Vec<u8>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubRelease {
+    tag_name: String,
+    assets: Vec<GitHubAsset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubAsset {
+    name: String,
+    browser_download_url: String,
+}
+
+impl GitHubRelease {
+    fn asset(&self, name: &str) -> Option<&GitHubAsset> {
+        self.assets.iter().find(|asset| asset.name == name)
+    }
 }
 
 pub struct SidecarManager;
@@ -25,22 +135,22 @@ pub struct SidecarManager;
 impl SidecarManager {
     /// Get comprehensive status of all sidecar binaries
     pub async fn get_status() -> SidecarStatus {
-        let platform_binary_name = GytmdlWrapper::get_platform_binary_name();
-        let sidecar_directory = GytmdlWrapper::get_sidecar_directory();
+        let platform_binary_name = BinaryLocator::get_platform_binary_name();
+        let sidecar_directory = BinaryLocator::get_sidecar_directory();
         
         // Try to get current binary info
-        let current_binary = match GytmdlWrapper::new() {
-            Ok(wrapper) => Some(Self::get_binary_info(&wrapper).await),
+        let current_binary = match BinaryLocator::detect() {
+            Ok(locator) => Some(Self::get_binary_info(&locator).await),
             Err(_) => None,
         };
 
         // Get all available binaries
-        let available_binaries = match GytmdlWrapper::list_available_binaries() {
+        let available_binaries = match BinaryLocator::list_available_binaries() {
             Ok(binaries) => {
                 let mut binary_infos = Vec::new();
                 for binary_path in binaries {
-                    if let Ok(wrapper) = GytmdlWrapper::with_binary_path(binary_path) {
-                        binary_infos.push(Self::get_binary_info(&wrapper).await);
+                    if let Ok(locator) = BinaryLocator::with_binary_path(binary_path) {
+                        binary_infos.push(Self::get_binary_info(&locator).await);
                     }
                 }
                 binary_infos
@@ -53,13 +163,104 @@ impl SidecarManager {
             available_binaries,
             platform_binary_name,
             sidecar_directory: sidecar_directory.to_string_lossy().to_string(),
+            dependencies: Self::check_dependencies().await,
         }
     }
 
+    /// Detect the tools gytmdl needs, preferring bundled copies over the system PATH
+    pub async fn check_dependencies() -> Vec<DependencyInfo> {
+        let mut dependencies = Vec::new();
+        for (name, version_flag) in DEPENDENCIES {
+            dependencies.push(Self::get_dependency_info(name, version_flag).await);
+        }
+        dependencies
+    }
+
+    /// Whether gytmdl can find ffmpeg, which it needs to remux every download
+    pub fn is_ffmpeg_reachable() -> bool {
+        Self::is_reachable_from_sidecar("ffmpeg")
+    }
+
+    /// Find a dependency binary such as `ffmpeg`, preferring the bundled copy
+    pub fn locate_dependency(name: &str) -> Option<(PathBuf, DependencySource)> {
+        let bundled = BinaryLocator::get_sidecar_directory().join(dependency_binary_name(name));
+        if bundled.exists() {
+            Some((bundled, DependencySource::Bundled))
+        } else {
+            which::which(name).ok().map(|path| (path, DependencySource::System))
+        }
+    }
+
+    async fn get_dependency_info(name: &str, version_flag: &str) -> DependencyInfo {
+        let located = Self::locate_dependency(name);
+
+        let mut info = DependencyInfo {
+            name: name.to_string(),
+            binary_path: located.as_ref().map(|(path, _)| path.to_string_lossy().to_string()),
+            source: located.as_ref().map(|(_, source)| *source),
+            version: None,
+            reachable_from_sidecar: Self::is_reachable_from_sidecar(name),
+            error: None,
+        };
+
+        let Some((path, _)) = located else {
+            info.error = Some(format!("{} was not found in the sidecar directory or on PATH", name));
+            return info;
+        };
+
+        match Self::dependency_version(&path, version_flag).await {
+            Ok(version) => info.version = Some(version),
+            Err(e) => info.error = Some(e),
+        }
+        if info.error.is_none() && !info.reachable_from_sidecar {
+            info.error = Some(format!(
+                "{} is bundled but not on PATH, so gytmdl will not find it",
+                name
+            ));
+        }
+
+        info
+    }
+
+    /// gytmdl looks dependencies up on its inherited PATH. On Windows the
+    /// directory of the calling executable is searched first, so a copy next to
+    /// gytmdl also counts.
+    fn is_reachable_from_sidecar(name: &str) -> bool {
+        if which::which(name).is_ok() {
+            return true;
+        }
+        cfg!(target_os = "windows")
+            && BinaryLocator::get_sidecar_directory().join(dependency_binary_name(name)).exists()
+    }
+
+    /// Run `binary <version_flag>` in the sandboxed sidecar environment
+    async fn dependency_version(binary: &Path, version_flag: &str) -> Result<String, String> {
+        let mut command = Command::new(binary);
+        command
+            .arg(version_flag)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .stdin(Stdio::null())
+            .kill_on_drop(true);
+        SidecarIsolation::new().apply_to_command(&mut command, false)
+            .map_err(|e| format!("Failed to prepare sidecar environment: {}", e))?;
+
+        let output = tokio::time::timeout(VERSION_CHECK_TIMEOUT, command.output()).await
+            .map_err(|_| format!("Timed out running {:?} {}", binary, version_flag))?
+            .map_err(|e| format!("Failed to run {:?}: {}", binary, e))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("Version check failed: {}", stderr.trim()));
+        }
+        parse_version(&String::from_utf8_lossy(&output.stdout))
+            .ok_or_else(|| "Version check printed nothing".to_string())
+    }
+
     /// Get detailed information about a specific binary
-    async fn get_binary_info(wrapper: &GytmdlWrapper) -> SidecarInfo {
-        let binary_path = wrapper.get_binary_path().to_string_lossy().to_string();
-        let is_available = wrapper.is_binary_available();
+    async fn get_binary_info(locator: &BinaryLocator) -> SidecarInfo {
+        let binary_path = locator.get_binary_path().to_string_lossy().to_string();
+        let is_available = locator.is_binary_available();
         
         let mut info = SidecarInfo {
             binary_path,
@@ -76,7 +277,7 @@ impl SidecarManager {
         }
 
         // Test binary functionality
-        match wrapper.test_binary().await {
+        match ProcessRunner::new(locator).test_binary().await {
             Ok(version) => {
                 info.version = Some(version);
                 info.is_valid = true;
@@ -88,7 +289,7 @@ impl SidecarManager {
         }
 
         // Load manifest if available
-        match wrapper.load_manifest() {
+        match locator.load_manifest() {
             Ok(manifest) => {
                 info.manifest = Some(manifest);
             }
@@ -100,7 +301,7 @@ impl SidecarManager {
 
         // Validate integrity if manifest is available
         if info.manifest.is_some() {
-            match wrapper.validate_integrity() {
+            match locator.validate_integrity() {
                 Ok(true) => {
                     // Integrity check passed
                 }
@@ -119,15 +320,15 @@ impl SidecarManager {
 
     /// Validate all available binaries
     pub async fn validate_all_binaries() -> Result<Vec<SidecarInfo>, String> {
-        let available_binaries = GytmdlWrapper::list_available_binaries()
+        let available_binaries = BinaryLocator::list_available_binaries()
             .map_err(|e| format!("Failed to list binaries: {}", e))?;
 
         let mut results = Vec::new();
         
         for binary_path in available_binaries {
-            match GytmdlWrapper::with_binary_path(binary_path) {
-                Ok(wrapper) => {
-                    results.push(Self::get_binary_info(&wrapper).await);
+            match BinaryLocator::with_binary_path(binary_path) {
+                Ok(locator) => {
+                    results.push(Self::get_binary_info(&locator).await);
                 }
                 Err(e) => {
                     results.push(SidecarInfo {
@@ -136,7 +337,7 @@ impl SidecarManager {
                         is_valid: false,
                         version: None,
                         manifest: None,
-                        error: Some(format!("Failed to create wrapper: {}", e)),
+                        error: Some(format!("Failed to locate binary: {}", e)),
                     });
                 }
             }
@@ -147,13 +348,13 @@ impl SidecarManager {
 
     /// Select and validate the best available binary
     pub async fn select_best_binary() -> Result<SidecarInfo, String> {
-        let binary_path = GytmdlWrapper::select_best_binary()
+        let binary_path = BinaryLocator::select_best_binary()
             .map_err(|e| format!("Failed to select binary: {}", e))?;
 
-        let wrapper = GytmdlWrapper::with_binary_path(binary_path)
-            .map_err(|e| format!("Failed to create wrapper: {}", e))?;
+        let locator = BinaryLocator::with_binary_path(binary_path)
+            .map_err(|e| format!("Failed to locate binary: {}", e))?;
 
-        Ok(Self::get_binary_info(&wrapper).await)
+        Ok(Self::get_binary_info(&locator).await)
     }
 
     /// Check if the current platform has a suitable binary
@@ -176,6 +377,217 @@ impl SidecarManager {
 
         Ok(false)
     }
+
+    /// Check the release feed for sidecar builds that differ from the installed ones
+    pub async fn check_updates() -> Result<Vec<SidecarUpdate>, String> {
+        public_key()?;
+        let client = Self::http_client(UPDATE_CHECK_TIMEOUT)?;
+        let component = SidecarComponent::Gytmdl;
+        let installed = BinaryLocator::get_sidecar_directory().join(component.installed_binary_name());
+        Ok(vec![Self::check_update(&client, component, &installed).await?])
+    }
+
+    /// Download the latest build of `component` and verify its signature
+    /// against the pinned key, then its manifest. `None` when the installed
+    /// build is current. Nothing is installed yet; see [`Self::install_update`].
+    pub async fn download_update(component: SidecarComponent) -> Result<Option<VerifiedSidecar>, String> {
+        let public_key = public_key()?;
+        let installed = BinaryLocator::get_sidecar_directory().join(component.installed_binary_name());
+        let client = Self::http_client(UPDATE_CHECK_TIMEOUT)?;
+        let update = Self::check_update(&client, component, &installed).await?;
+        if !update.update_available {
+            return Ok(None);
+        }
+
+        DEBUG_LOGGER.info("sidecar", format!("Downloading {:?} {} from {}", component, update.latest_version, update.download_url));
+        let response = Self::http_client(UPDATE_DOWNLOAD_TIMEOUT)?
+            .get(&update.download_url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| format!("Failed to download sidecar update: {}", e))?;
+        let content = response.bytes().await
+            .map_err(|e| format!("Failed to download sidecar update: {}", e))?
+            .to_vec();
+        let signature = Self::fetch_text(&client, &update.signature_url).await?;
+
+        verify_signature(&content, &signature, &public_key)?;
+        verify_download(&content, &update.manifest)?;
+        Ok(Some(VerifiedSidecar { update, content }))
+    }
+
+    /// Swap a verified build into the sidecar directory. The caller makes sure
+    /// no download is using the binary.
+    pub fn install_update(verified: VerifiedSidecar) -> Result<SidecarUpdate, String> {
+        let VerifiedSidecar { update, content } = verified;
+        let installed = PathBuf::from(&update.installed_path);
+        install_binary(&installed, &content, &update.manifest)
+            .map_err(|e| format!("Failed to install sidecar update: {}", e))?;
+
+        DEBUG_LOGGER.info("sidecar", format!("Installed {:?} {} at {:?}", update.component, update.latest_version, installed));
+        Ok(SidecarUpdate {
+            installed_version: Some(update.latest_version.clone()),
+            update_available: false,
+            ..update
+        })
+    }
+
+    /// The installed build's update status, after an update or when there was none
+    pub async fn current_update_status(component: SidecarComponent) -> Result<SidecarUpdate, String> {
+        let installed = BinaryLocator::get_sidecar_directory().join(component.installed_binary_name());
+        Self::check_update(&Self::http_client(UPDATE_CHECK_TIMEOUT)?, component, &installed).await
+    }
+
+    fn http_client(timeout: Duration) -> Result<reqwest::Client, String> {
+        reqwest::Client::builder()
+            .timeout(timeout)
+            // The GitHub API rejects requests without a user agent
+            .user_agent(concat!("gytmdl-gui/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .map_err(|e| format!("Failed to create HTTP client: {}", e))
+    }
+
+    async fn check_update(
+        client: &reqwest::Client,
+        component: SidecarComponent,
+        installed: &Path,
+    ) -> Result<SidecarUpdate, String> {
+        let release: GitHubRelease = serde_json::from_str(&Self::fetch_text(client, SIDECAR_RELEASES_URL).await?)
+            .map_err(|e| format!("Failed to parse release feed: {}", e))?;
+
+        let asset_name = component.release_asset_name();
+        let asset = release.asset(&asset_name)
+            .ok_or_else(|| format!("Release {} has no {} build for this platform", release.tag_name, asset_name))?;
+        let signature_asset = release.asset(&format!("{}.minisig", asset_name))
+            .ok_or_else(|| format!("Release {} has no signature for {}", release.tag_name, asset_name))?;
+
+        let manifest_name = Path::new(&asset_name).with_extension("json").to_string_lossy().to_string();
+        let manifest_asset = release.asset(&manifest_name)
+            .ok_or_else(|| format!("Release {} has no manifest for {}", release.tag_name, asset_name))?;
+        let manifest = serde_json::from_str::<BinaryManifest>(&Self::fetch_text(client, &manifest_asset.browser_download_url).await?)
+            .map_err(|e| format!("Failed to parse sidecar manifest: {}", e))?;
+
+        let installed_version = match component {
+            SidecarComponent::Gytmdl => BinaryLocator::with_binary_path(installed.to_path_buf())
+                .and_then(|locator| locator.load_manifest())
+                .map(|manifest| manifest.build_timestamp)
+                .ok(),
+        };
+        let update_available = match fs::read(installed) {
+            Ok(content) => sha256_hex(&content) != manifest.sha256.to_lowercase(),
+            Err(_) => true,
+        };
+
+        Ok(SidecarUpdate {
+            component,
+            installed_path: installed.to_string_lossy().to_string(),
+            installed_version,
+            latest_version: release.tag_name.clone(),
+            update_available,
+            download_url: asset.browser_download_url.clone(),
+            signature_url: signature_asset.browser_download_url.clone(),
+            manifest,
+        })
+    }
+
+    async fn fetch_text(client: &reqwest::Client, url: &str) -> Result<String, String> {
+        let response = client.get(url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| format!("Failed to fetch {}: {}", url, e))?;
+        response.text().await
+            .map_err(|e| format!("Failed to fetch {}: {}", url, e))
+    }
+}
+
+/// The pinned sidecar signing key
+fn public_key() -> Result<PublicKey, String> {
+    let key = SIDECAR_PUBLIC_KEY.ok_or("Sidecar updates are not configured in this build")?;
+    PublicKey::from_base64(key.trim()).map_err(|e| format!("Invalid sidecar signing key: {}", e))
+}
+
+/// Check a downloaded binary's minisign signature. The checksum in the
+/// release's manifest can't stand in for this: whoever can replace the
+/// binary can replace the manifest too.
+fn verify_signature(content: &[u8], signature: &str, public_key: &PublicKey) -> Result<(), String> {
+    let signature = Signature::decode(signature)
+        .map_err(|e| format!("Invalid sidecar signature: {}", e))?;
+    public_key.verify(content, &signature, false)
+        .map_err(|e| format!("Sidecar signature verification failed: {}", e))
+}
+
+fn sha256_hex(content: &[u8]) -> String {
+    Sha256::digest(content).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Check a downloaded binary's size and SHA-256 against its manifest
+fn verify_download(content: &[u8], manifest: &BinaryManifest) -> Result<(), String> {
+    if content.len() as u64 != manifest.size_bytes {
+        return Err(format!(
+            "Downloaded sidecar size mismatch. Expected: {}, Actual: {}",
+            manifest.size_bytes,
+            content.len()
+        ));
+    }
+
+    let actual = sha256_hex(content);
+    if actual != manifest.sha256.to_lowercase() {
+        return Err(format!(
+            "Downloaded sidecar checksum mismatch. Expected: {}, Actual: {}",
+            manifest.sha256, actual
+        ));
+    }
+
+    Ok(())
+}
+
+/// Write the new binary beside the old one, then rename it into place so a
+/// crash mid-update can't leave a truncated sidecar behind
+fn install_binary(target: &Path, content: &[u8], manifest: &BinaryManifest) -> std::io::Result<()> {
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut staging = target.as_os_str().to_owned();
+    staging.push(".download");
+    let staging = PathBuf::from(staging);
+    fs::write(&staging, content)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&staging, fs::Permissions::from_mode(0o755))?;
+    }
+
+    if let Err(e) = fs::rename(&staging, target) {
+        let _ = fs::remove_file(&staging);
+        return Err(e);
+    }
+
+    let manifest_json = serde_json::to_string_pretty(manifest).map_err(std::io::Error::other)?;
+    fs::write(target.with_extension("json"), manifest_json)
+}
+
+/// Platform file name of a dependency, e.g. `ffmpeg.exe` on Windows
+fn dependency_binary_name(name: &str) -> String {
+    if cfg!(target_os = "windows") {
+        format!("{}.exe", name)
+    } else {
+        name.to_string()
+    }
+}
+
+/// Version from the first line of `--version` output. ffmpeg prints
+/// `ffmpeg version 6.1.1 Copyright ...`; other tools print just the version.
+fn parse_version(output: &str) -> Option<String> {
+    let first_line = output.lines().map(str::trim).find(|line| !line.is_empty())?;
+    let mut words = first_line.split_whitespace();
+    let version = match words.position(|word| word == "version") {
+        Some(_) => words.next().unwrap_or(first_line),
+        None => first_line,
+    };
+    Some(version.to_string())
 }
 
 // Tauri commands for sidecar management
@@ -197,4 +609,63 @@ pub async fn select_best_sidecar() -> Result<SidecarInfo, String> {
 #[tauri::command]
 pub async fn check_sidecar_compatibility() -> Result<bool, String> {
     SidecarManager::check_platform_compatibility().await
+}
+
+#[tauri::command]
+pub async fn check_sidecar_dependencies() -> Result<Vec<DependencyInfo>, String> {
+    Ok(SidecarManager::check_dependencies().await)
+}
+
+#[tauri::command]
+pub async fn check_sidecar_updates() -> Result<Vec<SidecarUpdate>, String> {
+    SidecarManager::check_updates().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::binary_locator::PlatformInfo;
+
+    #[test]
+    fn test_parse_version() {
+        let ffmpeg = "ffmpeg version 6.1.1 Copyright (c) 2000-2023 the FFmpeg developers\nbuilt with clang\n";
+        assert_eq!(parse_version(ffmpeg), Some("6.1.1".to_string()));
+        assert_eq!(parse_version("\n2024.08.06\n"), Some("2024.08.06".to_string()));
+        assert_eq!(parse_version("  \n"), None);
+    }
+
+    #[test]
+    fn test_verify_and_install_binary() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let target = temp_dir.path().join("gytmdl-test");
+        fs::write(&target, b"old").unwrap();
+
+        let manifest = BinaryManifest {
+            binary_name: "gytmdl-test".to_string(),
+            platform: PlatformInfo {
+                os: std::env::consts::OS.to_string(),
+                arch: std::env::consts::ARCH.to_string(),
+                target: String::new(),
+                extension: String::new(),
+            },
+            size_bytes: 3,
+            sha256: sha256_hex(b"new"),
+            build_timestamp: String::new(),
+        };
+
+        assert!(verify_download(b"bad", &manifest).is_err());
+        assert!(verify_download(b"newer", &manifest).is_err());
+        verify_download(b"new", &manifest).unwrap();
+
+        install_binary(&target, b"new", &manifest).unwrap();
+        assert_eq!(fs::read(&target).unwrap(), b"new");
+        assert!(target.with_extension("json").exists());
+        assert!(!temp_dir.path().join("gytmdl-test.download").exists());
+    }
+
+    #[test]
+    fn test_verify_signature_rejects_bad_signatures() {
+        let public_key = PublicKey::from_base64("RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3").unwrap();
+        assert!(verify_signature(b"new", "not a signature", &public_key).is_err());
+    }
 }
\ No newline at end of file
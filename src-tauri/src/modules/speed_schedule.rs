@@ -0,0 +1,86 @@
+use crate::modules::state::AppConfig;
+use crate::modules::timezone::DisplayTimezone;
+use chrono::{DateTime, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Concurrency for part of the day, e.g. one download at a time during work
+/// hours and several overnight. Windows don't cap download speed: gytmdl has
+/// no option for it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScheduleWindow {
+    /// Local time the window starts, e.g. "09:00"
+    pub start: NaiveTime,
+    /// Local time the window ends; a window ending before it starts runs past midnight
+    pub end: NaiveTime,
+    pub concurrent_limit: usize,
+}
+
+impl ScheduleWindow {
+    /// Whether `time` falls in the window, which includes its start but not its end
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+/// The first configured window covering `now` in the configured timezone
+pub fn active_window(config: &AppConfig, now: DateTime<Utc>) -> Option<&ScheduleWindow> {
+    if config.speed_schedule.is_empty() {
+        return None;
+    }
+    let timezone = DisplayTimezone::from_config(config.timezone.as_deref()).unwrap_or(DisplayTimezone::System);
+    let time = timezone.local_time(&now);
+    config.speed_schedule.iter().find(|window| window.contains(time))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(start: &str, end: &str, concurrent_limit: usize) -> ScheduleWindow {
+        serde_json::from_value(serde_json::json!({
+            "start": start,
+            "end": end,
+            "concurrent_limit": concurrent_limit,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_window_contains_across_midnight() {
+        let time = |input: &str| input.parse::<NaiveTime>().unwrap();
+        let day = window("08:00", "22:00", 1);
+        assert!(day.contains(time("08:00")));
+        assert!(day.contains(time("21:59")));
+        assert!(!day.contains(time("22:00")));
+
+        let night = window("22:00", "08:00", 4);
+        assert!(night.contains(time("23:30")));
+        assert!(night.contains(time("03:00")));
+        assert!(!night.contains(time("12:00")));
+    }
+
+    #[test]
+    fn test_limits_follow_active_window() {
+        let mut config = AppConfig {
+            timezone: Some("UTC".to_string()),
+            ..AppConfig::default()
+        };
+        config.speed_schedule = vec![
+            window("08:00", "22:00", 1),
+            window("22:00", "08:00", 4),
+        ];
+
+        let noon = "2026-01-01T12:00:00Z".parse().unwrap();
+        assert_eq!(active_window(&config, noon).unwrap().concurrent_limit, 1);
+
+        let night = "2026-01-01T23:00:00Z".parse().unwrap();
+        assert_eq!(active_window(&config, night).unwrap().concurrent_limit, 4);
+
+        config.speed_schedule.clear();
+        assert!(active_window(&config, noon).is_none());
+    }
+}
@@ -1,16 +1,57 @@
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use chrono::{DateTime, Utc};
 use std::fs;
 use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
 use uuid::Uuid;
+use crate::modules::atomic_file;
+use crate::modules::clipboard_watcher::ClipboardAction;
+use crate::modules::converter::AudioFormat;
+use crate::modules::dry_run::DryRunReport;
+use crate::modules::error_classifier::{self, ClassifiedError};
+use crate::modules::library::OrganizeMode;
+use crate::modules::migrations::{self, CONFIG_SCHEMA_VERSION, STATE_SCHEMA_VERSION};
+use crate::modules::self_test::{DegradedFlags, SelfTestReport};
+use crate::modules::speed_schedule::ScheduleWindow;
+use crate::modules::job_list::JobList;
+use crate::modules::live_progress::LiveProgress;
+use crate::modules::job_groups::{GroupProgress, JobGroup};
+use crate::modules::verification::VerificationReport;
+use crate::modules::output_routing::{OutputRoute, RoutedOutput};
+use crate::modules::listen_log::{ListenLogService, ListenSubmission};
+use crate::modules::process_priority::ProcessPriority;
+use crate::modules::session_report::SessionReportFormat;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppState {
-    pub jobs: Vec<DownloadJob>,
+    #[serde(default = "current_state_schema_version")]
+    pub schema_version: u32,
+    pub jobs: JobList,
     pub config: AppConfig,
     pub is_paused: bool,
     pub concurrent_limit: usize,
+    /// Nothing is dispatched before this time
+    #[serde(default)]
+    pub queue_start_at: Option<DateTime<Utc>>,
+    /// Playlists and albums whose tracks were queued as separate jobs
+    #[serde(default)]
+    pub groups: Vec<JobGroup>,
+    /// Removed jobs kept so they can be restored, oldest first
+    #[serde(default)]
+    pub trash: Vec<TrashedJob>,
+    /// Features found unavailable by the startup self-test
+    #[serde(skip)]
+    pub degraded: DegradedFlags,
+    /// Result of the startup self-test, once it has finished
+    #[serde(skip)]
+    pub self_test: Option<SelfTestReport>,
+    /// Progress of running jobs, newer than their `progress` field
+    #[serde(skip)]
+    pub live_progress: std::sync::Arc<LiveProgress>,
+    /// Set when jobs change, so autosave can skip serializing an unchanged state
+    #[serde(skip)]
+    changed: std::sync::Arc<AtomicBool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,9 +62,70 @@ pub struct DownloadJob {
     pub progress: Progress,
     pub metadata: Option<JobMetadata>,
     pub error: Option<String>,
+    /// Cause of the failure and what to do about it, set alongside `error`
+    #[serde(default)]
+    pub error_details: Option<ClassifiedError>,
     pub created_at: DateTime<Utc>,
     pub started_at: Option<DateTime<Utc>>,
     pub completed_at: Option<DateTime<Utc>>,
+    /// Higher priority jobs are dispatched first; ties go by queue order
+    #[serde(default)]
+    pub priority: u32,
+    /// Set when the single-track job was satisfied from a file another job already downloaded
+    #[serde(default)]
+    pub from_cache: bool,
+    /// The job is not dispatched before this time
+    #[serde(default)]
+    pub scheduled_at: Option<DateTime<Utc>>,
+    /// Set when the download failed because the content is age-restricted
+    #[serde(default)]
+    pub age_restricted: bool,
+    /// Proxy for this job instead of the configured one, without credentials
+    #[serde(default)]
+    pub proxy: Option<String>,
+    /// Username and password for `proxy`. Kept in memory only, so they never
+    /// reach the state file or the frontend.
+    #[serde(skip)]
+    pub proxy_credentials: Option<ProxyCredentials>,
+    /// `proxy` needs credentials; set even after they were dropped on restart
+    #[serde(default)]
+    pub proxy_requires_auth: bool,
+    /// Number of times the job has been retried
+    #[serde(default)]
+    pub retry_count: u32,
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    /// Files the job wrote, as reported by gytmdl or found after completion
+    #[serde(default)]
+    pub output_files: Vec<PathBuf>,
+    /// Only resolve what would be downloaded; gytmdl is not run
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Outcome of a dry run, once it has finished
+    #[serde(default)]
+    pub dry_run_report: Option<DryRunReport>,
+    /// Group of the playlist or album this track was expanded from
+    #[serde(default)]
+    pub group_id: Option<String>,
+    /// Result of probing the finished files, when verification is on
+    #[serde(default)]
+    pub verification: Option<VerificationReport>,
+    /// Running when the connection dropped; queued again instead of failing
+    #[serde(default)]
+    pub retry_when_online: bool,
+    /// Whether the finished download was logged to ListenBrainz or Last.fm
+    #[serde(default)]
+    pub listen_submission: Option<ListenSubmission>,
+    /// Jobs that must complete before this one is dispatched
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// Output folder and templates fixed when the job was dispatched
+    #[serde(default)]
+    pub routed_output: Option<RoutedOutput>,
+}
+
+fn default_max_retries() -> u32 {
+    3
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -35,13 +137,19 @@ pub enum JobStatus {
     Cancelled,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct JobMetadata {
     pub title: Option<String>,
     pub artist: Option<String>,
     pub album: Option<String>,
     pub duration: Option<u32>,
     pub thumbnail: Option<String>,
+    /// Whether gytmdl saved synced lyrics; unknown until the job completes
+    #[serde(default)]
+    pub has_synced_lyrics: Option<bool>,
+    /// Tracks in the playlist or album, once it has been listed
+    #[serde(default)]
+    pub track_count: Option<u32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -51,15 +159,38 @@ pub struct Progress {
     pub current_step: String,
     pub total_steps: Option<u32>,
     pub current_step_index: Option<u32>,
+    /// Current transfer rate reported by yt-dlp
+    #[serde(default)]
+    pub speed_bytes_per_sec: Option<u64>,
+    #[serde(default)]
+    pub eta_seconds: Option<u64>,
+    #[serde(default)]
+    pub downloaded_bytes: Option<u64>,
+    #[serde(default)]
+    pub total_bytes: Option<u64>,
+    /// Position within a playlist or album job
+    #[serde(default)]
+    pub playlist: Option<PlaylistProgress>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Which track of a multi-track job is currently downloading
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PlaylistProgress {
+    pub current_track: u32,
+    pub total_tracks: u32,
+    pub track_title: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum DownloadStage {
     Initializing,
     FetchingMetadata,
     DownloadingAudio,
     Remuxing,
     ApplyingTags,
+    Converting,
+    Analyzing,
+    Verifying,
     Finalizing,
     Completed,
     Failed,
@@ -67,20 +198,43 @@ pub enum DownloadStage {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
+    #[serde(default = "current_config_schema_version")]
+    pub schema_version: u32,
+
     // Paths
     pub output_path: PathBuf,
     pub temp_path: PathBuf,
     pub cookies_path: Option<PathBuf>,
+    /// Cookies from a signed-in, age-verified account. Age-restricted
+    /// downloads are retried once with these when set.
+    #[serde(default)]
+    pub age_verified_cookies_path: Option<PathBuf>,
     
     // Download Settings
     pub itag: String,
     pub download_mode: DownloadMode,
     pub concurrent_limit: usize,
+    /// Time windows that override `concurrent_limit`; the first match wins
+    #[serde(default)]
+    pub speed_schedule: Vec<ScheduleWindow>,
+    /// HTTP(S) or SOCKS proxy for downloads, e.g. "socks5://127.0.0.1:1080"
+    #[serde(default)]
+    pub proxy_url: Option<String>,
+    #[serde(default)]
+    pub proxy_username: Option<String>,
+    #[serde(default)]
+    pub proxy_password: Option<String>,
+    /// CPU and disk priority of gytmdl and ffmpeg, so downloads stay in the background
+    #[serde(default)]
+    pub process_priority: ProcessPriority,
     
     // Quality Settings
     pub cover_size: u32,
     pub cover_format: CoverFormat,
     pub cover_quality: u8,
+    /// Also keep a copy of every job's cover here, named after its artist and album
+    #[serde(default)]
+    pub cover_folder: Option<PathBuf>,
     
     // Templates
     pub template_folder: String,
@@ -94,6 +248,346 @@ pub struct AppConfig {
     pub save_cover: bool,
     pub overwrite: bool,
     pub no_synced_lyrics: bool,
+
+    // Sidecar Environment
+    /// Let gytmdl pick up the user's config file instead of running sandboxed
+    #[serde(default)]
+    pub honor_system_config: bool,
+
+    // Metadata
+    /// Look up title/artist/thumbnail as soon as a job is queued
+    #[serde(default = "default_prefetch_metadata")]
+    pub prefetch_metadata: bool,
+
+    // Diagnostics
+    /// Keep a per-job journal of every progress update, persisted when a job fails
+    #[serde(default)]
+    pub progress_journal: bool,
+
+    // URL Intake
+    /// Rewrite rules applied in order to every URL before it is queued
+    #[serde(default)]
+    pub url_rewrite_rules: Vec<UrlRewriteRule>,
+
+    // Output Routing
+    /// Rules sending matching jobs to another folder or templates; the first match wins
+    #[serde(default)]
+    pub output_routes: Vec<OutputRoute>,
+
+    // Retention
+    /// Move finished jobs older than the retention window to history on startup
+    #[serde(default = "default_prune_jobs_on_load")]
+    pub prune_jobs_on_load: bool,
+    #[serde(default = "default_job_retention_days")]
+    pub job_retention_days: u32,
+    /// Finished jobs kept in the queue; older ones move to history as jobs finish
+    #[serde(default)]
+    pub max_completed_jobs_in_memory: Option<u32>,
+    /// Removed jobs stay restorable from the trash for this long
+    #[serde(default = "default_trash_retention_days")]
+    pub trash_retention_days: u32,
+
+    // Duplicate Detection
+    /// Also look for an existing file in the output folder before queueing
+    #[serde(default)]
+    pub scan_output_for_duplicates: bool,
+
+    // Localization
+    /// IANA timezone (e.g. "Europe/Berlin") for schedules and displayed times; system zone when unset
+    #[serde(default)]
+    pub timezone: Option<String>,
+
+    // Usage
+    /// Data price per GB on metered connections, used for queue cost estimates
+    #[serde(default)]
+    pub data_price_per_gb: Option<f64>,
+
+    // Cookies
+    /// Warn this many days before the cookies expire; 0 turns the warning off
+    #[serde(default = "default_cookie_expiry_warning_days")]
+    pub cookie_expiry_warning_days: u32,
+
+    // Disk space
+    /// Jobs are not started while the output or temp volume has less free space; 0 turns the check off
+    #[serde(default = "default_min_free_space_mb")]
+    pub min_free_space_mb: u64,
+    /// Pause the queue instead of failing the job when space runs low
+    #[serde(default)]
+    pub pause_on_low_disk_space: bool,
+
+    // Authentication
+    /// Pause the queue after this many downloads in a row fail with 403 or
+    /// sign-in errors; 0 turns it off
+    #[serde(default = "default_auth_failure_pause_threshold")]
+    pub auth_failure_pause_threshold: u32,
+
+    // Timeouts
+    /// Kill a download that has run this long; 0 turns the limit off
+    #[serde(default)]
+    pub job_timeout_minutes: u64,
+    /// Kill a download that hasn't printed anything for this long; 0 turns the check off
+    #[serde(default = "default_stall_timeout_minutes")]
+    pub stall_timeout_minutes: u64,
+    /// Queue timed-out downloads again while they have retries left
+    #[serde(default)]
+    pub retry_on_timeout: bool,
+
+    // Connectivity
+    /// Endpoint checked to tell whether the connection is up; any HTTP answer counts
+    #[serde(default = "default_connectivity_check_url")]
+    pub connectivity_check_url: String,
+    /// Seconds between checks; 0 turns the connectivity monitor off
+    #[serde(default = "default_connectivity_check_interval_secs")]
+    pub connectivity_check_interval_secs: u64,
+    /// How long the connection must be back before a queue paused by the monitor resumes
+    #[serde(default = "default_connectivity_resume_grace_secs")]
+    pub connectivity_resume_grace_secs: u64,
+
+    // App Lock
+    /// Hash of the PIN guarding destructive actions; no lock when unset.
+    /// Only changed through `set_app_lock`.
+    #[serde(default)]
+    pub lock_pin_hash: Option<String>,
+
+    // Temp files
+    /// Keep a failed job's folder under `temp_path` instead of deleting it, for debugging
+    #[serde(default)]
+    pub keep_failed_temp_dirs: bool,
+    /// Leftover job folders and partial files untouched this long are stale
+    #[serde(default = "default_stale_temp_age_hours")]
+    pub stale_temp_age_hours: u32,
+    /// Delete stale temp files without asking
+    #[serde(default)]
+    pub auto_clean_stale_temp: bool,
+
+    // Listen Log
+    /// Log finished downloads to this service; off when unset. Its token and
+    /// secret are stored outside the config.
+    #[serde(default)]
+    pub listen_log_service: Option<ListenLogService>,
+    /// Submission URL for self-hosted servers speaking the service's API
+    #[serde(default)]
+    pub listen_log_endpoint: Option<String>,
+    /// Last.fm application API key
+    #[serde(default)]
+    pub listen_log_api_key: Option<String>,
+
+    // Library
+    /// Music library that finished downloads are organized into; organizing is off when unset
+    #[serde(default)]
+    pub library_root: Option<PathBuf>,
+    /// Folder layout inside the library; supports {artist}, {album} and {title}
+    #[serde(default = "default_library_template")]
+    pub library_template: String,
+    /// Organize every completed job into the library automatically
+    #[serde(default)]
+    pub auto_organize: bool,
+    #[serde(default)]
+    pub organize_mode: OrganizeMode,
+    /// Write an .m3u8 playlist of a playlist or album group's tracks once the group finishes
+    #[serde(default)]
+    pub write_group_playlists: bool,
+    /// Write a report of each session into the output folder when the queue drains; off when unset
+    #[serde(default)]
+    pub session_report_format: Option<SessionReportFormat>,
+
+    // Filenames
+    /// Replace characters Windows and FAT32 don't allow in downloaded files' names and folders
+    #[serde(default)]
+    pub windows_safe_filenames: bool,
+    #[serde(default)]
+    pub strip_emoji_from_filenames: bool,
+    /// Transliterate names to ASCII, e.g. "Beyoncé" -> "Beyonce"
+    #[serde(default)]
+    pub transliterate_filenames: bool,
+    /// Shorten file names so the full path stays within this many characters
+    #[serde(default)]
+    pub max_path_length: Option<u32>,
+
+    // Conversion
+    /// Transcode downloaded m4a files to this format with ffmpeg; kept as m4a when unset
+    #[serde(default)]
+    pub convert_to: Option<AudioFormat>,
+    /// Target bitrate for lossy formats
+    #[serde(default = "default_convert_bitrate_kbps")]
+    pub convert_bitrate_kbps: u32,
+    /// Keep the downloaded m4a next to the converted file
+    #[serde(default)]
+    pub keep_original_after_convert: bool,
+
+    // Loudness
+    /// Measure each finished track with ffmpeg and write ReplayGain tags
+    #[serde(default)]
+    pub replaygain: bool,
+
+    // Verification
+    /// Probe finished files with ffprobe and flag truncated or low-quality ones
+    #[serde(default)]
+    pub verify_downloads: bool,
+    /// How far a file's duration may differ from the track's before it is suspect
+    #[serde(default = "default_verify_duration_tolerance_secs")]
+    pub verify_duration_tolerance_secs: u32,
+
+    // Lyrics
+    /// Embed unsynced lyrics in the track's tags
+    #[serde(default = "default_embed_lyrics")]
+    pub embed_lyrics: bool,
+    /// Only save the synced lyrics as .lrc files next to already downloaded tracks
+    #[serde(default)]
+    pub lyrics_only: bool,
+
+    // Notifications
+    #[serde(default = "default_notify")]
+    pub notify_on_completion: bool,
+    #[serde(default = "default_notify")]
+    pub notify_on_failure: bool,
+    #[serde(default = "default_notify")]
+    pub notify_on_cookie_expiry: bool,
+
+    // Exit
+    /// Ask before closing the window while downloads are running
+    #[serde(default)]
+    pub confirm_exit_with_active_downloads: bool,
+
+    // Resume
+    /// Restart downloads that were interrupted by a crash or forced quit when the app starts
+    #[serde(default)]
+    pub auto_resume_on_start: bool,
+
+    // Autosave
+    /// Longest a change to the queue goes unsaved; 0 turns autosave off
+    #[serde(default = "default_autosave_interval_secs")]
+    pub autosave_interval_secs: u64,
+    /// Save this long after the queue stops changing
+    #[serde(default = "default_autosave_debounce_ms")]
+    pub autosave_debounce_ms: u64,
+
+    // Clipboard
+    /// Watch the clipboard for copied YouTube Music URLs
+    #[serde(default)]
+    pub clipboard_watcher: bool,
+    #[serde(default)]
+    pub clipboard_action: ClipboardAction,
+
+    // Debug logs
+    /// Keep the debug log in rotating files in the app data directory
+    #[serde(default = "default_log_to_file")]
+    pub log_to_file: bool,
+    /// Start a new log file once the current one reaches this size
+    #[serde(default = "default_log_max_file_size_mb")]
+    pub log_max_file_size_mb: u64,
+    /// Delete log files older than this
+    #[serde(default = "default_log_retention_days")]
+    pub log_retention_days: u32,
+}
+
+fn current_state_schema_version() -> u32 {
+    STATE_SCHEMA_VERSION
+}
+
+fn current_config_schema_version() -> u32 {
+    CONFIG_SCHEMA_VERSION
+}
+
+fn default_prefetch_metadata() -> bool {
+    true
+}
+
+fn default_prune_jobs_on_load() -> bool {
+    true
+}
+
+fn default_job_retention_days() -> u32 {
+    7
+}
+
+fn default_trash_retention_days() -> u32 {
+    7
+}
+
+fn default_cookie_expiry_warning_days() -> u32 {
+    7
+}
+
+fn default_min_free_space_mb() -> u64 {
+    500
+}
+
+fn default_auth_failure_pause_threshold() -> u32 {
+    3
+}
+
+fn default_stall_timeout_minutes() -> u64 {
+    10
+}
+
+fn default_log_to_file() -> bool {
+    true
+}
+
+fn default_log_max_file_size_mb() -> u64 {
+    5
+}
+
+fn default_log_retention_days() -> u32 {
+    7
+}
+
+fn default_library_template() -> String {
+    "{artist}/{album}".to_string()
+}
+
+fn default_convert_bitrate_kbps() -> u32 {
+    320
+}
+
+fn default_verify_duration_tolerance_secs() -> u32 {
+    3
+}
+
+fn default_connectivity_check_url() -> String {
+    "https://music.youtube.com/generate_204".to_string()
+}
+
+fn default_connectivity_check_interval_secs() -> u64 {
+    30
+}
+
+fn default_connectivity_resume_grace_secs() -> u64 {
+    15
+}
+
+fn default_stale_temp_age_hours() -> u32 {
+    24
+}
+
+fn default_embed_lyrics() -> bool {
+    true
+}
+
+fn default_notify() -> bool {
+    true
+}
+
+fn default_autosave_interval_secs() -> u64 {
+    30
+}
+
+fn default_autosave_debounce_ms() -> u64 {
+    2000
+}
+
+/// A regex rewrite applied to URLs before they are queued
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UrlRewriteRule {
+    pub pattern: String,
+    pub replacement: String,
+    #[serde(default = "default_rule_enabled")]
+    pub enabled: bool,
+}
+
+fn default_rule_enabled() -> bool {
+    true
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -110,13 +604,31 @@ pub enum CoverFormat {
     Webp,
 }
 
+impl CoverFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            CoverFormat::Jpg => "jpg",
+            CoverFormat::Png => "png",
+            CoverFormat::Webp => "webp",
+        }
+    }
+}
+
 impl Default for AppState {
     fn default() -> Self {
         Self {
-            jobs: Vec::new(),
+            schema_version: STATE_SCHEMA_VERSION,
+            jobs: JobList::new(),
             config: AppConfig::default(),
             is_paused: false,
             concurrent_limit: 3,
+            queue_start_at: None,
+            groups: Vec::new(),
+            trash: Vec::new(),
+            degraded: DegradedFlags::NONE,
+            self_test: None,
+            live_progress: std::sync::Arc::new(LiveProgress::new()),
+            changed: std::sync::Arc::new(AtomicBool::new(false)),
         }
     }
 }
@@ -124,15 +636,23 @@ impl Default for AppState {
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
+            schema_version: CONFIG_SCHEMA_VERSION,
             output_path: PathBuf::from("../downloads"),
             temp_path: PathBuf::from("../temp"),
             cookies_path: None,
+            age_verified_cookies_path: None,
             itag: "140".to_string(),
             download_mode: DownloadMode::Audio,
             concurrent_limit: 3,
+            speed_schedule: Vec::new(),
+            proxy_url: None,
+            proxy_username: None,
+            proxy_password: None,
+            process_priority: ProcessPriority::Normal,
             cover_size: 1400,
             cover_format: CoverFormat::Jpg,
             cover_quality: 95,
+            cover_folder: None,
             template_folder: "{album_artist}/{album}".to_string(),
             template_file: "{track:02d} {title}".to_string(),
             template_date: "%Y-%m-%d".to_string(),
@@ -142,6 +662,65 @@ impl Default for AppConfig {
             save_cover: true,
             overwrite: false,
             no_synced_lyrics: false,
+            honor_system_config: false,
+            prefetch_metadata: true,
+            progress_journal: false,
+            url_rewrite_rules: Vec::new(),
+            output_routes: Vec::new(),
+            prune_jobs_on_load: true,
+            job_retention_days: 7,
+            max_completed_jobs_in_memory: None,
+            trash_retention_days: default_trash_retention_days(),
+            scan_output_for_duplicates: false,
+            timezone: None,
+            data_price_per_gb: None,
+            cookie_expiry_warning_days: 7,
+            min_free_space_mb: 500,
+            pause_on_low_disk_space: false,
+            auth_failure_pause_threshold: default_auth_failure_pause_threshold(),
+            job_timeout_minutes: 0,
+            stall_timeout_minutes: default_stall_timeout_minutes(),
+            retry_on_timeout: false,
+            connectivity_check_url: default_connectivity_check_url(),
+            connectivity_check_interval_secs: default_connectivity_check_interval_secs(),
+            connectivity_resume_grace_secs: default_connectivity_resume_grace_secs(),
+            lock_pin_hash: None,
+            keep_failed_temp_dirs: false,
+            stale_temp_age_hours: default_stale_temp_age_hours(),
+            auto_clean_stale_temp: false,
+            listen_log_service: None,
+            listen_log_endpoint: None,
+            listen_log_api_key: None,
+            library_root: None,
+            library_template: default_library_template(),
+            auto_organize: false,
+            organize_mode: OrganizeMode::Move,
+            write_group_playlists: false,
+            session_report_format: None,
+            windows_safe_filenames: false,
+            strip_emoji_from_filenames: false,
+            transliterate_filenames: false,
+            max_path_length: None,
+            convert_to: None,
+            convert_bitrate_kbps: default_convert_bitrate_kbps(),
+            keep_original_after_convert: false,
+            replaygain: false,
+            verify_downloads: false,
+            verify_duration_tolerance_secs: default_verify_duration_tolerance_secs(),
+            embed_lyrics: true,
+            lyrics_only: false,
+            notify_on_completion: true,
+            notify_on_failure: true,
+            notify_on_cookie_expiry: true,
+            confirm_exit_with_active_downloads: false,
+            auto_resume_on_start: false,
+            autosave_interval_secs: default_autosave_interval_secs(),
+            autosave_debounce_ms: default_autosave_debounce_ms(),
+            clipboard_watcher: false,
+            clipboard_action: ClipboardAction::Prompt,
+            log_to_file: default_log_to_file(),
+            log_max_file_size_mb: default_log_max_file_size_mb(),
+            log_retention_days: default_log_retention_days(),
         }
     }
 }
@@ -155,50 +734,69 @@ impl AppState {
     /// Load AppState from a JSON file
     pub fn load_from_file(path: &PathBuf) -> Result<Self, io::Error> {
         let content = fs::read_to_string(path)?;
-        let state: AppState = serde_json::from_str(&content)
+        let mut document: serde_json::Value = serde_json::from_str(&content)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        // Upgrade files written by older versions instead of failing on missing fields
+        let from_version = migrations::migrate_state(&mut document)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        if from_version < STATE_SCHEMA_VERSION {
+            migrations::backup_before_upgrade(path, from_version)?;
+        }
+
+        let state: AppState = serde_json::from_value(document)
             .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
         Ok(state)
     }
 
-    /// Save AppState to a JSON file
-    pub fn save_to_file(&self, path: &PathBuf) -> Result<(), io::Error> {
-        // Create parent directory if it doesn't exist
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent)?;
-        }
-        
+    /// Load the most recent backup of `path` that can still be read, along with its index
+    pub fn load_from_backups(path: &Path) -> Option<(usize, Self)> {
+        atomic_file::list_backups(path)
+            .into_iter()
+            .find_map(|backup| Self::load_from_file(&backup.path).ok().map(|state| (backup.index, state)))
+    }
+
+    /// Save AppState to a JSON file, replacing it atomically and keeping the
+    /// previous versions as rotated backups
+    pub fn save_to_file(&self, path: &Path) -> Result<(), io::Error> {
         let content = serde_json::to_string_pretty(self)
             .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
-        fs::write(path, content)?;
-        Ok(())
+        atomic_file::write_with_backups(path, content.as_bytes(), atomic_file::BACKUP_COUNT)
+    }
+
+    /// Note that the state changed since autosave last looked
+    pub fn mark_changed(&self) {
+        self.changed.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether the state was marked changed since the last call
+    pub fn take_changed(&self) -> bool {
+        self.changed.swap(false, Ordering::Relaxed)
     }
 
     /// Add a new job to the queue
     pub fn add_job(&mut self, url: String) -> String {
-        let job_id = Uuid::new_v4().to_string();
-        let job = DownloadJob {
-            id: job_id.clone(),
-            url,
-            status: JobStatus::Queued,
-            progress: Progress::default(),
-            metadata: None,
-            error: None,
-            created_at: Utc::now(),
-            started_at: None,
-            completed_at: None,
-        };
+        let job = DownloadJob::new(url);
+        let job_id = job.id.clone();
         self.jobs.push(job);
+        self.mark_changed();
         job_id
     }
 
     /// Get a job by ID
     pub fn get_job(&self, job_id: &str) -> Option<&DownloadJob> {
-        self.jobs.iter().find(|job| job.id == job_id)
+        self.jobs.get(job_id)
+    }
+
+    /// Find a job by its source URL
+    pub fn find_job_by_url(&self, url: &str) -> Option<&DownloadJob> {
+        self.jobs.iter().find(|job| job.url == url)
     }
 
     /// Get a mutable reference to a job by ID
     pub fn get_job_mut(&mut self, job_id: &str) -> Option<&mut DownloadJob> {
-        self.jobs.iter_mut().find(|job| job.id == job_id)
+        self.mark_changed();
+        self.jobs.get_mut(job_id)
     }
 
     /// Update job status
@@ -216,14 +814,37 @@ impl AppState {
                 }
                 _ => {}
             }
-            true
         } else {
-            false
+            return false;
+        }
+
+        if status == JobStatus::Completed {
+            self.release_dependents(job_id);
+        }
+        true
+    }
+
+    /// Drop the jobs that already completed from `depends_on`; they were
+    /// released when they completed and may be cleared out at any time
+    pub fn drop_completed_dependencies(&self, depends_on: &mut Vec<String>) {
+        depends_on.retain(|id| {
+            self.jobs.get(id).is_none_or(|dependency| dependency.status != JobStatus::Completed)
+        });
+    }
+
+    /// Drop a completed job from the dependencies of the jobs waiting on it,
+    /// so they no longer need it in the queue once it's cleared out
+    fn release_dependents(&mut self, job_id: &str) {
+        for job in self.jobs.iter_mut() {
+            job.depends_on.retain(|dependency| dependency != job_id);
         }
     }
 
     /// Update job progress
     pub fn update_job_progress(&mut self, job_id: &str, progress: Progress) -> bool {
+        if let Some(cell) = self.live_progress.cell(job_id) {
+            cell.set(progress.clone());
+        }
         if let Some(job) = self.get_job_mut(job_id) {
             job.progress = progress;
             true
@@ -232,6 +853,28 @@ impl AppState {
         }
     }
 
+    /// Current progress of a job, including updates not yet settled into it
+    pub fn job_progress(&self, job: &DownloadJob) -> Progress {
+        self.live_progress.get(&job.id).unwrap_or_else(|| job.progress.clone())
+    }
+
+    /// Copy of a job with its current progress, for handing to the frontend
+    pub fn job_snapshot(&self, job: &DownloadJob) -> DownloadJob {
+        DownloadJob {
+            progress: self.job_progress(job),
+            ..job.clone()
+        }
+    }
+
+    /// Stop tracking a job's live progress and store the last value in the job
+    pub fn settle_live_progress(&mut self, job_id: &str) {
+        if let Some(progress) = self.live_progress.untrack(job_id) {
+            if let Some(job) = self.get_job_mut(job_id) {
+                job.progress = progress;
+            }
+        }
+    }
+
     /// Update job metadata
     pub fn update_job_metadata(&mut self, job_id: &str, metadata: JobMetadata) -> bool {
         if let Some(job) = self.get_job_mut(job_id) {
@@ -244,8 +887,15 @@ impl AppState {
 
     /// Set job error
     pub fn set_job_error(&mut self, job_id: &str, error: String) -> bool {
+        let details = ClassifiedError::from(error_classifier::classify(&error));
+        self.set_job_failure(job_id, error, details)
+    }
+
+    /// Mark a job as failed with an already classified error
+    pub fn set_job_failure(&mut self, job_id: &str, error: String, details: ClassifiedError) -> bool {
         if let Some(job) = self.get_job_mut(job_id) {
             job.error = Some(error);
+            job.error_details = Some(details);
             job.status = JobStatus::Failed;
             job.completed_at = Some(Utc::now());
             true
@@ -254,13 +904,290 @@ impl AppState {
         }
     }
 
+    /// Set a job's dispatch priority
+    pub fn set_job_priority(&mut self, job_id: &str, priority: u32) -> bool {
+        if let Some(job) = self.get_job_mut(job_id) {
+            job.priority = priority;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Move a job to a new position in the queue.
+    /// Indices past the end move the job to the back.
+    pub fn reorder_job(&mut self, job_id: &str, new_index: usize) -> bool {
+        let Some(current_index) = self.jobs.position(job_id) else {
+            return false;
+        };
+
+        let job = self.jobs.remove(current_index);
+        let new_index = new_index.min(self.jobs.len());
+        self.jobs.insert(new_index, job);
+        true
+    }
+
+    /// Set or clear a job's scheduled start time
+    pub fn set_job_schedule(&mut self, job_id: &str, scheduled_at: Option<DateTime<Utc>>) -> bool {
+        if let Some(job) = self.get_job_mut(job_id) {
+            job.scheduled_at = scheduled_at;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Check if the queue-wide start time has been reached
+    pub fn is_queue_start_due(&self, now: DateTime<Utc>) -> bool {
+        self.queue_start_at.is_none_or(|start_at| start_at <= now)
+    }
+
+    /// Pick the next queued job to dispatch out of `candidates`:
+    /// highest priority first, then earliest position in the queue.
+    /// Jobs scheduled in the future or waiting on other jobs are skipped.
+    pub fn pick_next_job(&self, candidates: &[&str]) -> Option<String> {
+        let now = Utc::now();
+        self.jobs.iter()
+            .enumerate()
+            .filter(|(_, job)| job.status == JobStatus::Queued && job.is_due(now))
+            .filter(|(_, job)| self.dependency_state(job) == DependencyState::Ready)
+            .filter(|(_, job)| candidates.contains(&job.id.as_str()))
+            .min_by_key(|(index, job)| (std::cmp::Reverse(job.priority), *index))
+            .map(|(_, job)| job.id.clone())
+    }
+
+    /// Where `job` stands on the jobs it depends on. Completed dependencies
+    /// are dropped from `depends_on`, so one that is no longer in the queue
+    /// was removed before completing and blocks the job.
+    pub fn dependency_state(&self, job: &DownloadJob) -> DependencyState {
+        let mut state = DependencyState::Ready;
+        for id in &job.depends_on {
+            let Some(dependency) = self.jobs.get(id) else {
+                return DependencyState::Blocked(id.clone());
+            };
+            match dependency.status {
+                JobStatus::Completed => {}
+                JobStatus::Queued | JobStatus::Downloading => state = DependencyState::Waiting,
+                JobStatus::Failed | JobStatus::Cancelled => {
+                    return DependencyState::Blocked(dependency.id.clone());
+                }
+            }
+        }
+        state
+    }
+
+    /// Check that `job_id` can depend on `depends_on`: every dependency
+    /// exists and none of them leads back to the job
+    pub fn check_dependencies(&self, job_id: &str, depends_on: &[String]) -> Result<(), String> {
+        for dependency in depends_on {
+            if dependency == job_id {
+                return Err("A job cannot depend on itself".to_string());
+            }
+            if self.jobs.get(dependency).is_none() {
+                return Err(format!("Dependency {} not found", dependency));
+            }
+        }
+
+        // Walk the dependencies of the dependencies looking for the job
+        let mut visited = std::collections::HashSet::new();
+        let mut stack: Vec<&str> = depends_on.iter().map(String::as_str).collect();
+        while let Some(id) = stack.pop() {
+            if id == job_id {
+                return Err("Dependencies would form a cycle".to_string());
+            }
+            if visited.insert(id) {
+                if let Some(job) = self.jobs.get(id) {
+                    stack.extend(job.depends_on.iter().map(String::as_str));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Replace the jobs `job_id` waits on
+    pub fn set_job_dependencies(&mut self, job_id: &str, mut depends_on: Vec<String>) -> Result<(), String> {
+        let job = self.jobs.get(job_id).ok_or_else(|| "Job not found".to_string())?;
+        if job.is_active() {
+            return Err("Job is already running".to_string());
+        }
+        depends_on.sort();
+        depends_on.dedup();
+        self.check_dependencies(job_id, &depends_on)?;
+        self.drop_completed_dependencies(&mut depends_on);
+
+        if let Some(job) = self.jobs.get_mut(job_id) {
+            job.depends_on = depends_on;
+        }
+        Ok(())
+    }
+
+    /// Remove and return terminal jobs that finished before `cutoff`
+    pub fn take_terminal_jobs_before(&mut self, cutoff: DateTime<Utc>) -> Vec<DownloadJob> {
+        let expired = self.jobs.extract(|job| {
+            job.is_terminal() && job.completed_at.unwrap_or(job.created_at) < cutoff
+        });
+        self.prune_empty_groups();
+        expired
+    }
+
+    /// Remove finished jobs beyond the `keep` most recently finished ones.
+    /// Jobs of a group that is still running stay until the group finishes.
+    pub fn take_finished_jobs_over(&mut self, keep: usize) -> TakenJobs {
+        let running_groups: std::collections::HashSet<String> = self.group_summaries().into_iter()
+            .filter(|progress| !progress.finished)
+            .map(|progress| progress.group.id)
+            .collect();
+        let mut finished: Vec<(DateTime<Utc>, &str)> = self.jobs.iter()
+            .filter(|job| job.is_terminal())
+            .filter(|job| job.group_id.as_ref().is_none_or(|group_id| !running_groups.contains(group_id)))
+            .map(|job| (job.completed_at.unwrap_or(job.created_at), job.id.as_str()))
+            .collect();
+        if finished.len() <= keep {
+            return TakenJobs::default();
+        }
+
+        finished.sort_by_key(|(finished_at, _)| std::cmp::Reverse(*finished_at));
+        let overflow: std::collections::HashSet<String> = finished.into_iter()
+            .skip(keep)
+            .map(|(_, id)| id.to_string())
+            .collect();
+        let (positions, jobs) = self.jobs.extract_positioned(|job| overflow.contains(&job.id)).into_iter().unzip();
+        let groups = self.take_empty_groups();
+        TakenJobs { positions, jobs, groups }
+    }
+
+    /// Put back jobs taken for archiving that couldn't be written to history,
+    /// at their old positions and with their groups
+    pub fn restore_jobs(&mut self, taken: TakenJobs) {
+        self.jobs.restore(taken.positions.into_iter().zip(taken.jobs).collect());
+        for group in taken.groups {
+            if self.get_group(&group.id).is_none() {
+                self.groups.push(group);
+            }
+        }
+        self.groups.sort_by_key(|group| group.created_at);
+    }
+
+    /// Remove queued jobs whose URL is already queued earlier in the list.
+    /// Returns the number of jobs removed.
+    pub fn dedupe_queued_jobs(&mut self) -> usize {
+        let initial_len = self.jobs.len();
+        let mut seen = std::collections::HashSet::new();
+        self.jobs.retain(|job| job.status != JobStatus::Queued || seen.insert(job.url.clone()));
+        initial_len - self.jobs.len()
+    }
+
+    /// Put jobs left `Downloading` by a crash or forced quit back in the queue.
+    /// Their partial files in `temp_path` are kept so the download can continue
+    /// where it stopped. Returns the ids of the recovered jobs.
+    pub fn recover_interrupted_jobs(&mut self) -> Vec<String> {
+        self.jobs.iter_mut()
+            .filter(|job| job.status == JobStatus::Downloading)
+            .map(|job| {
+                job.requeue();
+                job.id.clone()
+            })
+            .collect()
+    }
+
     /// Remove a job from the queue
     pub fn remove_job(&mut self, job_id: &str) -> bool {
         let initial_len = self.jobs.len();
         self.jobs.retain(|job| job.id != job_id);
+        self.prune_empty_groups();
         self.jobs.len() != initial_len
     }
 
+    /// Move a job to the trash, stopping it from being dispatched. A job
+    /// that was running is marked cancelled.
+    pub fn trash_job(&mut self, job_id: &str) -> bool {
+        let Some(mut job) = self.jobs.extract(|job| job.id == job_id).pop() else {
+            return false;
+        };
+        if job.is_active() {
+            job.status = JobStatus::Cancelled;
+            job.completed_at = Some(Utc::now());
+        }
+        self.prune_empty_groups();
+        self.trash.push(TrashedJob { job, removed_at: Utc::now() });
+        true
+    }
+
+    /// Put a trashed job back in the queue and return it
+    pub fn restore_job(&mut self, job_id: &str) -> Result<&DownloadJob, String> {
+        let index = self.trash.iter()
+            .position(|trashed| trashed.job.id == job_id)
+            .ok_or_else(|| "Job not found in trash".to_string())?;
+        if self.find_job_by_url(&self.trash[index].job.url).is_some() {
+            return Err("URL is already in the queue".to_string());
+        }
+
+        let mut job = self.trash.remove(index).job;
+        // Its group may have been pruned while it was in the trash
+        if job.group_id.as_ref().is_some_and(|group_id| !self.groups.iter().any(|group| &group.id == group_id)) {
+            job.group_id = None;
+        }
+        self.jobs.push(job);
+        self.get_job(job_id).ok_or_else(|| "Job not found".to_string())
+    }
+
+    /// Permanently delete everything in the trash. Returns the number deleted.
+    pub fn empty_trash(&mut self) -> usize {
+        std::mem::take(&mut self.trash).len()
+    }
+
+    /// Permanently delete jobs trashed longer ago than the retention period.
+    /// Returns the number deleted.
+    pub fn purge_expired_trash(&mut self, now: DateTime<Utc>) -> usize {
+        let cutoff = now - chrono::Duration::days(self.config.trash_retention_days as i64);
+        let initial_len = self.trash.len();
+        self.trash.retain(|trashed| trashed.removed_at >= cutoff);
+        initial_len - self.trash.len()
+    }
+
+    /// Start a group for the tracks expanded from `source_url`
+    pub fn add_group(&mut self, source_url: String, name: Option<String>) -> String {
+        let group = JobGroup::new(source_url, name);
+        let group_id = group.id.clone();
+        self.groups.push(group);
+        group_id
+    }
+
+    pub fn get_group(&self, group_id: &str) -> Option<&JobGroup> {
+        self.groups.iter().find(|group| group.id == group_id)
+    }
+
+    /// Jobs in a group, in queue order
+    pub fn group_jobs(&self, group_id: &str) -> Vec<&DownloadJob> {
+        self.jobs.iter().filter(|job| job.group_id.as_deref() == Some(group_id)).collect()
+    }
+
+    /// Combined progress of a group's jobs
+    pub fn group_progress(&self, group_id: &str) -> Option<GroupProgress> {
+        let group = self.get_group(group_id)?;
+        let jobs = self.group_jobs(group_id);
+        Some(GroupProgress::summarize(group, jobs.into_iter().map(|job| (job, self.job_progress(job)))))
+    }
+
+    /// Progress of every group, oldest first
+    pub fn group_summaries(&self) -> Vec<GroupProgress> {
+        self.groups.iter().filter_map(|group| self.group_progress(&group.id)).collect()
+    }
+
+    /// Drop groups with no jobs left. Returns the number removed.
+    pub fn prune_empty_groups(&mut self) -> usize {
+        self.take_empty_groups().len()
+    }
+
+    /// Remove and return groups with no jobs left
+    fn take_empty_groups(&mut self) -> Vec<JobGroup> {
+        let jobs = &self.jobs;
+        let (kept, empty): (Vec<JobGroup>, Vec<JobGroup>) = self.groups.drain(..)
+            .partition(|group| jobs.iter().any(|job| job.group_id.as_deref() == Some(group.id.as_str())));
+        self.groups = kept;
+        empty
+    }
+
     /// Get jobs by status
     pub fn get_jobs_by_status(&self, status: &JobStatus) -> Vec<&DownloadJob> {
         self.jobs.iter().filter(|job| &job.status == status).collect()
@@ -271,9 +1198,19 @@ impl AppState {
         self.jobs.iter().filter(|job| &job.status == status).count()
     }
 
+    /// Tracks still to download across queued jobs, counting each playlist
+    /// or album by its listed track count
+    pub fn queued_track_count(&self) -> usize {
+        self.jobs.iter()
+            .filter(|job| job.status == JobStatus::Queued)
+            .map(|job| job.track_count() as usize)
+            .sum()
+    }
+
     /// Clear completed and failed jobs
     pub fn clear_completed_jobs(&mut self) {
         self.jobs.retain(|job| !matches!(job.status, JobStatus::Completed | JobStatus::Failed));
+        self.prune_empty_groups();
     }
 
     /// Pause the queue
@@ -300,10 +1237,63 @@ impl Default for Progress {
             current_step: "Initializing...".to_string(),
             total_steps: None,
             current_step_index: None,
+            speed_bytes_per_sec: None,
+            eta_seconds: None,
+            downloaded_bytes: None,
+            total_bytes: None,
+            playlist: None,
         }
     }
 }
 
+/// Credentials for a per-job proxy, percent-encoded as in a URL
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProxyCredentials {
+    pub username: String,
+    pub password: Option<String>,
+}
+
+/// Finished jobs taken out of the queue to be archived, with where they
+/// were so `AppState::restore_jobs` can put them back
+#[derive(Debug, Default)]
+pub struct TakenJobs {
+    positions: Vec<usize>,
+    jobs: Vec<DownloadJob>,
+    groups: Vec<JobGroup>,
+}
+
+impl TakenJobs {
+    pub fn jobs(&self) -> &[DownloadJob] {
+        &self.jobs
+    }
+
+    pub fn len(&self) -> usize {
+        self.jobs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
+    }
+}
+
+/// A removed job and when it was removed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashedJob {
+    pub job: DownloadJob,
+    pub removed_at: DateTime<Utc>,
+}
+
+/// Where a job stands on the jobs it depends on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DependencyState {
+    /// Every dependency has completed
+    Ready,
+    /// A dependency is still queued or running
+    Waiting,
+    /// This dependency failed, was cancelled or was removed, so the job can't run
+    Blocked(String),
+}
+
 impl DownloadJob {
     /// Create a new download job
     pub fn new(url: String) -> Self {
@@ -314,17 +1304,50 @@ impl DownloadJob {
             progress: Progress::default(),
             metadata: None,
             error: None,
+            error_details: None,
             created_at: Utc::now(),
             started_at: None,
             completed_at: None,
+            priority: 0,
+            from_cache: false,
+            retry_count: 0,
+            max_retries: default_max_retries(),
+            output_files: Vec::new(),
+            age_restricted: false,
+            proxy: None,
+            proxy_credentials: None,
+            proxy_requires_auth: false,
+            scheduled_at: None,
+            dry_run: false,
+            dry_run_report: None,
+            group_id: None,
+            verification: None,
+            retry_when_online: false,
+            listen_submission: None,
+            depends_on: Vec::new(),
+            routed_output: None,
         }
     }
 
+    /// Tracks the job downloads: the listed count for playlists and albums,
+    /// otherwise one
+    pub fn track_count(&self) -> u32 {
+        self.metadata.as_ref()
+            .and_then(|metadata| metadata.track_count)
+            .unwrap_or(1)
+            .max(1)
+    }
+
     /// Check if the job is in a terminal state (completed, failed, or cancelled)
     pub fn is_terminal(&self) -> bool {
         matches!(self.status, JobStatus::Completed | JobStatus::Failed | JobStatus::Cancelled)
     }
 
+    /// Check if the job's scheduled time (if any) has been reached
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.scheduled_at.is_none_or(|scheduled_at| scheduled_at <= now)
+    }
+
     /// Check if the job is active (downloading)
     pub fn is_active(&self) -> bool {
         matches!(self.status, JobStatus::Downloading)
@@ -335,11 +1358,28 @@ impl DownloadJob {
         matches!(self.status, JobStatus::Failed | JobStatus::Cancelled)
     }
 
-    /// Reset job for retry
+    /// Check if the job has retry attempts left
+    pub fn has_retries_left(&self) -> bool {
+        self.retry_count < self.max_retries
+    }
+
+    /// Reset job for retry, counting the attempt
     pub fn reset_for_retry(&mut self) {
+        self.retry_count += 1;
+        self.requeue();
+    }
+
+    /// Put an interrupted job back in the queue without counting it as a retry
+    pub fn requeue(&mut self) {
         self.status = JobStatus::Queued;
         self.progress = Progress::default();
         self.error = None;
+        self.error_details = None;
+        self.output_files.clear();
+        self.dry_run_report = None;
+        self.verification = None;
+        self.retry_when_online = false;
+        self.listen_submission = None;
         self.started_at = None;
         self.completed_at = None;
     }
@@ -436,6 +1476,23 @@ mod tests {
         assert!(job.error.is_none());
         assert!(job.started_at.is_none());
         assert!(job.completed_at.is_none());
+        assert_eq!(job.retry_count, 1);
+        assert!(job.has_retries_left());
+
+        job.retry_count = job.max_retries;
+        assert!(!job.has_retries_left());
+    }
+
+    #[test]
+    fn test_download_job_retry_fields_default_when_missing() {
+        let mut value = serde_json::to_value(DownloadJob::new("https://test.com".to_string())).unwrap();
+        let fields = value.as_object_mut().unwrap();
+        fields.remove("retry_count");
+        fields.remove("max_retries");
+
+        let job: DownloadJob = serde_json::from_value(value).unwrap();
+        assert_eq!(job.retry_count, 0);
+        assert_eq!(job.max_retries, 3);
     }
 
     #[test]
@@ -465,6 +1522,15 @@ mod tests {
         assert!(state.get_job("non-existent-id").is_none());
     }
 
+    #[test]
+    fn test_app_state_find_job_by_url() {
+        let mut state = AppState::new();
+        let job_id = state.add_job("https://test.com/a".to_string());
+
+        assert_eq!(state.find_job_by_url("https://test.com/a").unwrap().id, job_id);
+        assert!(state.find_job_by_url("https://test.com/b").is_none());
+    }
+
     #[test]
     fn test_app_state_update_job_status() {
         let mut state = AppState::new();
@@ -497,6 +1563,11 @@ mod tests {
             current_step: "Downloading...".to_string(),
             total_steps: Some(5),
             current_step_index: Some(3),
+            speed_bytes_per_sec: None,
+            eta_seconds: None,
+            downloaded_bytes: None,
+            total_bytes: None,
+            playlist: None,
         };
         
         assert!(state.update_job_progress(&job_id, progress.clone()));
@@ -508,6 +1579,118 @@ mod tests {
         assert!(!state.update_job_progress("non-existent", progress));
     }
 
+    #[test]
+    fn test_app_state_reorder_job() {
+        let mut state = AppState::new();
+        let first = state.add_job("https://music.youtube.com/watch?v=1".to_string());
+        let second = state.add_job("https://music.youtube.com/watch?v=2".to_string());
+        let third = state.add_job("https://music.youtube.com/watch?v=3".to_string());
+
+        assert!(state.reorder_job(&third, 0));
+        let order: Vec<&str> = state.jobs.iter().map(|job| job.id.as_str()).collect();
+        assert_eq!(order, vec![third.as_str(), first.as_str(), second.as_str()]);
+
+        // Out of range indices move the job to the back
+        assert!(state.reorder_job(&third, 100));
+        assert_eq!(state.jobs.last().unwrap().id, third);
+
+        assert!(!state.reorder_job("non-existent", 0));
+    }
+
+    #[test]
+    fn test_app_state_pick_next_job_by_priority() {
+        let mut state = AppState::new();
+        let first = state.add_job("https://music.youtube.com/watch?v=1".to_string());
+        let second = state.add_job("https://music.youtube.com/watch?v=2".to_string());
+        let third = state.add_job("https://music.youtube.com/watch?v=3".to_string());
+        let candidates = [first.as_str(), second.as_str(), third.as_str()];
+
+        // FIFO when priorities are equal
+        assert_eq!(state.pick_next_job(&candidates), Some(first.clone()));
+
+        assert!(state.set_job_priority(&third, 5));
+        assert_eq!(state.pick_next_job(&candidates), Some(third.clone()));
+
+        // Only queued candidates are considered
+        state.update_job_status(&third, JobStatus::Downloading);
+        assert_eq!(state.pick_next_job(&candidates), Some(first.clone()));
+        assert_eq!(state.pick_next_job(&[second.as_str()]), Some(second.clone()));
+        assert_eq!(state.pick_next_job(&[]), None);
+    }
+
+    #[test]
+    fn test_app_state_pick_next_job_respects_schedule() {
+        let mut state = AppState::new();
+        let later = state.add_job("https://music.youtube.com/watch?v=1".to_string());
+        let now = state.add_job("https://music.youtube.com/watch?v=2".to_string());
+        let candidates = [later.as_str(), now.as_str()];
+
+        assert!(state.set_job_schedule(&later, Some(Utc::now() + chrono::Duration::hours(1))));
+        assert!(state.set_job_priority(&later, 10));
+
+        // Future jobs are skipped even with higher priority
+        assert_eq!(state.pick_next_job(&candidates), Some(now.clone()));
+
+        assert!(state.set_job_schedule(&later, Some(Utc::now() - chrono::Duration::minutes(1))));
+        assert_eq!(state.pick_next_job(&candidates), Some(later.clone()));
+    }
+
+    #[test]
+    fn test_app_state_pick_next_job_waits_on_dependencies() {
+        let mut state = AppState::new();
+        let album = state.add_job("https://test1.com".to_string());
+        let organize = state.add_job("https://test2.com".to_string());
+        state.set_job_dependencies(&organize, vec![album.clone()]).unwrap();
+        let organize_job = state.get_job(&organize).unwrap().clone();
+
+        assert_eq!(state.pick_next_job(&[organize.as_str()]), None);
+        assert_eq!(state.dependency_state(&organize_job), DependencyState::Waiting);
+
+        state.update_job_status(&album, JobStatus::Failed);
+        assert_eq!(state.dependency_state(&organize_job), DependencyState::Blocked(album.clone()));
+        assert_eq!(state.pick_next_job(&[organize.as_str()]), None);
+
+        // A dependency removed before completing blocks the job
+        state.remove_job(&album);
+        assert_eq!(state.dependency_state(&organize_job), DependencyState::Blocked(album.clone()));
+
+        // A completed one is released, so clearing it out afterwards is fine
+        let album = state.add_job("https://test3.com".to_string());
+        state.set_job_dependencies(&organize, vec![album.clone()]).unwrap();
+        state.update_job_status(&album, JobStatus::Completed);
+        assert!(state.get_job(&organize).unwrap().depends_on.is_empty());
+        state.remove_job(&album);
+        assert_eq!(state.pick_next_job(&[organize.as_str()]), Some(organize.clone()));
+    }
+
+    #[test]
+    fn test_app_state_set_job_dependencies_rejects_cycles() {
+        let mut state = AppState::new();
+        let first = state.add_job("https://test1.com".to_string());
+        let second = state.add_job("https://test2.com".to_string());
+        let third = state.add_job("https://test3.com".to_string());
+
+        assert!(state.set_job_dependencies(&second, vec![first.clone()]).is_ok());
+        assert!(state.set_job_dependencies(&third, vec![second.clone(), second.clone()]).is_ok());
+        assert_eq!(state.get_job(&third).unwrap().depends_on, vec![second.clone()]);
+
+        assert!(state.set_job_dependencies(&first, vec![third.clone()]).is_err());
+        assert!(state.set_job_dependencies(&first, vec![first.clone()]).is_err());
+        assert!(state.set_job_dependencies(&first, vec!["missing".to_string()]).is_err());
+        assert!(state.get_job(&first).unwrap().depends_on.is_empty());
+    }
+
+    #[test]
+    fn test_app_state_queue_start() {
+        let mut state = AppState::new();
+        let now = Utc::now();
+        assert!(state.is_queue_start_due(now));
+
+        state.queue_start_at = Some(now + chrono::Duration::hours(8));
+        assert!(!state.is_queue_start_due(now));
+        assert!(state.is_queue_start_due(now + chrono::Duration::hours(9)));
+    }
+
     #[test]
     fn test_app_state_update_job_metadata() {
         let mut state = AppState::new();
@@ -519,6 +1702,8 @@ mod tests {
             album: Some("Test Album".to_string()),
             duration: Some(180),
             thumbnail: Some("https://thumbnail.url".to_string()),
+            has_synced_lyrics: None,
+            track_count: None,
         };
         
         assert!(state.update_job_metadata(&job_id, metadata.clone()));
@@ -540,6 +1725,7 @@ mod tests {
         
         let job = state.get_job(&job_id).unwrap();
         assert_eq!(job.error, Some(error_msg));
+        assert_eq!(job.error_details.as_ref().map(|details| details.kind), Some(error_classifier::JobErrorKind::Unknown));
         assert_eq!(job.status, JobStatus::Failed);
         assert!(job.completed_at.is_some());
         
@@ -560,6 +1746,62 @@ mod tests {
         assert!(!state.remove_job("non-existent"));
     }
 
+    #[test]
+    fn test_app_state_trash_and_restore_job() {
+        let mut state = AppState::new();
+        let queued = state.add_job("https://test1.com".to_string());
+        let running = state.add_job("https://test2.com".to_string());
+        state.update_job_status(&running, JobStatus::Downloading);
+
+        assert!(state.trash_job(&queued));
+        assert!(state.trash_job(&running));
+        assert!(!state.trash_job(&queued));
+        assert_eq!(state.jobs.len(), 0);
+        assert_eq!(state.trash.len(), 2);
+        assert_eq!(state.trash[1].job.status, JobStatus::Cancelled);
+
+        assert_eq!(state.restore_job(&queued).unwrap().status, JobStatus::Queued);
+        assert!(state.restore_job(&queued).is_err());
+        assert_eq!(state.trash.len(), 1);
+
+        // Restoring is refused once the URL has been queued again
+        state.add_job("https://test2.com".to_string());
+        assert!(state.restore_job(&running).is_err());
+        assert_eq!(state.empty_trash(), 1);
+        assert!(state.trash.is_empty());
+    }
+
+    #[test]
+    fn test_app_state_purge_expired_trash() {
+        let mut state = AppState::new();
+        state.config.trash_retention_days = 7;
+        let old = state.add_job("https://test1.com".to_string());
+        let recent = state.add_job("https://test2.com".to_string());
+        state.trash_job(&old);
+        state.trash_job(&recent);
+        state.trash[0].removed_at = Utc::now() - chrono::Duration::days(8);
+
+        assert_eq!(state.purge_expired_trash(Utc::now()), 1);
+        assert_eq!(state.trash.len(), 1);
+        assert_eq!(state.trash[0].job.id, recent);
+    }
+
+    #[test]
+    fn test_app_state_recover_interrupted_jobs() {
+        let mut state = AppState::new();
+        let interrupted = state.add_job("https://test1.com".to_string());
+        let completed = state.add_job("https://test2.com".to_string());
+        state.update_job_status(&interrupted, JobStatus::Downloading);
+        state.update_job_status(&completed, JobStatus::Completed);
+
+        assert_eq!(state.recover_interrupted_jobs(), vec![interrupted.clone()]);
+        let job = state.get_job(&interrupted).unwrap();
+        assert_eq!(job.status, JobStatus::Queued);
+        assert_eq!(job.retry_count, 0);
+        assert_eq!(state.get_job(&completed).unwrap().status, JobStatus::Completed);
+        assert!(state.recover_interrupted_jobs().is_empty());
+    }
+
     #[test]
     fn test_app_state_get_jobs_by_status() {
         let mut state = AppState::new();
@@ -597,6 +1839,81 @@ mod tests {
         assert_eq!(state.count_jobs_by_status(&JobStatus::Downloading), 1);
     }
 
+    #[test]
+    fn test_app_state_queued_track_count() {
+        let mut state = AppState::new();
+        let album = state.add_job("https://music.youtube.com/playlist?list=OLAK5uy_abc".to_string());
+        state.add_job("https://music.youtube.com/watch?v=a".to_string());
+        assert_eq!(state.queued_track_count(), 2);
+
+        state.get_job_mut(&album).unwrap().metadata = Some(JobMetadata {
+            track_count: Some(12),
+            ..JobMetadata::default()
+        });
+        assert_eq!(state.queued_track_count(), 13);
+
+        state.update_job_status(&album, JobStatus::Downloading);
+        assert_eq!(state.queued_track_count(), 1);
+    }
+
+    #[test]
+    fn test_app_state_marks_job_changes() {
+        let mut state = AppState::new();
+        assert!(!state.take_changed());
+
+        let job_id = state.add_job("https://music.youtube.com/watch?v=a".to_string());
+        assert!(state.take_changed());
+        assert!(!state.take_changed());
+
+        state.update_job_status(&job_id, JobStatus::Downloading);
+        assert!(state.take_changed());
+    }
+
+    #[test]
+    fn test_app_state_group_progress() {
+        let mut state = AppState::new();
+        let group_id = state.add_group("https://music.youtube.com/playlist?list=abc".to_string(), None);
+        let first = state.add_job("https://music.youtube.com/watch?v=a".to_string());
+        let second = state.add_job("https://music.youtube.com/watch?v=b".to_string());
+        state.add_job("https://music.youtube.com/watch?v=other".to_string());
+        for job_id in [&first, &second] {
+            state.get_job_mut(job_id).unwrap().group_id = Some(group_id.clone());
+        }
+        state.update_job_status(&first, JobStatus::Completed);
+
+        let progress = state.group_progress(&group_id).unwrap();
+        assert_eq!(progress.total_jobs, 2);
+        assert_eq!(progress.percentage, 50.0);
+
+        state.remove_job(&first);
+        assert_eq!(state.group_summaries().len(), 1);
+        state.remove_job(&second);
+        assert!(state.group_summaries().is_empty());
+        assert!(state.groups.is_empty());
+    }
+
+    #[test]
+    fn test_app_state_restore_taken_jobs() {
+        let mut state = AppState::new();
+        let group_id = state.add_group("https://music.youtube.com/playlist?list=abc".to_string(), None);
+        let grouped = state.add_job("https://music.youtube.com/watch?v=a".to_string());
+        let queued = state.add_job("https://music.youtube.com/watch?v=b".to_string());
+        let single = state.add_job("https://music.youtube.com/watch?v=c".to_string());
+        state.get_job_mut(&grouped).unwrap().group_id = Some(group_id.clone());
+        for job_id in [&grouped, &single] {
+            state.update_job_status(job_id, JobStatus::Completed);
+        }
+
+        let taken = state.take_finished_jobs_over(0);
+        assert_eq!(taken.len(), 2);
+        assert!(state.groups.is_empty());
+
+        state.restore_jobs(taken);
+        let order: Vec<&str> = state.jobs.iter().map(|job| job.id.as_str()).collect();
+        assert_eq!(order, vec![grouped.as_str(), queued.as_str(), single.as_str()]);
+        assert!(state.get_group(&group_id).is_some());
+    }
+
     #[test]
     fn test_app_state_clear_completed_jobs() {
         let mut state = AppState::new();
@@ -720,6 +2037,8 @@ mod tests {
             album: Some("Test Album".to_string()),
             duration: Some(180),
             thumbnail: Some("https://thumbnail.url".to_string()),
+            has_synced_lyrics: None,
+            track_count: None,
         };
         
         let serialized = serde_json::to_string(&metadata).expect("Failed to serialize metadata");
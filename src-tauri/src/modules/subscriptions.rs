@@ -0,0 +1,424 @@
+use crate::modules::binary_locator::BinaryLocator;
+use crate::modules::process_runner::ProcessRunner;
+use crate::modules::sidecar_isolation::SidecarIsolation;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::process::Command;
+use uuid::Uuid;
+
+/// How often the background task looks for subscriptions that are due
+pub const POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Shortest polling interval a subscription may use
+pub const MIN_INTERVAL_MINUTES: u32 = 15;
+
+/// Longest wait before retrying a subscription whose checks keep failing,
+/// unless its own polling interval is shorter
+const MAX_RETRY_DELAY_MINUTES: i64 = 24 * 60;
+
+/// How long listing one artist or playlist may take
+const LISTING_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Makes the gytmdl sidecar run the yt-dlp it bundles instead of gytmdl,
+/// with the remaining arguments (see `build-scripts/sidecar_entry.py`)
+pub const YT_DLP_FLAG: &str = "--yt-dlp";
+
+/// One line per entry: id, URL and title, tab separated
+const LISTING_FORMAT: &str = "%(id)s\t%(url)s\t%(title)s";
+
+#[derive(Debug)]
+pub enum SubscriptionError {
+    InvalidInterval(u32),
+    NotFound(String),
+    ListingUnavailable,
+    ListingFailed(String),
+    StorageError(io::Error),
+}
+
+impl std::fmt::Display for SubscriptionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SubscriptionError::InvalidInterval(minutes) => write!(
+                f,
+                "Polling interval must be at least {} minutes, got {}",
+                MIN_INTERVAL_MINUTES, minutes
+            ),
+            SubscriptionError::NotFound(id) => write!(f, "Subscription not found: {}", id),
+            SubscriptionError::ListingUnavailable => {
+                write!(f, "The gytmdl sidecar was not found, so artists and playlists can't be listed")
+            }
+            SubscriptionError::ListingFailed(msg) => write!(f, "Failed to list new releases: {}", msg),
+            SubscriptionError::StorageError(e) => write!(f, "Failed to store subscriptions: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SubscriptionError {}
+
+/// An artist, channel or playlist URL that is checked for new tracks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Subscription {
+    pub id: String,
+    pub url: String,
+    pub name: Option<String>,
+    pub interval_minutes: u32,
+    pub created_at: DateTime<Utc>,
+    pub last_checked_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    /// IDs of every entry found by earlier checks; anything else is new.
+    /// Never trimmed, or forgotten entries would be queued again.
+    #[serde(default)]
+    seen_ids: HashSet<String>,
+    /// Checks that failed in a row since the last successful one
+    #[serde(default)]
+    consecutive_failures: u32,
+    #[serde(default)]
+    last_failed_at: Option<DateTime<Utc>>,
+}
+
+impl Subscription {
+    /// Whether the polling interval has passed since the last check, and
+    /// the backoff since the last failed one
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        if let Some(failed_at) = self.last_failed_at {
+            if now - failed_at < self.retry_delay() {
+                return false;
+            }
+        }
+        match self.last_checked_at {
+            None => true,
+            Some(last) => now - last >= ChronoDuration::minutes(self.interval_minutes as i64),
+        }
+    }
+
+    /// 1, 2, 4... minutes after each failed check, so a subscription that
+    /// never got its baseline isn't retried on every poll forever
+    fn retry_delay(&self) -> ChronoDuration {
+        let doublings = self.consecutive_failures.saturating_sub(1).min(16);
+        let minutes = (1i64 << doublings)
+            .min(MAX_RETRY_DELAY_MINUTES)
+            .min(self.interval_minutes as i64);
+        ChronoDuration::minutes(minutes)
+    }
+}
+
+/// Public view of a subscription returned to the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubscriptionInfo {
+    pub id: String,
+    pub url: String,
+    pub name: Option<String>,
+    pub interval_minutes: u32,
+    pub created_at: DateTime<Utc>,
+    pub last_checked_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub seen_entries: usize,
+}
+
+impl From<&Subscription> for SubscriptionInfo {
+    fn from(subscription: &Subscription) -> Self {
+        Self {
+            id: subscription.id.clone(),
+            url: subscription.url.clone(),
+            name: subscription.name.clone(),
+            interval_minutes: subscription.interval_minutes,
+            created_at: subscription.created_at,
+            last_checked_at: subscription.last_checked_at,
+            last_error: subscription.last_error.clone(),
+            seen_entries: subscription.seen_ids.len(),
+        }
+    }
+}
+
+/// A track or release found when listing a subscription
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ListedEntry {
+    pub id: String,
+    pub url: String,
+    pub title: Option<String>,
+}
+
+/// Persistent list of subscriptions
+pub struct SubscriptionStore {
+    subscriptions_file: PathBuf,
+    subscriptions: Vec<Subscription>,
+}
+
+impl SubscriptionStore {
+    /// Load subscriptions from the app data directory
+    pub fn new() -> Self {
+        let subscriptions_file = std::env::current_dir()
+            .unwrap_or_else(|_| PathBuf::from("."))
+            .join(".gytmdl-gui")
+            .join("subscriptions.json");
+
+        Self::with_subscriptions_file(subscriptions_file)
+    }
+
+    /// Load subscriptions from a specific file. A missing or unreadable file starts empty.
+    pub fn with_subscriptions_file(subscriptions_file: PathBuf) -> Self {
+        let subscriptions = fs::read_to_string(&subscriptions_file)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+
+        Self { subscriptions_file, subscriptions }
+    }
+
+    fn save(&self) -> Result<(), SubscriptionError> {
+        if let Some(parent) = self.subscriptions_file.parent() {
+            fs::create_dir_all(parent).map_err(SubscriptionError::StorageError)?;
+        }
+        let content = serde_json::to_string_pretty(&self.subscriptions)
+            .map_err(|e| SubscriptionError::StorageError(io::Error::new(io::ErrorKind::InvalidData, e)))?;
+        fs::write(&self.subscriptions_file, content).map_err(SubscriptionError::StorageError)
+    }
+
+    /// Subscribe to a URL. Its first check only records what already exists,
+    /// so only releases published afterwards are queued.
+    pub fn add(&mut self, url: &str, name: Option<String>, interval_minutes: u32) -> Result<SubscriptionInfo, SubscriptionError> {
+        if interval_minutes < MIN_INTERVAL_MINUTES {
+            return Err(SubscriptionError::InvalidInterval(interval_minutes));
+        }
+
+        let subscription = Subscription {
+            id: Uuid::new_v4().to_string(),
+            url: url.to_string(),
+            name: name.filter(|n| !n.trim().is_empty()),
+            interval_minutes,
+            created_at: Utc::now(),
+            last_checked_at: None,
+            last_error: None,
+            seen_ids: HashSet::new(),
+            consecutive_failures: 0,
+            last_failed_at: None,
+        };
+
+        let info = SubscriptionInfo::from(&subscription);
+        self.subscriptions.push(subscription);
+        self.save()?;
+        Ok(info)
+    }
+
+    pub fn list(&self) -> Vec<SubscriptionInfo> {
+        self.subscriptions.iter().map(SubscriptionInfo::from).collect()
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Subscription> {
+        self.subscriptions.iter().find(|subscription| subscription.id == id)
+    }
+
+    /// Remove a subscription by ID. Returns false if it didn't exist.
+    pub fn remove(&mut self, id: &str) -> Result<bool, SubscriptionError> {
+        let initial_len = self.subscriptions.len();
+        self.subscriptions.retain(|subscription| subscription.id != id);
+        if self.subscriptions.len() == initial_len {
+            return Ok(false);
+        }
+        self.save()?;
+        Ok(true)
+    }
+
+    /// Subscriptions whose polling interval has passed
+    pub fn due(&self, now: DateTime<Utc>) -> Vec<Subscription> {
+        self.subscriptions.iter().filter(|s| s.is_due(now)).cloned().collect()
+    }
+
+    pub fn all(&self) -> Vec<Subscription> {
+        self.subscriptions.clone()
+    }
+
+    /// Record a successful listing and return the entries not seen before.
+    /// The first listing of a subscription only establishes the baseline.
+    pub fn record_listing(
+        &mut self,
+        id: &str,
+        entries: &[ListedEntry],
+        now: DateTime<Utc>,
+    ) -> Result<Vec<ListedEntry>, SubscriptionError> {
+        let subscription = self.subscriptions.iter_mut()
+            .find(|subscription| subscription.id == id)
+            .ok_or_else(|| SubscriptionError::NotFound(id.to_string()))?;
+
+        let is_baseline = subscription.last_checked_at.is_none();
+        let mut new_entries = Vec::new();
+        for entry in entries {
+            if subscription.seen_ids.insert(entry.id.clone()) && !is_baseline {
+                new_entries.push(entry.clone());
+            }
+        }
+
+        subscription.last_checked_at = Some(now);
+        subscription.last_error = None;
+        subscription.consecutive_failures = 0;
+        subscription.last_failed_at = None;
+
+        self.save()?;
+        Ok(new_entries)
+    }
+
+    /// Record a failed check; it is retried after the normal interval, or
+    /// after a growing backoff while it has no baseline yet
+    pub fn record_error(&mut self, id: &str, error: &str, now: DateTime<Utc>) -> Result<(), SubscriptionError> {
+        let subscription = self.subscriptions.iter_mut()
+            .find(|subscription| subscription.id == id)
+            .ok_or_else(|| SubscriptionError::NotFound(id.to_string()))?;
+
+        subscription.last_error = Some(error.to_string());
+        subscription.consecutive_failures += 1;
+        subscription.last_failed_at = Some(now);
+        // Without a baseline the next successful check would queue everything
+        if subscription.last_checked_at.is_some() {
+            subscription.last_checked_at = Some(now);
+        }
+        self.save()
+    }
+}
+
+impl Default for SubscriptionStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// List the entries of an artist, channel or playlist with yt-dlp's flat
+/// playlist mode, run through the gytmdl sidecar, which bundles yt-dlp
+pub async fn list_entries(isolation: &SidecarIsolation, url: &str) -> Result<Vec<ListedEntry>, SubscriptionError> {
+    let locator = BinaryLocator::detect().map_err(|_| SubscriptionError::ListingUnavailable)?;
+    let runner = ProcessRunner::new(&locator);
+    runner.verify_binary().await
+        .map_err(|e| SubscriptionError::ListingFailed(e.to_string()))?;
+    let sidecar = runner.get_binary_path();
+
+    let mut command = Command::new(sidecar);
+    command
+        .args([YT_DLP_FLAG, "--ignore-config", "--flat-playlist", "--no-warnings", "--print", LISTING_FORMAT])
+        .arg(url)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .stdin(Stdio::null())
+        .kill_on_drop(true);
+    isolation.apply_to_command(&mut command, false)
+        .map_err(|e| SubscriptionError::ListingFailed(format!("Failed to prepare sidecar environment: {}", e)))?;
+
+    let output = tokio::time::timeout(LISTING_TIMEOUT, command.output()).await
+        .map_err(|_| SubscriptionError::ListingFailed(format!("Timed out listing {}", url)))?
+        .map_err(|e| SubscriptionError::ListingFailed(format!("Failed to run {:?}: {}", sidecar, e)))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(SubscriptionError::ListingFailed(stderr.lines().last().unwrap_or("").trim().to_string()));
+    }
+
+    Ok(parse_listing(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parse `--print` output in [`LISTING_FORMAT`]. yt-dlp prints `NA` for missing fields.
+pub fn parse_listing(output: &str) -> Vec<ListedEntry> {
+    let present = |value: Option<&str>| value
+        .map(str::trim)
+        .filter(|v| !v.is_empty() && *v != "NA")
+        .map(str::to_string);
+
+    output.lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let id = present(fields.next())?;
+            let url = present(fields.next())
+                .unwrap_or_else(|| format!("https://music.youtube.com/watch?v={}", id));
+            let title = present(fields.next());
+            Some(ListedEntry { id, url, title })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn entry(id: &str) -> ListedEntry {
+        ListedEntry {
+            id: id.to_string(),
+            url: format!("https://www.youtube.com/watch?v={}", id),
+            title: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_listing() {
+        let output = "abc\thttps://www.youtube.com/watch?v=abc\tSong\ndef\tNA\tNA\n\nNA\tNA\tNA\n";
+        let entries = parse_listing(output);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].title.as_deref(), Some("Song"));
+        assert_eq!(entries[1].url, "https://music.youtube.com/watch?v=def");
+        assert_eq!(entries[1].title, None);
+    }
+
+    #[test]
+    fn test_first_listing_is_baseline() {
+        let temp_dir = tempdir().unwrap();
+        let file = temp_dir.path().join("subscriptions.json");
+        let mut store = SubscriptionStore::with_subscriptions_file(file.clone());
+        assert!(matches!(store.add("https://music.youtube.com/channel/x", None, 5), Err(SubscriptionError::InvalidInterval(5))));
+
+        let info = store.add("https://music.youtube.com/channel/x", Some("Artist".to_string()), 60).unwrap();
+        let now = Utc::now();
+        assert_eq!(store.due(now).len(), 1);
+
+        let new_entries = store.record_listing(&info.id, &[entry("a"), entry("b")], now).unwrap();
+        assert!(new_entries.is_empty());
+        assert!(store.due(now + ChronoDuration::minutes(30)).is_empty());
+
+        let new_entries = store.record_listing(&info.id, &[entry("a"), entry("b"), entry("c")], now).unwrap();
+        assert_eq!(new_entries, vec![entry("c")]);
+
+        // Persisted across reloads
+        let reloaded = SubscriptionStore::with_subscriptions_file(file);
+        assert_eq!(reloaded.list()[0].seen_entries, 3);
+    }
+
+    #[test]
+    fn test_large_listing_is_not_requeued() {
+        let temp_dir = tempdir().unwrap();
+        let mut store = SubscriptionStore::with_subscriptions_file(temp_dir.path().join("subscriptions.json"));
+        let info = store.add("https://music.youtube.com/channel/x", None, 60).unwrap();
+
+        let entries: Vec<ListedEntry> = (0..6000).map(|i| entry(&i.to_string())).collect();
+        let now = Utc::now();
+        store.record_listing(&info.id, &entries, now).unwrap();
+        assert!(store.record_listing(&info.id, &entries, now).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_failed_baseline_backs_off() {
+        let temp_dir = tempdir().unwrap();
+        let mut store = SubscriptionStore::with_subscriptions_file(temp_dir.path().join("subscriptions.json"));
+        let info = store.add("https://music.youtube.com/channel/x", None, 60).unwrap();
+        let now = Utc::now();
+
+        store.record_error(&info.id, "offline", now).unwrap();
+        assert!(store.due(now).is_empty());
+        assert_eq!(store.due(now + ChronoDuration::minutes(1)).len(), 1);
+
+        store.record_error(&info.id, "offline", now).unwrap();
+        store.record_error(&info.id, "offline", now).unwrap();
+        assert!(store.due(now + ChronoDuration::minutes(3)).is_empty());
+        assert_eq!(store.due(now + ChronoDuration::minutes(4)).len(), 1);
+
+        // Capped at the polling interval
+        for _ in 0..10 {
+            store.record_error(&info.id, "offline", now).unwrap();
+        }
+        assert_eq!(store.due(now + ChronoDuration::minutes(60)).len(), 1);
+
+        store.record_listing(&info.id, &[entry("a")], now).unwrap();
+        assert_eq!(store.get(&info.id).unwrap().consecutive_failures, 0);
+    }
+}
@@ -0,0 +1,181 @@
+use lofty::config::WriteOptions;
+use lofty::prelude::*;
+use lofty::probe::Probe;
+use lofty::tag::Tag;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug)]
+pub enum TaggingError {
+    FileNotFound(PathBuf),
+    ReadFailed(String),
+    WriteFailed(String),
+}
+
+impl fmt::Display for TaggingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TaggingError::FileNotFound(path) => write!(f, "File not found: {}", path.display()),
+            TaggingError::ReadFailed(msg) => write!(f, "Failed to read tags: {}", msg),
+            TaggingError::WriteFailed(msg) => write!(f, "Failed to write tags: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for TaggingError {}
+
+/// The tags the editor shows. Anything else in the file (lyrics, cover,
+/// ReplayGain) is left alone when these are written.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct TrackTags {
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub album_artist: Option<String>,
+    pub track_number: Option<u32>,
+    pub track_total: Option<u32>,
+    pub disc_number: Option<u32>,
+    pub year: Option<u32>,
+    pub genre: Option<String>,
+}
+
+impl TrackTags {
+    pub fn from_tag(tag: &Tag) -> Self {
+        Self {
+            title: tag.title().map(|value| value.into_owned()),
+            artist: tag.artist().map(|value| value.into_owned()),
+            album: tag.album().map(|value| value.into_owned()),
+            album_artist: tag.get_string(&ItemKey::AlbumArtist).map(str::to_string),
+            track_number: tag.track(),
+            track_total: tag.track_total(),
+            disc_number: tag.disk(),
+            year: tag.year(),
+            genre: tag.genre().map(|value| value.into_owned()),
+        }
+    }
+
+    /// Set every field on `tag`; unset and blank fields are removed from it
+    pub fn apply_to(&self, tag: &mut Tag) {
+        fn text(value: &Option<String>) -> Option<String> {
+            value.as_deref().map(str::trim).filter(|value| !value.is_empty()).map(str::to_string)
+        }
+
+        match text(&self.title) {
+            Some(title) => tag.set_title(title),
+            None => tag.remove_title(),
+        }
+        match text(&self.artist) {
+            Some(artist) => tag.set_artist(artist),
+            None => tag.remove_artist(),
+        }
+        match text(&self.album) {
+            Some(album) => tag.set_album(album),
+            None => tag.remove_album(),
+        }
+        match text(&self.album_artist) {
+            Some(album_artist) => {
+                tag.insert_text(ItemKey::AlbumArtist, album_artist);
+            }
+            None => tag.remove_key(&ItemKey::AlbumArtist),
+        }
+        match self.track_number {
+            Some(track) => tag.set_track(track),
+            None => tag.remove_track(),
+        }
+        match self.track_total {
+            Some(total) => tag.set_track_total(total),
+            None => tag.remove_track_total(),
+        }
+        match self.disc_number {
+            Some(disk) => tag.set_disk(disk),
+            None => tag.remove_disk(),
+        }
+        match self.year {
+            Some(year) => tag.set_year(year),
+            None => tag.remove_year(),
+        }
+        match text(&self.genre) {
+            Some(genre) => tag.set_genre(genre),
+            None => tag.remove_genre(),
+        }
+    }
+}
+
+/// Read a file's tags; a file without any tag reads as empty
+pub fn read_tags(path: &Path) -> Result<TrackTags, TaggingError> {
+    if !path.is_file() {
+        return Err(TaggingError::FileNotFound(path.to_path_buf()));
+    }
+    let tagged_file = Probe::open(path)
+        .and_then(|probe| probe.read())
+        .map_err(|e| TaggingError::ReadFailed(e.to_string()))?;
+
+    Ok(tagged_file.primary_tag()
+        .or_else(|| tagged_file.first_tag())
+        .map(TrackTags::from_tag)
+        .unwrap_or_default())
+}
+
+/// Write `tags` into the file's primary tag, creating the tag if the file has none
+pub fn write_tags(path: &Path, tags: &TrackTags) -> Result<(), TaggingError> {
+    if !path.is_file() {
+        return Err(TaggingError::FileNotFound(path.to_path_buf()));
+    }
+    let mut tagged_file = Probe::open(path)
+        .and_then(|probe| probe.read())
+        .map_err(|e| TaggingError::ReadFailed(e.to_string()))?;
+
+    let mut tag = match tagged_file.primary_tag_mut() {
+        Some(tag) => tag.clone(),
+        None => Tag::new(tagged_file.primary_tag_type()),
+    };
+    tags.apply_to(&mut tag);
+    tag.save_to_path(path, WriteOptions::default())
+        .map_err(|e| TaggingError::WriteFailed(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lofty::tag::TagType;
+
+    #[test]
+    fn test_tags_round_trip_through_a_tag() {
+        let tags = TrackTags {
+            title: Some("Song".to_string()),
+            artist: Some("Artist".to_string()),
+            album: Some("Album".to_string()),
+            album_artist: Some("Various Artists".to_string()),
+            track_number: Some(3),
+            track_total: Some(12),
+            disc_number: Some(1),
+            year: Some(2020),
+            genre: Some("Pop".to_string()),
+        };
+        let mut tag = Tag::new(TagType::Mp4Ilst);
+        tags.apply_to(&mut tag);
+        assert_eq!(TrackTags::from_tag(&tag), tags);
+
+        // Cleared and blank fields are removed
+        let edited = TrackTags {
+            album_artist: None,
+            genre: Some("  ".to_string()),
+            track_total: None,
+            ..tags.clone()
+        };
+        edited.apply_to(&mut tag);
+        let read_back = TrackTags::from_tag(&tag);
+        assert_eq!(read_back.album_artist, None);
+        assert_eq!(read_back.genre, None);
+        assert_eq!(read_back.track_total, None);
+        assert_eq!(read_back.track_number, Some(3));
+    }
+
+    #[test]
+    fn test_missing_file() {
+        let path = Path::new("/nonexistent/song.m4a");
+        assert!(matches!(read_tags(path), Err(TaggingError::FileNotFound(_))));
+        assert!(matches!(write_tags(path, &TrackTags::default()), Err(TaggingError::FileNotFound(_))));
+    }
+}
@@ -0,0 +1,224 @@
+use crate::modules::debug_logger::DEBUG_LOGGER;
+use crate::modules::events::EventEmitter;
+use crate::modules::state::AppState;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+/// How often the temp folder is re-scanned after the startup scan
+const SCAN_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Extensions yt-dlp gives partial downloads. Generic ones like `tmp` are
+/// left alone, since the temp folder may be shared with other programs.
+const PARTIAL_EXTENSIONS: &[&str] = &["part", "ytdl"];
+
+/// A leftover job folder or partial file
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StaleTempEntry {
+    pub path: PathBuf,
+    pub bytes: u64,
+    /// Newest modification time of anything in it
+    pub modified: DateTime<Utc>,
+}
+
+/// Space used by the temp folder and how much of it can be reclaimed
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct TempUsage {
+    pub temp_path: PathBuf,
+    pub total_bytes: u64,
+    pub reclaimable_bytes: u64,
+    pub stale_entries: Vec<StaleTempEntry>,
+}
+
+/// Result of deleting stale temp entries
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct TempCleanup {
+    pub removed_entries: usize,
+    pub freed_bytes: u64,
+}
+
+/// Total size and newest modification time of a file or folder
+fn measure(path: &Path) -> (u64, SystemTime) {
+    let mut bytes = 0;
+    let mut newest = SystemTime::UNIX_EPOCH;
+    let mut pending = vec![path.to_path_buf()];
+
+    while let Some(current) = pending.pop() {
+        let Ok(metadata) = fs::symlink_metadata(&current) else {
+            continue;
+        };
+        if let Ok(modified) = metadata.modified() {
+            newest = newest.max(modified);
+        }
+        if metadata.is_dir() {
+            if let Ok(entries) = fs::read_dir(&current) {
+                pending.extend(entries.flatten().map(|entry| entry.path()));
+            }
+        } else {
+            bytes += metadata.len();
+        }
+    }
+
+    (bytes, newest)
+}
+
+/// Whether gytmdl-gui could have left this entry behind: a per-job folder
+/// (named after a job id) or a partial download. Anything else in a shared
+/// temp folder is left alone.
+fn is_ours(path: &Path, is_dir: bool) -> bool {
+    if is_dir {
+        return path.file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| Uuid::parse_str(name).is_ok());
+    }
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| PARTIAL_EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str()))
+}
+
+/// Scan `temp_path` for leftovers untouched for `max_age` and not belonging
+/// to any job in `kept_job_ids`
+pub fn scan(temp_path: &Path, kept_job_ids: &HashSet<String>, max_age: Duration, now: SystemTime) -> TempUsage {
+    let mut usage = TempUsage {
+        temp_path: temp_path.to_path_buf(),
+        ..TempUsage::default()
+    };
+    let Ok(entries) = fs::read_dir(temp_path) else {
+        return usage;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let (bytes, modified) = measure(&path);
+        usage.total_bytes += bytes;
+
+        let belongs_to_kept_job = path.file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| kept_job_ids.contains(name));
+        let is_stale = now.duration_since(modified).is_ok_and(|age| age >= max_age);
+        if is_ours(&path, file_type.is_dir()) && !belongs_to_kept_job && is_stale {
+            usage.reclaimable_bytes += bytes;
+            usage.stale_entries.push(StaleTempEntry { path, bytes, modified: modified.into() });
+        }
+    }
+
+    usage.stale_entries.sort_by(|a, b| a.path.cmp(&b.path));
+    usage
+}
+
+/// Delete the stale entries of a scan. Entries that can't be deleted are logged and skipped.
+pub fn clean(usage: &TempUsage) -> TempCleanup {
+    let mut cleanup = TempCleanup::default();
+    for entry in &usage.stale_entries {
+        let removed = if entry.path.is_dir() {
+            fs::remove_dir_all(&entry.path)
+        } else {
+            fs::remove_file(&entry.path)
+        };
+        match removed {
+            Ok(()) => {
+                cleanup.removed_entries += 1;
+                cleanup.freed_bytes += entry.bytes;
+            }
+            Err(e) => DEBUG_LOGGER.warn("temp", format!("Failed to delete {:?}: {}", entry.path, e)),
+        }
+    }
+    cleanup
+}
+
+/// Scan the configured temp folder, skipping the folders of queued and
+/// running jobs. With `keep_failed_temp_dirs` on, the folders of every job
+/// still in the queue are kept, so a retry can pick up its partials.
+pub async fn scan_state(state: &Arc<RwLock<AppState>>) -> TempUsage {
+    let (temp_path, kept_job_ids, max_age) = {
+        let state_guard = state.read().await;
+        let keep_finished = state_guard.config.keep_failed_temp_dirs;
+        let kept_job_ids: HashSet<String> = state_guard.jobs.iter()
+            .filter(|job| keep_finished || !job.is_terminal())
+            .map(|job| job.id.clone())
+            .collect();
+        let max_age = Duration::from_secs(state_guard.config.stale_temp_age_hours as u64 * 60 * 60);
+        (state_guard.config.temp_path.clone(), kept_job_ids, max_age)
+    };
+
+    tokio::task::spawn_blocking(move || scan(&temp_path, &kept_job_ids, max_age, SystemTime::now()))
+        .await
+        .unwrap_or_default()
+}
+
+/// Scan the temp folder at startup and every hour after. Stale entries are
+/// deleted when `auto_clean_stale_temp` is on; otherwise a `temp-usage`
+/// event lets the user confirm. Runs for the lifetime of the app.
+pub async fn run(state: Arc<RwLock<AppState>>, events: EventEmitter) {
+    let mut ticker = tokio::time::interval(SCAN_INTERVAL);
+
+    loop {
+        ticker.tick().await;
+
+        let mut usage = scan_state(&state).await;
+        if usage.stale_entries.is_empty() {
+            continue;
+        }
+
+        if state.read().await.config.auto_clean_stale_temp {
+            let cleanup = tokio::task::spawn_blocking({
+                let usage = usage.clone();
+                move || clean(&usage)
+            }).await.unwrap_or_default();
+            DEBUG_LOGGER.info("temp", format!(
+                "Deleted {} stale temp entries, freeing {} bytes", cleanup.removed_entries, cleanup.freed_bytes
+            ));
+            usage = scan_state(&state).await;
+        }
+        events.temp_usage(&usage);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_scan_skips_active_fresh_and_foreign_entries() {
+        let temp_dir = tempdir().unwrap();
+        let stale_job = Uuid::new_v4().to_string();
+        let active_job = Uuid::new_v4().to_string();
+        fs::create_dir_all(temp_dir.path().join(&stale_job).join("nested")).unwrap();
+        fs::write(temp_dir.path().join(&stale_job).join("nested").join("a.part"), b"abcd").unwrap();
+        fs::create_dir(temp_dir.path().join(&active_job)).unwrap();
+        fs::write(temp_dir.path().join(&active_job).join("b.part"), b"ab").unwrap();
+        fs::write(temp_dir.path().join("track.webm.part"), b"abc").unwrap();
+        fs::write(temp_dir.path().join("notes.txt"), b"a").unwrap();
+        fs::write(temp_dir.path().join("editor.tmp"), b"a").unwrap();
+
+        let active = HashSet::from([active_job.clone()]);
+        let later = SystemTime::now() + Duration::from_secs(2 * 60 * 60);
+
+        // Everything was just written, so nothing is stale yet
+        let usage = scan(temp_dir.path(), &active, Duration::from_secs(60 * 60), SystemTime::now());
+        assert_eq!(usage.total_bytes, 11);
+        assert!(usage.stale_entries.is_empty());
+
+        let usage = scan(temp_dir.path(), &active, Duration::from_secs(60 * 60), later);
+        let stale: Vec<_> = usage.stale_entries.iter().map(|entry| entry.path.clone()).collect();
+        assert_eq!(stale, vec![temp_dir.path().join(&stale_job), temp_dir.path().join("track.webm.part")]);
+        assert_eq!(usage.reclaimable_bytes, 7);
+
+        let cleanup = clean(&usage);
+        assert_eq!(cleanup, TempCleanup { removed_entries: 2, freed_bytes: 7 });
+        assert!(!temp_dir.path().join(&stale_job).exists());
+        assert!(temp_dir.path().join(&active_job).exists());
+        assert!(temp_dir.path().join("notes.txt").exists());
+        assert!(temp_dir.path().join("editor.tmp").exists());
+    }
+}
@@ -0,0 +1,309 @@
+use chrono::format::{Item, StrftimeItems};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Tags gytmdl fills in for `--template-folder` and `--template-file`
+pub const SUPPORTED_PLACEHOLDERS: &[&str] = &[
+    "album",
+    "album_artist",
+    "artist",
+    "comment",
+    "date",
+    "media_type",
+    "rating",
+    "title",
+    "track",
+    "track_total",
+];
+
+/// Placeholders holding numbers, which accept an integer format spec such as `:02d`.
+/// The others hold text and accept a precision such as `:.50` to truncate it.
+const NUMERIC_PLACEHOLDERS: &[&str] = &["media_type", "rating", "track", "track_total"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemplateError {
+    Empty,
+    UnclosedPlaceholder(String),
+    UnmatchedBrace(String),
+    UnknownPlaceholder(String),
+    InvalidFormatSpec { placeholder: String, spec: String },
+    InvalidDateFormat(String),
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TemplateError::Empty => write!(f, "Template cannot be empty"),
+            TemplateError::UnclosedPlaceholder(t) => write!(f, "Unclosed placeholder in template: '{}'", t),
+            TemplateError::UnmatchedBrace(t) => write!(f, "Unmatched '}}' in template: '{}' (use '}}}}' for a literal brace)", t),
+            TemplateError::UnknownPlaceholder(name) => write!(
+                f,
+                "Unknown template placeholder '{{{}}}'; supported placeholders are {}",
+                name,
+                SUPPORTED_PLACEHOLDERS.join(", ")
+            ),
+            TemplateError::InvalidFormatSpec { placeholder, spec } => write!(
+                f,
+                "Unsupported format '{}' for placeholder '{{{}}}'",
+                spec, placeholder
+            ),
+            TemplateError::InvalidDateFormat(format) => write!(f, "Invalid date format: '{}'", format),
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+/// One piece of a parsed template
+#[derive(Debug, Clone, PartialEq)]
+pub enum Segment {
+    Literal(String),
+    Placeholder { name: String, spec: Option<String> },
+}
+
+/// Tag values used to render a template
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SampleMetadata {
+    pub album: String,
+    pub album_artist: String,
+    pub artist: String,
+    pub comment: String,
+    pub date: NaiveDate,
+    pub media_type: u32,
+    pub rating: u32,
+    pub title: String,
+    pub track: u32,
+    pub track_total: u32,
+}
+
+impl Default for SampleMetadata {
+    fn default() -> Self {
+        Self {
+            album: "Random Access Memories".to_string(),
+            album_artist: "Daft Punk".to_string(),
+            artist: "Daft Punk".to_string(),
+            comment: "https://music.youtube.com/watch?v=5NV6Rdv1a3I".to_string(),
+            date: NaiveDate::from_ymd_opt(2013, 5, 17).expect("valid date"),
+            media_type: 1,
+            rating: 0,
+            title: "Get Lucky".to_string(),
+            track: 8,
+            track_total: 13,
+        }
+    }
+}
+
+/// Example output of the folder and file templates
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplatePreview {
+    pub folder: String,
+    pub file: String,
+    /// Full path of the example download inside the output folder
+    pub path: PathBuf,
+}
+
+/// Split a template into literals and placeholders. Follows Python's
+/// `str.format` syntax, which gytmdl uses: `{name}`, `{name:spec}`, and
+/// `{{`/`}}` for literal braces.
+pub fn parse(template: &str) -> Result<Vec<Segment>, TemplateError> {
+    if template.trim().is_empty() {
+        return Err(TemplateError::Empty);
+    }
+
+    let mut segments = Vec::new();
+    let mut literal = String::new();
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                literal.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                literal.push('}');
+            }
+            '}' => return Err(TemplateError::UnmatchedBrace(template.to_string())),
+            '{' => {
+                let mut field = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => field.push(c),
+                        None => return Err(TemplateError::UnclosedPlaceholder(template.to_string())),
+                    }
+                }
+
+                let (name, spec) = match field.split_once(':') {
+                    Some((name, spec)) => (name, Some(spec.to_string())),
+                    None => (field.as_str(), None),
+                };
+                if !SUPPORTED_PLACEHOLDERS.contains(&name) {
+                    return Err(TemplateError::UnknownPlaceholder(name.to_string()));
+                }
+                if let Some(spec) = &spec {
+                    let valid = if NUMERIC_PLACEHOLDERS.contains(&name) {
+                        parse_integer_spec(spec).is_some()
+                    } else {
+                        parse_precision_spec(spec).is_some()
+                    };
+                    if !valid {
+                        return Err(TemplateError::InvalidFormatSpec {
+                            placeholder: name.to_string(),
+                            spec: spec.clone(),
+                        });
+                    }
+                }
+
+                if !literal.is_empty() {
+                    segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                }
+                segments.push(Segment::Placeholder { name: name.to_string(), spec });
+            }
+            c => literal.push(c),
+        }
+    }
+
+    if !literal.is_empty() {
+        segments.push(Segment::Literal(literal));
+    }
+    Ok(segments)
+}
+
+/// Check a strftime-style `template_date` such as `%Y-%m-%d`
+pub fn validate_date_format(format: &str) -> Result<(), TemplateError> {
+    if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+        return Err(TemplateError::InvalidDateFormat(format.to_string()));
+    }
+    Ok(())
+}
+
+/// Render a template with sample tag values
+pub fn render(template: &str, date_format: &str, metadata: &SampleMetadata) -> Result<String, TemplateError> {
+    validate_date_format(date_format)?;
+
+    let mut rendered = String::new();
+    for segment in parse(template)? {
+        match segment {
+            Segment::Literal(text) => rendered.push_str(&text),
+            Segment::Placeholder { name, spec } => {
+                let number = match name.as_str() {
+                    "media_type" => Some(metadata.media_type),
+                    "rating" => Some(metadata.rating),
+                    "track" => Some(metadata.track),
+                    "track_total" => Some(metadata.track_total),
+                    _ => None,
+                };
+                let value = match (number, spec.as_deref().and_then(parse_integer_spec)) {
+                    (Some(n), Some((zero_pad, width))) if zero_pad => format!("{:0width$}", n, width = width),
+                    (Some(n), Some((_, width))) => format!("{:>width$}", n, width = width),
+                    (Some(n), None) => n.to_string(),
+                    (None, _) => {
+                        let text = match name.as_str() {
+                            "album" => metadata.album.clone(),
+                            "album_artist" => metadata.album_artist.clone(),
+                            "artist" => metadata.artist.clone(),
+                            "comment" => metadata.comment.clone(),
+                            "date" => metadata.date.format(date_format).to_string(),
+                            "title" => metadata.title.clone(),
+                            _ => String::new(),
+                        };
+                        match spec.as_deref().and_then(parse_precision_spec) {
+                            Some(precision) => text.chars().take(precision).collect(),
+                            None => text,
+                        }
+                    }
+                };
+                rendered.push_str(&value);
+            }
+        }
+    }
+    Ok(rendered)
+}
+
+/// Render the folder and file templates into an example download path
+pub fn preview(
+    output_path: &Path,
+    template_folder: &str,
+    template_file: &str,
+    template_date: &str,
+    metadata: &SampleMetadata,
+) -> Result<TemplatePreview, TemplateError> {
+    let folder = render(template_folder, template_date, metadata)?;
+    let file = render(template_file, template_date, metadata)?;
+    let path = output_path.join(&folder).join(format!("{}.m4a", file));
+    Ok(TemplatePreview { folder, file, path })
+}
+
+/// `02d` -> (zero padded, width 2); `d` -> (false, 0). Only integer specs are supported.
+fn parse_integer_spec(spec: &str) -> Option<(bool, usize)> {
+    let digits = spec.strip_suffix('d')?;
+    if digits.is_empty() {
+        return Some((false, 0));
+    }
+    if !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let zero_pad = digits.starts_with('0');
+    let width = digits.parse().ok()?;
+    Some((zero_pad, width))
+}
+
+/// `.50` or `.50s` -> 50: at most that many characters of the text
+fn parse_precision_spec(spec: &str) -> Option<usize> {
+    let digits = spec.strip_prefix('.')?;
+    let digits = digits.strip_suffix('s').unwrap_or(digits);
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_default_templates() {
+        let metadata = SampleMetadata::default();
+        let preview = preview(
+            Path::new("/music"),
+            "{album_artist}/{album}",
+            "{track:02d} {title}",
+            "%Y-%m-%d",
+            &metadata,
+        )
+        .unwrap();
+
+        assert_eq!(preview.folder, "Daft Punk/Random Access Memories");
+        assert_eq!(preview.file, "08 Get Lucky");
+        assert_eq!(preview.path, Path::new("/music/Daft Punk/Random Access Memories/08 Get Lucky.m4a"));
+        assert_eq!(render("{date} {{x}}", "%Y", &metadata).unwrap(), "2013 {x}");
+    }
+
+    #[test]
+    fn test_render_truncates_text_with_precision() {
+        let metadata = SampleMetadata::default();
+        assert_eq!(render("{title:.3}", "%Y", &metadata).unwrap(), "Get");
+        assert_eq!(render("{album:.6s} {title:.50}", "%Y", &metadata).unwrap(), "Random Get Lucky");
+        assert!(matches!(parse("{track:.2}"), Err(TemplateError::InvalidFormatSpec { .. })));
+        assert!(matches!(parse("{title:.}"), Err(TemplateError::InvalidFormatSpec { .. })));
+    }
+
+    #[test]
+    fn test_rejects_invalid_templates() {
+        assert_eq!(
+            parse("{albm}/{title}"),
+            Err(TemplateError::UnknownPlaceholder("albm".to_string()))
+        );
+        assert!(matches!(parse("{title"), Err(TemplateError::UnclosedPlaceholder(_))));
+        assert!(matches!(parse("title}"), Err(TemplateError::UnmatchedBrace(_))));
+        assert!(matches!(parse("{title:02d}"), Err(TemplateError::InvalidFormatSpec { .. })));
+        assert_eq!(parse("  "), Err(TemplateError::Empty));
+        assert!(validate_date_format("%Y-%m-%d").is_ok());
+        assert!(validate_date_format("%Q").is_err());
+    }
+}
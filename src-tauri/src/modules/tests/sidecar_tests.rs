@@ -231,9 +231,9 @@ mod sidecar_tests {
 
         // Verify essential arguments are present
         assert!(args.contains(&"--output-path".to_string()));
-        assert!(args.contains(&"/test/output".to_string()));
+        assert!(args.contains(&"/test/output/.gytmdl-staging/test-job".to_string()));
         assert!(args.contains(&"--temp-path".to_string()));
-        assert!(args.contains(&"/test/temp".to_string()));
+        assert!(args.contains(&"/test/temp/test-job".to_string()));
         assert!(args.contains(&"--itag".to_string()));
         assert!(args.contains(&"140".to_string()));
         assert!(args.contains(&"--cover-size".to_string()));
@@ -245,6 +245,26 @@ mod sidecar_tests {
         assert!(args.contains(&"--verbose".to_string()));
     }
 
+    #[test]
+    fn test_command_args_ignore_system_config() {
+        use crate::modules::state::AppConfig;
+
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let binary_path = create_mock_sidecar_binary(temp_dir.path(), "test-binary", "test content");
+        let wrapper = GytmdlWrapper::with_binary_path(binary_path).expect("Failed to create wrapper");
+        let url = "https://music.youtube.com/watch?v=test";
+
+        // Sandboxed by default
+        let mut config = AppConfig::default();
+        let args = wrapper.build_command_args(&config, url, "test-job").unwrap();
+        assert!(args.contains(&"--no-config-file".to_string()));
+
+        // Escape hatch lets the system config through
+        config.honor_system_config = true;
+        let args = wrapper.build_command_args(&config, url, "test-job").unwrap();
+        assert!(!args.contains(&"--no-config-file".to_string()));
+    }
+
     #[test]
     fn test_error_display() {
         let errors = vec![
@@ -0,0 +1,303 @@
+use crate::modules::state::{AppState, JobStatus};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Number of recent downloads used to estimate throughput
+const SAMPLE_WINDOW: usize = 20;
+
+const BYTES_PER_MB: f64 = 1024.0 * 1024.0;
+const BYTES_PER_GB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+/// Bytes written by one finished job, how many tracks and how long it took
+#[derive(Debug, Clone, Copy)]
+struct ThroughputSample {
+    bytes: u64,
+    tracks: u32,
+    duration: Duration,
+}
+
+/// Rolling per-job download throughput
+#[derive(Debug, Default)]
+pub struct ThroughputTracker {
+    samples: VecDeque<ThroughputSample>,
+}
+
+/// Estimated remaining data, time and cost for the queue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueueForecast {
+    /// Queued tracks plus the unfinished share of running ones
+    pub remaining_tracks: f64,
+    pub remaining_mb: Option<f64>,
+    /// Tracks sized from their length rather than the average track size
+    #[serde(default)]
+    pub tracks_sized_from_metadata: usize,
+    /// Combined throughput across concurrent downloads
+    pub bytes_per_second: Option<f64>,
+    pub seconds_remaining: Option<u64>,
+    #[serde(default)]
+    pub estimated_completion: Option<DateTime<Utc>>,
+    pub estimated_cost: Option<f64>,
+    pub message: String,
+}
+
+/// What is left of the queue, sized as well as the session allows
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RemainingWork {
+    /// Queued tracks plus the unfinished share of running ones
+    pub tracks: f64,
+    /// `None` unless every remaining track could be sized
+    pub bytes: Option<f64>,
+    pub tracks_sized_from_metadata: usize,
+}
+
+impl RemainingWork {
+    /// Count playlist and album jobs by their listed tracks, and size each
+    /// single track from its length when metadata has one and
+    /// `bytes_per_track_second` is known, otherwise from the average track size
+    pub fn of_queue(state: &AppState, average_track_bytes: Option<u64>, bytes_per_track_second: Option<f64>) -> Self {
+        let mut work = Self { bytes: Some(0.0), ..Self::default() };
+
+        for job in state.jobs.iter() {
+            let track_count = job.track_count();
+            let left = match job.status {
+                JobStatus::Queued => track_count as f64,
+                JobStatus::Downloading => {
+                    let progress = state.job_progress(job);
+                    let done = progress.percentage.unwrap_or(0.0).clamp(0.0, 100.0) as f64 / 100.0;
+                    match &progress.playlist {
+                        // The percentage is that of the track being downloaded
+                        Some(playlist) => {
+                            let after_current = playlist.total_tracks.saturating_sub(playlist.current_track) as f64;
+                            after_current + 1.0 - done
+                        }
+                        None => track_count as f64 * (1.0 - done),
+                    }
+                }
+                _ => continue,
+            };
+            work.tracks += left;
+
+            // A collection's metadata doesn't describe the length of its tracks
+            let track_secs = job.metadata.as_ref()
+                .and_then(|metadata| metadata.duration)
+                .filter(|_| track_count == 1);
+            let size = match (track_secs, bytes_per_track_second) {
+                (Some(secs), Some(rate)) => {
+                    work.tracks_sized_from_metadata += 1;
+                    Some(secs as f64 * rate)
+                }
+                _ => average_track_bytes.map(|bytes| bytes as f64),
+            };
+            work.bytes = work.bytes.zip(size).map(|(total, size)| total + size * left);
+        }
+        work
+    }
+}
+
+impl ThroughputTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a finished download of `tracks` tracks; empty or instantaneous
+    /// ones are ignored
+    pub fn record(&mut self, bytes: u64, tracks: u32, duration: Duration) {
+        if bytes == 0 || duration.is_zero() {
+            return;
+        }
+        if self.samples.len() == SAMPLE_WINDOW {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(ThroughputSample { bytes, tracks: tracks.max(1), duration });
+    }
+
+    /// Average throughput of a single job, if any samples were recorded
+    pub fn bytes_per_second(&self) -> Option<f64> {
+        let bytes: u64 = self.samples.iter().map(|sample| sample.bytes).sum();
+        let seconds: f64 = self.samples.iter().map(|sample| sample.duration.as_secs_f64()).sum();
+        if seconds > 0.0 {
+            Some(bytes as f64 / seconds)
+        } else {
+            None
+        }
+    }
+
+    /// Average time a recorded download took per track
+    pub fn average_track_secs(&self) -> Option<f64> {
+        let seconds: f64 = self.samples.iter().map(|sample| sample.duration.as_secs_f64()).sum();
+        let tracks: u32 = self.samples.iter().map(|sample| sample.tracks).sum();
+        (tracks > 0).then(|| seconds / tracks as f64)
+    }
+
+    /// Forecast the rest of the queue. Jobs run `parallelism` at a time, so the
+    /// combined rate is the per-job rate times the number that will run together.
+    /// Without a size for every track the average time per track is used instead.
+    pub fn forecast(
+        &self,
+        work: RemainingWork,
+        parallelism: usize,
+        price_per_gb: Option<f64>,
+        now: DateTime<Utc>,
+    ) -> QueueForecast {
+        let remaining_tracks = work.tracks;
+        let remaining_bytes = work.bytes;
+        let parallel_jobs = (parallelism as f64).min(remaining_tracks.ceil()).max(1.0);
+        let bytes_per_second = self.bytes_per_second().map(|rate| rate * parallel_jobs);
+
+        let seconds_remaining = match (remaining_bytes, bytes_per_second, self.average_track_secs()) {
+            _ if remaining_tracks <= 0.0 => Some(0),
+            (Some(bytes), Some(rate), _) if rate > 0.0 => Some((bytes / rate).ceil() as u64),
+            (_, _, Some(track_secs)) => Some((remaining_tracks * track_secs / parallel_jobs).ceil() as u64),
+            _ => None,
+        };
+        let estimated_cost = match (remaining_bytes, price_per_gb) {
+            (Some(bytes), Some(price)) => Some(bytes / BYTES_PER_GB * price),
+            _ => None,
+        };
+
+        let remaining_mb = remaining_bytes.map(|bytes| bytes / BYTES_PER_MB);
+        let message = match (remaining_mb, seconds_remaining) {
+            _ if remaining_tracks <= 0.0 => "Queue is empty".to_string(),
+            (Some(mb), Some(seconds)) => format!("~{:.0} MB left, about {} remaining", mb, format_duration(seconds)),
+            (Some(mb), None) => format!("~{:.0} MB left", mb),
+            (None, Some(seconds)) => format!("About {} remaining", format_duration(seconds)),
+            (None, None) => "Not enough downloads yet to estimate".to_string(),
+        };
+
+        QueueForecast {
+            remaining_tracks,
+            remaining_mb,
+            tracks_sized_from_metadata: work.tracks_sized_from_metadata,
+            bytes_per_second,
+            seconds_remaining,
+            estimated_completion: seconds_remaining.map(|secs| now + chrono::Duration::seconds(secs as i64)),
+            estimated_cost,
+            message,
+        }
+    }
+}
+
+/// Render seconds as a short "1h 5m" style duration
+fn format_duration(seconds: u64) -> String {
+    let hours = seconds / 3600;
+    let minutes = (seconds % 3600) / 60;
+    match (hours, minutes) {
+        (0, 0) => format!("{}s", seconds),
+        (0, m) => format!("{}m", m),
+        (h, m) => format!("{}h {}m", h, m),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::state::{JobMetadata, PlaylistProgress, Progress};
+
+    #[test]
+    fn test_bytes_per_second() {
+        let mut tracker = ThroughputTracker::new();
+        assert!(tracker.bytes_per_second().is_none());
+
+        tracker.record(10_000_000, 1, Duration::from_secs(10));
+        tracker.record(0, 1, Duration::from_secs(5));
+        tracker.record(30_000_000, 1, Duration::from_secs(30));
+        assert_eq!(tracker.bytes_per_second(), Some(1_000_000.0));
+
+        // 80s over 6 tracks, the album counting each of its 4
+        tracker.record(40_000_000, 4, Duration::from_secs(40));
+        let track_secs = tracker.average_track_secs().unwrap();
+        assert!((track_secs - 80.0 / 6.0).abs() < 1e-9);
+    }
+
+    fn work(tracks: f64, track_bytes: Option<f64>) -> RemainingWork {
+        RemainingWork {
+            tracks,
+            bytes: track_bytes.map(|bytes| bytes * tracks),
+            tracks_sized_from_metadata: 0,
+        }
+    }
+
+    #[test]
+    fn test_forecast() {
+        let mut tracker = ThroughputTracker::new();
+        tracker.record(10 * 1024 * 1024, 1, Duration::from_secs(10));
+        let now = Utc::now();
+
+        // 10 tracks of 10 MB, 2 at a time at 1 MB/s each
+        let forecast = tracker.forecast(work(10.0, Some(10.0 * BYTES_PER_MB)), 2, Some(2.0), now);
+        assert_eq!(forecast.remaining_mb, Some(100.0));
+        assert_eq!(forecast.seconds_remaining, Some(50));
+        assert_eq!(forecast.estimated_completion, Some(now + chrono::Duration::seconds(50)));
+        let cost = forecast.estimated_cost.unwrap();
+        assert!((cost - 100.0 / 1024.0 * 2.0).abs() < 1e-9);
+
+        // Parallelism never exceeds the remaining work
+        let forecast = tracker.forecast(work(1.0, Some(10.0 * BYTES_PER_MB)), 3, None, now);
+        assert_eq!(forecast.seconds_remaining, Some(10));
+        assert!(forecast.estimated_cost.is_none());
+
+        // Unsized tracks fall back to the average time per track
+        let forecast = tracker.forecast(work(4.0, None), 2, None, now);
+        assert!(forecast.remaining_mb.is_none());
+        assert_eq!(forecast.seconds_remaining, Some(20));
+
+        // No samples yet
+        let forecast = ThroughputTracker::new().forecast(work(5.0, None), 3, Some(1.0), now);
+        assert!(forecast.remaining_mb.is_none());
+        assert!(forecast.seconds_remaining.is_none());
+    }
+
+    #[test]
+    fn test_remaining_work_uses_track_lengths() {
+        let mut state = AppState::new();
+        let long = state.add_job("https://music.youtube.com/watch?v=long".to_string());
+        state.get_job_mut(&long).unwrap().metadata = Some(JobMetadata {
+            duration: Some(600),
+            ..JobMetadata::default()
+        });
+        state.add_job("https://music.youtube.com/watch?v=unknown".to_string());
+
+        // 10 KB per second of audio, 4 MB per unknown track
+        let work = RemainingWork::of_queue(&state, Some(4_000_000), Some(10_000.0));
+        assert_eq!(work.tracks, 2.0);
+        assert_eq!(work.tracks_sized_from_metadata, 1);
+        assert_eq!(work.bytes, Some(10_000_000.0));
+
+        // Without an average track size the unknown track can't be sized
+        assert_eq!(RemainingWork::of_queue(&state, None, Some(10_000.0)).bytes, None);
+    }
+
+    #[test]
+    fn test_remaining_work_counts_collection_tracks() {
+        let mut state = AppState::new();
+        let album = state.add_job("https://music.youtube.com/playlist?list=OLAK5uy_abc".to_string());
+        state.get_job_mut(&album).unwrap().metadata = Some(JobMetadata {
+            track_count: Some(12),
+            duration: Some(2400),
+            ..JobMetadata::default()
+        });
+
+        let work = RemainingWork::of_queue(&state, Some(4_000_000), Some(10_000.0));
+        assert_eq!(work.tracks, 12.0);
+        assert_eq!(work.tracks_sized_from_metadata, 0);
+        assert_eq!(work.bytes, Some(48_000_000.0));
+
+        // Track 4 of 12 half done leaves 8.5 tracks
+        state.update_job_status(&album, JobStatus::Downloading);
+        state.update_job_progress(&album, Progress {
+            percentage: Some(50.0),
+            playlist: Some(PlaylistProgress { current_track: 4, total_tracks: 12, track_title: None }),
+            ..Progress::default()
+        });
+        assert_eq!(RemainingWork::of_queue(&state, Some(4_000_000), None).tracks, 8.5);
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(42), "42s");
+        assert_eq!(format_duration(125), "2m");
+        assert_eq!(format_duration(3900), "1h 5m");
+    }
+}
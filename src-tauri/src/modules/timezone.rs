@@ -0,0 +1,150 @@
+use crate::modules::state::DownloadJob;
+use chrono::{DateTime, Local, LocalResult, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+/// Format used for pre-rendered local timestamps
+const DISPLAY_FORMAT: &str = "%Y-%m-%d %H:%M:%S %:z";
+
+/// Accepted formats for local times entered by the user
+const INPUT_FORMATS: &[&str] = &["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M"];
+
+/// The zone used to interpret schedules and render timestamps
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DisplayTimezone {
+    /// The operating system's local zone
+    System,
+    /// An IANA zone configured by the user
+    Named(Tz),
+}
+
+impl DisplayTimezone {
+    /// Resolve the configured zone name; `None` or an empty name means the system zone
+    pub fn from_config(name: Option<&str>) -> Result<Self, String> {
+        match name.map(str::trim) {
+            None | Some("") => Ok(Self::System),
+            Some(name) => name.parse::<Tz>()
+                .map(Self::Named)
+                .map_err(|_| format!("Unknown timezone: '{}'", name)),
+        }
+    }
+
+    /// Get the zone's display name
+    pub fn name(&self) -> String {
+        match self {
+            Self::System => "System".to_string(),
+            Self::Named(tz) => tz.name().to_string(),
+        }
+    }
+
+    /// Render a UTC timestamp in this zone
+    pub fn format(&self, timestamp: &DateTime<Utc>) -> String {
+        match self {
+            Self::System => timestamp.with_timezone(&Local).format(DISPLAY_FORMAT).to_string(),
+            Self::Named(tz) => timestamp.with_timezone(tz).format(DISPLAY_FORMAT).to_string(),
+        }
+    }
+
+    /// Wall-clock time of a UTC timestamp in this zone
+    pub fn local_time(&self, timestamp: &DateTime<Utc>) -> NaiveTime {
+        match self {
+            Self::System => timestamp.with_timezone(&Local).time(),
+            Self::Named(tz) => timestamp.with_timezone(tz).time(),
+        }
+    }
+
+    /// Interpret a wall-clock time entered by the user in this zone.
+    /// Ambiguous times (DST fall-back) resolve to the earlier instant;
+    /// times skipped by a DST jump are rejected.
+    pub fn parse_local(&self, input: &str) -> Result<DateTime<Utc>, String> {
+        let naive = INPUT_FORMATS.iter()
+            .find_map(|format| NaiveDateTime::parse_from_str(input.trim(), format).ok())
+            .ok_or_else(|| format!("Invalid date/time: '{}'. Use YYYY-MM-DD HH:MM", input))?;
+
+        let resolved = match self {
+            Self::System => Self::earliest(Local.from_local_datetime(&naive)),
+            Self::Named(tz) => Self::earliest(tz.from_local_datetime(&naive)),
+        };
+
+        resolved.ok_or_else(|| format!("'{}' does not exist in timezone {}", input, self.name()))
+    }
+
+    /// Parse a schedule time: RFC 3339 timestamps are taken as-is,
+    /// anything else is read as wall-clock time in this zone
+    pub fn parse_schedule(&self, input: &str) -> Result<DateTime<Utc>, String> {
+        match DateTime::parse_from_rfc3339(input.trim()) {
+            Ok(timestamp) => Ok(timestamp.with_timezone(&Utc)),
+            Err(_) => self.parse_local(input),
+        }
+    }
+
+    fn earliest<T: TimeZone>(result: LocalResult<DateTime<T>>) -> Option<DateTime<Utc>> {
+        result.earliest().map(|dt| dt.with_timezone(&Utc))
+    }
+}
+
+/// Pre-formatted local versions of a job's timestamps
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalTimestamps {
+    pub created_at: String,
+    pub started_at: Option<String>,
+    pub completed_at: Option<String>,
+}
+
+impl LocalTimestamps {
+    pub fn for_job(job: &DownloadJob, timezone: &DisplayTimezone) -> Self {
+        Self {
+            created_at: timezone.format(&job.created_at),
+            started_at: job.started_at.as_ref().map(|t| timezone.format(t)),
+            completed_at: job.completed_at.as_ref().map(|t| timezone.format(t)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_config() {
+        assert_eq!(DisplayTimezone::from_config(None).unwrap(), DisplayTimezone::System);
+        assert_eq!(DisplayTimezone::from_config(Some(" ")).unwrap(), DisplayTimezone::System);
+        assert_eq!(
+            DisplayTimezone::from_config(Some("Asia/Seoul")).unwrap(),
+            DisplayTimezone::Named(chrono_tz::Asia::Seoul)
+        );
+        assert!(DisplayTimezone::from_config(Some("Mars/Olympus")).is_err());
+    }
+
+    #[test]
+    fn test_format_named_zone() {
+        let timezone = DisplayTimezone::Named(chrono_tz::Asia::Seoul);
+        let timestamp = Utc.with_ymd_and_hms(2024, 1, 1, 15, 30, 0).unwrap();
+        assert_eq!(timezone.format(&timestamp), "2024-01-02 00:30:00 +09:00");
+    }
+
+    #[test]
+    fn test_parse_local() {
+        let timezone = DisplayTimezone::Named(chrono_tz::America::New_York);
+
+        let parsed = timezone.parse_local("2024-07-01 09:00").unwrap();
+        assert_eq!(parsed, Utc.with_ymd_and_hms(2024, 7, 1, 13, 0, 0).unwrap());
+
+        // 02:30 is skipped by the spring-forward transition
+        assert!(timezone.parse_local("2024-03-10 02:30").is_err());
+        assert!(timezone.parse_local("tomorrow").is_err());
+    }
+
+    #[test]
+    fn test_parse_schedule_accepts_rfc3339() {
+        let timezone = DisplayTimezone::Named(chrono_tz::Asia::Seoul);
+        assert_eq!(
+            timezone.parse_schedule("2024-07-01T09:00:00Z").unwrap(),
+            Utc.with_ymd_and_hms(2024, 7, 1, 9, 0, 0).unwrap()
+        );
+        assert_eq!(
+            timezone.parse_schedule("2024-07-01 09:00").unwrap(),
+            Utc.with_ymd_and_hms(2024, 7, 1, 0, 0, 0).unwrap()
+        );
+    }
+}
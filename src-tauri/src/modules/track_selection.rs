@@ -0,0 +1,89 @@
+use crate::modules::subscriptions::ListedEntry;
+use serde::{Deserialize, Serialize};
+
+/// Tracks of an album or playlist URL to download instead of all of them
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TrackSelection {
+    /// 1-based positions in the album or playlist
+    Indices(Vec<u32>),
+    /// Video IDs of the tracks
+    VideoIds(Vec<String>),
+}
+
+impl TrackSelection {
+    /// Check the selection can be applied to `url`
+    pub fn validate(&self, url: &str) -> Result<(), String> {
+        if !is_collection_url(url) {
+            return Err("Tracks can only be selected from album or playlist URLs".to_string());
+        }
+        match self {
+            Self::Indices(indices) if indices.is_empty() => Err("No tracks selected".to_string()),
+            Self::Indices(indices) if indices.contains(&0) => Err("Track numbers start at 1".to_string()),
+            Self::VideoIds(ids) if ids.is_empty() => Err("No tracks selected".to_string()),
+            Self::VideoIds(ids) => match ids.iter().find(|id| !is_video_id(id)) {
+                Some(id) => Err(format!("Invalid video ID: {}", id)),
+                None => Ok(()),
+            },
+            Self::Indices(_) => Ok(()),
+        }
+    }
+
+    /// The selected tracks of a listed album or playlist, in listing order.
+    /// Positions past the end and IDs not in the listing are skipped.
+    pub fn select(&self, entries: &[ListedEntry]) -> Vec<ListedEntry> {
+        entries.iter()
+            .enumerate()
+            .filter(|(index, entry)| match self {
+                Self::Indices(indices) => indices.contains(&(*index as u32 + 1)),
+                Self::VideoIds(ids) => ids.contains(&entry.id),
+            })
+            .map(|(_, entry)| entry.clone())
+            .collect()
+    }
+}
+
+/// Album, playlist and browse URLs, which expand to several tracks
+pub fn is_collection_url(url: &str) -> bool {
+    url.contains("list=") || url.contains("/playlist") || url.contains("/browse/")
+}
+
+fn is_video_id(id: &str) -> bool {
+    id.len() == 11 && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALBUM: &str = "https://music.youtube.com/playlist?list=OLAK5uy_abc";
+
+    fn listing() -> Vec<ListedEntry> {
+        ["aaaaaaaaaaa", "bbbbbbbbbbb", "ccccccccccc"].iter()
+            .map(|id| ListedEntry {
+                id: id.to_string(),
+                url: format!("https://music.youtube.com/watch?v={}", id),
+                title: None,
+            })
+            .collect()
+    }
+
+    fn ids(entries: &[ListedEntry]) -> Vec<&str> {
+        entries.iter().map(|entry| entry.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_select_tracks_from_listing() {
+        let indices = TrackSelection::Indices(vec![3, 1, 1, 12]);
+        assert!(indices.validate(ALBUM).is_ok());
+        assert_eq!(ids(&indices.select(&listing())), vec!["aaaaaaaaaaa", "ccccccccccc"]);
+
+        let video_ids = TrackSelection::VideoIds(vec!["bbbbbbbbbbb".to_string(), "dQw4w9WgXcQ".to_string()]);
+        assert!(video_ids.validate(ALBUM).is_ok());
+        assert_eq!(ids(&video_ids.select(&listing())), vec!["bbbbbbbbbbb"]);
+
+        assert!(TrackSelection::Indices(vec![0]).validate(ALBUM).is_err());
+        assert!(TrackSelection::VideoIds(vec!["bad id".to_string()]).validate(ALBUM).is_err());
+        assert!(indices.validate("https://music.youtube.com/watch?v=dQw4w9WgXcQ").is_err());
+    }
+}
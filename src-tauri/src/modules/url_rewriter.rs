@@ -0,0 +1,96 @@
+use crate::modules::state::UrlRewriteRule;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Outcome of running a URL through the rewrite rules
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RewriteResult {
+    pub original: String,
+    pub rewritten: String,
+    /// Indices of the rules that changed the URL, in application order
+    pub applied_rules: Vec<usize>,
+}
+
+/// Compile a rule's pattern
+fn compile_rule(index: usize, rule: &UrlRewriteRule) -> Result<Regex, String> {
+    Regex::new(&rule.pattern)
+        .map_err(|e| format!("Invalid pattern in rewrite rule {}: {}", index + 1, e))
+}
+
+/// Check that every rule has a valid pattern
+pub fn validate_rules(rules: &[UrlRewriteRule]) -> Result<(), String> {
+    for (index, rule) in rules.iter().enumerate() {
+        compile_rule(index, rule)?;
+    }
+    Ok(())
+}
+
+/// Apply enabled rules in order, each one seeing the output of the previous.
+/// Replacements support `$1`/`${name}` capture group references.
+pub fn rewrite_url(url: &str, rules: &[UrlRewriteRule]) -> Result<RewriteResult, String> {
+    let mut rewritten = url.to_string();
+    let mut applied_rules = Vec::new();
+
+    for (index, rule) in rules.iter().enumerate() {
+        if !rule.enabled {
+            continue;
+        }
+
+        let regex = compile_rule(index, rule)?;
+        let next = regex.replace_all(&rewritten, rule.replacement.as_str()).to_string();
+        if next != rewritten {
+            applied_rules.push(index);
+            rewritten = next;
+        }
+    }
+
+    Ok(RewriteResult {
+        original: url.to_string(),
+        rewritten,
+        applied_rules,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(pattern: &str, replacement: &str) -> UrlRewriteRule {
+        UrlRewriteRule {
+            pattern: pattern.to_string(),
+            replacement: replacement.to_string(),
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn test_rules_apply_in_order() {
+        let rules = vec![
+            rule(r"^https://(www\.)?youtube\.com/", "https://music.youtube.com/"),
+            rule(r"&list=[^&]+", ""),
+            rule(r"^https://example\.com/", "https://nothing.example/"),
+        ];
+
+        let result = rewrite_url("https://www.youtube.com/watch?v=abc&list=PL123", &rules).unwrap();
+        assert_eq!(result.rewritten, "https://music.youtube.com/watch?v=abc");
+        assert_eq!(result.applied_rules, vec![0, 1]);
+        assert_eq!(result.original, "https://www.youtube.com/watch?v=abc&list=PL123");
+    }
+
+    #[test]
+    fn test_disabled_rules_are_skipped() {
+        let mut disabled = rule(r"&list=[^&]+", "");
+        disabled.enabled = false;
+
+        let result = rewrite_url("https://music.youtube.com/watch?v=abc&list=PL123", &[disabled]).unwrap();
+        assert_eq!(result.rewritten, "https://music.youtube.com/watch?v=abc&list=PL123");
+        assert!(result.applied_rules.is_empty());
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        let rules = vec![rule(r"(unclosed", "")];
+        assert!(validate_rules(&rules).is_err());
+        assert!(rewrite_url("https://music.youtube.com/", &rules).is_err());
+    }
+}
@@ -0,0 +1,260 @@
+use crate::modules::converter::{AudioFormat, ConvertError};
+use crate::modules::sidecar_manager::SidecarManager;
+use crate::modules::state::AppConfig;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::process::Command;
+
+/// Share of the expected bitrate a file may fall short by before it is suspect
+const BITRATE_TOLERANCE: f64 = 0.25;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum VerificationStatus {
+    /// Every file matched what was requested
+    Verified,
+    /// At least one file looks truncated or doesn't match the requested quality
+    Suspect,
+}
+
+/// What ffprobe found in one output file and what didn't match
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileVerification {
+    pub path: PathBuf,
+    pub duration_secs: Option<f64>,
+    pub codec: Option<String>,
+    pub bitrate_kbps: Option<u32>,
+    pub issues: Vec<String>,
+}
+
+/// Outcome of probing a completed job's files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerificationReport {
+    pub status: VerificationStatus,
+    pub files: Vec<FileVerification>,
+    pub checked_at: DateTime<Utc>,
+}
+
+impl VerificationReport {
+    pub fn new(files: Vec<FileVerification>) -> Self {
+        let status = if files.iter().all(|file| file.issues.is_empty()) {
+            VerificationStatus::Verified
+        } else {
+            VerificationStatus::Suspect
+        };
+        Self { status, files, checked_at: Utc::now() }
+    }
+
+    /// Every issue, prefixed with the file it was found in
+    pub fn issues(&self) -> Vec<String> {
+        self.files.iter()
+            .flat_map(|file| {
+                let name = file.path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+                file.issues.iter().map(move |issue| format!("{}: {}", name, issue))
+            })
+            .collect()
+    }
+}
+
+/// Codec and bitrate a finished file should have
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpectedAudio {
+    pub codec: Option<&'static str>,
+    pub bitrate_kbps: Option<u32>,
+}
+
+impl ExpectedAudio {
+    /// From the converted format when converting, otherwise from the requested itag
+    pub fn for_config(config: &AppConfig) -> Self {
+        if let Some(format) = config.convert_to {
+            let codec = match format {
+                AudioFormat::Mp3 => "mp3",
+                AudioFormat::Flac => "flac",
+                AudioFormat::Opus => "opus",
+            };
+            return Self {
+                codec: Some(codec),
+                bitrate_kbps: format.is_lossy().then_some(config.convert_bitrate_kbps),
+            };
+        }
+
+        match config.itag.as_str() {
+            "140" => Self { codec: Some("aac"), bitrate_kbps: Some(128) },
+            "141" => Self { codec: Some("aac"), bitrate_kbps: Some(256) },
+            "251" => Self { codec: Some("opus"), bitrate_kbps: None },
+            _ => Self { codec: None, bitrate_kbps: None },
+        }
+    }
+}
+
+/// Values read from ffprobe's JSON output
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProbeResult {
+    pub duration_secs: Option<f64>,
+    pub codec: Option<String>,
+    pub bitrate_kbps: Option<u32>,
+    /// Errors ffprobe printed while reading the file, e.g. for a truncated container
+    pub errors: Vec<String>,
+}
+
+/// Parse `ffprobe -print_format json -show_format -show_streams` output
+pub fn parse_probe_output(stdout: &str, stderr: &str) -> ProbeResult {
+    let json: serde_json::Value = serde_json::from_str(stdout).unwrap_or_default();
+    let number = |value: &serde_json::Value| -> Option<f64> {
+        value.as_str().and_then(|value| value.parse().ok()).or_else(|| value.as_f64())
+    };
+
+    let audio = json["streams"].as_array()
+        .and_then(|streams| streams.iter().find(|stream| stream["codec_type"] == "audio"));
+    let bitrate = audio.and_then(|stream| number(&stream["bit_rate"]))
+        .or_else(|| number(&json["format"]["bit_rate"]));
+
+    ProbeResult {
+        duration_secs: number(&json["format"]["duration"])
+            .or_else(|| audio.and_then(|stream| number(&stream["duration"]))),
+        codec: audio.and_then(|stream| stream["codec_name"].as_str()).map(str::to_string),
+        bitrate_kbps: bitrate.map(|bits| (bits / 1000.0).round() as u32),
+        errors: stderr.lines().map(str::trim).filter(|line| !line.is_empty()).map(str::to_string).collect(),
+    }
+}
+
+/// Compare a probed file with what was requested. `expected_duration_secs`
+/// comes from the job's metadata.
+pub fn find_issues(
+    probe: &ProbeResult,
+    expected: &ExpectedAudio,
+    expected_duration_secs: Option<u32>,
+    tolerance_secs: u32,
+) -> Vec<String> {
+    let mut issues = Vec::new();
+    if let Some(error) = probe.errors.last() {
+        issues.push(format!("ffprobe reported errors, the file may be truncated: {}", error));
+    }
+
+    match (probe.duration_secs, expected_duration_secs) {
+        (None, _) => issues.push("no duration could be read".to_string()),
+        (Some(actual), Some(expected)) if (actual - f64::from(expected)).abs() > f64::from(tolerance_secs) => {
+            issues.push(format!("duration is {:.0}s, expected {}s", actual, expected));
+        }
+        _ => {}
+    }
+
+    match (probe.codec.as_deref(), expected.codec) {
+        (None, _) => issues.push("no audio stream found".to_string()),
+        (Some(actual), Some(expected)) if actual != expected => {
+            issues.push(format!("codec is {}, expected {}", actual, expected));
+        }
+        _ => {}
+    }
+
+    if let (Some(actual), Some(expected)) = (probe.bitrate_kbps, expected.bitrate_kbps) {
+        if f64::from(actual) < f64::from(expected) * (1.0 - BITRATE_TOLERANCE) {
+            issues.push(format!("bitrate is {} kbps, expected about {} kbps", actual, expected));
+        }
+    }
+
+    issues
+}
+
+/// ffprobe from the sidecar folder or PATH
+pub fn locate_ffprobe() -> Option<PathBuf> {
+    SidecarManager::locate_dependency("ffprobe").map(|(path, _)| path)
+}
+
+/// Run ffprobe on one file
+pub async fn probe(ffprobe: &Path, file: &Path) -> Result<ProbeResult, ConvertError> {
+    let output = Command::new(ffprobe)
+        .args(["-v", "error", "-print_format", "json", "-show_format", "-show_streams"])
+        .arg(file)
+        .stdin(Stdio::null())
+        .kill_on_drop(true)
+        .output()
+        .await?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() && stdout.trim().is_empty() {
+        let message = stderr.lines().last().unwrap_or("").trim().to_string();
+        return Err(ConvertError::FfmpegFailed(message));
+    }
+    Ok(parse_probe_output(&stdout, &stderr))
+}
+
+/// Probe a file and check it against what was requested; a file ffprobe
+/// can't read at all is reported as an issue rather than an error
+pub async fn verify_file(
+    ffprobe: &Path,
+    file: &Path,
+    expected: &ExpectedAudio,
+    expected_duration_secs: Option<u32>,
+    tolerance_secs: u32,
+) -> FileVerification {
+    let (probe, issues) = match probe(ffprobe, file).await {
+        Ok(probe) => {
+            let issues = find_issues(&probe, expected, expected_duration_secs, tolerance_secs);
+            (probe, issues)
+        }
+        Err(e) => (ProbeResult::default(), vec![format!("could not be probed: {}", e)]),
+    };
+
+    FileVerification {
+        path: file.to_path_buf(),
+        duration_secs: probe.duration_secs,
+        codec: probe.codec,
+        bitrate_kbps: probe.bitrate_kbps,
+        issues,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROBE_JSON: &str = r#"{
+        "streams": [
+            { "codec_type": "video", "codec_name": "mjpeg" },
+            { "codec_type": "audio", "codec_name": "aac", "bit_rate": "255987", "duration": "212.4" }
+        ],
+        "format": { "duration": "212.480000", "bit_rate": "262000" }
+    }"#;
+
+    #[test]
+    fn test_parse_probe_output() {
+        let probe = parse_probe_output(PROBE_JSON, "");
+        assert_eq!(probe.codec.as_deref(), Some("aac"));
+        assert_eq!(probe.bitrate_kbps, Some(256));
+        assert_eq!(probe.duration_secs, Some(212.48));
+        assert!(probe.errors.is_empty());
+
+        let truncated = parse_probe_output("", "[mov,mp4,m4a @ 0x1] moov atom not found\n");
+        assert_eq!(truncated, ProbeResult {
+            errors: vec!["[mov,mp4,m4a @ 0x1] moov atom not found".to_string()],
+            ..ProbeResult::default()
+        });
+    }
+
+    #[test]
+    fn test_find_issues() {
+        let config = AppConfig {
+            itag: "141".to_string(),
+            ..AppConfig::default()
+        };
+        let expected = ExpectedAudio::for_config(&config);
+        let probe = parse_probe_output(PROBE_JSON, "");
+        assert!(find_issues(&probe, &expected, Some(213), 3).is_empty());
+
+        let issues = find_issues(&probe, &expected, Some(240), 3);
+        assert_eq!(issues, vec!["duration is 212s, expected 240s".to_string()]);
+
+        let low_quality = ProbeResult { bitrate_kbps: Some(128), ..probe.clone() };
+        assert_eq!(find_issues(&low_quality, &expected, None, 3).len(), 1);
+
+        let converted = ExpectedAudio::for_config(&AppConfig {
+            convert_to: Some(AudioFormat::Flac),
+            ..AppConfig::default()
+        });
+        assert_eq!(converted, ExpectedAudio { codec: Some("flac"), bitrate_kbps: None });
+        assert_eq!(find_issues(&probe, &converted, None, 3), vec!["codec is aac, expected flac".to_string()]);
+    }
+}
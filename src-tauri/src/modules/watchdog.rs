@@ -0,0 +1,105 @@
+use crate::modules::state::AppConfig;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Limits on a running download: total run time, and time without any
+/// output from the process. Either limit is off when configured as 0.
+#[derive(Debug, Clone)]
+pub struct Watchdog {
+    started_at: Instant,
+    last_output_at: Instant,
+    job_timeout: Option<Duration>,
+    stall_timeout: Option<Duration>,
+}
+
+impl Watchdog {
+    pub fn new(config: &AppConfig, now: Instant) -> Self {
+        let minutes = |value: u64| (value > 0).then(|| Duration::from_secs(value * 60));
+        Self {
+            started_at: now,
+            last_output_at: now,
+            job_timeout: minutes(config.job_timeout_minutes),
+            stall_timeout: minutes(config.stall_timeout_minutes),
+        }
+    }
+
+    /// Record a line of output, pushing back the stall deadline
+    pub fn output_received(&mut self, now: Instant) {
+        self.last_output_at = now;
+    }
+
+    /// The earliest time a limit runs out, if any limit is set
+    pub fn deadline(&self) -> Option<Instant> {
+        let job_deadline = self.job_timeout.map(|timeout| self.started_at + timeout);
+        let stall_deadline = self.stall_timeout.map(|timeout| self.last_output_at + timeout);
+        match (job_deadline, stall_deadline) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    /// Why the process should be killed, once a limit has run out
+    pub fn expired(&self, now: Instant) -> Option<String> {
+        if let Some(timeout) = self.job_timeout {
+            if now >= self.started_at + timeout {
+                return Some(format!("Download timed out after {} minutes", timeout.as_secs() / 60));
+            }
+        }
+        if let Some(timeout) = self.stall_timeout {
+            if now >= self.last_output_at + timeout {
+                return Some(format!("Download stalled: no output for {} minutes", timeout.as_secs() / 60));
+            }
+        }
+        None
+    }
+
+    /// Resolve when the deadline passes; never, when no limit is set
+    pub async fn wait(&self) {
+        match self.deadline() {
+            Some(deadline) => tokio::time::sleep_until(deadline).await,
+            None => std::future::pending().await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stall_and_job_timeouts() {
+        let start = Instant::now();
+        let config = AppConfig {
+            job_timeout_minutes: 30,
+            stall_timeout_minutes: 5,
+            ..AppConfig::default()
+        };
+        let mut watchdog = Watchdog::new(&config, start);
+        assert_eq!(watchdog.deadline(), Some(start + Duration::from_secs(5 * 60)));
+        assert!(watchdog.expired(start + Duration::from_secs(4 * 60)).is_none());
+
+        // Output keeps the stall timer from running out, but not the job timer
+        for minute in 1..=29 {
+            watchdog.output_received(start + Duration::from_secs(minute * 60));
+        }
+        assert!(watchdog.expired(start + Duration::from_secs(29 * 60)).is_none());
+        let reason = watchdog.expired(start + Duration::from_secs(30 * 60)).unwrap();
+        assert!(reason.contains("timed out after 30 minutes"));
+
+        let stalled = Watchdog::new(&config, start).expired(start + Duration::from_secs(5 * 60)).unwrap();
+        assert!(stalled.contains("stalled"));
+    }
+
+    #[test]
+    fn test_disabled_limits() {
+        let config = AppConfig {
+            job_timeout_minutes: 0,
+            stall_timeout_minutes: 0,
+            ..AppConfig::default()
+        };
+        let start = Instant::now();
+        let watchdog = Watchdog::new(&config, start);
+        assert_eq!(watchdog.deadline(), None);
+        assert!(watchdog.expired(start + Duration::from_secs(86_400)).is_none());
+    }
+}
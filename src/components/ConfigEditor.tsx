@@ -1,5 +1,6 @@
 import React, { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { errorMessage } from '../services/errorHandler';
 import { AppConfig, DownloadMode, CoverFormat, ConfigValidationError } from '../types';
 import './ConfigEditor.css';
 
@@ -33,7 +34,7 @@ const ConfigEditor: React.FC = () => {
       setErrors([]);
     } catch (error) {
       console.error('Failed to load config:', error);
-      setErrors([{ field: 'general', message: `Failed to load configuration: ${error}` }]);
+      setErrors([{ field: 'general', message: `Failed to load configuration: ${errorMessage(error)}` }]);
     } finally {
       setIsLoading(false);
     }
@@ -107,7 +108,7 @@ const ConfigEditor: React.FC = () => {
       setSuccessMessage('Configuration saved successfully!');
       setTimeout(() => setSuccessMessage(null), 3000);
     } catch (error) {
-      setErrors([{ field: 'general', message: `Failed to save configuration: ${error}` }]);
+      setErrors([{ field: 'general', message: `Failed to save configuration: ${errorMessage(error)}` }]);
     } finally {
       setIsSaving(false);
     }
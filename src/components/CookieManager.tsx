@@ -1,5 +1,6 @@
 import React, { useState, useEffect, useRef } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { errorMessage } from '../services/errorHandler';
 import './CookieManager.css';
 
 interface CookieStatus {
@@ -76,7 +77,7 @@ const CookieManager: React.FC = () => {
       // Clear success message after 3 seconds
       setTimeout(() => setSuccessMessage(null), 3000);
     } catch (error) {
-      setError(`Failed to import cookies: ${error}`);
+      setError(`Failed to import cookies: ${errorMessage(error)}`);
     } finally {
       setIsLoading(false);
       // Clear file input
@@ -115,7 +116,7 @@ const CookieManager: React.FC = () => {
       }
       await loadCookieStatus();
     } catch (error) {
-      setError(`Cookie validation failed: ${error}`);
+      setError(`Cookie validation failed: ${errorMessage(error)}`);
     } finally {
       setIsValidating(false);
     }
@@ -131,7 +132,7 @@ const CookieManager: React.FC = () => {
       await loadCookieStatus();
       setTimeout(() => setSuccessMessage(null), 3000);
     } catch (error) {
-      setError(`Failed to save PO Token: ${error}`);
+      setError(`Failed to save PO Token: ${errorMessage(error)}`);
     } finally {
       setIsLoading(false);
     }
@@ -149,7 +150,7 @@ const CookieManager: React.FC = () => {
       await loadCookieStatus();
       setTimeout(() => setSuccessMessage(null), 3000);
     } catch (error) {
-      setError(`Failed to clear cookies: ${error}`);
+      setError(`Failed to clear cookies: ${errorMessage(error)}`);
     } finally {
       setIsLoading(false);
     }
@@ -1,5 +1,6 @@
 import React, { useState, useEffect, useRef } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { errorMessage } from '../services/errorHandler';
 import { DownloadJob, JobStatus, QueueStats } from '../types';
 import QueueItem from './QueueItem';
 import './QueueView.css';
@@ -79,7 +80,7 @@ const QueueView: React.FC = () => {
         setError(response.error || 'Failed to add URL');
       }
     } catch (err) {
-      setError(`Failed to add URL: ${err}`);
+      setError(`Failed to add URL: ${errorMessage(err)}`);
     } finally {
       setIsLoading(false);
     }
@@ -99,7 +100,7 @@ const QueueView: React.FC = () => {
       }
       await loadQueue();
     } catch (err) {
-      setError(`Failed to ${isPaused ? 'resume' : 'pause'} queue: ${err}`);
+      setError(`Failed to ${isPaused ? 'resume' : 'pause'} queue: ${errorMessage(err)}`);
     }
   };
 
@@ -117,7 +118,7 @@ const QueueView: React.FC = () => {
       
       await loadQueue();
     } catch (err) {
-      setError(`Failed to clear completed jobs: ${err}`);
+      setError(`Failed to clear completed jobs: ${errorMessage(err)}`);
     }
   };
 
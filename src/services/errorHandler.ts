@@ -16,6 +16,23 @@ export interface ErrorAction {
   variant?: 'primary' | 'secondary' | 'danger';
 }
 
+/**
+ * Message of an error thrown by `invoke`. Commands returning an `AppError`
+ * reject with `{ code, message, details }`; the others reject with a string.
+ */
+export function errorMessage(error: unknown): string {
+  if (typeof error === 'string') {
+    return error;
+  }
+  if (error instanceof Error) {
+    return error.message;
+  }
+  if (typeof error === 'object' && error !== null && 'message' in error) {
+    return String((error as { message: unknown }).message);
+  }
+  return String(error);
+}
+
 /**
  * Error handler service for managing application errors and user feedback
  */
@@ -124,10 +141,8 @@ export class ErrorHandler {
           variant: 'primary',
         });
       }
-    } else if (error instanceof Error) {
-      message = error.message;
-    } else if (typeof error === 'string') {
-      message = error;
+    } else if (error !== undefined && error !== null) {
+      message = errorMessage(error);
     }
 
     return this.addNotification({